@@ -0,0 +1,246 @@
+use crate::render::dominant_color::palette_from_image_bytes;
+use crate::ui::theme::{lerp_rgb, Theme, ThemePalette};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+
+type Rgb = (u8, u8, u8);
+
+/// How long the UI takes to fade from one palette to the next.
+const FADE: Duration = Duration::from_millis(800);
+
+/// Median-cut swatches taken from each cover.
+const SWATCHES: usize = 8;
+
+/// Covers whose swatches are kept (least recently used go first).
+const SWATCH_CACHE: usize = 16;
+
+/// (color, pixel count) swatches of one cover, most common first.
+pub type Swatches = Vec<(Rgb, u32)>;
+
+/// Minimum contrast ratios (WCAG) of the derived colors against the backgrounds.
+const TEXT_CONTRAST: f32 = 7.0;
+const SUBTEXT_CONTRAST: f32 = 4.5;
+const ACCENT_CONTRAST: f32 = 3.0;
+
+/// What the palette is currently derived from: the cover (if adaptive and there is one)
+/// and the theme file it falls back to.
+type Target = (Option<u64>, PathBuf, Option<SystemTime>);
+
+/// Adaptive theme: the palette follows the current album cover and fades over `FADE`
+/// whenever the cover (or the theme underneath) changes.
+#[derive(Debug, Default)]
+pub struct AdaptiveTheme {
+    target: Option<Target>,
+    from: Option<ThemePalette>,
+    to: Option<ThemePalette>,
+    started: Option<Instant>,
+    /// Last palette written to the theme; a new fade starts from here.
+    shown: Option<ThemePalette>,
+    /// Swatches by cover hash, most recently used last.
+    swatches: VecDeque<(u64, Swatches)>,
+    /// Cover whose swatches the cover worker is computing.
+    requested: Option<u64>,
+}
+
+/// Swatches of a cover image; slow enough that it runs on the cover worker.
+pub fn cover_swatches(bytes: &[u8]) -> Swatches {
+    palette_from_image_bytes(bytes, SWATCHES)
+}
+
+impl AdaptiveTheme {
+    /// Whether the swatches of `hash` still have to be computed; true only once per cover,
+    /// the caller then hands the image to the cover worker.
+    pub fn wants_swatches(&mut self, hash: u64) -> bool {
+        if self.requested == Some(hash) || self.swatches.iter().any(|(h, _)| *h == hash) {
+            return false;
+        }
+        self.requested = Some(hash);
+        true
+    }
+
+    /// Swatches computed by the cover worker.
+    pub fn add_swatches(&mut self, hash: u64, swatches: Swatches) {
+        if self.requested == Some(hash) {
+            self.requested = None;
+        }
+        self.swatches.retain(|(h, _)| *h != hash);
+        if self.swatches.len() >= SWATCH_CACHE {
+            self.swatches.pop_front();
+        }
+        self.swatches.push_back((hash, swatches));
+    }
+
+    fn cached_swatches(&mut self, hash: u64) -> Option<&Swatches> {
+        let i = self.swatches.iter().position(|(h, _)| *h == hash)?;
+        let entry = self.swatches.remove(i)?;
+        self.swatches.push_back(entry);
+        self.swatches.back().map(|(_, s)| s)
+    }
+
+    /// Retarget on cover / theme changes and advance the fade. A new cover is only picked up
+    /// once its swatches have arrived; the current palette stays until then.
+    pub fn update(&mut self, theme: &mut Theme, enabled: bool, cover_hash: Option<u64>, now: Instant) {
+        let cover_hash = cover_hash.filter(|_| enabled);
+        let target: Target = (cover_hash, theme.path.clone(), theme.modified);
+        let ready = cover_hash.is_none_or(|h| self.swatches.iter().any(|(c, _)| *c == h));
+        if self.target.as_ref() != Some(&target) && ready {
+            let to = cover_hash
+                .and_then(|hash| palette_from_swatches(self.cached_swatches(hash)?, &theme.file_palette))
+                .unwrap_or_else(|| theme.file_palette.clone());
+            self.from = Some(self.shown.clone().unwrap_or_else(|| theme.palette.clone()));
+            self.to = Some(to);
+            self.started = Some(now);
+            self.target = Some(target);
+        }
+
+        let (Some(from), Some(to), Some(started)) = (self.from.as_ref(), self.to.as_ref(), self.started) else {
+            return;
+        };
+        let t = (now.saturating_duration_since(started).as_secs_f32() / FADE.as_secs_f32()).min(1.0);
+        let eased = t * t * (3.0 - 2.0 * t);
+        let palette = if t >= 1.0 { to.clone() } else { from.lerp(to, eased) };
+        theme.palette = palette.clone();
+        self.shown = Some(palette);
+        if t >= 1.0 {
+            self.from = None;
+            self.started = None;
+        }
+    }
+}
+
+/// Build a full palette from cover swatches. Light or dark follows the theme it replaces,
+/// whose accents also stand in when the cover has no usable colors.
+fn palette_from_swatches(swatches: &[(Rgb, u32)], fallback: &ThemePalette) -> Option<ThemePalette> {
+    let dominant = swatches.first()?.0;
+    let light = luminance(fallback.base) > 0.5;
+    let (paper, ink) = if light { ((255, 255, 255), (0, 0, 0)) } else { ((0, 0, 0), (255, 255, 255)) };
+
+    // Backgrounds: the dominant color pushed almost all the way to black (or white).
+    let base = lerp_rgb(dominant, paper, 0.86);
+    let surface = lerp_rgb(dominant, paper, 0.74);
+    let text = readable(lerp_rgb(ink, dominant, 0.12), &[base, surface], TEXT_CONTRAST, ink);
+    let subtext = readable(lerp_rgb(text, surface, 0.35), &[base, surface], SUBTEXT_CONTRAST, ink);
+
+    // Accents: the most vivid swatches with clearly different hues.
+    let total: u32 = swatches.iter().map(|s| s.1).sum::<u32>().max(1);
+    let mut vivid: Vec<(Rgb, f32)> = swatches
+        .iter()
+        .filter(|(c, _)| saturation(*c) >= 0.2)
+        .map(|&(c, n)| (c, saturation(c) * (n as f32 / total as f32).sqrt()))
+        .collect();
+    vivid.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let mut accents: Vec<Rgb> = Vec::new();
+    for (c, _) in &vivid {
+        if accents.iter().all(|a| hue_distance(*a, *c) >= 30.0) {
+            accents.push(*c);
+        }
+    }
+    for (c, _) in &vivid {
+        if accents.len() < 3 && !accents.contains(c) {
+            accents.push(*c);
+        }
+    }
+    let pick = |i: usize, fb: Rgb| readable(accents.get(i).copied().unwrap_or(fb), &[base, surface], ACCENT_CONTRAST, ink);
+    let accent = pick(0, fallback.accent);
+    let accent2 = pick(1, fallback.accent2);
+    let accent3 = pick(2, fallback.accent3);
+
+    Some(ThemePalette {
+        text,
+        subtext,
+        base,
+        surface,
+        accent,
+        accent2,
+        accent3,
+        spectrum: vec![accent2, accent, accent3],
+        progress: accent2,
+        lyric_highlight: accent,
+        border: lerp_rgb(subtext, accent, 0.4),
+        // Selected rows draw `base` on this color.
+        selection: readable(accent, &[base], SUBTEXT_CONTRAST, ink),
+    })
+}
+
+/// `color` moved toward `ink` until it reaches `min` contrast against every background.
+fn readable(color: Rgb, backgrounds: &[Rgb], min: f32, ink: Rgb) -> Rgb {
+    let mut c = color;
+    for step in 1..=10 {
+        if backgrounds.iter().all(|&bg| contrast(c, bg) >= min) {
+            break;
+        }
+        c = lerp_rgb(color, ink, step as f32 / 10.0);
+    }
+    c
+}
+
+/// WCAG relative luminance.
+fn luminance((r, g, b): Rgb) -> f32 {
+    let lin = |v: u8| {
+        let s = v as f32 / 255.0;
+        if s <= 0.03928 {
+            s / 12.92
+        } else {
+            ((s + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * lin(r) + 0.7152 * lin(g) + 0.0722 * lin(b)
+}
+
+fn contrast(a: Rgb, b: Rgb) -> f32 {
+    let (la, lb) = (luminance(a), luminance(b));
+    (la.max(lb) + 0.05) / (la.min(lb) + 0.05)
+}
+
+fn saturation((r, g, b): Rgb) -> f32 {
+    let max = r.max(g).max(b) as f32;
+    let min = r.min(g).min(b) as f32;
+    if max == 0.0 {
+        0.0
+    } else {
+        (max - min) / max
+    }
+}
+
+fn hue((r, g, b): Rgb) -> f32 {
+    let (r, g, b) = (r as f32, g as f32, b as f32);
+    let max = r.max(g).max(b);
+    let d = max - r.min(g).min(b);
+    if d == 0.0 {
+        return 0.0;
+    }
+    let h = if max == r {
+        ((g - b) / d).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / d + 2.0
+    } else {
+        (r - g) / d + 4.0
+    };
+    h * 60.0
+}
+
+fn hue_distance(a: Rgb, b: Rgb) -> f32 {
+    let d = (hue(a) - hue(b)).abs();
+    d.min(360.0 - d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swatches_are_requested_once_and_the_cache_stays_bounded() {
+        let mut adaptive = AdaptiveTheme::default();
+        assert!(adaptive.wants_swatches(1));
+        assert!(!adaptive.wants_swatches(1));
+        adaptive.add_swatches(1, vec![((200, 40, 40), 10)]);
+        assert!(!adaptive.wants_swatches(1));
+
+        for hash in 2..40 {
+            adaptive.add_swatches(hash, Vec::new());
+        }
+        assert_eq!(adaptive.swatches.len(), SWATCH_CACHE);
+        assert!(adaptive.wants_swatches(1));
+    }
+}
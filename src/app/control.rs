@@ -0,0 +1,457 @@
+// Headless remote control over a Unix domain socket.
+//
+// Protocol: one request per line, one JSON reply per line.
+// A request is either plain text (`seek +10`) or a JSON object (`{"cmd":"seek","arg":"+10"}`).
+// Every reply is `{"ok":true,"status":{...}}` or `{"ok":false,"error":"..."}`.
+
+use crate::app::state::{AppState, PlayMode, PlaybackState, RepeatMode};
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+pub const SOCKET_NAME: &str = "cli-music-player.sock";
+
+/// `$XDG_RUNTIME_DIR/cli-music-player.sock`, or the same name in a per-user directory in
+/// the temp dir that only its owner can enter.
+pub fn socket_path() -> PathBuf {
+    if let Some(dir) = std::env::var_os("XDG_RUNTIME_DIR").filter(|d| !d.is_empty()) {
+        return PathBuf::from(dir).join(SOCKET_NAME);
+    }
+    imp::fallback_dir().join(SOCKET_NAME)
+}
+
+/// Where the player keeps files other users must not touch (the socket, MPRIS cover art):
+/// `$XDG_RUNTIME_DIR`, or the per-user fallback directory, created and checked here.
+pub fn runtime_dir() -> Result<PathBuf> {
+    if let Some(dir) = std::env::var_os("XDG_RUNTIME_DIR").filter(|d| !d.is_empty()) {
+        return Ok(PathBuf::from(dir));
+    }
+    let dir = imp::fallback_dir();
+    #[cfg(unix)]
+    imp::check_private_dir(&dir, true)?;
+    Ok(dir)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SeekTarget {
+    Absolute(f32),
+    Relative(f32),
+    Percent(f32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ControlCommand {
+    Play,
+    Pause,
+    /// Local playback only: stop and rewind to the start of the track.
+    Stop,
+    Toggle,
+    Next,
+    Prev,
+    /// Seconds (`90`, `+10`, `-10`) or percent of the track (`50%`).
+    Seek(SeekTarget),
+    /// Percent, absolute (`40`) or relative (`+5`, `-5`).
+    Volume { value: f32, relative: bool },
+    /// Folder or playlist file.
+    Open(PathBuf),
+    /// Local playback only.
+    Repeat(RepeatMode),
+    Status,
+}
+
+pub const HELP: &str = "commands: play | pause | stop | toggle | next | prev | seek <secs|+secs|-secs|pct%> | volume <0-100|+n|-n> | open <folder|playlist> | repeat <sequence|loop_all|loop_one|shuffle> | status";
+
+pub fn parse_command(line: &str) -> Result<ControlCommand> {
+    let line = line.trim();
+    if line.starts_with('{') {
+        let v: Value = serde_json::from_str(line).map_err(|e| anyhow!("invalid JSON: {e}"))?;
+        let cmd = v
+            .get("cmd")
+            .and_then(|c| c.as_str())
+            .ok_or_else(|| anyhow!("missing \"cmd\""))?;
+        let arg = match v.get("arg") {
+            Some(Value::String(s)) => s.clone(),
+            Some(Value::Number(n)) => n.to_string(),
+            Some(Value::Null) | None => String::new(),
+            Some(other) => other.to_string(),
+        };
+        return parse_parts(cmd, &arg);
+    }
+    let (cmd, arg) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    parse_parts(cmd, arg.trim())
+}
+
+fn parse_parts(cmd: &str, arg: &str) -> Result<ControlCommand> {
+    let number = |s: &str| -> Result<f32> {
+        s.parse::<f32>()
+            .ok()
+            .filter(|v| v.is_finite())
+            .ok_or_else(|| anyhow!("invalid number: {s:?}"))
+    };
+
+    match cmd.to_ascii_lowercase().as_str() {
+        "play" => Ok(ControlCommand::Play),
+        "pause" => Ok(ControlCommand::Pause),
+        "stop" => Ok(ControlCommand::Stop),
+        "toggle" | "play-pause" => Ok(ControlCommand::Toggle),
+        "next" => Ok(ControlCommand::Next),
+        "prev" | "previous" => Ok(ControlCommand::Prev),
+        "status" => Ok(ControlCommand::Status),
+        "seek" => {
+            if arg.is_empty() {
+                return Err(anyhow!("seek needs an argument"));
+            }
+            if let Some(p) = arg.strip_suffix('%') {
+                return Ok(ControlCommand::Seek(SeekTarget::Percent(number(p)?)));
+            }
+            if arg.starts_with('+') || arg.starts_with('-') {
+                return Ok(ControlCommand::Seek(SeekTarget::Relative(number(arg)?)));
+            }
+            Ok(ControlCommand::Seek(SeekTarget::Absolute(number(arg)?)))
+        }
+        "volume" | "vol" => {
+            if arg.is_empty() {
+                return Err(anyhow!("volume needs an argument"));
+            }
+            let arg = arg.trim_end_matches('%');
+            let relative = arg.starts_with('+') || arg.starts_with('-');
+            Ok(ControlCommand::Volume {
+                value: number(arg)?,
+                relative,
+            })
+        }
+        "open" => {
+            if arg.is_empty() {
+                return Err(anyhow!("open needs a path"));
+            }
+            Ok(ControlCommand::Open(PathBuf::from(arg)))
+        }
+        "repeat" => match arg.to_ascii_lowercase().replace('-', "_").as_str() {
+            "sequence" => Ok(ControlCommand::Repeat(RepeatMode::Sequence)),
+            "loop_all" => Ok(ControlCommand::Repeat(RepeatMode::LoopAll)),
+            "loop_one" => Ok(ControlCommand::Repeat(RepeatMode::LoopOne)),
+            "shuffle" => Ok(ControlCommand::Repeat(RepeatMode::Shuffle)),
+            other => Err(anyhow!("unknown repeat mode: {other:?}")),
+        },
+        "" => Err(anyhow!("empty command")),
+        other => Err(anyhow!("unknown command: {other}")),
+    }
+}
+
+/// Snapshot of `PlayerState` sent back with every successful reply.
+pub fn status_json(app: &AppState) -> Value {
+    let p = &app.player;
+    let mode = match p.mode {
+        PlayMode::Idle => "idle",
+        PlayMode::LocalPlayback => "local",
+        PlayMode::SystemMonitor => "system",
+    };
+    let playback = match p.playback {
+        PlaybackState::Playing => "playing",
+        PlaybackState::Paused => "paused",
+        PlaybackState::Stopped => "stopped",
+    };
+    let repeat = match p.repeat_mode {
+        RepeatMode::Sequence => "sequence",
+        RepeatMode::LoopAll => "loop_all",
+        RepeatMode::LoopOne => "loop_one",
+        RepeatMode::Shuffle => "shuffle",
+    };
+    let path = if p.mode == PlayMode::LocalPlayback {
+        app.playlist.current_path().map(|p| p.to_string_lossy().to_string())
+    } else {
+        None
+    };
+
+    json!({
+        "mode": mode,
+        "playback": playback,
+        "repeat": repeat,
+        "position_ms": p.position.as_millis() as u64,
+        "duration_ms": p.track.duration.as_millis() as u64,
+        "volume": (p.volume * 100.0).round() as i32,
+        "title": p.track.title,
+        "artist": p.track.artist,
+        "album": p.track.album,
+        "path": path,
+        "folder": app.local_folder.as_ref().map(|f| f.to_string_lossy().to_string()),
+        "playlist_len": app.playlist.items.len(),
+        "up_next": app.up_next.len(),
+    })
+}
+
+pub fn ok_reply(app: &AppState) -> String {
+    json!({ "ok": true, "status": status_json(app) }).to_string()
+}
+
+pub fn error_reply(msg: impl std::fmt::Display) -> String {
+    json!({ "ok": false, "error": msg.to_string() }).to_string()
+}
+
+/// `cli-music-player ctl <cmd> [arg]`: send one command to the running player and print the reply.
+pub fn run_ctl(args: &[String]) -> Result<()> {
+    if args.is_empty() || matches!(args[0].as_str(), "-h" | "--help" | "help") {
+        println!("usage: cli-music-player ctl <command> [arg]\n{HELP}");
+        return Ok(());
+    }
+    let mut line = args.join(" ");
+    // Validate locally so typos don't need a running player to be reported.
+    if let ControlCommand::Open(path) = parse_command(&line)? {
+        // The player has its own working directory.
+        if path.is_relative() {
+            let abs = std::env::current_dir()?.join(path);
+            line = format!("open {}", abs.display());
+        }
+    }
+
+    let reply = imp::send(&socket_path(), &line)?;
+    println!("{reply}");
+    let ok = serde_json::from_str::<Value>(&reply)
+        .ok()
+        .and_then(|v| v.get("ok").and_then(|o| o.as_bool()))
+        .unwrap_or(false);
+    if !ok {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// A parsed command waiting to be executed on the UI thread.
+#[derive(Debug)]
+pub struct ControlRequest {
+    pub command: ControlCommand,
+    reply: std::sync::mpsc::Sender<String>,
+}
+
+impl ControlRequest {
+    /// A request nobody waits on (e.g. from MPRIS, which replies before the command runs).
+    pub fn detached(command: ControlCommand) -> Self {
+        let (reply, _) = std::sync::mpsc::channel();
+        Self { command, reply }
+    }
+
+    pub fn respond(self, reply: String) {
+        let _ = self.reply.send(reply);
+    }
+}
+
+pub use imp::ControlServer;
+
+#[cfg(unix)]
+mod imp {
+    use super::{error_reply, parse_command, ControlRequest};
+    use anyhow::{anyhow, Context, Result};
+    use std::io::{BufRead, BufReader, ErrorKind, Write};
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::{Path, PathBuf};
+    use std::sync::mpsc::{self, Receiver, Sender};
+    use std::thread;
+    use std::time::Duration;
+
+    // The UI thread answers within a frame; this only guards against a stalled loop.
+    const REPLY_TIMEOUT: Duration = Duration::from_secs(3);
+
+    /// `<temp dir>/cli-music-player-<uid>`, used without `XDG_RUNTIME_DIR`.
+    pub fn fallback_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("cli-music-player-{}", current_uid()))
+    }
+
+    fn current_uid() -> u32 {
+        // SAFETY: getuid has no preconditions and cannot fail.
+        unsafe { libc::getuid() }
+    }
+
+    /// The fallback directory must be ours and closed to everyone else, or another user
+    /// could put their own socket there (or read ours). `create` makes it 0700 if missing.
+    pub(super) fn check_private_dir(dir: &Path, create: bool) -> Result<()> {
+        if create {
+            match std::fs::DirBuilder::new().mode(0o700).create(dir) {
+                Err(e) if e.kind() != ErrorKind::AlreadyExists => {
+                    return Err(e).with_context(|| format!("create {}", dir.display()));
+                }
+                _ => {}
+            }
+        }
+        // Not following symlinks: a link planted by someone else is refused too.
+        let meta = std::fs::symlink_metadata(dir).with_context(|| format!("stat {}", dir.display()))?;
+        if !meta.is_dir() || meta.uid() != current_uid() {
+            return Err(anyhow!("{} is not a directory owned by you; refusing to use it", dir.display()));
+        }
+        if meta.mode() & 0o077 != 0 {
+            return Err(anyhow!("{} is open to other users; refusing to use it", dir.display()));
+        }
+        Ok(())
+    }
+
+    /// Listens on the control socket; requests are handed to the event loop via `drain()`.
+    pub struct ControlServer {
+        path: PathBuf,
+        rx: Receiver<ControlRequest>,
+    }
+
+    impl ControlServer {
+        pub fn start(path: &Path) -> Result<Self> {
+            let in_fallback = path.parent() == Some(fallback_dir().as_path());
+            if in_fallback {
+                check_private_dir(&fallback_dir(), true)?;
+            }
+            if path.exists() {
+                if UnixStream::connect(path).is_ok() {
+                    return Err(anyhow!("another instance is listening on {}", path.display()));
+                }
+                // Stale socket from a crashed run.
+                let _ = std::fs::remove_file(path);
+            }
+            if let Some(parent) = path.parent().filter(|_| !in_fallback) {
+                let _ = std::fs::create_dir_all(parent);
+            }
+            // Owner only from the moment it exists: the socket can open files and control
+            // playback. (The umask is process-wide; this runs once, at startup.)
+            // SAFETY: umask has no preconditions and cannot fail.
+            let old_mask = unsafe { libc::umask(0o077) };
+            let bound = UnixListener::bind(path);
+            unsafe { libc::umask(old_mask) };
+            let listener = bound.with_context(|| format!("bind {}", path.display()))?;
+
+            let (tx, rx) = mpsc::channel::<ControlRequest>();
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let Ok(stream) = stream else {
+                        continue;
+                    };
+                    let tx = tx.clone();
+                    thread::spawn(move || serve_client(stream, tx));
+                }
+            });
+
+            Ok(Self {
+                path: path.to_path_buf(),
+                rx,
+            })
+        }
+
+        pub fn drain(&self) -> Vec<ControlRequest> {
+            self.rx.try_iter().collect()
+        }
+    }
+
+    impl Drop for ControlServer {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+
+    fn serve_client(stream: UnixStream, tx: Sender<ControlRequest>) {
+        let Ok(mut writer) = stream.try_clone() else {
+            return;
+        };
+        let reader = BufReader::new(stream);
+        for line in reader.lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            let reply = match parse_command(&line) {
+                Ok(command) => {
+                    let (reply_tx, reply_rx) = mpsc::channel::<String>();
+                    if tx.send(ControlRequest { command, reply: reply_tx }).is_err() {
+                        return;
+                    }
+                    reply_rx
+                        .recv_timeout(REPLY_TIMEOUT)
+                        .unwrap_or_else(|_| error_reply("player did not respond"))
+                }
+                Err(e) => error_reply(e),
+            };
+            if writeln!(writer, "{reply}").is_err() {
+                break;
+            }
+        }
+    }
+
+    pub fn send(path: &Path, line: &str) -> Result<String> {
+        if path.parent() == Some(fallback_dir().as_path()) {
+            check_private_dir(&fallback_dir(), false)?;
+        }
+        let mut stream = UnixStream::connect(path)
+            .with_context(|| format!("connect {} (is the player running?)", path.display()))?;
+        stream.set_read_timeout(Some(REPLY_TIMEOUT + Duration::from_secs(1)))?;
+        writeln!(stream, "{}", line.trim())?;
+        let mut reply = String::new();
+        BufReader::new(&stream).read_line(&mut reply)?;
+        if reply.trim().is_empty() {
+            return Err(anyhow!("no reply from {}", path.display()));
+        }
+        Ok(reply.trim().to_string())
+    }
+}
+
+#[cfg(not(unix))]
+mod imp {
+    use super::ControlRequest;
+    use anyhow::{anyhow, Result};
+    use std::path::{Path, PathBuf};
+
+    pub fn fallback_dir() -> PathBuf {
+        std::env::temp_dir()
+    }
+
+    pub struct ControlServer;
+
+    impl ControlServer {
+        pub fn start(_path: &Path) -> Result<Self> {
+            Err(anyhow!("The control socket is only supported on Unix"))
+        }
+
+        pub fn drain(&self) -> Vec<ControlRequest> {
+            Vec::new()
+        }
+    }
+
+    pub fn send(_path: &Path, _line: &str) -> Result<String> {
+        Err(anyhow!("The control socket is only supported on Unix"))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::{symlink, PermissionsExt};
+
+    #[test]
+    fn fallback_dir_must_be_private_and_ours() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("private");
+        imp::check_private_dir(&dir, true).unwrap();
+        assert_eq!(std::fs::metadata(&dir).unwrap().permissions().mode() & 0o777, 0o700);
+
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(imp::check_private_dir(&dir, true).is_err());
+
+        let link = tmp.path().join("link");
+        symlink(&dir, &link).unwrap();
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o700)).unwrap();
+        assert!(imp::check_private_dir(&link, false).is_err());
+        assert!(imp::check_private_dir(&tmp.path().join("missing"), false).is_err());
+    }
+
+    #[test]
+    fn socket_is_owner_only_and_answers() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join(SOCKET_NAME);
+        let server = ControlServer::start(&path).unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o077, 0);
+        assert!(ControlServer::start(&path).is_err(), "a second instance must not take over");
+
+        let client = std::thread::spawn({
+            let path = path.clone();
+            move || imp::send(&path, "bogus")
+        });
+        let reply = client.join().unwrap().unwrap();
+        assert!(reply.contains("\"ok\":false"));
+        assert!(server.drain().is_empty());
+    }
+}
@@ -1,21 +1,36 @@
+use crate::app::control::{self, ControlCommand, ControlRequest, ControlServer, SeekTarget};
 use crate::app::mode_manager::ModeManager;
-use crate::app::state::{AppState, CoverSnapshot, LocalFolderKind, Overlay, PlayMode, PlaybackState, RepeatMode};
+use crate::app::state::{AbLoop, AppState, CoverSnapshot, LocalFolderKind, LyricLine, LyricTiming, LyricTrack, LyricTrackKind, Overlay, PlayMode, PlaybackState, PlaylistNameMode, RepeatMode};
+use crate::app::lyric_sync::LyricSync;
+use crate::app::playlist_search::PlaylistSearch;
+use crate::app::library_browser::LibraryRow;
+use crate::app::tag_form::{EditScope, TagForm, TagSave};
+use crate::data::library::LibraryTrack;
+use crate::data::playlist_file;
+use crate::playback::library_scanner::LibraryScanEvent;
+use crate::playback::tag_editor::EditJob;
+use crate::playback::mpris_server::MprisServer;
+use crate::audio::capture::AudioCapture;
 use crate::audio::cava::{CavaChannels, CavaConfig, CavaRunner};
+use crate::audio::spectrum::SpectrumAnalyzer;
 use crate::data::theme_loader::ThemeLoader;
 use crate::data::config::{BarChannels, BarNumber, VisualizeMode};
 use crate::ui::tui::{Tui, UiLayout};
-use crate::ui::theme::ThemeName;
-use crate::utils::input::{map_key, map_mouse, Action};
+use crate::utils::input::{map_key_with_count, map_mouse, Action};
+use crate::utils::timefmt;
 use crate::utils::system_volume::SystemVolume;
 use anyhow::Result;
 use crossterm::event::{self, Event};
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
 use std::path::PathBuf;
-use std::time::SystemTime;
-use std::time::UNIX_EPOCH;
 use std::time::{Duration, Instant};
 
 fn sync_playlists_when_viewing_playback(app: &mut AppState) {
+    // A user playlist / library list has a single list: view and playback queue are the same.
+    if app.local_folder.is_none() && app.local_view_album_folder.is_none() && !app.playlist.items.is_empty() {
+        app.playlist = app.playlist_view.clone();
+        return;
+    }
     if app.local_view_album_folder.is_some() && app.local_folder.is_some() {
         if app.local_view_album_folder.as_ref() == app.local_folder.as_ref() {
             app.playlist = app.playlist_view.clone();
@@ -34,6 +49,7 @@ fn clear_spectrum(app: &mut AppState) {
 
 fn open_local_folder(app: &mut AppState, mode_manager: &mut ModeManager, folder: &std::path::Path) -> Result<()> {
     let res = mode_manager.local.load_path(folder)?;
+    app.user_playlist = None;
     mode_manager.pause_other(PlayMode::LocalPlayback);
     app.player.mode = PlayMode::LocalPlayback;
     app.playlist = res.playlist;
@@ -86,6 +102,831 @@ fn open_local_folder(app: &mut AppState, mode_manager: &mut ModeManager, folder:
     Ok(())
 }
 
+/// Open a playlist file (M3U/M3U8/PLS/XSPF) as the playback queue.
+fn open_user_playlist(app: &mut AppState, mode_manager: &mut ModeManager, path: &std::path::Path) -> Result<()> {
+    // Entries that can't be played stay in the list (and in the file when it is saved).
+    let mut items = playlist_file::read_playlist(path)?;
+    for it in &mut items {
+        it.missing |= !crate::playback::local_player::is_audio(&it.path);
+    }
+
+    let playing = play_item_list(app, mode_manager, items, 0)?;
+    app.user_playlist = Some(path.to_path_buf());
+    app.user_playlist_target = Some(path.to_path_buf());
+
+    let name = playlist_file::playlist_name(path);
+    if playing {
+        app.set_toast(format!("Playlist: {name} ({} tracks)", app.playlist.len()));
+    } else {
+        app.set_toast(format!("Playlist {name} is empty"));
+    }
+    Ok(())
+}
+
+/// Replace the playback list with `items` (not tied to a folder) and play `start`.
+/// Returns false when there is nothing to play.
+fn play_item_list(
+    app: &mut AppState,
+    mode_manager: &mut ModeManager,
+    items: Vec<crate::data::playlist::PlaylistItem>,
+    start: usize,
+) -> Result<bool> {
+    let mut playlist = crate::data::playlist::Playlist::new(items);
+    playlist.selected = start;
+    playlist.clamp_selected();
+    // Start at the first entry from `start` whose file is there.
+    playlist.current = playlist.first_playable_from(playlist.selected);
+    if let Some(cur) = playlist.current {
+        playlist.selected = cur;
+    }
+
+    let track = match playlist.current_path().cloned() {
+        Some(first) => {
+            mode_manager.pause_other(PlayMode::LocalPlayback);
+            let track = mode_manager.local.play_file(&first)?;
+            app.player.mode = PlayMode::LocalPlayback;
+            Some((first, track))
+        }
+        None => None,
+    };
+
+    app.playlist = playlist;
+    app.playlist_view = app.playlist.clone();
+
+    app.user_playlist = None;
+    app.local_folder = None;
+    app.local_root_folder = None;
+    app.local_folder_kind = LocalFolderKind::Plain;
+    app.local_album_folders = Vec::new();
+    app.local_view_album_index = 0;
+    app.local_view_album_folder = None;
+    app.local_view_album_cover = None;
+    app.local_view_album_cover_hash = None;
+
+    let Some((first, track)) = track else {
+        return Ok(false);
+    };
+    note_local_started(app, &first);
+    app.player.track = track;
+    app.player.volume = mode_manager.local.volume();
+    app.player.playback = mode_manager.local.playback_state();
+    app.eq.bands_db = app.config.eq_bands_db;
+    let _ = mode_manager.local.set_eq(app.eq);
+    app.queue_remote_fetch(Some(&first));
+    Ok(true)
+}
+
+fn apply_playlist_search_selection(app: &mut AppState) {
+    if let Some(i) = app.playlist_search.as_ref().and_then(|s| s.selected_item()) {
+        app.playlist_view.selected = i;
+        app.playlist_view.clamp_selected();
+        sync_playlists_when_viewing_playback(app);
+    }
+}
+
+/// A–B loop key: `A` steps through mark A → mark B (loop on) → clear.
+fn cycle_ab_loop(app: &mut AppState) {
+    if app.player.mode != PlayMode::LocalPlayback {
+        app.set_toast("A–B loop: Local only");
+        return;
+    }
+    let Some(path) = app.playlist.current_path().cloned() else {
+        return;
+    };
+    let pos = app.player.position;
+    match app.ab_loop.take() {
+        Some(mut ab) if ab.path == path && ab.b.is_none() => {
+            if pos <= ab.a {
+                app.set_toast("A–B loop: B must come after A");
+            } else {
+                ab.b = Some(pos);
+                app.set_toast(format!("A–B loop: {} – {}", timefmt::mmss(ab.a), timefmt::mmss(pos)));
+            }
+            app.ab_loop = Some(ab);
+        }
+        Some(ab) if ab.path == path => app.set_toast("A–B loop off"),
+        _ => {
+            app.ab_loop = Some(AbLoop { path, a: pos, b: None });
+            app.set_toast(format!("A–B loop: A = {} (press A again for B)", timefmt::mmss(pos)));
+        }
+    }
+}
+
+/// Jump back to A once playback passes B; a loop set on another song is dropped.
+fn enforce_ab_loop(app: &mut AppState, mode_manager: &mut ModeManager) {
+    let Some(ab) = app.ab_loop.as_ref() else {
+        return;
+    };
+    if app.playlist.current_path() != Some(&ab.path) {
+        app.ab_loop = None;
+        return;
+    }
+    let (a, Some(b)) = (ab.a, ab.b) else {
+        return;
+    };
+    if app.player.position >= b && mode_manager.local.seek(a).is_ok() {
+        app.player.position = a;
+    }
+}
+
+/// Shift the current song's lyric timing; local songs keep it in their folder's `.order.toml`.
+fn nudge_lyrics_offset(app: &mut AppState, mode_manager: &mut ModeManager, delta_ms: i64) {
+    if app.player.track.lyrics.is_empty() {
+        app.set_toast("No lyrics");
+        return;
+    }
+    let offset = (app.player.track.lyrics_offset_ms + delta_ms).clamp(-60_000, 60_000);
+    app.player.track.lyrics_offset_ms = offset;
+    app.set_toast(format!("Lyrics offset: {:+.1}s", offset as f64 / 1000.0));
+
+    if app.player.mode == PlayMode::LocalPlayback {
+        if let Some(path) = app.playlist.current_path().cloned() {
+            if let Some(folder) = path.parent() {
+                let _ = crate::playback::local_player::write_lyrics_offset(folder, &path, offset);
+            }
+            mode_manager.local.replace_cached_metadata(&path, &app.player.track);
+        }
+    }
+}
+
+/// Write the tap-to-sync result as the song's LRC and use it right away. Translation and
+/// romanization layers move onto the new stamps: those from the song's LRC are written back
+/// into it, those from `<stem>.<lang>.lrc` side files into their own file.
+fn save_lyric_sync(app: &mut AppState, mode_manager: &mut ModeManager) {
+    use crate::playback::metadata::{format_lrc, write_lrc_for_audio, write_side_lrc};
+
+    let Some(sync) = app.lyric_sync.as_ref() else {
+        return;
+    };
+    let (path, lines) = (sync.path.clone(), sync.stamped());
+    if app.playlist.current_path() != Some(&path) {
+        app.set_toast("Song changed; lyric sync cancelled");
+        app.close_overlay();
+        return;
+    }
+    if lines.is_empty() {
+        app.set_toast("Nothing stamped yet");
+        return;
+    }
+    let layers: Vec<Vec<LyricLine>> = match app.player.track.lyrics.split_first() {
+        Some((original, rest)) => rest.iter().map(|layer| sync.restamp(original, layer)).collect(),
+        None => Vec::new(),
+    };
+    let langs: Vec<Option<String>> = app.player.track.lyrics.iter().skip(1).map(|t| t.lang.clone()).collect();
+
+    let mut in_file = vec![lines.clone()];
+    in_file.extend(layers.iter().zip(&langs).filter(|(_, lang)| lang.is_none()).map(|(l, _)| l.clone()));
+    let content = format_lrc(&app.player.track, &in_file);
+    match write_lrc_for_audio(&path, &content) {
+        Ok(written) => {
+            let mut side_error = None;
+            for (layer, lang) in layers.iter().zip(&langs) {
+                let Some(lang) = lang else {
+                    continue;
+                };
+                let side = format_lrc(&app.player.track, std::slice::from_ref(layer));
+                if let Some(Err(e)) = write_side_lrc(&path, lang, &side) {
+                    side_error = Some(format!("{lang}: {e}"));
+                }
+            }
+
+            let track = &mut app.player.track;
+            match track.lyrics.first_mut() {
+                Some(original) => {
+                    original.lines = lines;
+                    original.kind = LyricTrackKind::Original;
+                    original.timing = LyricTiming::Synced;
+                }
+                None => track.lyrics.push(LyricTrack::new(LyricTrackKind::Original, lines)),
+            }
+            for (t, layer) in track.lyrics.iter_mut().skip(1).zip(layers) {
+                t.lines = layer;
+                t.timing = LyricTiming::Synced;
+            }
+            // The stamps are real playback times, so any old correction no longer applies.
+            track.lyrics_offset_ms = 0;
+            if let Some(folder) = path.parent() {
+                let _ = crate::playback::local_player::write_lyrics_offset(folder, &path, 0);
+            }
+            mode_manager.local.replace_cached_metadata(&path, &app.player.track);
+
+            let name = written.file_name().and_then(|s| s.to_str()).unwrap_or("lyrics");
+            match side_error {
+                Some(e) => app.set_toast(format!("Saved {name}; side file failed ({e})")),
+                None => app.set_toast(format!("Saved {name}")),
+            }
+            app.lyric_sync = None;
+            app.open_lyrics_view();
+        }
+        Err(e) => app.set_toast(format!("Save failed: {e}")),
+    }
+}
+
+/// Ctrl+F input and `ctl open`: playlist files open as a user playlist, anything else as a folder.
+fn open_folder_or_playlist(app: &mut AppState, mode_manager: &mut ModeManager, path: &std::path::Path) -> Result<()> {
+    if playlist_file::is_playlist_file(path) {
+        open_user_playlist(app, mode_manager, path)
+    } else {
+        open_local_folder(app, mode_manager, path)
+    }
+}
+
+/// Run commands received on the control socket, then reply with the resulting status.
+fn handle_control_requests(
+    app: &mut AppState,
+    mode_manager: &mut ModeManager,
+    system_volume: Option<&SystemVolume>,
+    requests: Vec<ControlRequest>,
+    layout: &UiLayout,
+) {
+    for req in requests {
+        let res = run_control_command(app, mode_manager, system_volume, &req.command, layout);
+        if app.player.mode == PlayMode::LocalPlayback {
+            // Reflect the command right away instead of on the next frame.
+            if let Some(pos) = mode_manager.local.position() {
+                app.player.position = pos;
+            }
+            app.player.volume = mode_manager.local.volume();
+            app.player.playback = mode_manager.local.playback_state();
+            app.player.seeks = mode_manager.local.seeks();
+        }
+        match res {
+            Ok(()) => req.respond(control::ok_reply(app)),
+            Err(e) => req.respond(control::error_reply(e)),
+        }
+    }
+}
+
+fn run_control_command(
+    app: &mut AppState,
+    mode_manager: &mut ModeManager,
+    system_volume: Option<&SystemVolume>,
+    command: &ControlCommand,
+    layout: &UiLayout,
+) -> Result<()> {
+    let action = match command {
+        ControlCommand::Status => return Ok(()),
+        ControlCommand::Play if app.player.playback == PlaybackState::Playing => return Ok(()),
+        ControlCommand::Pause if app.player.playback != PlaybackState::Playing => return Ok(()),
+        ControlCommand::Play | ControlCommand::Pause | ControlCommand::Toggle => Action::TogglePlayPause,
+        ControlCommand::Stop => {
+            if app.player.mode != PlayMode::LocalPlayback {
+                return Err(anyhow::anyhow!("stop only applies to local playback"));
+            }
+            mode_manager.local.stop();
+            app.player.position = Duration::from_secs(0);
+            return Ok(());
+        }
+        ControlCommand::Next => Action::Next,
+        ControlCommand::Prev => Action::Prev,
+        ControlCommand::Seek(target) => {
+            let dur = app.player.track.duration.as_secs_f32();
+            if dur <= 0.0 {
+                return Err(anyhow::anyhow!("nothing to seek"));
+            }
+            match *target {
+                SeekTarget::Absolute(s) => Action::SeekToFraction((s / dur).clamp(0.0, 1.0)),
+                SeekTarget::Relative(d) => Action::SeekRelative((d * 1000.0) as i64),
+                SeekTarget::Percent(p) => Action::SeekToFraction((p / 100.0).clamp(0.0, 1.0)),
+            }
+        }
+        ControlCommand::Volume { value, relative } => {
+            let base = if *relative { app.player.volume * 100.0 } else { 0.0 };
+            Action::SetVolume(((base + value) / 100.0).clamp(0.0, 1.0))
+        }
+        ControlCommand::Open(path) => {
+            return open_folder_or_playlist(app, mode_manager, path);
+        }
+        ControlCommand::Repeat(mode) => {
+            // Same rule as `m`: repeat modes only apply to local playback.
+            if app.player.mode != PlayMode::LocalPlayback {
+                return Err(anyhow::anyhow!("repeat mode only applies to local playback"));
+            }
+            app.player.repeat_mode = *mode;
+            return Ok(());
+        }
+    };
+    handle_action(app, mode_manager, system_volume, action, layout)
+}
+
+fn poll_library_scan(app: &mut AppState) {
+    let Some(scanner) = app.library_scanner.as_ref() else {
+        return;
+    };
+    for ev in scanner.drain() {
+        match ev {
+            LibraryScanEvent::Progress { seen, updated } => {
+                app.library_scan_status = Some(format!("Scanning… {seen} files ({updated} new/changed)"));
+            }
+            LibraryScanEvent::Done { library, updated, removed } => {
+                app.library = library;
+                app.library_scanner = None;
+                // The scan may have read these files before the edit; the edit is newer.
+                let mut retagged = false;
+                for t in std::mem::take(&mut app.library_scan_retagged) {
+                    retagged |= app.library.replace_track(t);
+                }
+                if retagged {
+                    if let Err(e) = app.library.save() {
+                        log::warn!("library save failed: {e}");
+                    }
+                }
+                app.library_scan_status = None;
+                app.library_browser.reload(&app.library);
+                if updated > 0 || removed > 0 || app.overlay == Overlay::Library {
+                    app.set_toast(format!(
+                        "Library: {} tracks ({updated} updated, {removed} removed)",
+                        app.library.len()
+                    ));
+                }
+                break;
+            }
+        }
+    }
+}
+
+fn poll_tagger(app: &mut AppState, mode_manager: &mut ModeManager) {
+    let Some(review) = app.tag_review.as_mut() else {
+        return;
+    };
+    let msg = review.poll();
+    let retagged = std::mem::take(&mut review.retagged);
+    apply_retagged(app, mode_manager, retagged);
+    if let Some(msg) = msg {
+        app.set_toast(format!("Tagger: {msg}"));
+    }
+    // A write that outlived its overlay is done: nothing left to show.
+    if app.overlay != Overlay::Tagger && app.tag_review.as_ref().is_some_and(|r| r.busy().is_none()) {
+        app.tag_review = None;
+    }
+}
+
+/// Files of the playlist view for the batch tagger, with a title naming where they're from.
+fn open_tagger_for_view(app: &mut AppState) {
+    let paths: Vec<PathBuf> = app
+        .playlist_view
+        .items
+        .iter()
+        .map(|i| i.path.clone())
+        .filter(|p| p.is_file())
+        .collect();
+    let from = match (app.user_playlist.as_deref(), app.local_view_album_folder.as_deref().or(app.local_folder.as_deref())) {
+        (Some(p), _) => playlist_file::playlist_name(p),
+        (None, Some(folder)) => folder
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| folder.display().to_string()),
+        (None, None) => "Playlist".to_string(),
+    };
+    app.open_tagger(format!("Tagger — {from}"), paths);
+}
+
+/// Tag editor for the selected playlist / library track, or every track of its album.
+fn open_tag_editor(app: &mut AppState, scope: EditScope) {
+    if app.tag_save.is_some() {
+        app.set_toast("Tag editor: still saving the last edit");
+        return;
+    }
+    let (selected, mut candidates): (PathBuf, Vec<PathBuf>) = match app.overlay {
+        Overlay::Playlist if !app.up_next_view => {
+            let Some(item) = app.playlist_view.items.get(app.playlist_view.selected) else {
+                return;
+            };
+            (item.path.clone(), app.playlist_view.items.iter().map(|i| i.path.clone()).collect())
+        }
+        Overlay::Library => {
+            let Some(LibraryRow::Track(idx)) = app.library_browser.selected_row() else {
+                return;
+            };
+            let Some(t) = app.library.tracks.get(idx) else {
+                return;
+            };
+            // The whole album, not only what the current (filtered) list shows.
+            let album = app.library.tracks.iter().filter(|o| same_album(t, o)).map(|o| o.path.clone()).collect();
+            (t.path.clone(), album)
+        }
+        _ => return,
+    };
+    let first = match crate::playback::metadata::read_library_tags(&selected) {
+        Ok(t) => t,
+        Err(e) => {
+            app.set_toast(format!("Can't read tags: {e}"));
+            return;
+        }
+    };
+    let tracks = match scope {
+        EditScope::Track => vec![first],
+        EditScope::Album => {
+            candidates.sort();
+            candidates.dedup();
+            let mut tracks: Vec<LibraryTrack> = candidates
+                .iter()
+                .filter(|p| **p != selected && p.is_file())
+                .filter_map(|p| crate::playback::metadata::read_library_tags(p).ok())
+                .filter(|t| same_album(&first, t))
+                .collect();
+            tracks.insert(0, first);
+            tracks
+        }
+    };
+    app.open_tag_editor(TagForm::new(scope, tracks, app.overlay));
+}
+
+/// Same album and album artist; untagged files count as one album per folder.
+fn same_album(a: &LibraryTrack, b: &LibraryTrack) -> bool {
+    if a.album.trim().is_empty() {
+        return b.album.trim().is_empty() && a.path.parent() == b.path.parent();
+    }
+    a.album.trim() == b.album.trim() && a.grouping_artist().trim() == b.grouping_artist().trim()
+}
+
+/// Start writing the tag editor's changes in the background; `poll_tag_save` finishes up.
+fn save_tag_edits(app: &mut AppState, mode_manager: &mut ModeManager) {
+    if app.tag_save.is_some() {
+        return;
+    }
+    let Some(form) = app.tag_form.as_mut() else {
+        return;
+    };
+    let edits = match form.edits() {
+        Ok(e) => e,
+        Err(e) => {
+            form.error = Some(e);
+            return;
+        }
+    };
+    if edits.is_empty() {
+        app.close_overlay();
+        app.set_toast("Tags unchanged");
+        return;
+    }
+    let tracks = form.tracks.clone();
+
+    // Rewriting a file under its decoder breaks playback: let go of it until the write is done.
+    let mut resume = None;
+    let local = &mut mode_manager.local;
+    if app.player.mode == PlayMode::LocalPlayback && local.playback_state() != PlaybackState::Stopped {
+        if let Some(path) = app.playlist.current_path().filter(|p| tracks.iter().any(|t| t.path == **p)) {
+            let paused = local.playback_state() == PlaybackState::Paused;
+            resume = Some((path.clone(), local.position().unwrap_or(app.player.position), paused));
+            local.stop();
+        }
+    }
+    app.tag_save = Some(TagSave {
+        job: EditJob::spawn(tracks, edits),
+        resume,
+    });
+}
+
+/// Finish a tag editor write: bring the player and the library up to date, pick the playing
+/// track up again and report how it went.
+fn poll_tag_save(app: &mut AppState, mode_manager: &mut ModeManager) {
+    let Some(outcome) = app.tag_save.as_ref().and_then(|s| s.job.poll()) else {
+        return;
+    };
+    let resume = app.tag_save.take().and_then(|s| s.resume);
+    let written = outcome.written;
+    apply_retagged(app, mode_manager, outcome.retagged);
+
+    let mut play_error = None;
+    if let Some((path, pos, paused)) = resume {
+        // Only if nothing else was started meanwhile.
+        if app.playlist.current_path() == Some(&path) && mode_manager.local.playback_state() == PlaybackState::Stopped {
+            // `stop` kept the track loaded (and paused): reopen it where it was.
+            let local = &mut mode_manager.local;
+            let reopened = local.seek(pos).and_then(|_| if paused { Ok(()) } else { local.toggle_play_pause() });
+            if let Err(e) = reopened {
+                app.player.playback = PlaybackState::Stopped;
+                play_error = Some(e);
+            }
+        }
+    }
+
+    let mut msg = format!("Saved tags of {written} file(s)");
+    if !outcome.unsupported.is_empty() {
+        msg.push_str(&format!("; not supported: {}", outcome.unsupported.join(", ")));
+    }
+    if let Some((path, err)) = outcome.failed.first() {
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        msg.push_str(&format!("; {} failed ({name}: {err})", outcome.failed.len()));
+    }
+    if let Some(e) = play_error {
+        msg.push_str(&format!("; play error: {e}"));
+    }
+    let form_open = app.overlay == Overlay::TagEditor && app.tag_form.is_some();
+    if written == 0 && form_open {
+        // Nothing went through: stay in the editor so the values aren't lost.
+        if let Some(form) = app.tag_form.as_mut() {
+            form.error = Some(msg);
+        }
+        return;
+    }
+    if form_open {
+        app.close_overlay();
+    }
+    app.set_toast(msg);
+}
+
+/// Bring the player, the playlists and the library up to date with files whose tags were
+/// rewritten (`(before, after)` pairs), by the tag editor or the batch tagger.
+fn apply_retagged(app: &mut AppState, mode_manager: &mut ModeManager, retagged: Vec<(LibraryTrack, LibraryTrack)>) {
+    let mut library_changed = false;
+    for (old, new) in retagged {
+        refresh_edited_track(app, mode_manager, &old, &new);
+        if app.library_scanner.is_some() {
+            app.library_scan_retagged.push(new.clone());
+        }
+        library_changed |= app.library.replace_track(new);
+    }
+    if library_changed {
+        if let Err(e) = app.library.save() {
+            log::warn!("library save failed: {e}");
+        }
+        app.library_browser.reload(&app.library);
+        if app.library_browser.entry.is_some() && app.library_browser.tracks.is_empty() {
+            // The album / artist was renamed away from the entry being browsed.
+            app.library_browser.back(&app.library);
+        }
+    }
+}
+
+/// Show a track's edited tags in the player, the metadata cache and the lists titled from tags.
+fn refresh_edited_track(app: &mut AppState, mode_manager: &mut ModeManager, old: &LibraryTrack, new: &LibraryTrack) {
+    // Cleared tags keep what was shown (file name, or a fetched identification).
+    let apply = |m: &mut crate::app::state::TrackMetadata| {
+        for (dst, src) in [(&mut m.title, &new.title), (&mut m.artist, &new.artist), (&mut m.album, &new.album)] {
+            if !src.trim().is_empty() {
+                *dst = src.clone();
+            }
+        }
+    };
+    let path = new.path.as_path();
+    mode_manager.local.patch_cached_metadata(path, apply);
+    if app.playlist.current_path().is_some_and(|p| p == path) {
+        apply(&mut app.player.track);
+    }
+    // Folder lists show file names; only titles that came from the tag follow the edit.
+    if !old.title.trim().is_empty() {
+        let (from, to) = (old.display_title(), new.display_title());
+        let items = app.playlist.items.iter_mut().chain(app.playlist_view.items.iter_mut()).chain(app.up_next.iter_mut());
+        for item in items.filter(|i| i.path == path && i.title == from) {
+            item.title = to.clone();
+        }
+    }
+}
+
+/// Persist the playlist view after a reorder / cut / paste: the folder's `.order.toml`,
+/// or the user playlist file.
+fn save_playlist_view_order(app: &mut AppState, cut: &[crate::data::playlist::PlaylistItem]) {
+    if let Some(folder) = app.local_view_album_folder.as_deref() {
+        if let Err(e) = crate::playback::local_player::write_order_file(folder, &app.playlist_view, cut) {
+            app.set_toast(format!("Order save error: {e}"));
+        }
+    } else {
+        save_user_playlist_view(app);
+    }
+}
+
+/// Whether the playlist view can be edited (reordered, cut, pasted) right now.
+fn playlist_view_editable(app: &AppState) -> bool {
+    app.overlay == Overlay::Playlist && !app.up_next_view && app.player.mode == PlayMode::LocalPlayback
+}
+
+/// Select row `idx` (clamped) of the list shown in the playlist overlay.
+fn select_playlist_row(app: &mut AppState, idx: usize) {
+    if app.up_next_view {
+        app.up_next_selected = idx.min(app.up_next.len().saturating_sub(1));
+    } else {
+        app.playlist_view.selected = idx;
+        app.playlist_view.clamp_selected();
+        sync_playlists_when_viewing_playback(app);
+    }
+}
+
+/// `dd`: cut `n` tracks from the selection into the register.
+fn cut_playlist_items(app: &mut AppState, n: usize) {
+    let cut = if app.overlay == Overlay::Playlist && app.up_next_view {
+        let start = app.up_next_selected.min(app.up_next.len());
+        let end = (start + n).min(app.up_next.len());
+        let cut: Vec<_> = app.up_next.drain(start..end).collect();
+        app.up_next_selected = start.min(app.up_next.len().saturating_sub(1));
+        if !cut.is_empty() {
+            save_up_next(app);
+        }
+        cut
+    } else if playlist_view_editable(app) {
+        let start = app.playlist_view.selected;
+        let cut = app.playlist_view.remove_range(start..start + n);
+        if cut.is_empty() && app.playlist_view.current == Some(start) {
+            app.set_toast("The playing track can't be cut");
+            return;
+        }
+        if !cut.is_empty() {
+            save_playlist_view_order(app, &cut);
+            sync_playlists_when_viewing_playback(app);
+        }
+        cut
+    } else {
+        return;
+    };
+    match cut.as_slice() {
+        [] => return,
+        [one] => app.set_toast(format!("Cut: {}", one.title)),
+        many => app.set_toast(format!("Cut {} tracks", many.len())),
+    }
+    app.playlist_register = cut;
+}
+
+/// `p` / `P`: paste the register after / before the selection.
+fn paste_playlist_items(app: &mut AppState, before: bool) {
+    if app.overlay != Overlay::Playlist {
+        return;
+    }
+    if app.playlist_register.is_empty() {
+        app.set_toast("Nothing to paste (cut with dd)");
+        return;
+    }
+    let mut items = app.playlist_register.clone();
+    if app.up_next_view {
+        let at = if app.up_next.is_empty() || before {
+            app.up_next_selected.min(app.up_next.len())
+        } else {
+            app.up_next_selected + 1
+        };
+        app.up_next.splice(at..at, items);
+        app.up_next_selected = at;
+        save_up_next(app);
+        return;
+    }
+    if !playlist_view_editable(app) {
+        return;
+    }
+    if let Some(folder) = app.local_view_album_folder.as_deref() {
+        // A folder's order only holds its own tracks, each once.
+        let view = &app.playlist_view;
+        items.retain(|it| it.path.starts_with(folder) && !view.items.iter().any(|v| v.path == it.path));
+        if items.is_empty() {
+            app.set_toast("Nothing to paste here (tracks from another folder or already listed)");
+            return;
+        }
+    }
+    let at = if app.playlist_view.is_empty() || before {
+        app.playlist_view.selected
+    } else {
+        app.playlist_view.selected + 1
+    };
+    if app.playlist_view.insert_at(at, items) {
+        save_playlist_view_order(app, &[]);
+        sync_playlists_when_viewing_playback(app);
+    }
+}
+
+/// Counted Ctrl+Up / Ctrl+Down: move the selected track `delta` rows in one step.
+fn move_playlist_item_by(app: &mut AppState, delta: i64) {
+    if app.overlay == Overlay::Playlist && app.up_next_view {
+        let i = app.up_next_selected;
+        if i >= app.up_next.len() {
+            return;
+        }
+        let to = (i as i64 + delta).clamp(0, app.up_next.len() as i64 - 1) as usize;
+        if to != i {
+            let item = app.up_next.remove(i);
+            app.up_next.insert(to, item);
+            app.up_next_selected = to;
+            save_up_next(app);
+        }
+    } else if playlist_view_editable(app) {
+        let i = app.playlist_view.selected;
+        let to = (i as i64 + delta).max(0) as usize;
+        if app.playlist_view.move_range(i..i + 1, to) {
+            save_playlist_view_order(app, &[]);
+            sync_playlists_when_viewing_playback(app);
+        }
+    }
+}
+
+/// Apply a vim-style count ("5j", "3dd", "12G") to a key action. Motions repeat,
+/// `dd` cuts that many tracks, `gg`/`G` go to that track number.
+fn handle_counted_action(
+    app: &mut AppState,
+    mode_manager: &mut ModeManager,
+    system_volume: Option<&SystemVolume>,
+    action: Action,
+    count: Option<usize>,
+    layout: &UiLayout,
+) -> Result<()> {
+    let Some(n) = count else {
+        return handle_action(app, mode_manager, system_volume, action, layout);
+    };
+    match action {
+        Action::PlaylistCut => cut_playlist_items(app, n),
+        Action::PlaylistTop | Action::PlaylistBottom => select_playlist_row(app, n.saturating_sub(1)),
+        Action::PlaylistMoveItemUp => move_playlist_item_by(app, -(n as i64)),
+        Action::PlaylistMoveItemDown => move_playlist_item_by(app, n as i64),
+        Action::PlaylistUp
+        | Action::PlaylistDown
+        | Action::PlaylistHalfPage(_)
+        | Action::PlaylistPaste
+        | Action::PlaylistPasteBefore => {
+            for _ in 0..n {
+                handle_action(app, mode_manager, system_volume, action, layout)?;
+            }
+        }
+        _ => handle_action(app, mode_manager, system_volume, action, layout)?,
+    }
+    Ok(())
+}
+
+/// Persist the current user playlist after an edit in the playlist overlay.
+fn save_user_playlist_view(app: &mut AppState) {
+    let Some(path) = app.user_playlist.clone() else {
+        return;
+    };
+    if let Err(e) = playlist_file::write_playlist(&path, &app.playlist_view.items) {
+        app.set_toast(format!("Playlist save error: {e}"));
+    }
+}
+
+/// Handles Enter in the playlist name prompt (new / rename / save queue as).
+fn confirm_playlist_name(app: &mut AppState) {
+    let name = app.playlist_name_input.trim().to_string();
+    let mode = app.playlist_name_mode;
+    let back = if mode == PlaylistNameMode::SaveQueue { Overlay::None } else { Overlay::PlaylistManager };
+
+    let dst = match playlist_file::playlist_path_for_name(&name) {
+        Ok(p) => p,
+        Err(e) => {
+            app.set_toast(format!("Playlist error: {e}"));
+            return;
+        }
+    };
+
+    let result = match mode {
+        PlaylistNameMode::New | PlaylistNameMode::SaveQueue => {
+            if dst.exists() {
+                app.set_toast(format!("Playlist {} already exists", playlist_file::playlist_name(&dst)));
+                return;
+            }
+            let items = if mode == PlaylistNameMode::SaveQueue {
+                app.playlist_view.items.clone()
+            } else {
+                Vec::new()
+            };
+            playlist_file::write_playlist(&dst, &items).map(|_| {
+                app.user_playlist_target = Some(dst.clone());
+                format!("Saved playlist {}", playlist_file::playlist_name(&dst))
+            })
+        }
+        PlaylistNameMode::Rename => {
+            let Some(src) = app.playlist_manager_items.get(app.playlist_manager_selected).cloned() else {
+                app.overlay = back;
+                return;
+            };
+            // Keep the original format unless the new name spells out another extension.
+            let dst = if playlist_file::PlaylistFormat::from_path(std::path::Path::new(&name)).is_none() {
+                let ext = src.extension().and_then(|e| e.to_str()).unwrap_or(playlist_file::DEFAULT_PLAYLIST_EXT);
+                dst.with_extension(ext)
+            } else {
+                dst.clone()
+            };
+            if dst == src {
+                app.overlay = back;
+                return;
+            }
+            if dst.exists() {
+                app.set_toast(format!("Playlist {} already exists", playlist_file::playlist_name(&dst)));
+                return;
+            }
+            playlist_file::read_playlist(&src)
+                .and_then(|items| playlist_file::write_playlist(&dst, &items))
+                .and_then(|_| std::fs::remove_file(&src).map_err(Into::into))
+                .map(|_| {
+                    if app.user_playlist.as_ref() == Some(&src) {
+                        app.user_playlist = Some(dst.clone());
+                    }
+                    if app.user_playlist_target.as_ref() == Some(&src) {
+                        app.user_playlist_target = Some(dst.clone());
+                    }
+                    format!("Renamed to {}", playlist_file::playlist_name(&dst))
+                })
+        }
+    };
+
+    match result {
+        Ok(msg) => app.set_toast(msg),
+        Err(e) => app.set_toast(format!("Playlist error: {e}")),
+    }
+
+    if back == Overlay::PlaylistManager {
+        app.open_playlist_manager();
+        if let Some(i) = app.playlist_manager_items.iter().position(|p| *p == dst || p.file_stem() == dst.file_stem()) {
+            app.playlist_manager_selected = i;
+        }
+    } else {
+        app.overlay = back;
+    }
+}
+
 fn maybe_open_default_folder(app: &mut AppState, mode_manager: &mut ModeManager) {
     let raw = app.config.default_opening_folder.trim().to_string();
     if raw.is_empty() {
@@ -113,16 +954,49 @@ pub fn run(app: &mut AppState) -> Result<()> {
     let mut mode_manager = ModeManager::new();
 
     // Prefer cava for system-wide visualization (keeps our renderer/style; cava only provides bars).
-    // If cava isn't installed, we leave the spectrum empty.
+    // If cava isn't installed, fall back to the in-process FFT analyzer fed from our own
+    // playback (local mode) or a cpal loopback capture (system monitor).
     let mut cava: Option<CavaRunner> = None;
     let mut cava_cfg: Option<CavaConfig> = None;
+    let mut native: Option<SpectrumAnalyzer> = None;
+    let mut capture: Option<AudioCapture> = None;
 
     maybe_open_default_folder(app, &mut mode_manager);
+    app.up_next = playlist_file::read_up_next();
+    app.library = crate::data::library::Library::load();
+    if !app.library_roots().is_empty() {
+        // Incremental: only new/changed files are read.
+        app.start_library_scan();
+    }
 
     let system_volume = SystemVolume::try_new().ok();
 
+    let control_server = if app.config.control_socket {
+        match ControlServer::start(&control::socket_path()) {
+            Ok(server) => Some(server),
+            Err(e) => {
+                log::warn!("control socket disabled: {e}");
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let mut mpris_server = if app.config.mpris_server {
+        match MprisServer::start() {
+            Ok(server) => Some(server),
+            Err(e) => {
+                log::warn!("mpris server disabled: {e}");
+                None
+            }
+        }
+    } else {
+        None
+    };
+
     let mut last_spectrum = Instant::now();
     let mut last_mpris = Instant::now();
+    let mut last_theme_check = Instant::now();
 
     let mut last_layout = UiLayout::default();
 
@@ -134,15 +1008,27 @@ pub fn run(app: &mut AppState) -> Result<()> {
 
         // poll input (non-blocking-ish)
         // apply async remote metadata results (lyrics/cover/fingerprint)
+        poll_library_scan(app);
+        poll_tagger(app, &mut mode_manager);
+        poll_tag_save(app, &mut mode_manager);
+        if app.playlist_search.as_mut().is_some_and(|s| s.poll(&app.playlist_view)) {
+            apply_playlist_search_selection(app);
+        }
         let results = app.drain_remote_fetch_results();
         if !results.is_empty() {
             apply_remote_fetch_results(app, &mut mode_manager, results);
         }
+        if let Some(server) = control_server.as_ref() {
+            handle_control_requests(app, &mut mode_manager, system_volume.as_ref(), server.drain(), &last_layout);
+        }
+        if let Some(server) = mpris_server.as_ref() {
+            handle_control_requests(app, &mut mode_manager, system_volume.as_ref(), server.drain(), &last_layout);
+        }
         while event::poll(Duration::from_millis(0))? {
             match event::read()? {
                 Event::Key(k) => {
-                    let action = map_key(k, app.overlay);
-                    handle_action(app, &mut mode_manager, system_volume.as_ref(), action, &last_layout)?;
+                    let (action, count) = map_key_with_count(k, app.overlay, &app.keymap, &mut app.key_pending);
+                    handle_counted_action(app, &mut mode_manager, system_volume.as_ref(), action, count, &last_layout)?;
                 }
                 Event::Mouse(m) => {
                     let action = map_mouse(m);
@@ -158,6 +1044,20 @@ pub fn run(app: &mut AppState) -> Result<()> {
 
         ensure_cava(&mut cava, &mut cava_cfg, desired_cava_config(app, &last_layout));
 
+        // Live-reload the theme file while it is being edited. A broken save keeps the old colors.
+        if frame_start.duration_since(last_theme_check) >= THEME_RELOAD_INTERVAL {
+            last_theme_check = frame_start;
+            match ThemeLoader::reload_if_changed(&app.theme) {
+                Some(Ok(theme)) => app.theme = theme,
+                Some(Err(e)) => {
+                    app.set_toast(format!("Theme error: {e:#}"));
+                    // Don't repeat the toast until the file changes again.
+                    app.theme.modified = std::fs::metadata(&app.theme.path).and_then(|m| m.modified()).ok();
+                }
+                None => {}
+            }
+        }
+
         if app.config.visualize == VisualizeMode::Bars {
             let bars = desired_bar_count(app, &last_layout);
             ensure_bar_buffers(app, bars);
@@ -224,24 +1124,34 @@ pub fn run(app: &mut AppState) -> Result<()> {
         {
             last_spectrum = frame_start;
 
+            if cava.is_none() {
+                let desired = desired_cava_config(app, &last_layout);
+                update_native_spectrum(app, &mode_manager, &mut native, &mut capture, desired, frame_start);
+            } else {
+                native = None;
+                capture = None;
+            }
+
+            let latest = match cava.as_ref() {
+                Some(c) => Some((c.latest_stereo_bars(), c.latest_bars())),
+                None => native.as_ref().map(|a| (a.latest_stereo_bars(), a.latest_bars())),
+            };
+
             match app.config.visualize {
                 VisualizeMode::Bars => {
-                    if let Some(c) = cava.as_ref() {
-                        let (l, r) = c.latest_stereo_bars();
+                    if let Some(((l, r), raw)) = latest {
                         app.spectrum.bars_left = l;
                         app.spectrum.bars_right = r;
-                        let raw = c.latest_bars();
                         app.spectrum.bars = app.spectrum_bar_smoother.apply(&raw);
                     } else {
                         clear_spectrum(app);
                     }
                 }
                 VisualizeMode::Oscilloscope => {
-                    if let Some(c) = cava.as_ref() {
-                        let (l, r) = c.latest_stereo_bars();
+                    if let Some(((l, r), raw)) = latest {
                         fill_fixed_bars(&mut app.spectrum.stereo_left, &l);
                         fill_fixed_bars(&mut app.spectrum.stereo_right, &r);
-                        app.spectrum.bars = c.latest_bars();
+                        app.spectrum.bars = raw;
                     } else {
                         clear_spectrum(app);
                     }
@@ -255,6 +1165,20 @@ pub fn run(app: &mut AppState) -> Result<()> {
 
         // local player position update
         if app.player.mode == PlayMode::LocalPlayback {
+            // Settings changed in the local audio modal take effect on the next frame.
+            mode_manager
+                .local
+                .set_crossfade(Duration::from_millis(app.config.crossfade_ms as u64));
+            mode_manager
+                .local
+                .set_replay_gain(app.config.replay_gain, app.config.replay_gain_scan);
+            mode_manager.local.poll_loudness();
+
+            maybe_preload_next_track(app, &mut mode_manager);
+            if let Some((path, track)) = mode_manager.local.poll_advance() {
+                handle_local_track_advanced(app, &path, track);
+            }
+
             // Detect end-of-track and stop position accumulation.
             let just_finished = mode_manager.local.poll_end();
             if just_finished {
@@ -263,11 +1187,13 @@ pub fn run(app: &mut AppState) -> Result<()> {
             if let Some(pos) = mode_manager.local.position() {
                 app.player.position = pos;
             }
+            enforce_ab_loop(app, &mut mode_manager);
             if let Some(dur) = mode_manager.local.duration() {
                 app.player.track.duration = dur;
             }
             app.player.volume = mode_manager.local.volume();
             app.player.playback = mode_manager.local.playback_state();
+            app.player.seeks = mode_manager.local.seeks();
         }
 
         if app.player.mode == PlayMode::SystemMonitor {
@@ -280,6 +1206,10 @@ pub fn run(app: &mut AppState) -> Result<()> {
 
         app.tick(frame_start);
 
+        if let Some(server) = mpris_server.as_mut() {
+            server.update(app);
+        }
+
         // draw
         last_layout = tui.draw(app)?;
 
@@ -295,47 +1225,178 @@ pub fn run(app: &mut AppState) -> Result<()> {
         }
     }
 
+    app.flush_lookup_cache();
     tui.exit()?;
     disable_raw_mode()?;
     Ok(())
 }
 
+const THEME_RELOAD_INTERVAL: Duration = Duration::from_millis(500);
+
 fn fps_to_dt(fps: u32) -> Duration {
     let fps = fps.clamp(30, 60);
     Duration::from_millis((1000 / fps) as u64)
 }
 
+/// Which playlist entry follows the current one when it ends on its own.
+fn next_local_index(app: &mut AppState) -> Option<usize> {
+    match app.player.repeat_mode {
+        RepeatMode::Sequence => app.playlist.next_index_no_wrap(),
+        RepeatMode::LoopAll => app.playlist.next_index_sequence(),
+        RepeatMode::LoopOne => app.playlist.current,
+        // Album shuffle plays each album in order; the album switch happens at its end.
+        RepeatMode::Shuffle if shuffle_albums_active(app) => app.playlist.next_index_no_wrap(),
+        RepeatMode::Shuffle => app.shuffle.peek_next(&app.playlist),
+    }
+}
+
+fn shuffle_albums_active(app: &AppState) -> bool {
+    app.config.shuffle_albums
+        && app.local_folder_kind == LocalFolderKind::MultiAlbum
+        && app.local_album_folders.len() > 1
+        && app.user_playlist.is_none()
+}
+
+/// Remember what actually started playing (shuffle order + history for Prev).
+fn note_local_started(app: &mut AppState, path: &std::path::Path) {
+    let idx = app.playlist.items.iter().position(|it| it.path == path);
+    app.shuffle.record(&app.playlist, path, idx);
+}
+
+/// Make album `idx` of a MultiAlbum root the playback playlist (album shuffle / Prev through history).
+fn switch_playback_album(app: &mut AppState, mode_manager: &mut ModeManager, idx: usize) -> bool {
+    let Some(folder) = app.local_album_folders.get(idx).cloned() else {
+        return false;
+    };
+    let Ok(mut pl) = mode_manager.local.load_playlist_only(&folder, false) else {
+        return false;
+    };
+    if pl.items.is_empty() {
+        return false;
+    }
+    pl.selected = 0;
+    pl.current = Some(0);
+
+    let viewing_playback = app.local_view_album_folder.is_some() && app.local_view_album_folder == app.local_folder;
+    app.playlist = pl;
+    app.local_folder = Some(folder.clone());
+    if viewing_playback {
+        app.playlist_view = app.playlist.clone();
+        app.local_view_album_index = idx;
+        app.local_view_album_folder = Some(folder.clone());
+        let cover = crate::playback::metadata::read_cover_from_folder(&folder);
+        app.local_view_album_cover = cover.as_ref().map(|(b, _)| b.clone());
+        app.local_view_album_cover_hash = cover.map(|(_, h)| Some(h)).unwrap_or(None);
+    }
+    if let Some(root) = app.local_root_folder.as_deref() {
+        let _ = crate::playback::local_player::write_last_album(root, &folder);
+    }
+    true
+}
+
+/// Album shuffle: move on to the next album of the shuffled album order.
+fn next_shuffled_album(app: &mut AppState, mode_manager: &mut ModeManager) -> Option<usize> {
+    let count = app.local_album_folders.len();
+    let current = app
+        .local_folder
+        .as_ref()
+        .and_then(|f| app.local_album_folders.iter().position(|a| a == f))
+        .unwrap_or(0);
+    // Skip albums that fail to load, but give up after one full round.
+    for _ in 0..count {
+        let idx = app.shuffle.next_album(count, current)?;
+        if switch_playback_album(app, mode_manager, idx) {
+            return Some(0);
+        }
+    }
+    None
+}
+
+/// Shuffle Prev: walk back through what actually played.
+fn shuffle_prev_index(app: &mut AppState, mode_manager: &mut ModeManager) -> Option<usize> {
+    while let Some(path) = app.shuffle.back(&app.playlist) {
+        if let Some(i) = app.playlist.items.iter().position(|it| it.path == path) {
+            return Some(i);
+        }
+        // Played from another album of the MultiAlbum root.
+        let album = path
+            .parent()
+            .and_then(|p| app.local_album_folders.iter().position(|a| a == p));
+        if let Some(a) = album {
+            if app.local_folder_kind == LocalFolderKind::MultiAlbum && switch_playback_album(app, mode_manager, a) {
+                if let Some(i) = app.playlist.items.iter().position(|it| it.path == path) {
+                    return Some(i);
+                }
+            }
+        }
+    }
+    // Nothing to go back to: restart the current track.
+    app.playlist.current
+}
+
+fn save_up_next(app: &mut AppState) {
+    if let Err(e) = playlist_file::write_up_next(&app.up_next) {
+        app.set_toast(format!("Queue save error: {e}"));
+    }
+}
+
+/// Pop the next playable entry from the up-next queue.
+/// If the track is also in the playback playlist, the playlist continues after it.
+fn take_up_next(app: &mut AppState) -> Option<PathBuf> {
+    let mut taken = None;
+    while !app.up_next.is_empty() {
+        let it = app.up_next.remove(0);
+        if it.path.is_file() {
+            taken = Some(it.path);
+            break;
+        }
+    }
+    app.up_next_selected = app.up_next_selected.min(app.up_next.len().saturating_sub(1));
+    save_up_next(app);
+
+    let path = taken?;
+    if let Some(i) = app.playlist.items.iter().position(|it| it.path == path) {
+        app.playlist.current = Some(i);
+    }
+    Some(path)
+}
+
 fn handle_local_track_finished(app: &mut AppState, mode_manager: &mut ModeManager) {
     // 自动续播仅用于本地播放。
     if app.player.mode != PlayMode::LocalPlayback {
         return;
     }
-    if app.playlist.items.is_empty() {
+    if app.playlist.items.is_empty() && app.up_next.is_empty() {
         return;
     }
 
     let from = CoverSnapshot::from(&app.player.track);
-    let next = match app.player.repeat_mode {
-        RepeatMode::Sequence => app.playlist.next_index_no_wrap(),
-        RepeatMode::LoopAll => app.playlist.next_index_sequence(),
-        RepeatMode::LoopOne => app.playlist.current,
-        RepeatMode::Shuffle => pick_shuffle_index(&app.playlist),
-    };
 
-    let Some(i) = next else {
-        // Sequence mode at end: stop.
-        app.player.playback = PlaybackState::Stopped;
-        return;
-    };
-
-    app.playlist.current = Some(i);
-    let Some(path) = app.playlist.current_path().cloned() else {
-        app.player.playback = PlaybackState::Stopped;
-        return;
+    // The up-next queue always wins over the repeat mode.
+    let path = match take_up_next(app) {
+        Some(p) => p,
+        None => {
+            let mut next = next_local_index(app);
+            if next.is_none() && app.player.repeat_mode == RepeatMode::Shuffle && shuffle_albums_active(app) {
+                next = next_shuffled_album(app, mode_manager);
+            }
+            let Some(i) = next else {
+                // Sequence mode at end: stop.
+                app.player.playback = PlaybackState::Stopped;
+                return;
+            };
+            app.playlist.current = Some(i);
+            let Some(p) = app.playlist.current_path().cloned() else {
+                app.player.playback = PlaybackState::Stopped;
+                return;
+            };
+            p
+        }
     };
 
     match mode_manager.local.play_file(&path) {
         Ok(track) => {
+            note_local_started(app, &path);
             app.player.track = track;
             let to = CoverSnapshot::from(&app.player.track);
             app.start_cover_anim(from, to, -1, Instant::now());
@@ -353,6 +1414,64 @@ fn handle_local_track_finished(app: &mut AppState, mode_manager: &mut ModeManage
     }
 }
 
+/// What plays after the current track: the first playable up-next entry, else the playlist's next.
+fn upcoming_local_path(app: &mut AppState) -> Option<PathBuf> {
+    let queued = app.up_next.iter().find(|it| it.path.is_file()).map(|it| it.path.clone());
+    queued.or_else(|| next_local_index(app).and_then(|i| app.playlist.items.get(i)).map(|it| it.path.clone()))
+}
+
+/// Gapless/crossfade: hand the next track to the player shortly before the current one ends.
+fn maybe_preload_next_track(app: &mut AppState, mode_manager: &mut ModeManager) {
+    if app.player.mode != PlayMode::LocalPlayback {
+        return;
+    }
+    // The queue (or playlist order) changed after the successor was preloaded: queue the new one.
+    if let Some(preloaded) = mode_manager.local.preloaded_path() {
+        if upcoming_local_path(app).as_deref() != Some(preloaded) {
+            mode_manager.local.drop_preloaded();
+        }
+    }
+    if (app.playlist.items.is_empty() && app.up_next.is_empty()) || !mode_manager.local.wants_preload() {
+        return;
+    }
+    let Some(path) = upcoming_local_path(app) else {
+        return;
+    };
+
+    match mode_manager.local.preload_next(&path) {
+        Ok(Some(track)) => handle_local_track_advanced(app, &path, track),
+        Ok(None) => {}
+        Err(e) => log::warn!("preload failed for {}: {e}", path.display()),
+    }
+}
+
+/// The player moved on to a preloaded track by itself; sync playlist/UI state.
+fn handle_local_track_advanced(app: &mut AppState, path: &std::path::Path, track: crate::app::state::TrackMetadata) {
+    let from = CoverSnapshot::from(&app.player.track);
+    if app.up_next.iter().any(|it| it.path == path) {
+        // Consumes the queue entry (and skips missing files ahead of it) like a normal advance.
+        let _ = take_up_next(app);
+    } else if let Some(i) = app.playlist.items.iter().position(|it| it.path == path) {
+        app.playlist.current = Some(i);
+    }
+    if app.local_view_album_folder.is_some() && app.local_view_album_folder == app.local_folder {
+        app.playlist_view.current = app.playlist.current;
+    }
+
+    note_local_started(app, path);
+
+    app.player.track = track;
+    app.player.position = Duration::from_secs(0);
+    let to = CoverSnapshot::from(&app.player.track);
+    app.start_cover_anim(from, to, -1, Instant::now());
+
+    app.queue_remote_fetch(Some(path));
+
+    if let Some(folder) = app.local_folder.as_deref() {
+        let _ = crate::playback::local_player::write_last_opened_song(folder, path);
+    }
+}
+
 fn handle_action(
     app: &mut AppState,
     mode_manager: &mut ModeManager,
@@ -381,52 +1500,190 @@ fn handle_action(
         Action::OpenHelpModal => {
             app.overlay = Overlay::HelpModal;
         }
-        Action::OpenEqModal => {
-            // 需求：均衡器仅对本地音频播放生效
-            if app.player.mode == PlayMode::LocalPlayback {
-                app.overlay = Overlay::EqModal;
-                app.eq_selected = 0;
-            } else {
-                app.set_toast("EQ only for local playback");
+        Action::OpenEqModal => {
+            // 需求：均衡器仅对本地音频播放生效
+            if app.player.mode == PlayMode::LocalPlayback {
+                app.overlay = Overlay::EqModal;
+                app.eq_selected = 0;
+            } else {
+                app.set_toast("EQ only for local playback");
+            }
+        }
+        Action::EqSetBandDb { band, db } => {
+            if app.overlay == Overlay::EqModal {
+                app.eq_selected = band.min(crate::app::state::EQ_BANDS.saturating_sub(1));
+                let db = db.clamp(-12.0, 12.0);
+                if app.eq_selected < crate::app::state::EQ_BANDS {
+                    app.eq.bands_db[app.eq_selected] = db;
+                }
+
+                // Persist EQ to config (best-effort).
+                app.config.eq_bands_db = app.eq.bands_db;
+                let _ = app.config.save();
+
+                // 需求：均衡器自动生效
+                if app.player.mode == PlayMode::LocalPlayback {
+                    let _ = mode_manager.local.set_eq(app.eq);
+                }
+            }
+        }
+        Action::EqResetDefault => {
+            if app.overlay == Overlay::EqModal {
+                app.eq = crate::app::state::EqSettings::default();
+                app.eq_selected = 0;
+
+                // Persist reset.
+                app.config.eq_bands_db = app.eq.bands_db;
+                let _ = app.config.save();
+
+                if app.player.mode == PlayMode::LocalPlayback {
+                    let _ = mode_manager.local.set_eq(app.eq);
+                }
+            }
+        }
+        Action::FolderChar(_) | Action::FolderBackspace if app.overlay == Overlay::PlaylistSearch => {
+            if let Some(search) = app.playlist_search.as_mut() {
+                match action {
+                    Action::FolderChar(c) => search.query.push(c),
+                    _ => {
+                        search.query.pop();
+                    }
+                }
+                search.update(&app.playlist_view);
+            }
+            apply_playlist_search_selection(app);
+        }
+        Action::PlaylistUp | Action::PlaylistDown if app.overlay == Overlay::PlaylistSearch => {
+            if let Some(search) = app.playlist_search.as_mut() {
+                search.move_by(if action == Action::PlaylistUp { -1 } else { 1 });
+            }
+            apply_playlist_search_selection(app);
+        }
+        Action::PlaylistSelect(_) if app.overlay == Overlay::PlaylistSearch => {}
+        Action::PlaylistSearchStart => {
+            if app.overlay == Overlay::Playlist && !app.up_next_view && !app.playlist_view.items.is_empty() {
+                let local = &mode_manager.local;
+                let cached = |p: &std::path::Path| local.peek_cached_metadata(p).map(|m| (m.artist.clone(), m.album.clone()));
+                app.playlist_search = Some(PlaylistSearch::new(&app.playlist_view, &app.library, cached));
+                app.overlay = Overlay::PlaylistSearch;
+            }
+        }
+        Action::FolderChar(c) if app.overlay == Overlay::LibraryFilter => {
+            app.library_browser.filter.push(c);
+            app.library_browser.apply_filter(&app.library);
+        }
+        Action::FolderBackspace if app.overlay == Overlay::LibraryFilter => {
+            app.library_browser.filter.pop();
+            app.library_browser.apply_filter(&app.library);
+        }
+        Action::ModalUp | Action::ModalDown
+            if app.overlay == Overlay::Library || app.overlay == Overlay::LibraryFilter =>
+        {
+            app.library_browser.move_by(if action == Action::ModalUp { -1 } else { 1 });
+        }
+        Action::OpenLibrary => {
+            app.open_library();
+        }
+        Action::ToggleLyricsView => {
+            if app.overlay == Overlay::Lyrics {
+                app.close_overlay();
+            } else {
+                app.open_lyrics_view();
+            }
+        }
+        Action::CycleLyricTracks => app.cycle_lyric_layout(),
+        Action::LyricsOffset(delta) => nudge_lyrics_offset(app, mode_manager, delta),
+        Action::LyricsScroll(lines) => app.scroll_lyrics(lines),
+        Action::ToggleLyricsEstimate => app.toggle_lyrics_estimated_sync(),
+        Action::LyricSyncStart => {
+            let path = app.playlist.current_path().cloned();
+            match (path, app.player.track.lyrics.first()) {
+                (Some(path), Some(original)) if app.player.mode == PlayMode::LocalPlayback => {
+                    app.lyric_sync = Some(LyricSync::new(path, &original.lines));
+                    app.overlay = Overlay::LyricSync;
+                }
+                _ if app.player.mode != PlayMode::LocalPlayback => app.set_toast("Lyric sync: Local only"),
+                _ => app.set_toast("No lyrics to sync"),
+            }
+        }
+        Action::LyricSyncTap => {
+            let pos_ms = mode_manager.local.position().unwrap_or(app.player.position).as_millis() as u64;
+            if let Some(sync) = app.lyric_sync.as_mut() {
+                if sync.stamp(pos_ms) && sync.is_done() {
+                    app.set_toast("All lines stamped (Ctrl+S to save)");
+                }
+            }
+        }
+        Action::LyricSyncUndo => {
+            if let Some(sync) = app.lyric_sync.as_mut() {
+                sync.undo();
+            }
+        }
+        Action::LyricSyncSave => save_lyric_sync(app, mode_manager),
+        Action::TagFolder => {
+            if app.overlay == Overlay::Playlist && !app.up_next_view {
+                open_tagger_for_view(app);
+            }
+        }
+        Action::TagToggle => {
+            if let Some(review) = app.tag_review.as_mut() {
+                review.toggle_selected();
+            }
+        }
+        Action::TagToggleAll => {
+            if let Some(review) = app.tag_review.as_mut() {
+                review.toggle_all();
             }
         }
-        Action::EqSetBandDb { band, db } => {
-            if app.overlay == Overlay::EqModal {
-                app.eq_selected = band.min(crate::app::state::EQ_BANDS.saturating_sub(1));
-                let db = db.clamp(-12.0, 12.0);
-                if app.eq_selected < crate::app::state::EQ_BANDS {
-                    app.eq.bands_db[app.eq_selected] = db;
-                }
-
-                // Persist EQ to config (best-effort).
-                app.config.eq_bands_db = app.eq.bands_db;
-                let _ = app.config.save();
-
-                // 需求：均衡器自动生效
-                if app.player.mode == PlayMode::LocalPlayback {
-                    let _ = mode_manager.local.set_eq(app.eq);
-                }
+        Action::TagDryRun => {
+            if let Some(review) = app.tag_review.as_mut() {
+                review.dry_run = !review.dry_run;
             }
         }
-        Action::EqResetDefault => {
-            if app.overlay == Overlay::EqModal {
-                app.eq = crate::app::state::EqSettings::default();
-                app.eq_selected = 0;
-
-                // Persist reset.
-                app.config.eq_bands_db = app.eq.bands_db;
-                let _ = app.config.save();
-
-                if app.player.mode == PlayMode::LocalPlayback {
-                    let _ = mode_manager.local.set_eq(app.eq);
+        Action::TagUndo => {
+            if let Some(review) = app.tag_review.as_mut() {
+                if !review.start_undo() && review.busy().is_none() {
+                    app.set_toast("Tagger: nothing to undo");
                 }
             }
         }
+        Action::EditTags | Action::EditAlbumTags => {
+            let scope = if action == Action::EditTags { EditScope::Track } else { EditScope::Album };
+            open_tag_editor(app, scope);
+        }
+        Action::ModalUp | Action::ModalDown if app.overlay == Overlay::TagEditor => {
+            if let Some(form) = app.tag_form.as_mut() {
+                form.move_by(if action == Action::ModalUp { -1 } else { 1 });
+            }
+        }
+        Action::ModalUp | Action::ModalDown if app.overlay == Overlay::Tagger => {
+            if let Some(review) = app.tag_review.as_mut() {
+                review.move_by(if action == Action::ModalUp { -1 } else { 1 });
+            }
+        }
+        Action::LibraryBack => {
+            if !app.library_browser.back(&app.library) {
+                app.close_overlay();
+            }
+        }
+        Action::LibraryPage(dir) => {
+            app.library_browser.move_by(dir * 20);
+        }
+        Action::LibraryFilterStart => {
+            app.overlay = Overlay::LibraryFilter;
+        }
+        Action::LibraryRescan => {
+            app.start_library_scan();
+        }
         Action::FolderChar(c) => {
             if app.overlay == Overlay::FolderInput {
                 app.folder_input.buf.push(c);
             } else if app.overlay == Overlay::AcoustIdModal {
                 app.acoustid_input.push(c);
+            } else if app.overlay == Overlay::PlaylistNameInput {
+                app.playlist_name_input.push(c);
+            } else if let Some(form) = app.tag_form.as_mut().filter(|_| app.overlay == Overlay::TagEditor) {
+                form.push(c);
             }
         }
         Action::FolderBackspace => {
@@ -434,6 +1691,10 @@ fn handle_action(
                 app.folder_input.buf.pop();
             } else if app.overlay == Overlay::AcoustIdModal {
                 app.acoustid_input.pop();
+            } else if app.overlay == Overlay::PlaylistNameInput {
+                app.playlist_name_input.pop();
+            } else if let Some(form) = app.tag_form.as_mut().filter(|_| app.overlay == Overlay::TagEditor) {
+                form.pop();
             }
         }
         Action::CloseOverlay => {
@@ -448,11 +1709,137 @@ fn handle_action(
                 || app.overlay == Overlay::LocalAudioSettingsModal
                 || app.overlay == Overlay::AboutModal
             {
+                app.lookup_cache_purge_armed = false;
                 app.overlay = Overlay::SettingsModal;
+            } else if app.overlay == Overlay::PlaylistNameInput && app.playlist_name_mode != PlaylistNameMode::SaveQueue {
+                app.overlay = Overlay::PlaylistManager;
+            } else if app.overlay == Overlay::PlaylistSearch {
+                // Leave search; the selection stays on the last match.
+                app.playlist_search = None;
+                app.overlay = Overlay::Playlist;
+            } else if app.overlay == Overlay::LibraryFilter {
+                app.library_browser.filter.clear();
+                app.library_browser.apply_filter(&app.library);
+                app.overlay = Overlay::Library;
             } else {
                 app.close_overlay();
             }
         }
+        Action::OpenPlaylistManager => {
+            app.open_playlist_manager();
+        }
+        Action::PlaylistManagerNew => {
+            if app.overlay == Overlay::PlaylistManager {
+                app.open_playlist_name_input(PlaylistNameMode::New, "");
+            }
+        }
+        Action::PlaylistManagerRename => {
+            if app.overlay == Overlay::PlaylistManager {
+                if let Some(p) = app.playlist_manager_items.get(app.playlist_manager_selected) {
+                    let name = playlist_file::playlist_name(p);
+                    app.open_playlist_name_input(PlaylistNameMode::Rename, &name);
+                }
+            }
+        }
+        Action::PlaylistManagerDelete => {
+            if app.overlay == Overlay::PlaylistManager {
+                if let Some(p) = app.playlist_manager_items.get(app.playlist_manager_selected).cloned() {
+                    // Require a second press to actually delete.
+                    if app.playlist_manager_delete_armed.as_ref() != Some(&p) {
+                        app.set_toast(format!("Press d again to delete {}", playlist_file::playlist_name(&p)));
+                        app.playlist_manager_delete_armed = Some(p);
+                    } else {
+                        match std::fs::remove_file(&p) {
+                            Ok(()) => {
+                                app.set_toast(format!("Deleted {}", playlist_file::playlist_name(&p)));
+                                if app.user_playlist_target.as_ref() == Some(&p) {
+                                    app.user_playlist_target = None;
+                                }
+                                if app.user_playlist.as_ref() == Some(&p) {
+                                    app.user_playlist = None;
+                                }
+                            }
+                            Err(e) => app.set_toast(format!("Delete error: {e}")),
+                        }
+                        app.open_playlist_manager();
+                    }
+                }
+            }
+        }
+        Action::PlaylistAddToUserPlaylist => {
+            if app.overlay == Overlay::Playlist {
+                let Some(item) = app.playlist_view.items.get(app.playlist_view.selected).cloned() else {
+                    return Ok(());
+                };
+                let Some(target) = app.user_playlist_target.clone() else {
+                    app.set_toast("No target playlist (Ctrl+P to create one)");
+                    return Ok(());
+                };
+                let name = playlist_file::playlist_name(&target);
+                if app.user_playlist.as_ref() == Some(&target) && app.local_view_album_folder.is_none() {
+                    app.set_toast(format!("Already viewing {name}"));
+                    return Ok(());
+                }
+                let res = playlist_file::read_playlist(&target).and_then(|mut items| {
+                    items.push(item.clone());
+                    playlist_file::write_playlist(&target, &items)
+                });
+                match res {
+                    Ok(()) => app.set_toast(format!("Added {} to {name}", item.title)),
+                    Err(e) => app.set_toast(format!("Playlist error: {e}")),
+                }
+            }
+        }
+        Action::PlaylistRemoveItem if app.up_next_view => {
+            if app.up_next_selected < app.up_next.len() {
+                app.up_next.remove(app.up_next_selected);
+                app.up_next_selected = app.up_next_selected.min(app.up_next.len().saturating_sub(1));
+                save_up_next(app);
+            }
+        }
+        Action::PlaylistRemoveItem => {
+            if app.overlay == Overlay::Playlist && app.user_playlist.is_some() && app.local_view_album_folder.is_none() {
+                let idx = app.playlist_view.selected;
+                if app.playlist_view.remove_at(idx).is_some() {
+                    save_user_playlist_view(app);
+                    sync_playlists_when_viewing_playback(app);
+                }
+            }
+        }
+        Action::QueuePlayNext | Action::QueueAppend => {
+            if app.overlay == Overlay::Playlist && !app.up_next_view {
+                let Some(item) = app.playlist_view.items.get(app.playlist_view.selected).cloned() else {
+                    return Ok(());
+                };
+                if action == Action::QueuePlayNext {
+                    app.set_toast(format!("Play next: {}", item.title));
+                    app.up_next.insert(0, item);
+                } else {
+                    app.set_toast(format!("Queued: {} ({} up next)", item.title, app.up_next.len() + 1));
+                    app.up_next.push(item);
+                }
+                save_up_next(app);
+            }
+        }
+        Action::QueueClear => {
+            if app.overlay == Overlay::Playlist && app.up_next_view && !app.up_next.is_empty() {
+                app.up_next.clear();
+                app.up_next_selected = 0;
+                save_up_next(app);
+                app.set_toast("Queue cleared");
+            }
+        }
+        Action::ToggleUpNextView => {
+            if app.overlay == Overlay::Playlist {
+                app.up_next_view = !app.up_next_view;
+                app.up_next_selected = app.up_next_selected.min(app.up_next.len().saturating_sub(1));
+            }
+        }
+        Action::SaveQueueAsPlaylist => {
+            if app.overlay == Overlay::Playlist && !app.playlist_view.items.is_empty() {
+                app.open_playlist_name_input(PlaylistNameMode::SaveQueue, "");
+            }
+        }
         Action::TogglePlaylist => {
             if app.overlay == Overlay::Playlist {
                 app.playlist_slide_target_x = -(layout.left_width as i16);
@@ -471,6 +1858,9 @@ fn handle_action(
                     let cover = crate::playback::metadata::read_cover_from_folder(folder);
                     app.local_view_album_cover = cover.as_ref().map(|(b, _)| b.clone());
                     app.local_view_album_cover_hash = cover.map(|(_, h)| Some(h)).unwrap_or(None);
+                } else {
+                    app.local_view_album_cover = None;
+                    app.local_view_album_cover_hash = None;
                 }
                 app.playlist_album_anim = None;
 
@@ -496,10 +1886,35 @@ fn handle_action(
                         return Ok(());
                     }
                     let p = PathBuf::from(&folder);
-                    if let Err(e) = open_local_folder(app, mode_manager, &p) {
-                        app.set_toast(format!("Folder error: {e}"));
+                    if let Err(e) = open_folder_or_playlist(app, mode_manager, &p) {
+                        let kind = if playlist_file::is_playlist_file(&p) { "Playlist" } else { "Folder" };
+                        app.set_toast(format!("{kind} error: {e}"));
+                    }
+                }
+                // Jump straight to the selected queue entry.
+                Overlay::Playlist if app.up_next_view && app.up_next_selected < app.up_next.len() => {
+                    let skipped: Vec<_> = app.up_next.drain(..app.up_next_selected).collect();
+                    app.up_next_selected = 0;
+                    let from = CoverSnapshot::from(&app.player.track);
+                    if let Some(path) = take_up_next(app) {
+                        mode_manager.pause_other(PlayMode::LocalPlayback);
+                        match mode_manager.local.play_file(&path) {
+                            Ok(track) => {
+                                note_local_started(app, &path);
+                                app.player.mode = PlayMode::LocalPlayback;
+                                app.player.track = track;
+                                let to = CoverSnapshot::from(&app.player.track);
+                                app.start_cover_anim(from, to, -1, Instant::now());
+                                app.queue_remote_fetch(Some(&path));
+                            }
+                            Err(e) => app.set_toast(format!("Play error: {e}")),
+                        }
                     }
+                    // Entries above the chosen one stay queued.
+                    app.up_next.splice(0..0, skipped);
+                    save_up_next(app);
                 }
+                Overlay::Playlist if app.up_next_view => {}
                 Overlay::Playlist => {
                     app.playlist_view.set_current_selected();
                     if let Some(path) = app.playlist_view.current_path().cloned() {
@@ -510,6 +1925,9 @@ fn handle_action(
 
                             app.queue_remote_fetch(Some(&path));
 
+                            if view_folder.is_none() && app.local_folder.is_none() {
+                                app.playlist = app.playlist_view.clone();
+                            }
                             if let Some(folder) = view_folder {
                                 app.local_folder = Some(folder.clone());
                                 app.playlist = app.playlist_view.clone();
@@ -521,6 +1939,7 @@ fn handle_action(
                                     }
                                 }
                             }
+                            note_local_started(app, &path);
                         }
                     }
                 }
@@ -528,14 +1947,18 @@ fn handle_action(
                     // Enter toggles boolean settings only.
                     match app.settings_selected {
                         1 => {
-                            app.config.transparent_background = !app.config.transparent_background;
+                            app.config.adaptive_theme = !app.config.adaptive_theme;
                             let _ = app.config.save();
                         }
                         2 => {
-                            app.config.album_border = !app.config.album_border;
+                            app.config.transparent_background = !app.config.transparent_background;
                             let _ = app.config.save();
                         }
                         3 => {
+                            app.config.album_border = !app.config.album_border;
+                            let _ = app.config.save();
+                        }
+                        4 => {
                             // Visualize mode toggle
                             app.config.visualize = match app.config.visualize {
                                 crate::data::config::VisualizeMode::Bars => crate::data::config::VisualizeMode::Oscilloscope,
@@ -543,23 +1966,23 @@ fn handle_action(
                             };
                             let _ = app.config.save();
                         }
-                        4 => {
-                            if app.config.visualize == crate::data::config::VisualizeMode::Bars {
-                                app.bar_settings_selected = 0;
-                                app.overlay = Overlay::BarSettingsModal;
-                            }
+                        5 if app.config.visualize == crate::data::config::VisualizeMode::Bars => {
+                            app.bar_settings_selected = 0;
+                            app.overlay = Overlay::BarSettingsModal;
                         }
-                        5 => {
-                            if app.kitty_graphics_supported {
-                                app.config.kitty_graphics = !app.config.kitty_graphics;
-                                let _ = app.config.save();
-                            }
+                        6 => {
+                            app.config.cover_mode = app.config.cover_mode.next();
+                            let _ = app.config.save();
                         }
-                        7 => {
+                        7 if app.graphics_protocol.is_some() => {
+                            app.config.cover_images = !app.config.cover_images;
+                            let _ = app.config.save();
+                        }
+                        9 => {
                             app.local_audio_settings_selected = 0;
                             app.overlay = Overlay::LocalAudioSettingsModal;
                         }
-                        8 => {
+                        10 => {
                             app.overlay = Overlay::AboutModal;
                         }
                         _ => {}
@@ -610,11 +2033,9 @@ fn handle_action(
                             app.config.lyrics_cover_download = !app.config.lyrics_cover_download;
                             let _ = app.config.save();
                         }
-                        2 => {
-                            if !app.config.acoustid_api_key.trim().is_empty() {
-                                app.config.audio_fingerprint = !app.config.audio_fingerprint;
-                                let _ = app.config.save();
-                            }
+                        2 if !app.config.acoustid_api_key.trim().is_empty() => {
+                            app.config.audio_fingerprint = !app.config.audio_fingerprint;
+                            let _ = app.config.save();
                         }
                         3 => {
                             app.acoustid_input = app.config.acoustid_api_key.clone();
@@ -624,6 +2045,29 @@ fn handle_action(
                             app.config.resume_last_position = !app.config.resume_last_position;
                             let _ = app.config.save();
                         }
+                        5 => {
+                            app.config.crossfade_ms = cycle_crossfade_ms(app.config.crossfade_ms, 1);
+                            let _ = app.config.save();
+                        }
+                        6 => {
+                            app.config.replay_gain = app.config.replay_gain.next();
+                            let _ = app.config.save();
+                        }
+                        7 => {
+                            app.config.replay_gain_scan = !app.config.replay_gain_scan;
+                            let _ = app.config.save();
+                        }
+                        8 => {
+                            app.config.shuffle_albums = !app.config.shuffle_albums;
+                            let _ = app.config.save();
+                        }
+                        9 => {
+                            if app.lookup_cache_purge_armed {
+                                app.purge_lookup_cache();
+                            } else {
+                                app.lookup_cache_purge_armed = true;
+                            }
+                        }
                         _ => {}
                     }
                 }
@@ -636,6 +2080,55 @@ fn handle_action(
                     let _ = app.config.save();
                     app.overlay = Overlay::SettingsModal;
                 }
+                Overlay::PlaylistManager => {
+                    if let Some(path) = app.playlist_manager_items.get(app.playlist_manager_selected).cloned() {
+                        app.close_overlay();
+                        if let Err(e) = open_user_playlist(app, mode_manager, &path) {
+                            app.set_toast(format!("Playlist error: {e}"));
+                        }
+                    }
+                }
+                Overlay::LibraryFilter => {
+                    app.overlay = Overlay::Library;
+                }
+                Overlay::PlaylistSearch => {
+                    // Play the selected match through the normal playlist path.
+                    let has_match = app.playlist_search.as_ref().and_then(|s| s.selected_item()).is_some();
+                    app.playlist_search = None;
+                    app.overlay = Overlay::Playlist;
+                    if has_match {
+                        return handle_action(app, mode_manager, system_volume, Action::Confirm, layout);
+                    }
+                }
+                Overlay::Library => {
+                    let opened = app.library_browser.enter(&app.library);
+                    if !opened {
+                        // Track row: play the (filtered) list starting at it.
+                        let tracks = app.library_browser.visible_tracks();
+                        let start = app.library_browser.selected;
+                        let items = app.library.playlist_items(&tracks);
+                        let count = items.len();
+                        match play_item_list(app, mode_manager, items, start) {
+                            Ok(true) => {
+                                app.close_overlay();
+                                app.set_toast(format!("{} ({count} tracks)", app.library_browser.title()));
+                            }
+                            Ok(false) => {}
+                            Err(e) => app.set_toast(format!("Play error: {e}")),
+                        }
+                    }
+                }
+                Overlay::PlaylistNameInput => {
+                    confirm_playlist_name(app);
+                }
+                Overlay::TagEditor => save_tag_edits(app, mode_manager),
+                Overlay::Tagger => {
+                    if let Some(review) = app.tag_review.as_mut() {
+                        if review.busy().is_none() && !review.start_write() {
+                            app.set_toast("Tagger: nothing selected");
+                        }
+                    }
+                }
                 Overlay::HelpModal => {
                     app.close_overlay();
                 }
@@ -645,6 +2138,33 @@ fn handle_action(
                 _ => {}
             }
         }
+        Action::PlaylistUp if app.up_next_view => {
+            app.up_next_selected = app.up_next_selected.saturating_sub(1);
+        }
+        Action::PlaylistDown if app.up_next_view => {
+            app.up_next_selected = (app.up_next_selected + 1).min(app.up_next.len().saturating_sub(1));
+        }
+        Action::PlaylistMoveItemUp if app.up_next_view => {
+            let i = app.up_next_selected;
+            if i > 0 && i < app.up_next.len() {
+                app.up_next.swap(i, i - 1);
+                app.up_next_selected = i - 1;
+                save_up_next(app);
+            }
+        }
+        Action::PlaylistMoveItemDown if app.up_next_view => {
+            let i = app.up_next_selected;
+            if i + 1 < app.up_next.len() {
+                app.up_next.swap(i, i + 1);
+                app.up_next_selected = i + 1;
+                save_up_next(app);
+            }
+        }
+        Action::PlaylistSelect(idx) if app.up_next_view => {
+            if idx < app.up_next.len() {
+                app.up_next_selected = idx;
+            }
+        }
         Action::PlaylistUp => {
             app.playlist_view.move_up();
             app.playlist_view.clamp_selected();
@@ -658,11 +2178,7 @@ fn handle_action(
         Action::PlaylistMoveItemUp => {
             if app.overlay == Overlay::Playlist && app.player.mode == PlayMode::LocalPlayback {
                 if app.playlist_view.move_selected_item_up() {
-                    if let Some(folder) = app.local_view_album_folder.as_deref() {
-                        if let Err(e) = crate::playback::local_player::write_order_file(folder, &app.playlist_view) {
-                            app.set_toast(format!("Order save error: {e}"));
-                        }
-                    }
+                    save_playlist_view_order(app, &[]);
                     sync_playlists_when_viewing_playback(app);
                 }
             }
@@ -670,15 +2186,47 @@ fn handle_action(
         Action::PlaylistMoveItemDown => {
             if app.overlay == Overlay::Playlist && app.player.mode == PlayMode::LocalPlayback {
                 if app.playlist_view.move_selected_item_down() {
-                    if let Some(folder) = app.local_view_album_folder.as_deref() {
-                        if let Err(e) = crate::playback::local_player::write_order_file(folder, &app.playlist_view) {
-                            app.set_toast(format!("Order save error: {e}"));
-                        }
-                    }
+                    save_playlist_view_order(app, &[]);
                     sync_playlists_when_viewing_playback(app);
                 }
             }
         }
+        Action::PlaylistTop => select_playlist_row(app, 0),
+        Action::PlaylistBottom => select_playlist_row(app, usize::MAX),
+        Action::PlaylistHalfPage(dir) => {
+            let step = (layout.playlist_list_inner.height / 2).max(1) as i64 * dir as i64;
+            let cur = if app.up_next_view { app.up_next_selected } else { app.playlist_view.selected };
+            select_playlist_row(app, (cur as i64 + step).max(0) as usize);
+        }
+        Action::PlaylistCut => cut_playlist_items(app, 1),
+        Action::PlaylistPaste => paste_playlist_items(app, false),
+        Action::PlaylistPasteBefore => paste_playlist_items(app, true),
+        Action::PlaylistSetMark(c) => {
+            if app.overlay == Overlay::Playlist {
+                let path = if app.up_next_view {
+                    app.up_next.get(app.up_next_selected).map(|it| it.path.clone())
+                } else {
+                    app.playlist_view.selected_path().cloned()
+                };
+                if let Some(path) = path {
+                    app.playlist_marks.insert(c, path);
+                    app.set_toast(format!("Mark {c} set"));
+                }
+            }
+        }
+        Action::PlaylistJumpToMark(c) => {
+            if app.overlay == Overlay::Playlist {
+                let Some(path) = app.playlist_marks.get(&c) else {
+                    app.set_toast(format!("Mark {c} not set"));
+                    return Ok(());
+                };
+                let items = if app.up_next_view { &app.up_next } else { &app.playlist_view.items };
+                match items.iter().position(|it| &it.path == path) {
+                    Some(idx) => select_playlist_row(app, idx),
+                    None => app.set_toast(format!("Mark {c} is not in this list")),
+                }
+            }
+        }
         Action::PrevAlbum | Action::NextAlbum => {
             if app.overlay == Overlay::Playlist
                 && app.player.mode == PlayMode::LocalPlayback
@@ -688,15 +2236,11 @@ fn handle_action(
                 let count = app.local_album_folders.len();
                 let mut idx = app.local_view_album_index;
                 match action {
-                    Action::PrevAlbum => {
-                        if idx > 0 {
-                            idx -= 1;
-                        }
+                    Action::PrevAlbum if idx > 0 => {
+                        idx -= 1;
                     }
-                    Action::NextAlbum => {
-                        if idx + 1 < count {
-                            idx += 1;
-                        }
+                    Action::NextAlbum if idx + 1 < count => {
+                        idx += 1;
                     }
                     _ => {}
                 }
@@ -743,7 +2287,7 @@ fn handle_action(
         }
         Action::ModalUp => {
             if app.overlay == Overlay::SettingsModal {
-                let count = 9;
+                let count = 11;
                 if app.settings_selected == 0 {
                     app.settings_selected = count - 1;
                 } else {
@@ -756,8 +2300,13 @@ fn handle_action(
                 } else {
                     app.bar_settings_selected -= 1;
                 }
+            } else if app.overlay == Overlay::PlaylistManager {
+                let count = app.playlist_manager_items.len().max(1);
+                app.playlist_manager_selected = (app.playlist_manager_selected + count - 1) % count;
+                app.playlist_manager_delete_armed = None;
             } else if app.overlay == Overlay::LocalAudioSettingsModal {
-                let count = 5;
+                let count = 10;
+                app.lookup_cache_purge_armed = false;
                 if app.local_audio_settings_selected == 0 {
                     app.local_audio_settings_selected = count - 1;
                 } else {
@@ -778,13 +2327,18 @@ fn handle_action(
         }
         Action::ModalDown => {
             if app.overlay == Overlay::SettingsModal {
-                let count = 9;
+                let count = 11;
                 app.settings_selected = (app.settings_selected + 1) % count;
             } else if app.overlay == Overlay::BarSettingsModal {
                 let count = 5;
                 app.bar_settings_selected = (app.bar_settings_selected + 1) % count;
+            } else if app.overlay == Overlay::PlaylistManager {
+                let count = app.playlist_manager_items.len().max(1);
+                app.playlist_manager_selected = (app.playlist_manager_selected + 1) % count;
+                app.playlist_manager_delete_armed = None;
             } else if app.overlay == Overlay::LocalAudioSettingsModal {
-                let count = 5;
+                let count = 10;
+                app.lookup_cache_purge_armed = false;
                 app.local_audio_settings_selected = (app.local_audio_settings_selected + 1) % count;
             } else if app.overlay == Overlay::EqModal {
                 let step = 1.0;
@@ -921,7 +2475,7 @@ fn handle_action(
                     RepeatMode::Sequence => app.playlist.prev_index_no_wrap(),
                     RepeatMode::LoopAll => app.playlist.prev_index_sequence(),
                     RepeatMode::LoopOne => app.playlist.current,
-                    RepeatMode::Shuffle => pick_shuffle_index(&app.playlist),
+                    RepeatMode::Shuffle => shuffle_prev_index(app, mode_manager),
                 };
                 if let Some(i) = i {
                     app.playlist.current = Some(i);
@@ -949,16 +2503,22 @@ fn handle_action(
         Action::Next => match app.player.mode {
             PlayMode::LocalPlayback => {
                 let from = CoverSnapshot::from(&app.player.track);
-                let next = match app.player.repeat_mode {
-                    RepeatMode::Sequence => app.playlist.next_index_no_wrap(),
-                    RepeatMode::LoopAll => app.playlist.next_index_sequence(),
-                    RepeatMode::LoopOne => app.playlist.current,
-                    RepeatMode::Shuffle => pick_shuffle_index(&app.playlist),
-                };
-                if let Some(i) = next {
-                    app.playlist.current = Some(i);
-                    if let Some(path) = app.playlist.current_path().cloned() {
+                let queued = take_up_next(app);
+                let mut next = if queued.is_some() { app.playlist.current } else { next_local_index(app) };
+                if next.is_none()
+                    && queued.is_none()
+                    && app.player.repeat_mode == RepeatMode::Shuffle
+                    && shuffle_albums_active(app)
+                {
+                    next = next_shuffled_album(app, mode_manager);
+                }
+                if queued.is_some() || next.is_some() {
+                    if queued.is_none() {
+                        app.playlist.current = next;
+                    }
+                    if let Some(path) = queued.or_else(|| app.playlist.current_path().cloned()) {
                         if let Ok(track) = mode_manager.local.play_file(&path) {
+                            note_local_started(app, &path);
                             app.player.track = track;
                             let to = CoverSnapshot::from(&app.player.track);
                             app.start_cover_anim(from, to, -1, Instant::now());
@@ -1058,6 +2618,20 @@ fn handle_action(
                 PlayMode::Idle => {}
             }
         }
+        Action::SeekRelative(delta_ms) => match app.player.mode {
+            PlayMode::LocalPlayback => {
+                if let Ok(pos) = mode_manager.local.seek_by(delta_ms) {
+                    app.player.position = pos;
+                }
+            }
+            PlayMode::SystemMonitor => {
+                if let Ok(Some(pos)) = mode_manager.mpris.seek_by(delta_ms) {
+                    app.player.position = pos;
+                }
+            }
+            PlayMode::Idle => {}
+        },
+        Action::AbLoopCycle => cycle_ab_loop(app),
         Action::MouseClick { col, row } => {
             // map click to controls/progress/volume/playlist
             if let Some(a) = crate::ui::tui::hit_test(layout, app, col, row) {
@@ -1070,39 +2644,6 @@ fn handle_action(
     Ok(())
 }
 
-fn themes() -> [ThemeName; 5] {
-    [
-        ThemeName::System,
-        ThemeName::Latte,
-        ThemeName::Frappe,
-        ThemeName::Macchiato,
-        ThemeName::Mocha,
-    ]
-}
-
-fn theme_count() -> usize {
-    themes().len()
-}
-
-fn theme_index(name: ThemeName) -> usize {
-    themes().iter().position(|&t| t == name).unwrap_or(0)
-}
-
-fn theme_by_index(idx: usize) -> ThemeName {
-    let t = themes();
-    t[idx.min(t.len().saturating_sub(1))]
-}
-
-fn theme_key(name: ThemeName) -> &'static str {
-    match name {
-        ThemeName::System => "system",
-        ThemeName::Latte => "latte",
-        ThemeName::Frappe => "frappe",
-        ThemeName::Macchiato => "macchiato",
-        ThemeName::Mocha => "mocha",
-    }
-}
-
 fn apply_remote_fetch_results(app: &mut AppState, mode_manager: &mut ModeManager, results: Vec<crate::playback::remote_fetch::RemoteFetchResult>) {
     let current_path = if app.player.mode == PlayMode::LocalPlayback {
         app.playlist.current_path().cloned()
@@ -1125,61 +2666,69 @@ fn apply_settings_delta(app: &mut AppState, delta: i32) {
     match app.settings_selected {
         // Theme
         0 => {
-            let count = theme_count() as i32;
+            // Built-ins plus whatever is in the themes directory right now.
+            let themes = ThemeLoader::list();
+            let count = themes.len() as i32;
             if count <= 0 {
                 return;
             }
-            let cur = theme_index(app.theme.name) as i32;
-            let next = (cur + delta).rem_euclid(count) as usize;
-            let name = theme_by_index(next);
-            let key = theme_key(name);
-            if let Ok(theme) = ThemeLoader::load(key) {
-                app.theme = theme;
-                app.config.theme = key.to_string();
-                let _ = app.config.save();
-            } else {
-                app.set_toast("Theme load error");
+            let cur = themes.iter().position(|t| t.key == app.theme.key).unwrap_or(0) as i32;
+            let next = &themes[(cur + delta).rem_euclid(count) as usize];
+            match ThemeLoader::load_entry(next) {
+                Ok(theme) => {
+                    app.theme = theme;
+                    app.config.theme = next.key.clone();
+                    let _ = app.config.save();
+                }
+                Err(e) => app.set_toast(format!("Theme error: {e:#}")),
             }
         }
+        // Adaptive colors
+        1 if delta != 0 => {
+            app.config.adaptive_theme = !app.config.adaptive_theme;
+            let _ = app.config.save();
+        }
         // Transparent background
-        1 => {
-            if delta != 0 {
-                app.config.transparent_background = !app.config.transparent_background;
-                let _ = app.config.save();
-            }
+        2 if delta != 0 => {
+            app.config.transparent_background = !app.config.transparent_background;
+            let _ = app.config.save();
         }
         // Album border
-        2 => {
-            if delta != 0 {
-                app.config.album_border = !app.config.album_border;
-                let _ = app.config.save();
-            }
+        3 if delta != 0 => {
+            app.config.album_border = !app.config.album_border;
+            let _ = app.config.save();
         }
         // Visualize
-        3 => {
-            if delta != 0 {
-                app.config.visualize = match app.config.visualize {
-                    crate::data::config::VisualizeMode::Bars => crate::data::config::VisualizeMode::Oscilloscope,
-                    crate::data::config::VisualizeMode::Oscilloscope => crate::data::config::VisualizeMode::Bars,
-                };
-                let _ = app.config.save();
-            }
+        4 if delta != 0 => {
+            app.config.visualize = match app.config.visualize {
+                crate::data::config::VisualizeMode::Bars => crate::data::config::VisualizeMode::Oscilloscope,
+                crate::data::config::VisualizeMode::Oscilloscope => crate::data::config::VisualizeMode::Bars,
+            };
+            let _ = app.config.save();
         }
         // Bar settings (Enter opens modal)
-        4 => {}
-        // Kitty graphics
-        5 => {
-            if delta != 0 && app.kitty_graphics_supported {
-                app.config.kitty_graphics = !app.config.kitty_graphics;
+        5 => {}
+        // Text cover renderer
+        6 => {
+            if delta > 0 {
+                app.config.cover_mode = app.config.cover_mode.next();
+                let _ = app.config.save();
+            } else if delta < 0 {
+                app.config.cover_mode = app.config.cover_mode.prev();
                 let _ = app.config.save();
             }
         }
-        // Cover image compression/scale (kitty-only)
-        6 => {
+        // Cover images (kitty / sixel / iTerm2)
+        7 if delta != 0 && app.graphics_protocol.is_some() => {
+            app.config.cover_images = !app.config.cover_images;
+            let _ = app.config.save();
+        }
+        // Cover image compression/scale (native images only)
+        8 => {
             if delta == 0 {
                 return;
             }
-            if !app.kitty_graphics_supported || !app.config.kitty_graphics {
+            if app.graphics_protocol.is_none() || !app.config.cover_images {
                 return;
             }
 
@@ -1192,7 +2741,7 @@ fn apply_settings_delta(app: &mut AppState, delta: i32) {
             let _ = app.config.save();
         }
         // Local audio settings (Enter opens modal)
-        7 => {}
+        9 => {}
         _ => {}
     }
 }
@@ -1221,21 +2770,46 @@ fn apply_local_audio_settings_delta(app: &mut AppState, delta: i32) {
             app.config.lyrics_cover_download = !app.config.lyrics_cover_download;
             let _ = app.config.save();
         }
-        2 => {
-            if !app.config.acoustid_api_key.trim().is_empty() {
-                app.config.audio_fingerprint = !app.config.audio_fingerprint;
-                let _ = app.config.save();
-            }
+        2 if !app.config.acoustid_api_key.trim().is_empty() => {
+            app.config.audio_fingerprint = !app.config.audio_fingerprint;
+            let _ = app.config.save();
         }
         3 => {}
         4 => {
             app.config.resume_last_position = !app.config.resume_last_position;
             let _ = app.config.save();
         }
+        5 => {
+            app.config.crossfade_ms = cycle_crossfade_ms(app.config.crossfade_ms, delta);
+            let _ = app.config.save();
+        }
+        6 => {
+            app.config.replay_gain = if delta > 0 {
+                app.config.replay_gain.next()
+            } else {
+                app.config.replay_gain.prev()
+            };
+            let _ = app.config.save();
+        }
+        7 => {
+            app.config.replay_gain_scan = !app.config.replay_gain_scan;
+            let _ = app.config.save();
+        }
+        8 => {
+            app.config.shuffle_albums = !app.config.shuffle_albums;
+            let _ = app.config.save();
+        }
         _ => {}
     }
 }
 
+fn cycle_crossfade_ms(cur: u32, delta: i32) -> u32 {
+    // 0 (gapless) .. 12s in 1s steps
+    const MAX_SECS: i32 = 12;
+    let secs = ((cur / 1000) as i32 + delta).rem_euclid(MAX_SECS + 1);
+    secs as u32 * 1000
+}
+
 fn cycle_bar_number(cur: BarNumber, delta: i32) -> BarNumber {
     let options = [
         BarNumber::Auto,
@@ -1333,7 +2907,7 @@ fn ensure_cava(cava: &mut Option<CavaRunner>, cfg: &mut Option<CavaConfig>, desi
         }
         Err(e) => {
             if cfg.is_none() {
-                log::warn!("cava unavailable; leaving spectrum empty: {e}");
+                log::warn!("cava unavailable; using built-in FFT spectrum: {e}");
             }
             *cava = None;
             *cfg = None;
@@ -1341,6 +2915,65 @@ fn ensure_cava(cava: &mut Option<CavaRunner>, cfg: &mut Option<CavaConfig>, desi
     }
 }
 
+fn update_native_spectrum(
+    app: &AppState,
+    mode_manager: &ModeManager,
+    native: &mut Option<SpectrumAnalyzer>,
+    capture: &mut Option<AudioCapture>,
+    desired: CavaConfig,
+    now: Instant,
+) {
+    if native.as_ref().map(|a| a.config()) != Some(desired) {
+        *native = Some(SpectrumAnalyzer::new(desired));
+    }
+    let Some(analyzer) = native.as_mut() else {
+        return;
+    };
+
+    // Only keep the capture device open while we actually monitor system playback.
+    if app.player.mode != PlayMode::SystemMonitor {
+        *capture = None;
+    }
+
+    if app.player.playback != PlaybackState::Playing {
+        analyzer.feed(&[], 2, 0);
+        return;
+    }
+
+    match app.player.mode {
+        PlayMode::LocalPlayback => {
+            let (channels, rate) = mode_manager.local.latest_samples_format();
+            let samples = mode_manager.local.latest_samples(analyzer.wanted_samples(channels));
+            analyzer.feed(&samples, channels, rate);
+        }
+        PlayMode::SystemMonitor => {
+            if capture.is_none() {
+                match AudioCapture::start() {
+                    Ok(c) => *capture = Some(c),
+                    Err(e) => log::warn!("audio capture unavailable: {e}"),
+                }
+            }
+            let Some(cap) = capture.as_mut() else {
+                analyzer.feed(&[], 2, 0);
+                return;
+            };
+            cap.maybe_restart_for_system_playback(now);
+            let fresh = cap
+                .last_sample_age(now)
+                .map(|age| age <= Duration::from_millis(900))
+                .unwrap_or(false);
+            if fresh {
+                let (channels, rate) = cap.format();
+                let samples = cap.latest_samples(analyzer.wanted_samples(channels));
+                analyzer.feed(&samples, channels, rate);
+            } else {
+                analyzer.feed(&[], 2, 0);
+            }
+        }
+        PlayMode::Idle => analyzer.feed(&[], 2, 0),
+    }
+}
+
 fn ensure_bar_buffers(app: &mut AppState, bars: usize) {
     if app.spectrum.bars.len() != bars {
         app.spectrum.bars = vec![0.0; bars];
@@ -1368,23 +3001,3 @@ fn fill_fixed_bars(dst: &mut [f32; 64], src: &[f32]) {
     }
 }
 
-fn pick_shuffle_index(pl: &crate::data::playlist::Playlist) -> Option<usize> {
-    if pl.items.is_empty() {
-        return None;
-    }
-    let len = pl.items.len();
-    if len == 1 {
-        return Some(0);
-    }
-    let nanos = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_nanos();
-    let mut idx = (nanos as usize) % len;
-    if Some(idx) == pl.current {
-        idx = (idx + 1) % len;
-    }
-    Some(idx)
-}
-
-// fallback bars removed (leave spectrum empty when unavailable)
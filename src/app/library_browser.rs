@@ -0,0 +1,139 @@
+use crate::data::library::{Library, LibraryCategory, LibraryEntry};
+
+/// Navigation state of the library overlay: categories -> entries -> tracks.
+#[derive(Debug, Default)]
+pub struct LibraryBrowser {
+    /// `None` while the category list is shown.
+    pub category: Option<LibraryCategory>,
+    /// Set once an artist/album/genre/year has been opened.
+    pub entry: Option<LibraryEntry>,
+    pub entries: Vec<LibraryEntry>,
+    /// Track indices into `Library::tracks`.
+    pub tracks: Vec<usize>,
+    /// Row indices (into the current level) that pass `filter`.
+    pub visible: Vec<usize>,
+    pub selected: usize,
+    pub filter: String,
+    // Selection of the parent levels, restored on `back`.
+    parent_selected: Vec<usize>,
+}
+
+/// What the selected row points at.
+pub enum LibraryRow<'a> {
+    Category(LibraryCategory),
+    Entry(&'a LibraryEntry),
+    Track(usize),
+}
+
+impl LibraryBrowser {
+    pub fn showing_tracks(&self) -> bool {
+        self.entry.is_some() || self.category == Some(LibraryCategory::Tracks)
+    }
+
+    pub fn title(&self) -> String {
+        match (&self.category, &self.entry) {
+            (None, _) => "Library".to_string(),
+            (Some(c), None) => format!("Library — {}", c.as_label()),
+            (Some(c), Some(e)) => format!("Library — {} — {}", c.as_label(), e.label),
+        }
+    }
+
+    pub fn row(&self, visible_idx: usize) -> Option<LibraryRow<'_>> {
+        let i = *self.visible.get(visible_idx)?;
+        if self.category.is_none() {
+            return LibraryCategory::ALL.get(i).copied().map(LibraryRow::Category);
+        }
+        if self.showing_tracks() {
+            return self.tracks.get(i).copied().map(LibraryRow::Track);
+        }
+        self.entries.get(i).map(LibraryRow::Entry)
+    }
+
+    pub fn selected_row(&self) -> Option<LibraryRow<'_>> {
+        self.row(self.selected)
+    }
+
+    /// Rebuild the rows of the current level (after a scan or navigation).
+    pub fn reload(&mut self, lib: &Library) {
+        self.entries.clear();
+        self.tracks.clear();
+        match self.category {
+            None => {}
+            Some(LibraryCategory::Tracks) => self.tracks = lib.tracks_for(LibraryCategory::Tracks, None),
+            Some(c) => match self.entry.as_ref() {
+                Some(e) => self.tracks = lib.tracks_for(c, Some(e)),
+                None => self.entries = lib.entries(c),
+            },
+        }
+        self.apply_filter(lib);
+    }
+
+    pub fn apply_filter(&mut self, lib: &Library) {
+        let q = self.filter.trim().to_lowercase();
+        self.visible = if self.category.is_none() {
+            (0..LibraryCategory::ALL.len())
+                .filter(|&i| q.is_empty() || LibraryCategory::ALL[i].as_label().to_lowercase().contains(&q))
+                .collect()
+        } else if self.showing_tracks() {
+            (0..self.tracks.len())
+                .filter(|&i| lib.track_matches(self.tracks[i], &q))
+                .collect()
+        } else {
+            (0..self.entries.len())
+                .filter(|&i| q.is_empty() || self.entries[i].label.to_lowercase().contains(&q))
+                .collect()
+        };
+        self.selected = self.selected.min(self.visible.len().saturating_sub(1));
+    }
+
+    /// Open the selected category/entry. Returns false when a track row is selected.
+    pub fn enter(&mut self, lib: &Library) -> bool {
+        let next = match self.selected_row() {
+            Some(LibraryRow::Category(c)) => (Some(c), None),
+            Some(LibraryRow::Entry(e)) => (self.category, Some(e.clone())),
+            _ => return false,
+        };
+        self.parent_selected.push(self.selected);
+        self.category = next.0;
+        self.entry = next.1;
+        self.selected = 0;
+        self.filter.clear();
+        self.reload(lib);
+        true
+    }
+
+    pub fn back(&mut self, lib: &Library) -> bool {
+        if self.entry.is_some() {
+            self.entry = None;
+        } else if self.category.is_some() {
+            self.category = None;
+        } else {
+            return false;
+        }
+        self.filter.clear();
+        self.reload(lib);
+        self.selected = self
+            .parent_selected
+            .pop()
+            .unwrap_or(0)
+            .min(self.visible.len().saturating_sub(1));
+        true
+    }
+
+    pub fn move_by(&mut self, delta: i32) {
+        let len = self.visible.len();
+        if len == 0 {
+            self.selected = 0;
+            return;
+        }
+        self.selected = (self.selected as i64 + delta as i64).clamp(0, len as i64 - 1) as usize;
+    }
+
+    /// Track indices of the current track list (filtered), for building a playlist.
+    pub fn visible_tracks(&self) -> Vec<usize> {
+        if !self.showing_tracks() {
+            return Vec::new();
+        }
+        self.visible.iter().filter_map(|&i| self.tracks.get(i).copied()).collect()
+    }
+}
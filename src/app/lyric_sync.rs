@@ -0,0 +1,133 @@
+use crate::app::state::{LyricLine, LyricTiming, LyricTrack};
+use std::path::PathBuf;
+
+/// Tap-to-sync editor: every tap stamps the playback position onto the next line,
+/// turning plain (or badly timed) lyrics into line-synced LRC.
+#[derive(Debug, Default)]
+pub struct LyricSync {
+    /// Song being synced; saving is refused once playback moved on to another one.
+    pub path: PathBuf,
+    /// Line text and the time stamped on it so far.
+    pub lines: Vec<(String, Option<u64>)>,
+    /// Next line to stamp.
+    pub cursor: usize,
+}
+
+impl LyricSync {
+    pub fn new(path: PathBuf, lines: &[LyricLine]) -> Self {
+        Self {
+            path,
+            lines: lines.iter().map(|l| (l.text.clone(), None)).collect(),
+            cursor: 0,
+        }
+    }
+
+    /// Stamp the next line. Times never go backwards, so a late tap cannot reorder lines.
+    pub fn stamp(&mut self, pos_ms: u64) -> bool {
+        if self.cursor >= self.lines.len() {
+            return false;
+        }
+        let floor = self
+            .cursor
+            .checked_sub(1)
+            .and_then(|i| self.lines[i].1)
+            .unwrap_or(0);
+        self.lines[self.cursor].1 = Some(pos_ms.max(floor));
+        self.cursor += 1;
+        true
+    }
+
+    pub fn undo(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            self.lines[self.cursor].1 = None;
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.cursor >= self.lines.len()
+    }
+
+    /// `layer` (a translation or romanization of `original`, the lines this sync started
+    /// from) moved onto the new stamps. Synced layers follow the original line that had
+    /// their time; unsynced ones pair up line by line. Lines whose original isn't stamped
+    /// yet are left out, like the original's.
+    pub fn restamp(&self, original: &LyricTrack, layer: &LyricTrack) -> Vec<LyricLine> {
+        let stamps: Vec<u64> = self.lines.iter().map_while(|(_, t)| *t).collect();
+        let by_index = original.timing != LyricTiming::Synced || layer.timing != LyricTiming::Synced;
+        let mut out: Vec<LyricLine> = layer
+            .lines
+            .iter()
+            .enumerate()
+            .filter_map(|(j, l)| {
+                let i = if by_index { j } else { original.lines.iter().position(|o| o.start_ms == l.start_ms)? };
+                Some(LyricLine {
+                    start_ms: *stamps.get(i)?,
+                    text: l.text.clone(),
+                    words: Vec::new(),
+                })
+            })
+            .collect();
+        out.sort_by_key(|l| l.start_ms);
+        out
+    }
+
+    /// Lines stamped so far, ready to save.
+    pub fn stamped(&self) -> Vec<LyricLine> {
+        self.lines
+            .iter()
+            .filter_map(|(text, t)| {
+                t.map(|start_ms| LyricLine {
+                    start_ms,
+                    text: text.clone(),
+                    words: Vec::new(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::state::LyricTrackKind;
+
+    fn lines(rows: &[(u64, &str)]) -> Vec<LyricLine> {
+        rows.iter()
+            .map(|&(start_ms, text)| LyricLine { start_ms, text: text.into(), words: Vec::new() })
+            .collect()
+    }
+
+    fn texts(lines: &[LyricLine]) -> Vec<(u64, &str)> {
+        lines.iter().map(|l| (l.start_ms, l.text.as_str())).collect()
+    }
+
+    #[test]
+    fn stamps_never_go_backwards_and_undo_clears_the_last() {
+        let mut sync = LyricSync::new("song.flac".into(), &lines(&[(0, "a"), (0, "b"), (0, "c")]));
+        assert!(sync.stamp(3000));
+        assert!(sync.stamp(2000));
+        assert_eq!(texts(&sync.stamped()), [(3000, "a"), (3000, "b")]);
+        sync.undo();
+        assert_eq!(sync.cursor, 1);
+        assert!(sync.stamp(4000) && sync.stamp(5000));
+        assert!(sync.is_done());
+        assert!(!sync.stamp(6000));
+    }
+
+    #[test]
+    fn layers_follow_the_new_stamps() {
+        let original = LyricTrack::new(LyricTrackKind::Original, lines(&[(1000, "hola"), (2000, "mundo"), (3000, "fin")]));
+        let mut sync = LyricSync::new("song.flac".into(), &original.lines);
+        sync.stamp(1500);
+        sync.stamp(2600);
+
+        // Synced: matched by the original line's old time; a line without a partner is skipped.
+        let translation = LyricTrack::new(LyricTrackKind::Translation, lines(&[(2000, "world"), (1000, "hello")]));
+        assert_eq!(texts(&sync.restamp(&original, &translation)), [(1500, "hello"), (2600, "world")]);
+
+        // Unsynced: line by line, and only as far as the original is stamped.
+        let plain = LyricTrack::unsynced(LyricTrackKind::Romanization, lines(&[(0, "o-la"), (0, "mun-do"), (0, "fin")]));
+        assert_eq!(texts(&sync.restamp(&original, &plain)), [(1500, "o-la"), (2600, "mun-do")]);
+    }
+}
@@ -1,3 +1,10 @@
+pub mod adaptive_theme;
+pub mod control;
 pub mod event_loop;
+pub mod library_browser;
+pub mod lyric_sync;
 pub mod mode_manager;
+pub mod playlist_search;
 pub mod state;
+pub mod tag_form;
+pub mod tag_review;
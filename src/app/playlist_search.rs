@@ -0,0 +1,144 @@
+use crate::data::library::Library;
+use crate::data::playlist::Playlist;
+use crate::playback::metadata::read_library_tags;
+use crate::utils::fuzzy::fuzzy_match;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+/// `/` search inside the playlist overlay. Matches keep their `playlist_view` index,
+/// so selecting one never changes what the underlying `Playlist` points at.
+#[derive(Debug, Default)]
+pub struct PlaylistSearch {
+    pub query: String,
+    /// (index into `playlist_view.items`, matched char positions in the title)
+    pub matches: Vec<(usize, Vec<usize>)>,
+    pub selected: usize,
+    // Artist / album / file name per item.
+    extra: Vec<String>,
+    // "artist album" of items whose tags are read in the background, by index.
+    pending: Option<Receiver<(usize, String)>>,
+}
+
+impl PlaylistSearch {
+    /// Artist and album come from the library index, else from `cached` (the player's
+    /// metadata cache), else from the file's tags, read on a worker; `poll` takes them in.
+    pub fn new(view: &Playlist, library: &Library, cached: impl Fn(&Path) -> Option<(String, String)>) -> Self {
+        let by_path: HashMap<&Path, usize> = library
+            .tracks
+            .iter()
+            .enumerate()
+            .map(|(i, t)| (t.path.as_path(), i))
+            .collect();
+        let mut unread: Vec<(usize, PathBuf)> = Vec::new();
+        let extra = view
+            .items
+            .iter()
+            .enumerate()
+            .map(|(i, it)| {
+                let file = it.path.file_name().and_then(|s| s.to_str()).unwrap_or("");
+                let tags = match by_path.get(it.path.as_path()) {
+                    Some(&t) => Some((library.tracks[t].artist.clone(), library.tracks[t].album.clone())),
+                    None if it.missing => None,
+                    None => cached(&it.path),
+                };
+                match tags {
+                    Some((artist, album)) => format!("{artist} {album} {file}"),
+                    None => {
+                        if !it.missing {
+                            unread.push((i, it.path.clone()));
+                        }
+                        file.to_string()
+                    }
+                }
+            })
+            .collect();
+
+        let mut s = Self {
+            extra,
+            pending: (!unread.is_empty()).then(|| read_tags(unread)),
+            ..Default::default()
+        };
+        s.update(view);
+        s
+    }
+
+    /// Take in tags the worker has read so far and re-run the query if any arrived (true then).
+    /// The selection stays on its item while it still matches.
+    pub fn poll(&mut self, view: &Playlist) -> bool {
+        let Some(rx) = self.pending.as_ref() else {
+            return false;
+        };
+        let arrived: Vec<(usize, String)> = rx.try_iter().collect();
+        if arrived.is_empty() {
+            return false;
+        }
+        for (i, tags) in arrived {
+            if let Some(e) = self.extra.get_mut(i) {
+                *e = format!("{tags} {e}");
+            }
+        }
+        let keep = self.selected_item();
+        self.update(view);
+        if let Some(m) = keep.and_then(|k| self.matches.iter().position(|(i, _)| *i == k)) {
+            self.selected = m;
+        }
+        true
+    }
+
+    /// Re-run the query; the selection jumps to the best match.
+    pub fn update(&mut self, view: &Playlist) {
+        let mut best: Option<(i32, usize)> = None;
+        self.matches.clear();
+        for (i, it) in view.items.iter().enumerate() {
+            let title = fuzzy_match(&self.query, &it.title);
+            let other = self.extra.get(i).and_then(|e| fuzzy_match(&self.query, e));
+            let score = match (&title, &other) {
+                (Some((a, _)), Some((b, _))) => *a.max(b),
+                (Some((a, _)), None) => *a,
+                (None, Some((b, _))) => *b,
+                (None, None) => continue,
+            };
+            if best.map(|(s, _)| score > s).unwrap_or(true) {
+                best = Some((score, self.matches.len()));
+            }
+            self.matches.push((i, title.map(|(_, p)| p).unwrap_or_default()));
+        }
+        self.selected = if self.query.trim().is_empty() {
+            // Empty query: stay on the current selection.
+            self.matches.iter().position(|(i, _)| *i == view.selected).unwrap_or(0)
+        } else {
+            best.map(|(_, m)| m).unwrap_or(0)
+        };
+    }
+
+    pub fn move_by(&mut self, delta: i32) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let last = self.matches.len() as i64 - 1;
+        self.selected = (self.selected as i64 + delta as i64).clamp(0, last) as usize;
+    }
+
+    /// `playlist_view` index of the selected match.
+    pub fn selected_item(&self) -> Option<usize> {
+        self.matches.get(self.selected).map(|(i, _)| *i)
+    }
+}
+
+/// Read artist and album of `items` one by one; stops once the search is dropped.
+fn read_tags(items: Vec<(usize, PathBuf)>) -> Receiver<(usize, String)> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for (i, path) in items {
+            let Ok(t) = read_library_tags(&path) else {
+                continue;
+            };
+            if tx.send((i, format!("{} {}", t.artist, t.album))).is_err() {
+                return;
+            }
+        }
+    });
+    rx
+}
@@ -1,15 +1,30 @@
 use crate::data::config::Config;
-use crate::data::playlist::Playlist;
+use crate::data::keymap::{Keymap, PendingKeys};
+use crate::app::adaptive_theme::{cover_swatches, AdaptiveTheme, Swatches};
+use crate::app::library_browser::LibraryBrowser;
+use crate::app::lyric_sync::LyricSync;
+use crate::app::playlist_search::PlaylistSearch;
+use crate::app::tag_form::{TagForm, TagSave};
+use crate::app::tag_review::{TagJobKind, TagReview};
+use crate::data::library::{Library, LibraryTrack};
+use crate::data::playlist::{Playlist, PlaylistItem, ShuffleState};
+use crate::playback::library_scanner::LibraryScanner;
 use crate::render::cover_cache::CoverCache;
 use crate::render::cover_cache::CoverKey;
-use crate::render::cover_renderer::render_cover_ascii;
+use crate::render::cover_renderer::{render_cover_text, CoverStyle};
 use crate::ui::theme::Theme;
+use crate::utils::graphics::GraphicsProtocol;
 use crate::audio::smoother::Ema;
+use crate::playback::lookup_cache::{CacheTtl, LookupCache, LookupCacheStats};
+use crate::playback::provider::ProviderChainConfig;
 use crate::playback::remote_fetch::{FetchOptions, RemoteFetchRequest, RemoteFetchResult, TrackKey, start_remote_fetch_worker};
-use std::cell::RefCell;
+use crate::playback::tagger::TaggerJob;
+use std::cell::{Cell, RefCell};
 use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
 use std::path::PathBuf;
 use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
 use std::sync::mpsc::{Receiver, Sender, TryRecvError};
 use std::time::{Duration, Instant};
 
@@ -85,6 +100,109 @@ impl EqSettings {
 pub struct LyricLine {
     pub start_ms: u64,
     pub text: String,
+    /// Word timing from enhanced LRC (`<mm:ss.xx>` tags); empty for plain line-synced lyrics.
+    /// A word with empty text only marks where the previous word ends.
+    pub words: Vec<LyricWord>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LyricWord {
+    pub start_ms: u64,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LyricTrackKind {
+    Original,
+    Translation,
+    Romanization,
+}
+
+impl LyricTrackKind {
+    pub fn as_label(self) -> &'static str {
+        match self {
+            LyricTrackKind::Original => "Original",
+            LyricTrackKind::Translation => "Translation",
+            LyricTrackKind::Romanization => "Romanization",
+        }
+    }
+}
+
+/// How the lines of a lyric track are timed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LyricTiming {
+    /// LRC time tags.
+    Synced,
+    /// Plain text. `start_ms` holds times spread over this song duration for estimated sync
+    /// (all zero while the duration is unknown).
+    Unsynced(Duration),
+}
+
+/// One set of lyrics for a song (the original, or a translation/romanization of it).
+#[derive(Debug, Clone)]
+pub struct LyricTrack {
+    pub kind: LyricTrackKind,
+    /// Language tag from a `<stem>.<lang>.lrc` side file.
+    pub lang: Option<String>,
+    pub lines: Vec<LyricLine>,
+    pub timing: LyricTiming,
+}
+
+impl LyricTrack {
+    pub fn new(kind: LyricTrackKind, lines: Vec<LyricLine>) -> Self {
+        Self { kind, lang: None, lines, timing: LyricTiming::Synced }
+    }
+
+    /// Plain lyrics without time tags.
+    pub fn unsynced(kind: LyricTrackKind, lines: Vec<LyricLine>) -> Self {
+        Self { kind, lang: None, lines, timing: LyricTiming::Unsynced(Duration::ZERO) }
+    }
+
+    pub fn is_synced(&self) -> bool {
+        self.timing == LyricTiming::Synced
+    }
+
+    /// Spread unsynced lines over `duration` (by length, blank lines as short pauses), leaving
+    /// a little room for an intro and outro. Does nothing for synced tracks or when the
+    /// estimate is already for this duration.
+    pub fn estimate_timing(&mut self, duration: Duration) {
+        match self.timing {
+            LyricTiming::Unsynced(d) if d != duration => {}
+            _ => return,
+        }
+        self.timing = LyricTiming::Unsynced(duration);
+        let weight = |l: &LyricLine| 8 + l.text.chars().count() as u64;
+        let total: u64 = self.lines.iter().map(weight).sum::<u64>().max(1);
+        let total_ms = duration.as_millis() as u64;
+        let (start, span) = (total_ms / 20, total_ms * 9 / 10);
+        let mut acc = 0u64;
+        for l in &mut self.lines {
+            l.start_ms = start + span * acc / total;
+            acc += weight(l);
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self.lang.as_deref() {
+            Some(lang) => format!("{} ({lang})", self.kind.as_label()),
+            None => self.kind.as_label().to_string(),
+        }
+    }
+}
+
+/// Loudness normalization info (ReplayGain 2.0 convention: gains relative to -18 LUFS).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ReplayGain {
+    pub track_gain_db: Option<f32>,
+    pub track_peak: Option<f32>,
+    pub album_gain_db: Option<f32>,
+    pub album_peak: Option<f32>,
+}
+
+impl ReplayGain {
+    pub fn is_empty(&self) -> bool {
+        self.track_gain_db.is_none() && self.album_gain_db.is_none()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -96,7 +214,11 @@ pub struct TrackMetadata {
     pub cover: Option<Vec<u8>>,
     pub cover_hash: Option<u64>,
     pub cover_folder: Option<PathBuf>,
-    pub lyrics: Option<Vec<LyricLine>>,
+    /// Original lyrics first, then translations / romanizations.
+    pub lyrics: Vec<LyricTrack>,
+    /// Per-song lyric timing correction (ms, positive = lyrics earlier), kept in `.order.toml`.
+    pub lyrics_offset_ms: i64,
+    pub replay_gain: ReplayGain,
 }
 
 #[derive(Debug, Clone)]
@@ -156,7 +278,9 @@ impl Default for TrackMetadata {
             cover: None,
             cover_hash: None,
             cover_folder: None,
-            lyrics: None,
+            lyrics: Vec::new(),
+            lyrics_offset_ms: 0,
+            replay_gain: ReplayGain::default(),
         }
     }
 }
@@ -188,6 +312,14 @@ impl Default for SpectrumData {
     }
 }
 
+/// A–B repeat points on a local song; `b` is unset while only A has been marked.
+#[derive(Debug, Clone)]
+pub struct AbLoop {
+    pub path: PathBuf,
+    pub a: Duration,
+    pub b: Option<Duration>,
+}
+
 #[derive(Debug)]
 pub struct PlayerState {
     pub mode: PlayMode,
@@ -196,6 +328,8 @@ pub struct PlayerState {
     pub volume: f32,
     pub repeat_mode: RepeatMode,
     pub track: TrackMetadata,
+    /// Seeks applied to local playback so far; each one is announced as MPRIS `Seeked`.
+    pub seeks: u64,
 }
 
 impl Default for PlayerState {
@@ -207,6 +341,7 @@ impl Default for PlayerState {
             volume: 0.0,
             repeat_mode: RepeatMode::Sequence,
             track: TrackMetadata::default(),
+            seeks: 0,
         }
     }
 }
@@ -223,6 +358,30 @@ pub enum Overlay {
     AcoustIdModal,
     HelpModal,
     EqModal,
+    PlaylistManager,
+    PlaylistNameInput,
+    Library,
+    LibraryFilter,
+    PlaylistSearch,
+    Lyrics,
+    LyricSync,
+    Tagger,
+    TagEditor,
+}
+
+impl Overlay {
+    /// The playlist panel is on screen (plain or with the `/` search prompt).
+    pub fn shows_playlist(self) -> bool {
+        matches!(self, Overlay::Playlist | Overlay::PlaylistSearch)
+    }
+}
+
+/// What the playlist name prompt is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaylistNameMode {
+    New,
+    Rename,
+    SaveQueue,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -247,8 +406,11 @@ impl Default for FolderInput {
 pub struct AppState {
     pub config: Config,
     pub theme: Theme,
+    pub adaptive_theme: AdaptiveTheme,
 
-    pub kitty_graphics_supported: bool,
+    /// Native cover image protocol, if the terminal has one (see `Config::image_protocol`).
+    /// Detected by `main` before the event loop starts; None until then.
+    pub graphics_protocol: Option<GraphicsProtocol>,
 
     pub player: PlayerState,
     pub playlist: Playlist,
@@ -262,13 +424,19 @@ pub struct AppState {
     pub cover_cache: RefCell<CoverCache>,
     pub cover_dominant_rgb_cache: RefCell<HashMap<u64, (u8, u8, u8)>>,
 
-    cover_render_tx: Sender<CoverRenderRequest>,
-    cover_render_rx: Receiver<CoverRenderResult>,
+    cover_render_tx: Sender<CoverJob>,
+    cover_render_rx: Receiver<CoverJobResult>,
     cover_render_inflight: RefCell<HashSet<CoverKey>>,
 
     remote_fetch_tx: Sender<RemoteFetchRequest>,
     remote_fetch_rx: Receiver<RemoteFetchResult>,
     remote_last_sent: Option<TrackKey>,
+    /// Shared with the remote fetch worker, which fills it.
+    pub lookup_cache: Arc<Mutex<LookupCache>>,
+    /// Last stats read from `lookup_cache`, shown while a worker holds its lock.
+    lookup_cache_stats: Cell<LookupCacheStats>,
+    /// First Enter on the lookup cache item arms the purge, the second one runs it.
+    pub lookup_cache_purge_armed: bool,
 
     pub overlay: Overlay,
     pub folder_input: FolderInput,
@@ -282,6 +450,40 @@ pub struct AppState {
 
     pub acoustid_input: String,
 
+    // User playlists (M3U8/PLS/XSPF under <asset root>/playlists).
+    // `user_playlist` is the playlist file backing the current queue, if any;
+    // `user_playlist_target` is where "add to playlist" appends.
+    pub user_playlist: Option<PathBuf>,
+    pub user_playlist_target: Option<PathBuf>,
+    // Up-next queue: played before the playlist continues (local playback only).
+    // `up_next_view` switches the playlist overlay to show/edit the queue.
+    pub up_next: Vec<PlaylistItem>,
+    pub up_next_view: bool,
+    pub up_next_selected: usize,
+    // Vim-style editing in the playlist overlay: tracks cut with `dd` (pasted with `p`),
+    // and marks set with `m<x>`. Marks hold paths so they survive reordering.
+    pub playlist_register: Vec<PlaylistItem>,
+    pub playlist_marks: HashMap<char, PathBuf>,
+    pub shuffle: ShuffleState,
+    pub playlist_search: Option<PlaylistSearch>,
+    pub library: Library,
+    pub library_browser: LibraryBrowser,
+    pub library_scanner: Option<LibraryScanner>,
+    /// Tracks retagged in the app while a scan runs, applied again to its result.
+    pub library_scan_retagged: Vec<LibraryTrack>,
+    // "Scanning… N files" while a scan runs.
+    pub library_scan_status: Option<String>,
+    /// Batch tagger session; kept while a write runs even if its overlay is closed.
+    pub tag_review: Option<TagReview>,
+    pub tag_form: Option<TagForm>,
+    /// Tag editor write in progress; outlives the form if it is closed meanwhile.
+    pub tag_save: Option<TagSave>,
+    pub playlist_manager_items: Vec<PathBuf>,
+    pub playlist_manager_selected: usize,
+    pub playlist_manager_delete_armed: Option<PathBuf>,
+    pub playlist_name_input: String,
+    pub playlist_name_mode: PlaylistNameMode,
+
     // Folder that backs the *current playback queue* (contains audio files).
     pub local_folder: Option<PathBuf>,
 
@@ -314,6 +516,19 @@ pub struct AppState {
     pub playlist_slide_target_x: i16,
 
     pub last_frame: Instant,
+    /// Lyrics view scroll position in lines; eases toward the active line, or is moved by
+    /// hand for unsynced lyrics.
+    pub lyrics_scroll: f32,
+    /// Song the manual lyrics scroll belongs to (hash of title and artist).
+    lyrics_scroll_song: u64,
+    /// Index into the lyric track layouts cycled with `B` (kept across songs).
+    pub lyric_layout: usize,
+    /// Tap-to-sync editor state (only while `Overlay::LyricSync` is open).
+    pub lyric_sync: Option<LyricSync>,
+    pub ab_loop: Option<AbLoop>,
+    pub keymap: Keymap,
+    /// Keys typed so far of a multi-key binding, and a pending count.
+    pub key_pending: PendingKeys,
 }
 
 #[derive(Debug)]
@@ -330,6 +545,19 @@ struct CoverRenderResult {
     ascii: String,
 }
 
+/// Work for the cover thread: ASCII renders, and swatches for the adaptive theme.
+#[derive(Debug)]
+enum CoverJob {
+    Render(CoverRenderRequest),
+    Swatches { hash: u64, bytes: Vec<u8> },
+}
+
+#[derive(Debug)]
+enum CoverJobResult {
+    Render(CoverRenderResult),
+    Swatches { hash: u64, swatches: Swatches },
+}
+
 fn fill_ascii(width: u16, height: u16, ch: char) -> String {
     let row = ch.to_string().repeat(width as usize);
     let mut s = String::new();
@@ -342,33 +570,37 @@ fn fill_ascii(width: u16, height: u16, ch: char) -> String {
 
 impl AppState {
     pub fn new(config: Config, theme: Theme) -> Self {
-        let (cover_render_tx, cover_render_req_rx) = mpsc::channel::<CoverRenderRequest>();
-        let (cover_render_res_tx, cover_render_rx) = mpsc::channel::<CoverRenderResult>();
+        let (cover_render_tx, cover_render_req_rx) = mpsc::channel::<CoverJob>();
+        let (cover_render_res_tx, cover_render_rx) = mpsc::channel::<CoverJobResult>();
 
         std::thread::spawn(move || {
-            while let Ok(req) = cover_render_req_rx.recv() {
-                let ascii = render_cover_ascii(&req.bytes, req.key.width, req.key.height)
+            while let Ok(job) = cover_render_req_rx.recv() {
+                let req = match job {
+                    CoverJob::Render(req) => req,
+                    CoverJob::Swatches { hash, bytes } => {
+                        let swatches = cover_swatches(&bytes);
+                        let _ = cover_render_res_tx.send(CoverJobResult::Swatches { hash, swatches });
+                        continue;
+                    }
+                };
+                let ascii = render_cover_text(&req.bytes, req.key.width, req.key.height, req.key.style)
                     .unwrap_or_else(|| fill_ascii(req.key.width, req.key.height, req.placeholder));
 
                 if let Some(folder) = req.persist_folder.as_deref() {
-                    let _ = crate::playback::local_player::write_cover_ascii_cache(
-                        folder,
-                        req.key.hash,
-                        req.key.width,
-                        req.key.height,
-                        &ascii,
-                    );
+                    let _ = crate::playback::local_player::write_cover_ascii_cache(folder, req.key, &ascii);
                 }
-                let _ = cover_render_res_tx.send(CoverRenderResult { key: req.key, ascii });
+                let _ = cover_render_res_tx.send(CoverJobResult::Render(CoverRenderResult { key: req.key, ascii }));
             }
         });
 
-        let (remote_fetch_tx, remote_fetch_rx) = start_remote_fetch_worker();
+        let lookup_cache = Arc::new(Mutex::new(LookupCache::load(lookup_cache_ttl(&config))));
+        let (remote_fetch_tx, remote_fetch_rx) = start_remote_fetch_worker(lookup_cache.clone());
 
         Self {
             config,
             theme,
-            kitty_graphics_supported: crate::utils::kitty::kitty_graphics_supported(),
+            adaptive_theme: AdaptiveTheme::default(),
+            graphics_protocol: None,
             player: PlayerState::default(),
             playlist: Playlist::default(),
             playlist_view: Playlist::default(),
@@ -382,6 +614,9 @@ impl AppState {
             remote_fetch_tx,
             remote_fetch_rx,
             remote_last_sent: None,
+            lookup_cache,
+            lookup_cache_stats: Cell::new(LookupCacheStats::default()),
+            lookup_cache_purge_armed: false,
             overlay: Overlay::None,
             folder_input: FolderInput::default(),
             settings_selected: 0,
@@ -393,6 +628,29 @@ impl AppState {
 
             acoustid_input: String::new(),
 
+            user_playlist: None,
+            user_playlist_target: None,
+            up_next: Vec::new(),
+            up_next_view: false,
+            up_next_selected: 0,
+            playlist_register: Vec::new(),
+            playlist_marks: HashMap::new(),
+            shuffle: ShuffleState::default(),
+            playlist_search: None,
+            library: Library::default(),
+            library_browser: LibraryBrowser::default(),
+            library_scanner: None,
+            library_scan_retagged: Vec::new(),
+            library_scan_status: None,
+            tag_review: None,
+            tag_form: None,
+            tag_save: None,
+            playlist_manager_items: Vec::new(),
+            playlist_manager_selected: 0,
+            playlist_manager_delete_armed: None,
+            playlist_name_input: String::new(),
+            playlist_name_mode: PlaylistNameMode::New,
+
             local_folder: None,
             local_root_folder: None,
             local_folder_kind: LocalFolderKind::Plain,
@@ -411,6 +669,13 @@ impl AppState {
             playlist_slide_x: 0,
             playlist_slide_target_x: 0,
             last_frame: Instant::now(),
+            lyrics_scroll: 0.0,
+            lyrics_scroll_song: 0,
+            lyric_layout: 0,
+            lyric_sync: None,
+            ab_loop: None,
+            keymap: Keymap::defaults(),
+            key_pending: PendingKeys::default(),
         }
     }
 
@@ -426,20 +691,10 @@ impl AppState {
         self.remote_last_sent = Some(key.clone());
 
         let duration_secs = self.player.track.duration.as_secs();
-        let has_lyrics = self.player.track.lyrics.is_some();
+        let has_lyrics = !self.player.track.lyrics.is_empty();
         let has_cover = self.player.track.cover.is_some();
 
-        let enable_fingerprint = self.config.audio_fingerprint && !self.config.acoustid_api_key.trim().is_empty();
-        let opts = FetchOptions {
-            enable_fetch: self.config.lyrics_cover_fetch,
-            download: self.config.lyrics_cover_download,
-            enable_fingerprint,
-            acoustid_api_key: if enable_fingerprint {
-                Some(self.config.acoustid_api_key.clone())
-            } else {
-                None
-            },
-        };
+        let opts = self.fetch_options();
 
         let req = RemoteFetchRequest {
             key,
@@ -456,10 +711,70 @@ impl AppState {
         let _ = self.remote_fetch_tx.send(req);
     }
 
+    /// Provider chain, cache and download settings for lookups, from the config.
+    pub fn fetch_options(&self) -> FetchOptions {
+        let enable_fingerprint = self.config.audio_fingerprint && !self.config.acoustid_api_key.trim().is_empty();
+        FetchOptions {
+            enable_fetch: self.config.lyrics_cover_fetch,
+            download: self.config.lyrics_cover_download,
+            providers: ProviderChainConfig {
+                order: self.config.provider_order.clone(),
+                providers: self.config.providers.clone(),
+                lyrics_folders: self
+                    .config
+                    .lyrics_folders
+                    .iter()
+                    .map(|s| s.trim())
+                    .filter(|s| !s.is_empty())
+                    .map(PathBuf::from)
+                    .collect(),
+                acoustid_api_key: if enable_fingerprint {
+                    Some(self.config.acoustid_api_key.clone())
+                } else {
+                    None
+                },
+            },
+            cache_ttl: lookup_cache_ttl(&self.config),
+        }
+    }
+
     pub fn reset_remote_fetch_state(&mut self) {
         self.remote_last_sent = None;
     }
 
+    /// Drawn every frame, so this never waits for the cache lock.
+    pub fn lookup_cache_stats(&self) -> LookupCacheStats {
+        if let Ok(c) = self.lookup_cache.try_lock() {
+            self.lookup_cache_stats.set(c.stats());
+        }
+        self.lookup_cache_stats.get()
+    }
+
+    /// Write lookups the debounced index save hasn't stored yet (on quit).
+    pub fn flush_lookup_cache(&self) {
+        if let Ok(mut c) = self.lookup_cache.lock() {
+            if let Err(e) = c.flush() {
+                log::warn!("lookup cache save failed: {e}");
+            }
+        }
+    }
+
+    /// Forget all cached lookups; the current song is looked up again.
+    pub fn purge_lookup_cache(&mut self) {
+        self.lookup_cache_purge_armed = false;
+        let res = match self.lookup_cache.lock() {
+            Ok(mut c) => c.purge(),
+            Err(_) => return,
+        };
+        match res {
+            Ok(()) => {
+                self.reset_remote_fetch_state();
+                self.set_toast("Lookup cache purged");
+            }
+            Err(e) => self.set_toast(format!("Lookup cache: {e}")),
+        }
+    }
+
     pub fn drain_remote_fetch_results(&mut self) -> Vec<RemoteFetchResult> {
         let mut out = Vec::new();
         loop {
@@ -485,6 +800,16 @@ impl AppState {
         self.toast = Some((msg.into(), Instant::now()));
     }
 
+    /// Cache key of a text cover in the current style.
+    pub fn cover_key(&self, hash: u64, width: u16, height: u16) -> CoverKey {
+        CoverKey {
+            hash,
+            width,
+            height,
+            style: CoverStyle::new(self.config.cover_mode, self.theme.capability),
+        }
+    }
+
     pub fn queue_cover_ascii_render(
         &self,
         key: CoverKey,
@@ -499,28 +824,56 @@ impl AppState {
             return;
         }
         self.cover_render_inflight.borrow_mut().insert(key);
-        let _ = self.cover_render_tx.send(CoverRenderRequest {
+        let _ = self.cover_render_tx.send(CoverJob::Render(CoverRenderRequest {
             key,
             bytes: bytes.to_vec(),
             placeholder,
             persist_folder,
-        });
+        }));
     }
 
     pub fn tick(&mut self, now: Instant) {
+        let dt = now.saturating_duration_since(self.last_frame);
         self.last_frame = now;
 
+        let duration = self.player.track.duration;
+        for t in &mut self.player.track.lyrics {
+            t.estimate_timing(duration);
+        }
+        if !self.lyrics_follow_playback() {
+            // Unsynced lyrics start from the top for every song.
+            let mut h = std::collections::hash_map::DefaultHasher::new();
+            (&self.player.track.title, &self.player.track.artist).hash(&mut h);
+            let song = h.finish();
+            if song != self.lyrics_scroll_song {
+                self.lyrics_scroll_song = song;
+                self.lyrics_scroll = 0.0;
+            }
+        } else if self.overlay == Overlay::Lyrics {
+            self.advance_lyrics_scroll(dt);
+        }
+
         loop {
             match self.cover_render_rx.try_recv() {
-                Ok(msg) => {
+                Ok(CoverJobResult::Render(msg)) => {
                     self.cover_render_inflight.borrow_mut().remove(&msg.key);
                     self.cover_cache.borrow_mut().put(msg.key, msg.ascii);
                 }
+                Ok(CoverJobResult::Swatches { hash, swatches }) => self.adaptive_theme.add_swatches(hash, swatches),
                 Err(TryRecvError::Empty) => break,
                 Err(TryRecvError::Disconnected) => break,
             }
         }
 
+        let enabled = self.config.adaptive_theme;
+        let cover_hash = self.player.track.cover_hash.filter(|_| self.player.track.cover.is_some());
+        if let (true, Some(hash), Some(bytes)) = (enabled, cover_hash, self.player.track.cover.as_deref()) {
+            if self.adaptive_theme.wants_swatches(hash) {
+                let _ = self.cover_render_tx.send(CoverJob::Swatches { hash, bytes: bytes.to_vec() });
+            }
+        }
+        self.adaptive_theme.update(&mut self.theme, enabled, cover_hash, now);
+
         if let Some(anim) = &self.cover_anim {
             if now.duration_since(anim.started_at) >= anim.duration {
                 self.cover_anim = None;
@@ -546,6 +899,108 @@ impl AppState {
         }
     }
 
+    pub fn open_lyrics_view(&mut self) {
+        // Start on the active line; only later line changes are animated. Lyrics scrolled by
+        // hand stay where they were left.
+        if let Some(i) = self.active_lyric_index() {
+            self.lyrics_scroll = i as f32;
+        }
+        self.overlay = Overlay::Lyrics;
+    }
+
+    /// Index of the line being sung; None for unsynced lyrics without estimated sync.
+    pub fn active_lyric_index(&self) -> Option<usize> {
+        if !self.lyrics_follow_playback() {
+            return None;
+        }
+        let (primary, _) = self.lyric_display();
+        crate::render::lyric_renderer::active_line(&primary?.lines, self.lyric_position_ms())
+    }
+
+    /// Whether the shown lyrics follow playback: synced ones, or unsynced ones with estimated
+    /// sync on once the song's duration is known.
+    pub fn lyrics_follow_playback(&self) -> bool {
+        match self.lyric_display().0.map(|t| t.timing) {
+            Some(LyricTiming::Synced) => true,
+            Some(LyricTiming::Unsynced(d)) => self.config.lyrics_estimated_sync && !d.is_zero(),
+            None => false,
+        }
+    }
+
+    /// Move unsynced lyrics by hand (synced ones follow playback).
+    pub fn scroll_lyrics(&mut self, lines: i32) {
+        if self.lyrics_follow_playback() {
+            return;
+        }
+        let len = self.lyric_display().0.map_or(0, |t| t.lines.len());
+        let max = len.saturating_sub(1) as i64;
+        self.lyrics_scroll = (self.lyrics_scroll.round() as i64 + lines as i64).clamp(0, max) as f32;
+    }
+
+    pub fn toggle_lyrics_estimated_sync(&mut self) {
+        self.config.lyrics_estimated_sync = !self.config.lyrics_estimated_sync;
+        let _ = self.config.save();
+        self.set_toast(format!(
+            "Estimated lyric sync: {}",
+            if self.config.lyrics_estimated_sync { "On" } else { "Off" }
+        ));
+    }
+
+    /// Playback position on the lyrics' clock (position plus the song's lyric offset).
+    pub fn lyric_position_ms(&self) -> u64 {
+        let pos = self.player.position.as_millis() as i64;
+        (pos + self.player.track.lyrics_offset_ms).max(0) as u64
+    }
+
+    /// Lyric track layouts `B` cycles through: original with each other track stacked
+    /// below it, then every track on its own.
+    fn lyric_layouts(&self) -> Vec<(usize, Option<usize>)> {
+        let n = self.player.track.lyrics.len();
+        if n == 0 {
+            return Vec::new();
+        }
+        let mut out: Vec<(usize, Option<usize>)> = (1..n).map(|i| (0, Some(i))).collect();
+        out.extend((0..n).map(|i| (i, None)));
+        out
+    }
+
+    /// Primary and (optional) stacked secondary lyric track for the current layout.
+    pub fn lyric_display(&self) -> (Option<&LyricTrack>, Option<&LyricTrack>) {
+        let layouts = self.lyric_layouts();
+        let Some(&(p, s)) = layouts.get(self.lyric_layout.min(layouts.len().saturating_sub(1))) else {
+            return (None, None);
+        };
+        let tracks = &self.player.track.lyrics;
+        (tracks.get(p), s.and_then(|i| tracks.get(i)))
+    }
+
+    pub fn cycle_lyric_layout(&mut self) {
+        let count = self.lyric_layouts().len();
+        if count <= 1 {
+            self.set_toast("No other lyric tracks");
+            return;
+        }
+        self.lyric_layout = (self.lyric_layout.min(count - 1) + 1) % count;
+        let label = match self.lyric_display() {
+            (Some(p), Some(s)) => format!("Lyrics: {} + {}", p.label(), s.label()),
+            (Some(p), None) => format!("Lyrics: {}", p.label()),
+            _ => return,
+        };
+        self.set_toast(label);
+    }
+
+    fn advance_lyrics_scroll(&mut self, dt: Duration) {
+        let target = self.active_lyric_index().unwrap_or(0) as f32;
+        let diff = target - self.lyrics_scroll;
+        if diff.abs() > 12.0 || diff.abs() < 0.01 {
+            // Big jumps (seek, new track) snap instead of scrolling through everything.
+            self.lyrics_scroll = target;
+            return;
+        }
+        let k = 1.0 - (-dt.as_secs_f32() * 10.0).exp();
+        self.lyrics_scroll += diff * k;
+    }
+
     pub fn start_cover_anim(&mut self, from: CoverSnapshot, to: CoverSnapshot, dir: i8, now: Instant) {
         self.cover_anim = Some(CoverAnim {
             from,
@@ -576,7 +1031,112 @@ impl AppState {
         self.folder_input.buf.clear();
     }
 
+    pub fn open_playlist_manager(&mut self) {
+        self.playlist_manager_items = crate::data::playlist_file::list_playlists();
+        self.playlist_manager_delete_armed = None;
+        if let Some(cur) = self.user_playlist.as_ref() {
+            if let Some(i) = self.playlist_manager_items.iter().position(|p| p == cur) {
+                self.playlist_manager_selected = i;
+            }
+        }
+        self.playlist_manager_selected = self
+            .playlist_manager_selected
+            .min(self.playlist_manager_items.len().saturating_sub(1));
+        self.overlay = Overlay::PlaylistManager;
+    }
+
+    pub fn open_playlist_name_input(&mut self, mode: PlaylistNameMode, initial: &str) {
+        self.playlist_name_mode = mode;
+        self.playlist_name_input = initial.to_string();
+        self.overlay = Overlay::PlaylistNameInput;
+    }
+
+    pub fn open_library(&mut self) {
+        self.library_browser.reload(&self.library);
+        self.overlay = Overlay::Library;
+    }
+
+    /// Library roots from the config, falling back to the default opening folder
+    /// and then to the folder that is currently open.
+    pub fn library_roots(&self) -> Vec<PathBuf> {
+        let mut roots: Vec<PathBuf> = self
+            .config
+            .library_folders
+            .iter()
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(PathBuf::from)
+            .collect();
+        if roots.is_empty() && !self.config.default_opening_folder.trim().is_empty() {
+            roots.push(PathBuf::from(self.config.default_opening_folder.trim()));
+        }
+        if roots.is_empty() {
+            if let Some(root) = self.local_root_folder.clone().or_else(|| self.local_folder.clone()) {
+                roots.push(root);
+            }
+        }
+        roots
+    }
+
+    pub fn start_library_scan(&mut self) {
+        if self.library_scanner.is_some() {
+            return;
+        }
+        let roots = self.library_roots();
+        if roots.is_empty() {
+            self.set_toast("Library: set library_folders in the config (or open a folder) first");
+            return;
+        }
+        self.library_scanner = Some(LibraryScanner::start(roots, &self.library));
+        self.library_scan_status = Some("Scanning…".to_string());
+    }
+
+    /// Look up `paths` for the batch tagger and open its review overlay.
+    pub fn open_tagger(&mut self, title: String, paths: Vec<PathBuf>) {
+        if self.tag_review.as_ref().is_some_and(|r| r.busy().is_some()) {
+            self.overlay = Overlay::Tagger;
+            return;
+        }
+        if paths.is_empty() {
+            self.set_toast("Tagger: no local files here");
+            return;
+        }
+        let total = paths.len();
+        let job = TaggerJob::scan(paths, self.fetch_options(), self.lookup_cache.clone());
+        self.tag_review = Some(TagReview::new(title, job, total));
+        self.overlay = Overlay::Tagger;
+    }
+
+    pub fn open_tag_editor(&mut self, form: TagForm) {
+        self.tag_form = Some(form);
+        self.overlay = Overlay::TagEditor;
+    }
+
     pub fn close_overlay(&mut self) {
+        if self.overlay == Overlay::TagEditor {
+            // Back to the playlist / library the editor was opened from.
+            self.overlay = self.tag_form.take().map_or(Overlay::None, |f| f.return_to);
+            return;
+        }
+        if self.overlay == Overlay::LyricSync {
+            // Cancelling the sync editor goes back to the lyrics view.
+            self.lyric_sync = None;
+            self.open_lyrics_view();
+            return;
+        }
+        if self.overlay == Overlay::Tagger {
+            // Stop looking up; a running write or undo is left to finish.
+            if self.tag_review.as_ref().is_some_and(|r| matches!(r.busy(), None | Some(TagJobKind::Lookup))) {
+                self.tag_review = None;
+            }
+        }
         self.overlay = Overlay::None;
     }
 }
+
+fn lookup_cache_ttl(config: &Config) -> CacheTtl {
+    CacheTtl {
+        hit_days: config.lookup_cache_ttl_days,
+        miss_days: config.lookup_cache_miss_ttl_days,
+    }
+}
@@ -0,0 +1,134 @@
+use crate::app::state::Overlay;
+use crate::data::library::LibraryTrack;
+use crate::playback::tag_editor::{validate, EditField, EditJob};
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditScope {
+    Track,
+    Album,
+}
+
+#[derive(Debug, Clone)]
+pub struct FormField {
+    pub field: EditField,
+    /// What the files hold now; None when the album's tracks disagree.
+    pub original: Option<String>,
+    pub value: String,
+}
+
+impl FormField {
+    /// Mixed fields only count once something was typed (they can't be cleared for all).
+    pub fn changed(&self) -> bool {
+        match self.original.as_deref() {
+            Some(o) => self.value.trim() != o.trim(),
+            None => !self.value.trim().is_empty(),
+        }
+    }
+}
+
+/// Tag editor overlay for one track or every track of an album.
+#[derive(Debug)]
+pub struct TagForm {
+    pub scope: EditScope,
+    pub title: String,
+    /// Tags as read when the editor opened, one per file being edited.
+    pub tracks: Vec<LibraryTrack>,
+    pub fields: Vec<FormField>,
+    pub selected: usize,
+    /// Validation or write error shown under the fields.
+    pub error: Option<String>,
+    /// Overlay the editor was opened from (playlist or library).
+    pub return_to: Overlay,
+}
+
+/// Tag edits being written. The playing track lets go of its file meanwhile and is picked
+/// up again afterwards.
+#[derive(Debug)]
+pub struct TagSave {
+    pub job: EditJob,
+    /// Path, position and paused state of the track that was playing one of the files.
+    pub resume: Option<(PathBuf, Duration, bool)>,
+}
+
+impl TagForm {
+    pub fn new(scope: EditScope, tracks: Vec<LibraryTrack>, return_to: Overlay) -> Self {
+        let offered: &[EditField] = match scope {
+            EditScope::Track => &EditField::ALL,
+            EditScope::Album => &EditField::ALBUM,
+        };
+        let fields = offered
+            .iter()
+            .map(|&field| {
+                let mut values = tracks.iter().map(|t| field.value_of(t));
+                let first = values.next().unwrap_or_default();
+                let original = values.all(|v| v == first).then_some(first);
+                FormField {
+                    field,
+                    value: original.clone().unwrap_or_default(),
+                    original,
+                }
+            })
+            .collect();
+        let title = match (scope, tracks.first()) {
+            (EditScope::Track, Some(t)) => format!("Edit tags — {}", t.display_title()),
+            (EditScope::Album, Some(t)) => {
+                let album = if t.album.trim().is_empty() { "Unknown album" } else { t.album.trim() };
+                format!("Edit album — {album} ({} tracks)", tracks.len())
+            }
+            (_, None) => "Edit tags".to_string(),
+        };
+        Self {
+            scope,
+            title,
+            tracks,
+            fields,
+            selected: 0,
+            error: None,
+            return_to,
+        }
+    }
+
+    pub fn move_by(&mut self, delta: i32) {
+        let len = self.fields.len();
+        if len == 0 {
+            return;
+        }
+        self.selected = (self.selected as i64 + delta as i64).rem_euclid(len as i64) as usize;
+    }
+
+    pub fn push(&mut self, c: char) {
+        if let Some(f) = self.fields.get_mut(self.selected) {
+            // Digits only in number fields; everything else is caught by `edits`.
+            if f.field.numeric() && !c.is_ascii_digit() {
+                return;
+            }
+            f.value.push(c);
+            self.error = None;
+        }
+    }
+
+    pub fn pop(&mut self) {
+        if let Some(f) = self.fields.get_mut(self.selected) {
+            f.value.pop();
+            self.error = None;
+        }
+    }
+
+    /// Changed fields, validated. Errors point the selection at the offending field.
+    pub fn edits(&mut self) -> Result<Vec<(EditField, String)>, String> {
+        let mut out = Vec::new();
+        for (i, f) in self.fields.iter().enumerate() {
+            if !f.changed() {
+                continue;
+            }
+            if let Err(e) = validate(f.field, &f.value) {
+                self.selected = i;
+                return Err(e);
+            }
+            out.push((f.field, f.value.trim().to_string()));
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,148 @@
+use crate::data::library::LibraryTrack;
+use crate::playback::tagger::{undo_depth, ApplyReport, TagProposal, TaggerEvent, TaggerJob};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagJobKind {
+    Lookup,
+    Write,
+    Undo,
+}
+
+/// Batch tagger overlay: proposals stream in while the folder is looked up, get reviewed
+/// here, then are written (or dry-run) in the background.
+#[derive(Debug)]
+pub struct TagReview {
+    pub title: String,
+    pub proposals: Vec<TagProposal>,
+    pub selected: usize,
+    /// Files handled / total of the running job.
+    pub progress: (usize, usize),
+    pub job: Option<(TagJobKind, TaggerJob)>,
+    pub dry_run: bool,
+    /// Outcome of the last write / undo.
+    pub status: Option<String>,
+    /// Written batches that `u` can revert.
+    pub undo_depth: usize,
+    /// Tags before / after of files the last write or undo changed, for the caller to
+    /// pass on to the player and the library.
+    pub retagged: Vec<(LibraryTrack, LibraryTrack)>,
+}
+
+impl TagReview {
+    pub fn new(title: String, job: TaggerJob, total: usize) -> Self {
+        Self {
+            title,
+            proposals: Vec::new(),
+            selected: 0,
+            progress: (0, total),
+            job: Some((TagJobKind::Lookup, job)),
+            dry_run: false,
+            status: None,
+            undo_depth: undo_depth(),
+            retagged: Vec::new(),
+        }
+    }
+
+    pub fn busy(&self) -> Option<TagJobKind> {
+        self.job.as_ref().map(|(kind, _)| *kind)
+    }
+
+    pub fn accepted_count(&self) -> usize {
+        self.proposals.iter().filter(|p| p.accepted).count()
+    }
+
+    pub fn move_by(&mut self, delta: i32) {
+        let len = self.proposals.len();
+        if len == 0 {
+            return;
+        }
+        self.selected = (self.selected as i64 + delta as i64).rem_euclid(len as i64) as usize;
+    }
+
+    pub fn toggle_selected(&mut self) {
+        if let Some(p) = self.proposals.get_mut(self.selected) {
+            p.accepted = !p.accepted;
+        }
+    }
+
+    /// Select all, or none when everything is already selected.
+    pub fn toggle_all(&mut self) {
+        let all = self.proposals.iter().all(|p| p.accepted);
+        for p in &mut self.proposals {
+            p.accepted = !all;
+        }
+    }
+
+    /// Write the accepted proposals (nothing happens while another job runs).
+    pub fn start_write(&mut self) -> bool {
+        if self.job.is_some() {
+            return false;
+        }
+        let accepted: Vec<TagProposal> = self.proposals.iter().filter(|p| p.accepted).cloned().collect();
+        if accepted.is_empty() {
+            return false;
+        }
+        self.progress = (0, accepted.len());
+        self.job = Some((TagJobKind::Write, TaggerJob::apply(accepted, self.dry_run)));
+        true
+    }
+
+    pub fn start_undo(&mut self) -> bool {
+        if self.job.is_some() || self.undo_depth == 0 {
+            return false;
+        }
+        self.progress = (0, 0);
+        self.job = Some((TagJobKind::Undo, TaggerJob::undo()));
+        true
+    }
+
+    /// Take in what the running job reported. Returns a message once a write or undo ends.
+    pub fn poll(&mut self) -> Option<String> {
+        let events = self.job.as_ref()?.1.drain();
+        let mut done: Option<String> = None;
+        for ev in events {
+            match ev {
+                TaggerEvent::Progress { done, total } => self.progress = (done, total),
+                TaggerEvent::Proposal(p) => self.proposals.push(p),
+                TaggerEvent::Scanned => {
+                    self.job = None;
+                }
+                TaggerEvent::Applied(mut report) => {
+                    self.job = None;
+                    self.retagged.append(&mut report.retagged);
+                    if !report.dry_run {
+                        // Written files drop out of the list; failures stay for another try.
+                        let failed: Vec<&PathBuf> = report.failed.iter().map(|(p, _)| p).collect();
+                        self.proposals.retain(|p| !p.accepted || failed.contains(&&p.path));
+                        self.selected = self.selected.min(self.proposals.len().saturating_sub(1));
+                        self.undo_depth = undo_depth();
+                    }
+                    done = Some(summary(&report, if report.dry_run { "Dry run: would write" } else { "Wrote" }));
+                }
+                TaggerEvent::Undone(mut report) => {
+                    self.job = None;
+                    self.retagged.append(&mut report.retagged);
+                    self.undo_depth = undo_depth();
+                    done = Some(summary(&report, "Undo: restored"));
+                }
+            }
+        }
+        if done.is_some() {
+            self.status = done.clone();
+        }
+        done
+    }
+}
+
+fn summary(report: &ApplyReport, verb: &str) -> String {
+    let mut s = format!("{verb} {} file(s)", report.written);
+    if report.skipped > 0 {
+        s.push_str(&format!(", {} unchanged", report.skipped));
+    }
+    if let Some((path, err)) = report.failed.first() {
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        s.push_str(&format!(", {} failed ({name}: {err})", report.failed.len()));
+    }
+    s
+}
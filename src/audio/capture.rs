@@ -7,6 +7,8 @@ pub struct AudioCapture {
     samples: Arc<Mutex<Vec<f32>>>,
     last_sample_at: Arc<Mutex<Option<Instant>>>,
     last_restart_at: Instant,
+    channels: u16,
+    sample_rate: u32,
     _stream: cpal::Stream,
 }
 
@@ -56,6 +58,8 @@ impl AudioCapture {
                 samples: dummy,
                 last_sample_at,
                 last_restart_at: Instant::now(),
+                channels: 2,
+                sample_rate: 44_100,
                 _stream,
             });
         };
@@ -65,6 +69,8 @@ impl AudioCapture {
         }
 
         let config = device.default_input_config()?;
+        let channels = config.channels();
+        let sample_rate = config.sample_rate().0;
         let samples = Arc::new(Mutex::new(Vec::with_capacity(8192)));
         let samples_cloned = Arc::clone(&samples);
 
@@ -101,6 +107,8 @@ impl AudioCapture {
                     samples: dummy,
                     last_sample_at,
                     last_restart_at: Instant::now(),
+                    channels: 2,
+                    sample_rate: 44_100,
                     _stream,
                 });
             }
@@ -111,15 +119,24 @@ impl AudioCapture {
             samples,
             last_sample_at,
             last_restart_at: Instant::now(),
+            channels,
+            sample_rate,
             _stream: stream,
         })
     }
 
+    /// (channels, sample_rate) of the captured interleaved samples.
+    pub fn format(&self) -> (u16, u32) {
+        (self.channels, self.sample_rate)
+    }
+
+    /// The last `n` samples, rounded down to whole frames (callbacks deliver whole frames,
+    /// so the buffer always ends on one).
     pub fn latest_samples(&self, n: usize) -> Vec<f32> {
+        let ch = self.channels.max(1) as usize;
         let guard = self.samples.lock().unwrap();
-        if guard.len() <= n {
-            return guard.clone();
-        }
+        let n = n.min(guard.len());
+        let n = n - n % ch;
         guard[guard.len() - n..].to_vec()
     }
 }
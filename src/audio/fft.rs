@@ -1 +1,92 @@
-// Internal FFT removed.
+// Minimal in-process FFT (radix-2, iterative). Only used for visualization,
+// so we keep it small and allocation-free after setup.
+
+use std::f32::consts::PI;
+
+pub struct Fft {
+    size: usize,
+    twiddle_re: Vec<f32>,
+    twiddle_im: Vec<f32>,
+    bitrev: Vec<usize>,
+    window: Vec<f32>,
+}
+
+impl Fft {
+    /// `size` is rounded up to the next power of two (min 64).
+    pub fn new(size: usize) -> Self {
+        let size = size.max(64).next_power_of_two();
+        let bits = size.trailing_zeros();
+
+        let half = size / 2;
+        let mut twiddle_re = Vec::with_capacity(half);
+        let mut twiddle_im = Vec::with_capacity(half);
+        for k in 0..half {
+            let a = -2.0 * PI * k as f32 / size as f32;
+            twiddle_re.push(a.cos());
+            twiddle_im.push(a.sin());
+        }
+
+        let bitrev = (0..size)
+            .map(|i| i.reverse_bits() >> (usize::BITS - bits))
+            .collect();
+
+        // Hann window
+        let window = (0..size)
+            .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / (size - 1) as f32).cos())
+            .collect();
+
+        Self {
+            size,
+            twiddle_re,
+            twiddle_im,
+            bitrev,
+            window,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Windowed magnitude spectrum of `input` (zero-padded/truncated to `size`).
+    /// Writes `size / 2` bins into `out`, normalized so a full-scale sine is ~1.0.
+    pub fn magnitudes(&self, input: &[f32], re: &mut Vec<f32>, im: &mut Vec<f32>, out: &mut Vec<f32>) {
+        let n = self.size;
+        re.clear();
+        re.resize(n, 0.0);
+        im.clear();
+        im.resize(n, 0.0);
+
+        // Take the most recent samples when the input is longer than the frame.
+        let src = if input.len() > n { &input[input.len() - n..] } else { input };
+        for (i, &s) in src.iter().enumerate() {
+            re[self.bitrev[i]] = s * self.window[i];
+        }
+
+        let mut len = 2;
+        while len <= n {
+            let step = n / len;
+            let half = len / 2;
+            for start in (0..n).step_by(len) {
+                for k in 0..half {
+                    let wr = self.twiddle_re[k * step];
+                    let wi = self.twiddle_im[k * step];
+                    let a = start + k;
+                    let b = a + half;
+                    let tr = re[b] * wr - im[b] * wi;
+                    let ti = re[b] * wi + im[b] * wr;
+                    re[b] = re[a] - tr;
+                    im[b] = im[a] - ti;
+                    re[a] += tr;
+                    im[a] += ti;
+                }
+            }
+            len <<= 1;
+        }
+
+        // Hann window has a coherent gain of 0.5.
+        let scale = 4.0 / n as f32;
+        out.clear();
+        out.extend((0..n / 2).map(|i| (re[i] * re[i] + im[i] * im[i]).sqrt() * scale));
+    }
+}
@@ -1,3 +1,5 @@
 pub mod capture;
 pub mod cava;
+pub mod fft;
 pub mod smoother;
+pub mod spectrum;
@@ -1 +1,157 @@
-// Internal FFT spectrum processing removed.
+// In-process spectrum analyzer used when the external `cava` binary is unavailable.
+// Output mirrors `CavaRunner`: per-channel bars in 0..1, low -> high frequency
+// (or reversed when configured).
+
+use crate::audio::cava::CavaConfig;
+use crate::audio::fft::Fft;
+
+const FFT_SIZE: usize = 2048;
+const MIN_FREQ_HZ: f32 = 50.0;
+const MAX_FREQ_HZ: f32 = 16_000.0;
+// dB range mapped to 0..1 before auto-gain.
+const FLOOR_DB: f32 = -70.0;
+// Bars fall at most this fraction per frame (cava-like "gravity").
+const FALL_PER_FRAME: f32 = 0.08;
+
+pub struct SpectrumAnalyzer {
+    cfg: CavaConfig,
+    fft: Fft,
+    sample_rate: u32,
+    // (lo_bin, hi_bin, tilt) per bar; rebuilt when the sample rate changes.
+    bands: Vec<(usize, usize, f32)>,
+    left: Vec<f32>,
+    right: Vec<f32>,
+    // Slowly decaying peak used to normalize levels (auto sensitivity).
+    peak: f32,
+    scratch_ch: Vec<f32>,
+    scratch_re: Vec<f32>,
+    scratch_im: Vec<f32>,
+    scratch_mag: Vec<f32>,
+}
+
+impl SpectrumAnalyzer {
+    pub fn new(cfg: CavaConfig) -> Self {
+        let bars = cfg.bars.clamp(8, 96);
+        Self {
+            cfg,
+            fft: Fft::new(FFT_SIZE),
+            sample_rate: 0,
+            bands: Vec::new(),
+            left: vec![0.0; bars],
+            right: vec![0.0; bars],
+            peak: 1.0,
+            scratch_ch: Vec::with_capacity(FFT_SIZE),
+            scratch_re: Vec::with_capacity(FFT_SIZE),
+            scratch_im: Vec::with_capacity(FFT_SIZE),
+            scratch_mag: Vec::with_capacity(FFT_SIZE / 2),
+        }
+    }
+
+    pub fn config(&self) -> CavaConfig {
+        self.cfg
+    }
+
+    /// Number of interleaved samples to request from a source with `channels` channels.
+    pub fn wanted_samples(&self, channels: u16) -> usize {
+        self.fft.size() * channels.max(1) as usize
+    }
+
+    /// Analyze the latest interleaved samples. An empty slice decays bars towards zero.
+    pub fn feed(&mut self, interleaved: &[f32], channels: u16, sample_rate: u32) {
+        let channels = channels.max(1) as usize;
+        if interleaved.len() < channels * 64 || sample_rate == 0 {
+            self.decay();
+            return;
+        }
+
+        if sample_rate != self.sample_rate {
+            self.sample_rate = sample_rate;
+            self.rebuild_bands();
+        }
+
+        let mut left = std::mem::take(&mut self.left);
+        let mut right = std::mem::take(&mut self.right);
+
+        self.analyze_channel(interleaved, channels, 0, &mut left);
+        if channels >= 2 {
+            self.analyze_channel(interleaved, channels, 1, &mut right);
+        } else {
+            right.copy_from_slice(&left);
+        }
+
+        self.left = left;
+        self.right = right;
+    }
+
+    pub fn latest_bars(&self) -> Vec<f32> {
+        self.left
+            .iter()
+            .zip(self.right.iter())
+            .map(|(l, r)| ((l + r) * 0.5).clamp(0.0, 1.0))
+            .collect()
+    }
+
+    pub fn latest_stereo_bars(&self) -> (Vec<f32>, Vec<f32>) {
+        (self.left.clone(), self.right.clone())
+    }
+
+    fn decay(&mut self) {
+        for v in self.left.iter_mut().chain(self.right.iter_mut()) {
+            *v = (*v - FALL_PER_FRAME).max(0.0);
+        }
+    }
+
+    fn rebuild_bands(&mut self) {
+        let bars = self.left.len();
+        let n = self.fft.size();
+        let nyquist = self.sample_rate as f32 / 2.0;
+        let fmax = MAX_FREQ_HZ.min(nyquist * 0.95).max(MIN_FREQ_HZ * 2.0);
+        let ratio = fmax / MIN_FREQ_HZ;
+        let bin_of = |f: f32| ((f * n as f32 / self.sample_rate as f32).round() as usize).clamp(1, n / 2 - 1);
+
+        self.bands.clear();
+        for i in 0..bars {
+            let f_lo = MIN_FREQ_HZ * ratio.powf(i as f32 / bars as f32);
+            let f_hi = MIN_FREQ_HZ * ratio.powf((i + 1) as f32 / bars as f32);
+            let lo = bin_of(f_lo);
+            let hi = bin_of(f_hi).max(lo + 1).min(n / 2);
+            // Music energy drops roughly 3 dB/octave; tilt so the highs stay visible.
+            let center = (f_lo * f_hi).sqrt();
+            let tilt = (center / 1000.0).max(0.05).sqrt();
+            self.bands.push((lo, hi, tilt));
+        }
+    }
+
+    fn analyze_channel(&mut self, interleaved: &[f32], channels: usize, ch: usize, out: &mut [f32]) {
+        self.scratch_ch.clear();
+        self.scratch_ch
+            .extend(interleaved.iter().skip(ch).step_by(channels).copied());
+        self.fft.magnitudes(
+            &self.scratch_ch,
+            &mut self.scratch_re,
+            &mut self.scratch_im,
+            &mut self.scratch_mag,
+        );
+
+        let bars = out.len();
+        let mut frame_peak = 0.0f32;
+        for (i, &(lo, hi, tilt)) in self.bands.iter().enumerate() {
+            let mag = self.scratch_mag[lo..hi].iter().copied().fold(0.0f32, f32::max) * tilt;
+            let db = 20.0 * (mag + 1e-9).log10();
+            let v = ((db - FLOOR_DB) / -FLOOR_DB).clamp(0.0, 1.5);
+            frame_peak = frame_peak.max(v);
+
+            let idx = if self.cfg.reverse { bars - 1 - i } else { i };
+            let target = (v / self.peak).clamp(0.0, 1.0);
+            let prev = out[idx];
+            out[idx] = if target >= prev { target } else { (prev - FALL_PER_FRAME).max(target) };
+        }
+
+        // Attack fast, release slowly; never boost quiet passages above ~2x.
+        if frame_peak > self.peak {
+            self.peak = frame_peak;
+        } else {
+            self.peak = (self.peak * 0.995 + frame_peak * 0.005).max(0.5);
+        }
+    }
+}
@@ -6,13 +6,22 @@ use std::path::{Path, PathBuf};
 const ENV_ASSET_DIR: &str = "CLI_MUSIC_PLAYER_ASSET_DIR";
 
 const DEFAULT_CONFIG_TOML: &str = include_str!("../../config/default.toml");
+const DEFAULT_KEYS_TOML: &str = include_str!("../../config/keys.toml");
 
-const THEME_SYSTEM_TOML: &str = include_str!("../../themes/system.toml");
+pub const THEME_SYSTEM_TOML: &str = include_str!("../../themes/system.toml");
 const THEME_LATTE_TOML: &str = include_str!("../../themes/catppuccin_latte.toml");
 const THEME_FRAPPE_TOML: &str = include_str!("../../themes/catppuccin_frappe.toml");
 const THEME_MACCHIATO_TOML: &str = include_str!("../../themes/catppuccin_macchiato.toml");
 const THEME_MOCHA_TOML: &str = include_str!("../../themes/catppuccin_mocha.toml");
 
+/// Point the asset root at one temp directory for the whole test run, so tests never touch
+/// the real config directory and never move each other's files mid-test.
+#[cfg(test)]
+pub(crate) fn isolate_for_tests() {
+    static INIT: std::sync::Once = std::sync::Once::new();
+    INIT.call_once(|| std::env::set_var(ENV_ASSET_DIR, tempfile::tempdir().unwrap().keep()));
+}
+
 pub fn resolve_asset_root() -> PathBuf {
     if let Some(p) = std::env::var_os(ENV_ASSET_DIR) {
         return PathBuf::from(p);
@@ -111,11 +120,13 @@ fn migrate_legacy_local_assets(sys_root: &Path) -> Result<()> {
 fn ensure_all_assets(root: &Path) -> Result<()> {
     // Create:
     //   <root>/config/default.toml
+    //   <root>/config/keys.toml
     //   <root>/themes/*.toml
     ensure_dir(&root.join("config"))?;
     ensure_dir(&root.join("themes"))?;
 
     write_if_missing(&root.join("config/default.toml"), DEFAULT_CONFIG_TOML)?;
+    write_if_missing(&root.join("config/keys.toml"), DEFAULT_KEYS_TOML)?;
     ensure_themes(root)?;
 
     Ok(())
@@ -22,11 +22,23 @@ pub struct Config {
     #[serde(default)]
     pub transparent_background: bool,
 
+    /// Derive the palette from the current album cover (the theme is the fallback and picks light/dark).
+    #[serde(default)]
+    pub adaptive_theme: bool,
+
     #[serde(default = "default_album_border")]
     pub album_border: bool,
 
+    /// Draw covers as real images (with whichever protocol `image_protocol` resolves to).
+    #[serde(default)]
+    pub cover_images: bool,
+
     #[serde(default)]
-    pub kitty_graphics: bool,
+    pub image_protocol: ImageProtocol,
+
+    /// How covers are drawn as text (when not using a terminal image protocol).
+    #[serde(default)]
+    pub cover_mode: CoverMode,
 
     #[serde(default = "default_kitty_cover_scale_percent")]
     pub kitty_cover_scale_percent: u8,
@@ -52,17 +64,204 @@ pub struct Config {
     #[serde(default)]
     pub lyrics_cover_download: bool,
 
+    /// Spread unsynced lyrics over the song and follow playback instead of scrolling by hand.
+    #[serde(default)]
+    pub lyrics_estimated_sync: bool,
+
     #[serde(default)]
     pub audio_fingerprint: bool,
 
     #[serde(default)]
     pub acoustid_api_key: String,
 
+    /// Order in which remote providers are asked; the first hit wins (synced lyrics beat plain).
+    #[serde(default = "default_provider_order")]
+    pub provider_order: Vec<ProviderKind>,
+
+    /// Days a found lyric / cover / identification stays in the lookup cache before it is
+    /// looked up again.
+    #[serde(default = "default_lookup_cache_ttl_days")]
+    pub lookup_cache_ttl_days: u32,
+
+    /// Days a "not found" is remembered (0 = always ask again).
+    #[serde(default = "default_lookup_cache_miss_ttl_days")]
+    pub lookup_cache_miss_ttl_days: u32,
+
+    /// Folders the `local` lyrics provider looks in (`<artist> - <title>.lrc`, `<title>.txt`, ...).
+    #[serde(default)]
+    pub lyrics_folders: Vec<String>,
+
     #[serde(default)]
     pub resume_last_position: bool,
 
+    /// Crossfade between local tracks in milliseconds (0 = gapless, no fade).
+    #[serde(default)]
+    pub crossfade_ms: u32,
+
+    #[serde(default = "default_replay_gain")]
+    pub replay_gain: ReplayGainMode,
+
+    #[serde(default)]
+    pub replay_gain_scan: bool,
+
+    /// Shuffle whole albums (tracks in album order) when a MultiAlbum root is open.
+    #[serde(default)]
+    pub shuffle_albums: bool,
+
+    /// Folders indexed (recursively) into the library. Empty = the default opening folder.
+    #[serde(default)]
+    pub library_folders: Vec<String>,
+
+    /// Listen on the control socket (`cli-music-player ctl ...`).
+    #[serde(default = "default_control_socket")]
+    pub control_socket: bool,
+
+    /// Publish local playback as an MPRIS2 player on the session bus.
+    #[serde(default = "default_mpris_server")]
+    pub mpris_server: bool,
+
     #[serde(default, rename = "default-opening-folder")]
     pub default_opening_folder: String,
+
+    /// Per-provider switches and base URLs. Kept last: it is written out as TOML tables.
+    #[serde(default)]
+    pub providers: ProvidersConfig,
+}
+
+/// A source of lyrics, covers or track identification for the remote fetch worker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    Local,
+    Lrclib,
+    Netease,
+    Qqmusic,
+    Genius,
+    Musicbrainz,
+    Coverartarchive,
+    Acoustid,
+}
+
+impl ProviderKind {
+    pub const ALL: [ProviderKind; 8] = [
+        Self::Local,
+        Self::Lrclib,
+        Self::Netease,
+        Self::Qqmusic,
+        Self::Genius,
+        Self::Musicbrainz,
+        Self::Coverartarchive,
+        Self::Acoustid,
+    ];
+
+    pub fn as_label(self) -> &'static str {
+        match self {
+            Self::Local => "Lyrics folders",
+            Self::Lrclib => "LRCLIB",
+            Self::Netease => "NetEase",
+            Self::Qqmusic => "QQ Music",
+            Self::Genius => "Genius",
+            Self::Musicbrainz => "MusicBrainz",
+            Self::Coverartarchive => "Cover Art Archive",
+            Self::Acoustid => "AcoustID",
+        }
+    }
+
+    /// The scraping providers (NetEase, QQ Music, Genius) only run with an explicit
+    /// `enabled = true`, even when their table sets other keys.
+    pub fn enabled_by_default(self) -> bool {
+        !matches!(self, Self::Netease | Self::Qqmusic | Self::Genius)
+    }
+
+    /// Public endpoint used when `base_url` is left empty.
+    pub fn default_base_url(self) -> &'static str {
+        match self {
+            Self::Local => "",
+            Self::Lrclib => "https://lrclib.net",
+            Self::Netease => "https://music.163.com",
+            Self::Qqmusic => "https://c.y.qq.com",
+            Self::Genius => "https://genius.com",
+            Self::Musicbrainz => "https://musicbrainz.org",
+            Self::Coverartarchive => "https://coverartarchive.org",
+            Self::Acoustid => "https://api.acoustid.org",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProviderSettings {
+    /// Unset means the provider's own default (see `ProviderKind::enabled_by_default`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// Overrides the public endpoint, e.g. to point the pipeline at a local mock server.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub base_url: String,
+}
+
+impl ProviderSettings {
+    fn new(kind: ProviderKind) -> Self {
+        Self {
+            enabled: Some(kind.enabled_by_default()),
+            base_url: kind.default_base_url().to_string(),
+        }
+    }
+
+    pub fn enabled(&self, kind: ProviderKind) -> bool {
+        self.enabled.unwrap_or_else(|| kind.enabled_by_default())
+    }
+
+    /// `base_url` without a trailing slash, or the public endpoint when empty.
+    pub fn base_url(&self, kind: ProviderKind) -> String {
+        let url = self.base_url.trim();
+        let url = if url.is_empty() { kind.default_base_url() } else { url };
+        url.trim_end_matches('/').to_string()
+    }
+}
+
+/// `[providers.<name>]` tables. NetEase, QQ Music and Genius are opt-in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProvidersConfig {
+    pub local: ProviderSettings,
+    pub lrclib: ProviderSettings,
+    pub netease: ProviderSettings,
+    pub qqmusic: ProviderSettings,
+    pub genius: ProviderSettings,
+    pub musicbrainz: ProviderSettings,
+    pub coverartarchive: ProviderSettings,
+    pub acoustid: ProviderSettings,
+}
+
+impl Default for ProvidersConfig {
+    fn default() -> Self {
+        use ProviderKind::*;
+        Self {
+            local: ProviderSettings::new(Local),
+            lrclib: ProviderSettings::new(Lrclib),
+            netease: ProviderSettings::new(Netease),
+            qqmusic: ProviderSettings::new(Qqmusic),
+            genius: ProviderSettings::new(Genius),
+            musicbrainz: ProviderSettings::new(Musicbrainz),
+            coverartarchive: ProviderSettings::new(Coverartarchive),
+            acoustid: ProviderSettings::new(Acoustid),
+        }
+    }
+}
+
+impl ProvidersConfig {
+    pub fn get(&self, kind: ProviderKind) -> &ProviderSettings {
+        match kind {
+            ProviderKind::Local => &self.local,
+            ProviderKind::Lrclib => &self.lrclib,
+            ProviderKind::Netease => &self.netease,
+            ProviderKind::Qqmusic => &self.qqmusic,
+            ProviderKind::Genius => &self.genius,
+            ProviderKind::Musicbrainz => &self.musicbrainz,
+            ProviderKind::Coverartarchive => &self.coverartarchive,
+            ProviderKind::Acoustid => &self.acoustid,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -72,6 +271,40 @@ pub enum VisualizeMode {
     Oscilloscope,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplayGainMode {
+    Off,
+    Track,
+    Album,
+}
+
+impl ReplayGainMode {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Off => Self::Track,
+            Self::Track => Self::Album,
+            Self::Album => Self::Off,
+        }
+    }
+
+    pub fn prev(self) -> Self {
+        match self {
+            Self::Off => Self::Album,
+            Self::Track => Self::Off,
+            Self::Album => Self::Track,
+        }
+    }
+
+    pub fn as_label(self) -> &'static str {
+        match self {
+            Self::Off => "Off",
+            Self::Track => "Track",
+            Self::Album => "Album",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum BarChannels {
@@ -79,6 +312,55 @@ pub enum BarChannels {
     Mono,
 }
 
+/// Text cover renderer. The colored modes use the 256-color palette on terminals without
+/// truecolor; `halfblock` needs colors and falls back to `ascii` without them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CoverMode {
+    #[default]
+    Ascii,
+    Halfblock,
+    Braille,
+}
+
+impl CoverMode {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Ascii => Self::Halfblock,
+            Self::Halfblock => Self::Braille,
+            Self::Braille => Self::Ascii,
+        }
+    }
+
+    pub fn prev(self) -> Self {
+        match self {
+            Self::Ascii => Self::Braille,
+            Self::Halfblock => Self::Ascii,
+            Self::Braille => Self::Halfblock,
+        }
+    }
+
+    pub fn as_label(self) -> &'static str {
+        match self {
+            Self::Ascii => "ASCII",
+            Self::Halfblock => "Half blocks",
+            Self::Braille => "Braille",
+        }
+    }
+}
+
+/// Terminal image protocol for covers. `auto` detects it at startup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageProtocol {
+    #[default]
+    Auto,
+    Kitty,
+    Sixel,
+    Iterm2,
+    None,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BarNumber {
     #[serde(rename = "auto")]
@@ -101,6 +383,18 @@ fn default_visualize() -> VisualizeMode {
     VisualizeMode::Bars
 }
 
+fn default_replay_gain() -> ReplayGainMode {
+    ReplayGainMode::Off
+}
+
+fn default_control_socket() -> bool {
+    true
+}
+
+fn default_mpris_server() -> bool {
+    true
+}
+
 fn default_album_border() -> bool {
     true
 }
@@ -121,6 +415,18 @@ fn default_bar_channels() -> BarChannels {
     BarChannels::Mono
 }
 
+fn default_lookup_cache_ttl_days() -> u32 {
+    90
+}
+
+fn default_lookup_cache_miss_ttl_days() -> u32 {
+    3
+}
+
+fn default_provider_order() -> Vec<ProviderKind> {
+    ProviderKind::ALL.to_vec()
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -131,8 +437,11 @@ impl Default for Config {
             visualize: default_visualize(),
             eq_bands_db: default_eq_bands_db(),
             transparent_background: false,
+            adaptive_theme: false,
             album_border: default_album_border(),
-            kitty_graphics: false,
+            cover_images: false,
+            image_protocol: ImageProtocol::Auto,
+            cover_mode: CoverMode::Ascii,
             kitty_cover_scale_percent: default_kitty_cover_scale_percent(),
             super_smooth_bar: false,
             bars_gap: false,
@@ -141,10 +450,23 @@ impl Default for Config {
             bar_channel_reverse: false,
             lyrics_cover_fetch: false,
             lyrics_cover_download: false,
+            lyrics_estimated_sync: false,
             audio_fingerprint: false,
             acoustid_api_key: String::new(),
+            provider_order: default_provider_order(),
+            lookup_cache_ttl_days: default_lookup_cache_ttl_days(),
+            lookup_cache_miss_ttl_days: default_lookup_cache_miss_ttl_days(),
+            lyrics_folders: Vec::new(),
             resume_last_position: false,
+            crossfade_ms: 0,
+            replay_gain: default_replay_gain(),
+            replay_gain_scan: false,
+            shuffle_albums: false,
+            library_folders: Vec::new(),
+            control_socket: default_control_socket(),
+            mpris_server: default_mpris_server(),
             default_opening_folder: String::new(),
+            providers: ProvidersConfig::default(),
         }
     }
 }
@@ -168,9 +490,32 @@ impl Config {
             cfg.ui_fps = synced;
         }
 
+        // `cover_images` was called `kitty_graphics` before other image protocols existed.
+        if !raw.contains("cover_images") {
+            if let Some(on) = toml::from_str::<toml::Table>(&raw)
+                .ok()
+                .and_then(|t| t.get("kitty_graphics").and_then(|v| v.as_bool()))
+            {
+                cfg.cover_images = on;
+            }
+        }
+
         // Auto-migrate missing fields into the config file.
         if !raw.contains("default-opening-folder")
             || !raw.contains("resume_last_position")
+            || !raw.contains("crossfade_ms")
+            || !raw.contains("replay_gain")
+            || !raw.contains("shuffle_albums")
+            || !raw.contains("library_folders")
+            || !raw.contains("control_socket")
+            || !raw.contains("mpris_server")
+            || !raw.contains("adaptive_theme")
+            || !raw.contains("image_protocol")
+            || !raw.contains("cover_images")
+            || !raw.contains("cover_mode")
+            || !raw.contains("provider_order")
+            || !raw.contains("lookup_cache_ttl_days")
+            || !raw.contains("lyrics_estimated_sync")
             || !raw.contains("bar_number")
             || !raw.contains("bar_channels")
             || !raw.contains("bar_channel_reverse")
@@ -0,0 +1,983 @@
+// Key bindings: built-in defaults, overridden per context by `<asset root>/config/keys.toml`.
+// Bindings map chords ("ctrl+f", "shift+left", "space") or chord sequences ("g g") to actions.
+// The help modal is generated from the active keymap, so it always matches what the keys do.
+// `vim = true` layers vim-style playlist keys (j/k, gg/G, dd/p, marks, counts) under the file's own.
+
+use crate::app::state::Overlay;
+use crate::data::assets;
+use crate::utils::input::Action;
+use anyhow::{anyhow, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyContext {
+    Global,
+    Playlist,
+    Library,
+    Lyrics,
+    LyricSync,
+    Settings,
+    Eq,
+    Help,
+    PlaylistManager,
+    Tagger,
+}
+
+impl KeyContext {
+    pub const ALL: [KeyContext; 10] = [
+        KeyContext::Global,
+        KeyContext::Playlist,
+        KeyContext::Library,
+        KeyContext::Lyrics,
+        KeyContext::LyricSync,
+        KeyContext::Settings,
+        KeyContext::Eq,
+        KeyContext::Help,
+        KeyContext::PlaylistManager,
+        KeyContext::Tagger,
+    ];
+
+    /// Section name in keys.toml.
+    pub fn key(self) -> &'static str {
+        match self {
+            KeyContext::Global => "global",
+            KeyContext::Playlist => "playlist",
+            KeyContext::Library => "library",
+            KeyContext::Lyrics => "lyrics",
+            KeyContext::LyricSync => "lyric_sync",
+            KeyContext::Settings => "settings",
+            KeyContext::Eq => "eq",
+            KeyContext::Help => "help",
+            KeyContext::PlaylistManager => "playlist_manager",
+            KeyContext::Tagger => "tagger",
+        }
+    }
+
+    pub fn as_label(self) -> &'static str {
+        match self {
+            KeyContext::Global => "Global",
+            KeyContext::Playlist => "Playlist",
+            KeyContext::Library => "Library",
+            KeyContext::Lyrics => "Lyrics view",
+            KeyContext::LyricSync => "Lyric sync",
+            KeyContext::Settings => "Settings",
+            KeyContext::Eq => "Equalizer",
+            KeyContext::Help => "Help",
+            KeyContext::PlaylistManager => "Playlists",
+            KeyContext::Tagger => "Tagger",
+        }
+    }
+
+    /// Contexts searched for a key in `overlay`, most specific first.
+    /// `None` for text prompts, which take raw characters instead.
+    pub fn for_overlay(overlay: Overlay) -> Option<&'static [KeyContext]> {
+        let ctx = match overlay {
+            Overlay::None => KeyContext::Global,
+            Overlay::Playlist => KeyContext::Playlist,
+            Overlay::Lyrics => KeyContext::Lyrics,
+            Overlay::LyricSync => KeyContext::LyricSync,
+            Overlay::Library => KeyContext::Library,
+            Overlay::SettingsModal | Overlay::BarSettingsModal | Overlay::LocalAudioSettingsModal => KeyContext::Settings,
+            Overlay::EqModal => KeyContext::Eq,
+            Overlay::HelpModal | Overlay::AboutModal => KeyContext::Help,
+            Overlay::PlaylistManager => KeyContext::PlaylistManager,
+            Overlay::Tagger => KeyContext::Tagger,
+            Overlay::FolderInput
+            | Overlay::AcoustIdModal
+            | Overlay::PlaylistNameInput
+            | Overlay::PlaylistSearch
+            | Overlay::LibraryFilter
+            | Overlay::TagEditor => return None,
+        };
+        Some(ctx.layers())
+    }
+
+    /// This context followed by the ones it falls back to.
+    fn layers(self) -> &'static [KeyContext] {
+        match self {
+            KeyContext::Global => &[KeyContext::Global],
+            KeyContext::Playlist => &[KeyContext::Playlist, KeyContext::Global],
+            KeyContext::Library => &[KeyContext::Library],
+            KeyContext::Lyrics => &[KeyContext::Lyrics, KeyContext::Global],
+            KeyContext::LyricSync => &[KeyContext::LyricSync],
+            KeyContext::Settings => &[KeyContext::Settings],
+            KeyContext::Eq => &[KeyContext::Eq],
+            KeyContext::Help => &[KeyContext::Help],
+            KeyContext::PlaylistManager => &[KeyContext::PlaylistManager],
+            KeyContext::Tagger => &[KeyContext::Tagger],
+        }
+    }
+}
+
+/// One key press. Shift is folded into the character for `Char` keys ("Q", "<").
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub mods: KeyModifiers,
+}
+
+impl KeyChord {
+    pub fn from_event(ev: &KeyEvent) -> Self {
+        let mut mods = ev.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        if matches!(ev.code, KeyCode::Char(_) | KeyCode::BackTab) {
+            mods.remove(KeyModifiers::SHIFT);
+        }
+        Self { code: ev.code, mods }
+    }
+
+    /// Same chord with an upper-case letter lowered, so "q" also answers to Shift+Q.
+    fn lowered(self) -> Option<Self> {
+        match self.code {
+            KeyCode::Char(c) if c.is_uppercase() => Some(Self {
+                code: KeyCode::Char(c.to_lowercase().next().unwrap_or(c)),
+                mods: self.mods,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err("empty key".to_string());
+        }
+        if s == "+" {
+            return Ok(Self { code: KeyCode::Char('+'), mods: KeyModifiers::NONE });
+        }
+        let parts: Vec<&str> = if s.len() > 1 && s.ends_with('+') {
+            // "ctrl++"
+            let mut p: Vec<&str> = s[..s.len() - 1].split('+').filter(|p| !p.is_empty()).collect();
+            p.push("+");
+            p
+        } else {
+            s.split('+').collect()
+        };
+        let (key, mod_names) = parts.split_last().ok_or_else(|| "empty key".to_string())?;
+
+        let mut mods = KeyModifiers::NONE;
+        for m in mod_names {
+            match m.trim().to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => mods |= KeyModifiers::CONTROL,
+                "alt" | "meta" => mods |= KeyModifiers::ALT,
+                "shift" => mods |= KeyModifiers::SHIFT,
+                other => return Err(format!("unknown modifier \"{other}\" (use ctrl, alt or shift)")),
+            }
+        }
+
+        let key = key.trim();
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key.to_ascii_lowercase().as_str() {
+                "space" => KeyCode::Char(' '),
+                "plus" => KeyCode::Char('+'),
+                "minus" => KeyCode::Char('-'),
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" | "bs" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" | "ins" => KeyCode::Insert,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" | "pgup" => KeyCode::PageUp,
+                "pagedown" | "pgdn" | "pgdown" => KeyCode::PageDown,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                k => match k.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n) if (1..=12).contains(&n) => KeyCode::F(n),
+                    _ => return Err(format!("unknown key \"{key}\"")),
+                },
+            },
+        };
+
+        let code = match code {
+            KeyCode::Char(c) if mods.contains(KeyModifiers::SHIFT) => {
+                if !c.is_alphabetic() {
+                    return Err(format!("shift+{c}: write the shifted character itself (e.g. \"<\")"));
+                }
+                mods.remove(KeyModifiers::SHIFT);
+                KeyCode::Char(c.to_uppercase().next().unwrap_or(c))
+            }
+            other => other,
+        };
+        Ok(Self { code, mods })
+    }
+
+    /// Display form used by the help modal ("Ctrl+K", "Shift+Left", "Space").
+    pub fn label(&self) -> String {
+        let mut out = String::new();
+        if self.mods.contains(KeyModifiers::CONTROL) {
+            out.push_str("Ctrl+");
+        }
+        if self.mods.contains(KeyModifiers::ALT) {
+            out.push_str("Alt+");
+        }
+        if self.mods.contains(KeyModifiers::SHIFT) {
+            out.push_str("Shift+");
+        }
+        let key = match self.code {
+            KeyCode::Char(' ') => "Space".to_string(),
+            // Letters are matched case-insensitively unless bound in upper case, so show them like key caps.
+            KeyCode::Char(c) if c.is_lowercase() => c.to_uppercase().collect(),
+            KeyCode::Char(c) if c.is_uppercase() => format!("Shift+{c}"),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Enter => "Enter".to_string(),
+            KeyCode::Esc => "Esc".to_string(),
+            KeyCode::Tab => "Tab".to_string(),
+            KeyCode::BackTab => "Shift+Tab".to_string(),
+            KeyCode::Backspace => "Backspace".to_string(),
+            KeyCode::Delete => "Del".to_string(),
+            KeyCode::Insert => "Ins".to_string(),
+            KeyCode::Home => "Home".to_string(),
+            KeyCode::End => "End".to_string(),
+            KeyCode::PageUp => "PgUp".to_string(),
+            KeyCode::PageDown => "PgDn".to_string(),
+            KeyCode::Up => "Up".to_string(),
+            KeyCode::Down => "Down".to_string(),
+            KeyCode::Left => "Left".to_string(),
+            KeyCode::Right => "Right".to_string(),
+            KeyCode::F(n) => format!("F{n}"),
+            other => format!("{other:?}"),
+        };
+        out.push_str(&key);
+        out
+    }
+}
+
+/// Keys typed so far: an unfinished sequence, and a vim-style count typed in front of it.
+#[derive(Debug, Clone, Default)]
+pub struct PendingKeys {
+    pub keys: Vec<KeyChord>,
+    pub count: Option<usize>,
+}
+
+impl PendingKeys {
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty() && self.count.is_none()
+    }
+
+    /// What has been typed, vim's "showcmd" ("5d", "g").
+    pub fn label(&self) -> String {
+        let mut out = self.count.map(|n| n.to_string()).unwrap_or_default();
+        for k in &self.keys {
+            match k.code {
+                KeyCode::Char(c) if k.mods.is_empty() => out.push(c),
+                _ => out.push_str(&k.label()),
+            }
+        }
+        out
+    }
+}
+
+fn parse_sequence(s: &str) -> Result<Vec<KeyChord>, String> {
+    let seq = s.split_whitespace().map(KeyChord::parse).collect::<Result<Vec<_>, _>>()?;
+    if seq.is_empty() {
+        return Err("empty key".to_string());
+    }
+    Ok(seq)
+}
+
+fn sequence_label(seq: &[KeyChord]) -> String {
+    seq.iter().map(KeyChord::label).collect::<Vec<_>>().join(" ")
+}
+
+/// Actions without an argument: (name in keys.toml, action, help text).
+const SIMPLE_ACTIONS: &[(&str, Action, &str)] = &[
+    ("none", Action::None, "Unbound"),
+    ("quit", Action::Quit, "Quit"),
+    ("play_pause", Action::TogglePlayPause, "Play/Pause"),
+    ("prev", Action::Prev, "Previous track"),
+    ("next", Action::Next, "Next track"),
+    ("volume_up", Action::VolumeUp, "Volume up"),
+    ("volume_down", Action::VolumeDown, "Volume down"),
+    ("repeat_mode", Action::ToggleRepeatMode, "Repeat mode (Local)"),
+    ("toggle_playlist", Action::TogglePlaylist, "Toggle playlist"),
+    ("confirm", Action::Confirm, "Confirm / play selected"),
+    ("close", Action::CloseOverlay, "Close"),
+    ("open_folder", Action::OpenFolder, "Open folder / playlist file"),
+    ("settings", Action::OpenSettingsModal, "Settings"),
+    ("help", Action::OpenHelpModal, "This help"),
+    ("equalizer", Action::OpenEqModal, "Equalizer (Local)"),
+    ("eq_reset", Action::EqResetDefault, "Reset equalizer"),
+    ("up", Action::ModalUp, "Move up"),
+    ("down", Action::ModalDown, "Move down"),
+    ("left", Action::ModalLeft, "Decrease / previous"),
+    ("right", Action::ModalRight, "Increase / next"),
+    ("playlist_up", Action::PlaylistUp, "Select previous"),
+    ("playlist_down", Action::PlaylistDown, "Select next"),
+    ("playlist_top", Action::PlaylistTop, "First track (count: track N)"),
+    ("playlist_bottom", Action::PlaylistBottom, "Last track (count: track N)"),
+    ("half_page_up", Action::PlaylistHalfPage(-1), "Half page up"),
+    ("half_page_down", Action::PlaylistHalfPage(1), "Half page down"),
+    ("cut", Action::PlaylistCut, "Cut track(s)"),
+    ("paste", Action::PlaylistPaste, "Paste after"),
+    ("paste_before", Action::PlaylistPasteBefore, "Paste before"),
+    ("move_up", Action::PlaylistMoveItemUp, "Move item up"),
+    ("move_down", Action::PlaylistMoveItemDown, "Move item down"),
+    ("prev_album", Action::PrevAlbum, "Prev album (Multi)"),
+    ("next_album", Action::NextAlbum, "Next album (Multi)"),
+    ("playlists", Action::OpenPlaylistManager, "Playlists"),
+    ("playlist_new", Action::PlaylistManagerNew, "New playlist"),
+    ("playlist_rename", Action::PlaylistManagerRename, "Rename playlist"),
+    ("playlist_delete", Action::PlaylistManagerDelete, "Delete playlist"),
+    ("add_to_playlist", Action::PlaylistAddToUserPlaylist, "Add to target playlist"),
+    ("remove_item", Action::PlaylistRemoveItem, "Remove (user playlist)"),
+    ("save_queue", Action::SaveQueueAsPlaylist, "Save as playlist"),
+    ("play_next", Action::QueuePlayNext, "Play next"),
+    ("queue_append", Action::QueueAppend, "Add to queue"),
+    ("queue_clear", Action::QueueClear, "Clear queue (queue view)"),
+    ("toggle_up_next", Action::ToggleUpNextView, "Show/hide queue"),
+    ("library", Action::OpenLibrary, "Library"),
+    ("library_back", Action::LibraryBack, "Back"),
+    ("library_filter", Action::LibraryFilterStart, "Filter"),
+    ("library_rescan", Action::LibraryRescan, "Rescan"),
+    ("search", Action::PlaylistSearchStart, "Search (fuzzy)"),
+    ("lyrics_view", Action::ToggleLyricsView, "Lyrics view"),
+    ("lyric_tracks", Action::CycleLyricTracks, "Cycle lyric tracks"),
+    ("lyrics_estimate", Action::ToggleLyricsEstimate, "Estimated sync (unsynced lyrics)"),
+    ("lyric_sync", Action::LyricSyncStart, "Sync lyrics"),
+    ("lyric_sync_tap", Action::LyricSyncTap, "Stamp next line"),
+    ("lyric_sync_undo", Action::LyricSyncUndo, "Undo last stamp"),
+    ("lyric_sync_save", Action::LyricSyncSave, "Save LRC"),
+    ("ab_loop", Action::AbLoopCycle, "A-B loop (Local)"),
+    ("tag_folder", Action::TagFolder, "Fix tags (batch tagger)"),
+    ("tag_toggle", Action::TagToggle, "Include/skip file"),
+    ("tag_toggle_all", Action::TagToggleAll, "Include/skip all"),
+    ("tag_dry_run", Action::TagDryRun, "Dry run on/off"),
+    ("tag_undo", Action::TagUndo, "Undo last write"),
+    ("edit_tags", Action::EditTags, "Edit track tags"),
+    ("edit_album_tags", Action::EditAlbumTags, "Edit album tags"),
+];
+
+/// Parse an action name, with an argument after `:` for the parameterised ones
+/// (`seek:-5` seconds, `jump:30` percent, `lyrics_offset:+100` ms, `library_page:1` pages,
+/// `lyrics_scroll:1` lines, `mark:a` / `goto_mark:a` letters).
+pub fn parse_action(s: &str) -> Result<Action, String> {
+    let s = s.trim();
+    let (name, arg) = match s.split_once(':') {
+        Some((n, a)) => (n.trim(), Some(a.trim())),
+        None => (s, None),
+    };
+    let num = |what: &str| -> Result<f64, String> {
+        let a = arg.ok_or_else(|| format!("\"{name}\" needs {what}, e.g. \"{name}:5\""))?;
+        a.trim_start_matches('+')
+            .parse::<f64>()
+            .map_err(|_| format!("\"{a}\" is not a number"))
+    };
+    match name {
+        "seek" => Ok(Action::SeekRelative((num("seconds")? * 1000.0) as i64)),
+        "jump" => {
+            let p = num("a percentage")?;
+            if !(0.0..=100.0).contains(&p) {
+                return Err(format!("jump:{p}: percentage must be 0-100"));
+            }
+            Ok(Action::SeekToFraction((p / 100.0) as f32))
+        }
+        "lyrics_offset" => Ok(Action::LyricsOffset(num("milliseconds")? as i64)),
+        "library_page" => Ok(Action::LibraryPage(num("a page count")? as i32)),
+        "lyrics_scroll" => Ok(Action::LyricsScroll(num("a line count")? as i32)),
+        "mark" | "goto_mark" => {
+            let mut chars = arg.unwrap_or_default().chars();
+            let c = match (chars.next(), chars.next()) {
+                (Some(c), None) if c.is_ascii_lowercase() => c,
+                _ => return Err(format!("\"{name}\" needs a letter a-z, e.g. \"{name}:a\"")),
+            };
+            Ok(if name == "mark" {
+                Action::PlaylistSetMark(c)
+            } else {
+                Action::PlaylistJumpToMark(c)
+            })
+        }
+        _ => {
+            if arg.is_some() {
+                return Err(format!("\"{name}\" takes no argument"));
+            }
+            SIMPLE_ACTIONS
+                .iter()
+                .find(|(n, _, _)| *n == name)
+                .map(|(_, a, _)| *a)
+                .ok_or_else(|| format!("unknown action \"{name}\""))
+        }
+    }
+}
+
+/// Help text for an action.
+pub fn describe(action: &Action) -> String {
+    match *action {
+        Action::SeekRelative(ms) => format!("Seek {:+}s", ms as f64 / 1000.0),
+        Action::SeekToFraction(f) => format!("Jump to {}%", (f * 100.0).round()),
+        Action::LyricsOffset(ms) => format!("Lyrics offset {:+}s", ms as f64 / 1000.0),
+        Action::LibraryPage(n) if n < 0 => "Page up".to_string(),
+        Action::LibraryPage(_) => "Page down".to_string(),
+        Action::LyricsScroll(n) if n < 0 => format!("Scroll lyrics up {}", -n),
+        Action::LyricsScroll(n) => format!("Scroll lyrics down {n}"),
+        Action::PlaylistSetMark(c) => format!("Set mark {c}"),
+        Action::PlaylistJumpToMark(c) => format!("Jump to mark {c}"),
+        _ => SIMPLE_ACTIONS
+            .iter()
+            .find(|(_, a, _)| a == action)
+            .map(|(_, _, d)| d.to_string())
+            .unwrap_or_else(|| format!("{action:?}")),
+    }
+}
+
+// (context, keys, action) — also the order shown in the help modal.
+const DEFAULT_BINDINGS: &[(KeyContext, &str, &str)] = &[
+    (KeyContext::Global, "ctrl+f", "open_folder"),
+    (KeyContext::Global, "ctrl+p", "playlists"),
+    (KeyContext::Global, "p", "toggle_playlist"),
+    (KeyContext::Global, "space", "play_pause"),
+    (KeyContext::Global, "left", "prev"),
+    (KeyContext::Global, "right", "next"),
+    (KeyContext::Global, "shift+left", "seek:-5"),
+    (KeyContext::Global, "shift+right", "seek:+5"),
+    (KeyContext::Global, "ctrl+left", "seek:-30"),
+    (KeyContext::Global, "ctrl+right", "seek:+30"),
+    (KeyContext::Global, "0", "jump:0"),
+    (KeyContext::Global, "1", "jump:10"),
+    (KeyContext::Global, "2", "jump:20"),
+    (KeyContext::Global, "3", "jump:30"),
+    (KeyContext::Global, "4", "jump:40"),
+    (KeyContext::Global, "5", "jump:50"),
+    (KeyContext::Global, "6", "jump:60"),
+    (KeyContext::Global, "7", "jump:70"),
+    (KeyContext::Global, "8", "jump:80"),
+    (KeyContext::Global, "9", "jump:90"),
+    (KeyContext::Global, "a", "ab_loop"),
+    (KeyContext::Global, "up", "volume_up"),
+    (KeyContext::Global, "down", "volume_down"),
+    (KeyContext::Global, "m", "repeat_mode"),
+    (KeyContext::Global, "l", "library"),
+    (KeyContext::Global, "y", "lyrics_view"),
+    (KeyContext::Global, "b", "lyric_tracks"),
+    (KeyContext::Global, ",", "lyrics_offset:-100"),
+    (KeyContext::Global, ".", "lyrics_offset:+100"),
+    (KeyContext::Global, "<", "lyrics_offset:-1000"),
+    (KeyContext::Global, ">", "lyrics_offset:+1000"),
+    (KeyContext::Global, "e", "equalizer"),
+    (KeyContext::Global, "t", "settings"),
+    (KeyContext::Global, "ctrl+k", "help"),
+    (KeyContext::Global, "esc", "close"),
+    (KeyContext::Global, "enter", "confirm"),
+    (KeyContext::Global, "q", "quit"),
+    (KeyContext::Playlist, "up", "playlist_up"),
+    (KeyContext::Playlist, "down", "playlist_down"),
+    (KeyContext::Playlist, "enter", "confirm"),
+    (KeyContext::Playlist, "/", "search"),
+    (KeyContext::Playlist, "ctrl+up", "move_up"),
+    (KeyContext::Playlist, "ctrl+down", "move_down"),
+    (KeyContext::Playlist, "ctrl+left", "prev_album"),
+    (KeyContext::Playlist, "ctrl+right", "next_album"),
+    (KeyContext::Playlist, "left", "none"),
+    (KeyContext::Playlist, "right", "none"),
+    (KeyContext::Playlist, "a", "add_to_playlist"),
+    (KeyContext::Playlist, "x", "remove_item"),
+    (KeyContext::Playlist, "delete", "remove_item"),
+    (KeyContext::Playlist, "ctrl+s", "save_queue"),
+    (KeyContext::Playlist, "n", "play_next"),
+    (KeyContext::Playlist, "u", "queue_append"),
+    (KeyContext::Playlist, "tab", "toggle_up_next"),
+    (KeyContext::Playlist, "c", "queue_clear"),
+    (KeyContext::Playlist, "p", "toggle_playlist"),
+    (KeyContext::Playlist, "T", "tag_folder"),
+    (KeyContext::Playlist, "i", "edit_tags"),
+    (KeyContext::Playlist, "I", "edit_album_tags"),
+    (KeyContext::Playlist, "esc", "close"),
+    (KeyContext::Library, "up", "up"),
+    (KeyContext::Library, "down", "down"),
+    (KeyContext::Library, "enter", "confirm"),
+    (KeyContext::Library, "right", "confirm"),
+    (KeyContext::Library, "left", "library_back"),
+    (KeyContext::Library, "backspace", "library_back"),
+    (KeyContext::Library, "pageup", "library_page:-1"),
+    (KeyContext::Library, "pagedown", "library_page:1"),
+    (KeyContext::Library, "/", "library_filter"),
+    (KeyContext::Library, "r", "library_rescan"),
+    (KeyContext::Library, "i", "edit_tags"),
+    (KeyContext::Library, "I", "edit_album_tags"),
+    (KeyContext::Library, "l", "close"),
+    (KeyContext::Library, "esc", "close"),
+    (KeyContext::Library, "q", "quit"),
+    (KeyContext::Lyrics, "s", "lyric_sync"),
+    (KeyContext::Lyrics, "j", "lyrics_scroll:1"),
+    (KeyContext::Lyrics, "k", "lyrics_scroll:-1"),
+    (KeyContext::Lyrics, "pagedown", "lyrics_scroll:10"),
+    (KeyContext::Lyrics, "pageup", "lyrics_scroll:-10"),
+    (KeyContext::Lyrics, "E", "lyrics_estimate"),
+    (KeyContext::LyricSync, "enter", "lyric_sync_tap"),
+    (KeyContext::LyricSync, "down", "lyric_sync_tap"),
+    (KeyContext::LyricSync, "backspace", "lyric_sync_undo"),
+    (KeyContext::LyricSync, "up", "lyric_sync_undo"),
+    (KeyContext::LyricSync, "left", "seek:-5"),
+    (KeyContext::LyricSync, "right", "seek:+5"),
+    (KeyContext::LyricSync, "space", "play_pause"),
+    (KeyContext::LyricSync, "ctrl+s", "lyric_sync_save"),
+    (KeyContext::LyricSync, "esc", "close"),
+    (KeyContext::Settings, "up", "up"),
+    (KeyContext::Settings, "down", "down"),
+    (KeyContext::Settings, "left", "left"),
+    (KeyContext::Settings, "right", "right"),
+    (KeyContext::Settings, "enter", "confirm"),
+    (KeyContext::Settings, "t", "close"),
+    (KeyContext::Settings, "esc", "close"),
+    (KeyContext::Eq, "up", "up"),
+    (KeyContext::Eq, "down", "down"),
+    (KeyContext::Eq, "left", "left"),
+    (KeyContext::Eq, "right", "right"),
+    (KeyContext::Eq, "enter", "confirm"),
+    (KeyContext::Eq, "alt+r", "eq_reset"),
+    (KeyContext::Eq, "e", "close"),
+    (KeyContext::Eq, "esc", "close"),
+    (KeyContext::Help, "ctrl+k", "close"),
+    (KeyContext::Help, "esc", "close"),
+    (KeyContext::PlaylistManager, "up", "up"),
+    (KeyContext::PlaylistManager, "down", "down"),
+    (KeyContext::PlaylistManager, "enter", "confirm"),
+    (KeyContext::PlaylistManager, "n", "playlist_new"),
+    (KeyContext::PlaylistManager, "r", "playlist_rename"),
+    (KeyContext::PlaylistManager, "d", "playlist_delete"),
+    (KeyContext::PlaylistManager, "delete", "playlist_delete"),
+    (KeyContext::PlaylistManager, "ctrl+p", "close"),
+    (KeyContext::PlaylistManager, "esc", "close"),
+    (KeyContext::Tagger, "up", "up"),
+    (KeyContext::Tagger, "down", "down"),
+    (KeyContext::Tagger, "space", "tag_toggle"),
+    (KeyContext::Tagger, "a", "tag_toggle_all"),
+    (KeyContext::Tagger, "enter", "confirm"),
+    (KeyContext::Tagger, "d", "tag_dry_run"),
+    (KeyContext::Tagger, "u", "tag_undo"),
+    (KeyContext::Tagger, "esc", "close"),
+];
+
+// Layered under the user's bindings by `vim = true`; marks ("m a", "' a") are added for a-z.
+const VIM_BINDINGS: &[(&str, &str)] = &[
+    // Marks take `m` over from repeat_mode in the playlist.
+    ("m", "none"),
+    ("j", "playlist_down"),
+    ("k", "playlist_up"),
+    ("g g", "playlist_top"),
+    ("G", "playlist_bottom"),
+    ("ctrl+d", "half_page_down"),
+    ("ctrl+u", "half_page_up"),
+    ("d d", "cut"),
+    ("p", "paste"),
+    ("P", "paste_before"),
+];
+
+enum Lookup {
+    Found(Action),
+    /// The keys so far start a longer binding.
+    Prefix,
+    Miss,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Keymap {
+    bindings: HashMap<KeyContext, Vec<(Vec<KeyChord>, Action)>>,
+    /// Vim-style playlist keys, including counts.
+    vim: bool,
+}
+
+impl Keymap {
+    /// `<asset root>/config/keys.toml`
+    pub fn default_path() -> PathBuf {
+        assets::resolve_asset_path(Path::new("config/keys.toml"))
+    }
+
+    pub fn defaults() -> Self {
+        let mut km = Self::default();
+        for (ctx, keys, action) in DEFAULT_BINDINGS {
+            let (Ok(seq), Ok(action)) = (parse_sequence(keys), parse_action(action)) else {
+                continue;
+            };
+            km.bind(*ctx, seq, action);
+        }
+        km
+    }
+
+    /// Defaults plus the user's keys.toml. Every problem in the file is reported at once.
+    pub fn load() -> Result<Self> {
+        let path = Self::default_path();
+        let raw = match fs::read_to_string(&path) {
+            Ok(s) => s,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::defaults()),
+            Err(e) => return Err(anyhow!("{}: {e}", path.display())),
+        };
+        Self::from_toml(&raw).map_err(|e| anyhow!("{}: {e}", path.display()))
+    }
+
+    fn from_toml(raw: &str) -> Result<Self> {
+        let doc: toml::Table = toml::from_str(raw)?;
+        let mut km = Self::defaults();
+        let mut errors: Vec<String> = Vec::new();
+
+        // Before the sections, so the file's own [playlist] keys win over the vim layer.
+        match doc.get("vim") {
+            None | Some(toml::Value::Boolean(false)) => {}
+            Some(toml::Value::Boolean(true)) => km.enable_vim(),
+            Some(_) => errors.push("vim: expected true or false".to_string()),
+        }
+
+        for (section, value) in &doc {
+            if section == "vim" {
+                continue;
+            }
+            let Some(ctx) = KeyContext::ALL.iter().copied().find(|c| c.key() == section) else {
+                let known: Vec<&str> = KeyContext::ALL.iter().map(|c| c.key()).collect();
+                errors.push(format!("[{section}]: unknown section (expected one of: {})", known.join(", ")));
+                continue;
+            };
+            let Some(table) = value.as_table() else {
+                errors.push(format!("{section}: expected a [{section}] table"));
+                continue;
+            };
+            for (keys, action) in table {
+                let Some(action) = action.as_str() else {
+                    errors.push(format!("[{section}] \"{keys}\": action must be a string"));
+                    continue;
+                };
+                match (parse_sequence(keys), parse_action(action)) {
+                    (Ok(seq), Ok(action)) => km.bind(ctx, seq, action),
+                    (Err(e), _) | (_, Err(e)) => errors.push(format!("[{section}] \"{keys}\": {e}")),
+                }
+            }
+        }
+
+        errors.extend(km.shadowed());
+        if errors.is_empty() {
+            Ok(km)
+        } else {
+            Err(anyhow!("invalid key bindings:\n  {}", errors.join("\n  ")))
+        }
+    }
+
+    /// Bindings that can never fire. Within a context the exact key wins, so a longer
+    /// sequence starting with it is dead; across layers a longer sequence in the overlay's own
+    /// context hides a shorter fallback key ("m a" in [playlist] hides [global] "m") unless the
+    /// overlay binds that key itself.
+    fn shadowed(&self) -> Vec<String> {
+        let list = |ctx: KeyContext| self.bindings.get(&ctx).map(Vec::as_slice).unwrap_or(&[]);
+        let mut errors = Vec::new();
+        for ctx in KeyContext::ALL {
+            let layers = ctx.layers();
+            for (long, action) in list(ctx) {
+                if *action == Action::None {
+                    continue;
+                }
+                for (depth, &lower) in layers.iter().enumerate() {
+                    for (short, action) in list(lower) {
+                        if *action == Action::None || short.len() >= long.len() || !long.starts_with(short) {
+                            continue;
+                        }
+                        if depth == 0 {
+                            errors.push(format!(
+                                "[{}] \"{}\" can never fire: \"{}\" is bound on its own",
+                                ctx.key(),
+                                sequence_label(long),
+                                sequence_label(short)
+                            ));
+                        } else if !layers[..depth].iter().any(|&c| list(c).iter().any(|(s, _)| s == short)) {
+                            errors.push(format!(
+                                "[{}] \"{}\" is hidden in the {} by the longer sequence \"{}\" in [{}]",
+                                lower.key(),
+                                sequence_label(short),
+                                ctx.as_label().to_lowercase(),
+                                sequence_label(long),
+                                ctx.key()
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        errors
+    }
+
+    fn enable_vim(&mut self) {
+        self.vim = true;
+        let marks = ('a'..='z').flat_map(|c| [(format!("m {c}"), format!("mark:{c}")), (format!("' {c}"), format!("goto_mark:{c}"))]);
+        let fixed = VIM_BINDINGS.iter().map(|(k, a)| (k.to_string(), a.to_string()));
+        for (keys, action) in fixed.chain(marks) {
+            let (Ok(seq), Ok(action)) = (parse_sequence(&keys), parse_action(&action)) else {
+                continue;
+            };
+            self.bind(KeyContext::Playlist, seq, action);
+        }
+    }
+
+    /// Whether digits typed in `contexts` are a count rather than keys of their own.
+    pub fn counts_in(&self, contexts: &[KeyContext]) -> bool {
+        self.vim && contexts.first() == Some(&KeyContext::Playlist)
+    }
+
+    fn bind(&mut self, ctx: KeyContext, seq: Vec<KeyChord>, action: Action) {
+        let list = self.bindings.entry(ctx).or_default();
+        match list.iter_mut().find(|(s, _)| *s == seq) {
+            Some(entry) => entry.1 = action,
+            None => list.push((seq, action)),
+        }
+    }
+
+    fn lookup(&self, ctx: KeyContext, seq: &[KeyChord]) -> Lookup {
+        let Some(list) = self.bindings.get(&ctx) else {
+            return Lookup::Miss;
+        };
+        // An unbound key ("none") still masks the fallback contexts, but frees it as a prefix.
+        let exact = list.iter().find(|(s, _)| s == seq).map(|(_, a)| *a);
+        if let Some(a) = exact.filter(|a| *a != Action::None) {
+            return Lookup::Found(a);
+        }
+        if list.iter().any(|(s, a)| *a != Action::None && s.len() > seq.len() && s.starts_with(seq)) {
+            return Lookup::Prefix;
+        }
+        exact.map_or(Lookup::Miss, Lookup::Found)
+    }
+
+    /// Resolve a key press in `contexts`. `pending` holds the keys of an unfinished sequence;
+    /// returns `Action::None` while a sequence is still being typed.
+    pub fn resolve(&self, contexts: &[KeyContext], chord: KeyChord, pending: &mut Vec<KeyChord>) -> Action {
+        pending.push(chord);
+        let mut candidates = vec![pending.clone()];
+        if let Some(lower) = chord.lowered() {
+            let mut seq = pending.clone();
+            if let Some(last) = seq.last_mut() {
+                *last = lower;
+            }
+            candidates.push(seq);
+        }
+
+        for &ctx in contexts {
+            for seq in &candidates {
+                match self.lookup(ctx, seq) {
+                    Lookup::Found(a) => {
+                        pending.clear();
+                        return a;
+                    }
+                    Lookup::Prefix => {
+                        *pending = seq.clone();
+                        return Action::None;
+                    }
+                    Lookup::Miss => {}
+                }
+            }
+        }
+
+        // A broken sequence: start over from the key just pressed.
+        let retry = pending.len() > 1;
+        pending.clear();
+        if retry {
+            return self.resolve(contexts, chord, pending);
+        }
+        Action::None
+    }
+
+    /// First key bound to `action` in `ctx` (for hints like the footer's "Ctrl+K: Keys").
+    pub fn key_for(&self, ctx: KeyContext, action: Action) -> Option<String> {
+        self.bindings
+            .get(&ctx)?
+            .iter()
+            .find(|(_, a)| *a == action)
+            .map(|(s, _)| sequence_label(s))
+    }
+
+    /// Help rows (keys, description) for a context. Keys with the same effect share a row,
+    /// opposite pairs (Prev/Next, -/+ seeks) are folded together, and so are runs of
+    /// number-key jumps and of marks.
+    pub fn help_rows(&self, ctx: KeyContext) -> Vec<(String, String)> {
+        let Some(list) = self.bindings.get(&ctx) else {
+            return Vec::new();
+        };
+        let mut rows: Vec<(String, Action)> = Vec::new();
+        for (seq, action) in list {
+            if *action == Action::None {
+                continue;
+            }
+            let label = sequence_label(seq);
+            match rows.iter_mut().find(|(_, a)| a == action) {
+                Some(row) => row.0 = format!("{}/{label}", row.0),
+                None => rows.push((label, *action)),
+            }
+        }
+
+        let mut out: Vec<(String, String)> = Vec::new();
+        let mut i = 0;
+        while i < rows.len() {
+            let (keys, action) = &rows[i];
+            let mut j = i;
+            while rows.get(j + 1).is_some_and(|(_, next)| same_run(action, next)) {
+                j += 1;
+            }
+            if j > i {
+                out.push((range_keys(keys, &rows[j].0), run_label(action, &rows[j].1)));
+                i = j + 1;
+                continue;
+            }
+            if let Some((keys2, next)) = rows.get(i + 1) {
+                if let Some(label) = pair_label(action, next) {
+                    out.push((format!("{keys}/{keys2}"), label));
+                    i += 2;
+                    continue;
+                }
+            }
+            out.push((keys.clone(), describe(action)));
+            i += 1;
+        }
+        if ctx == KeyContext::Playlist && self.vim {
+            out.push(("1-9".to_string(), "Count before a key (5j, 3dd, 12G)".to_string()));
+        }
+        out
+    }
+}
+
+/// Actions that differ only in their argument and fold into one help row when bound in a run.
+fn same_run(a: &Action, b: &Action) -> bool {
+    matches!(
+        (a, b),
+        (Action::SeekToFraction(_), Action::SeekToFraction(_))
+            | (Action::PlaylistSetMark(_), Action::PlaylistSetMark(_))
+            | (Action::PlaylistJumpToMark(_), Action::PlaylistJumpToMark(_))
+    )
+}
+
+/// "0" .. "9" -> "0-9"; "M A" .. "M Z" -> "M A-Z".
+fn range_keys(first: &str, last: &str) -> String {
+    match (first.rsplit_once(' '), last.rsplit_once(' ')) {
+        (Some((p1, a)), Some((p2, z))) if p1 == p2 => format!("{p1} {a}-{z}"),
+        _ => format!("{first}-{last}"),
+    }
+}
+
+fn run_label(first: &Action, last: &Action) -> String {
+    match (*first, *last) {
+        (Action::SeekToFraction(a), Action::SeekToFraction(z)) => {
+            format!("Jump to {}-{}%", (a * 100.0).round(), (z * 100.0).round())
+        }
+        (Action::PlaylistSetMark(_), _) => "Set mark".to_string(),
+        (Action::PlaylistJumpToMark(_), _) => "Jump to mark".to_string(),
+        _ => describe(first),
+    }
+}
+
+/// One help row for two opposite actions bound next to each other.
+fn pair_label(a: &Action, b: &Action) -> Option<String> {
+    Some(match (*a, *b) {
+        (Action::SeekRelative(x), Action::SeekRelative(y)) if x < 0 && x == -y => {
+            format!("Seek -/+{}s", y as f64 / 1000.0)
+        }
+        (Action::LyricsOffset(x), Action::LyricsOffset(y)) if x < 0 && x == -y => {
+            format!("Lyrics offset -/+{}s", y as f64 / 1000.0)
+        }
+        (Action::LibraryPage(x), Action::LibraryPage(y)) if x < 0 && x == -y => "Page up/down".to_string(),
+        (Action::LyricsScroll(x), Action::LyricsScroll(y)) if x > 0 && x == -y => {
+            format!("Scroll lyrics down/up {x} (unsynced)")
+        }
+        (Action::Prev, Action::Next) => "Prev/Next".to_string(),
+        (Action::VolumeUp, Action::VolumeDown) => "Volume".to_string(),
+        (Action::PlaylistUp, Action::PlaylistDown) => "Select track".to_string(),
+        (Action::PlaylistTop, Action::PlaylistBottom) => "First/Last track (count: track N)".to_string(),
+        (Action::PlaylistHalfPage(x), Action::PlaylistHalfPage(y)) if x == -y => "Half page down/up".to_string(),
+        (Action::PlaylistPaste, Action::PlaylistPasteBefore) => "Paste after/before".to_string(),
+        (Action::ModalUp, Action::ModalDown) => "Move selection".to_string(),
+        (Action::ModalLeft, Action::ModalRight) => "Change value".to_string(),
+        (Action::PlaylistMoveItemUp, Action::PlaylistMoveItemDown) => "Move item up/down".to_string(),
+        (Action::PrevAlbum, Action::NextAlbum) => "Prev/Next album (Multi)".to_string(),
+        (Action::EditTags, Action::EditAlbumTags) => "Edit tags (track/album)".to_string(),
+        _ => return None,
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chord(s: &str) -> KeyChord {
+        KeyChord::parse(s).unwrap()
+    }
+
+    fn press(km: &Keymap, contexts: &[KeyContext], keys: &str) -> Action {
+        let mut pending = Vec::new();
+        let mut last = Action::None;
+        for key in keys.split(' ') {
+            last = km.resolve(contexts, chord(key), &mut pending);
+        }
+        last
+    }
+
+    #[test]
+    fn parses_chords_and_actions() {
+        assert_eq!(chord("ctrl++"), KeyChord { code: KeyCode::Char('+'), mods: KeyModifiers::CONTROL });
+        assert_eq!(chord("shift+a"), KeyChord { code: KeyCode::Char('A'), mods: KeyModifiers::NONE });
+        assert_eq!(chord("Alt+F5"), KeyChord { code: KeyCode::F(5), mods: KeyModifiers::ALT });
+        assert!(KeyChord::parse("shift+,").is_err());
+        assert!(KeyChord::parse("hyper+a").is_err());
+        assert!(KeyChord::parse("f13").is_err());
+        assert_eq!(parse_sequence("g g").unwrap().len(), 2);
+
+        assert_eq!(parse_action("seek:-5"), Ok(Action::SeekRelative(-5000)));
+        assert_eq!(parse_action("mark:q"), Ok(Action::PlaylistSetMark('q')));
+        assert!(parse_action("mark:Q").is_err());
+        assert!(parse_action("fly").is_err());
+    }
+
+    #[test]
+    fn file_problems_are_reported_together() {
+        let err = Keymap::from_toml("vim = 1\n[nowhere]\nx = \"quit\"\n[global]\n\"hyper+x\" = \"quit\"\ny = \"fly\"\n")
+            .unwrap_err()
+            .to_string();
+        for part in ["vim: expected", "[nowhere]", "hyper", "\"y\""] {
+            assert!(err.contains(part), "{part} missing from {err}");
+        }
+    }
+
+    #[test]
+    fn overrides_sequences_and_fallbacks() {
+        let km = Keymap::from_toml("[global]\n\"g g\" = \"jump:0\"\nq = \"none\"\n[playlist]\n\"ctrl+x\" = \"remove_item\"\n").unwrap();
+        let playlist = &[KeyContext::Playlist, KeyContext::Global];
+        assert_eq!(press(&km, &[KeyContext::Global], "g g"), Action::SeekToFraction(0.0));
+        assert_eq!(press(&km, &[KeyContext::Global], "q"), Action::None);
+        assert_eq!(press(&km, playlist, "ctrl+x"), Action::PlaylistRemoveItem);
+        // [playlist] falls back to [global]; its own "left" = none masks the global seek.
+        assert_eq!(press(&km, playlist, "m"), Action::ToggleRepeatMode);
+        assert_eq!(press(&km, playlist, "left"), Action::None);
+        // Shift+E still reaches "e"; a broken sequence restarts from the last key.
+        assert_eq!(press(&km, &[KeyContext::Global], "E"), Action::OpenEqModal);
+        assert_eq!(press(&km, &[KeyContext::Global], "g e"), Action::OpenEqModal);
+    }
+
+    #[test]
+    fn vim_layer_loads_cleanly_and_keeps_user_keys_on_top() {
+        let km = Keymap::from_toml("vim = true\n[playlist]\np = \"toggle_playlist\"\n").unwrap();
+        let playlist = &[KeyContext::Playlist, KeyContext::Global];
+        assert!(km.counts_in(playlist));
+        assert!(!km.counts_in(&[KeyContext::Global]));
+        assert_eq!(press(&km, playlist, "g g"), Action::PlaylistTop);
+        assert_eq!(press(&km, playlist, "m x"), Action::PlaylistSetMark('x'));
+        assert_eq!(press(&km, playlist, "p"), Action::TogglePlaylist);
+        assert_eq!(press(&km, &[KeyContext::Global], "m"), Action::ToggleRepeatMode);
+    }
+
+    #[test]
+    fn unreachable_bindings_are_errors() {
+        // Same context: the exact key wins, so the sequence could never fire.
+        let err = Keymap::from_toml("[tagger]\n\"d x\" = \"close\"\n").unwrap_err().to_string();
+        assert!(err.contains("[tagger] \"D X\" can never fire"), "{err}");
+        // Unbinding the short key frees it as a prefix.
+        assert!(Keymap::from_toml("[tagger]\nd = \"none\"\n\"d x\" = \"close\"\n").is_ok());
+
+        // Across layers: a [playlist] sequence hides the [global] key it starts with.
+        let err = Keymap::from_toml("[playlist]\n\"q w\" = \"close\"\n").unwrap_err().to_string();
+        assert!(err.contains("[global] \"Q\" is hidden in the playlist"), "{err}");
+        let err = Keymap::from_toml("vim = true\n[global]\nd = \"quit\"\n").unwrap_err().to_string();
+        assert!(err.contains("[global] \"D\" is hidden in the playlist by the longer sequence \"D D\""), "{err}");
+        // Unless the playlist decides what the key does there.
+        assert!(Keymap::from_toml("[playlist]\nq = \"none\"\n\"q w\" = \"close\"\n").is_ok());
+    }
+}
@@ -0,0 +1,275 @@
+// Persistent local library index.
+// Stored as JSON under the asset root so it loads fast even for large collections;
+// rebuilt incrementally by `playback::library_scanner` (keyed on mtime + size).
+
+use crate::data::assets;
+use crate::data::playlist::PlaylistItem;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const LIBRARY_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LibraryTrack {
+    pub path: PathBuf,
+    pub mtime: u64,
+    pub size: u64,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub artist: String,
+    #[serde(default)]
+    pub album: String,
+    #[serde(default)]
+    pub album_artist: String,
+    #[serde(default)]
+    pub genre: String,
+    #[serde(default)]
+    pub year: Option<u32>,
+    #[serde(default)]
+    pub track_no: Option<u32>,
+    #[serde(default)]
+    pub disc_no: Option<u32>,
+    #[serde(default)]
+    pub duration_ms: u64,
+}
+
+impl LibraryTrack {
+    pub fn display_title(&self) -> String {
+        if !self.title.trim().is_empty() {
+            return self.title.clone();
+        }
+        self.path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("Unknown")
+            .to_string()
+    }
+
+    /// Artist used for grouping: album artist first, then track artist.
+    pub fn grouping_artist(&self) -> &str {
+        if !self.album_artist.trim().is_empty() {
+            &self.album_artist
+        } else {
+            &self.artist
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LibraryCategory {
+    Artists,
+    Albums,
+    Genres,
+    Years,
+    Tracks,
+}
+
+impl LibraryCategory {
+    pub const ALL: [LibraryCategory; 5] = [
+        LibraryCategory::Artists,
+        LibraryCategory::Albums,
+        LibraryCategory::Genres,
+        LibraryCategory::Years,
+        LibraryCategory::Tracks,
+    ];
+
+    pub fn as_label(self) -> &'static str {
+        match self {
+            LibraryCategory::Artists => "Artists",
+            LibraryCategory::Albums => "Albums",
+            LibraryCategory::Genres => "Genres",
+            LibraryCategory::Years => "Years",
+            LibraryCategory::Tracks => "All tracks",
+        }
+    }
+}
+
+/// One row of a browse list (artist / album / genre / year) with its track count.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LibraryEntry {
+    pub label: String,
+    pub key: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct LibraryFile {
+    #[serde(default)]
+    version: u32,
+    #[serde(default)]
+    roots: Vec<PathBuf>,
+    #[serde(default)]
+    tracks: Vec<LibraryTrack>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Library {
+    pub roots: Vec<PathBuf>,
+    pub tracks: Vec<LibraryTrack>,
+}
+
+const UNKNOWN: &str = "Unknown";
+
+/// Browse group of `t` in `category`: (sort / match key, label). None for the flat track list.
+fn group_of(category: LibraryCategory, t: &LibraryTrack) -> Option<(String, String)> {
+    Some(match category {
+        LibraryCategory::Artists => {
+            let a = or_unknown(t.grouping_artist());
+            (a.to_lowercase(), a.to_string())
+        }
+        LibraryCategory::Albums => {
+            let al = or_unknown(&t.album);
+            let ar = or_unknown(t.grouping_artist());
+            (format!("{}\u{1f}{}", al.to_lowercase(), ar.to_lowercase()), format!("{al} — {ar}"))
+        }
+        LibraryCategory::Genres => {
+            let g = or_unknown(&t.genre);
+            (g.to_lowercase(), g.to_string())
+        }
+        LibraryCategory::Years => match t.year {
+            // Invert so the BTreeMap yields newest first.
+            Some(y) => (format!("{:010}", u32::MAX - y), y.to_string()),
+            None => ("~".to_string(), UNKNOWN.to_string()),
+        },
+        LibraryCategory::Tracks => return None,
+    })
+}
+
+fn or_unknown(s: &str) -> &str {
+    let s = s.trim();
+    if s.is_empty() {
+        UNKNOWN
+    } else {
+        s
+    }
+}
+
+impl Library {
+    /// `<asset root>/library.json`
+    pub fn default_path() -> PathBuf {
+        assets::resolve_asset_path(Path::new("library.json"))
+    }
+
+    pub fn load() -> Self {
+        let Ok(raw) = fs::read(Self::default_path()) else {
+            return Self::default();
+        };
+        let Ok(file) = serde_json::from_slice::<LibraryFile>(&raw) else {
+            return Self::default();
+        };
+        if file.version != LIBRARY_VERSION {
+            // Unknown layout: start over, the next scan rebuilds it.
+            return Self::default();
+        }
+        Self {
+            roots: file.roots,
+            tracks: file.tracks,
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::default_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = LibraryFile {
+            version: LIBRARY_VERSION,
+            roots: self.roots.clone(),
+            tracks: self.tracks.clone(),
+        };
+        let data = serde_json::to_vec(&file)?;
+        // Written aside and renamed over the old index, so a crash never leaves it half-written.
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, data).with_context(|| format!("write {}", tmp.display()))?;
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.tracks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tracks.is_empty()
+    }
+
+    /// Swap in re-read tags for a track edited in the app. False when it isn't indexed.
+    pub fn replace_track(&mut self, track: LibraryTrack) -> bool {
+        match self.tracks.iter_mut().find(|t| t.path == track.path) {
+            Some(t) => {
+                *t = track;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Browse rows for a category, sorted case-insensitively (years newest first).
+    pub fn entries(&self, category: LibraryCategory) -> Vec<LibraryEntry> {
+        let mut groups: BTreeMap<String, LibraryEntry> = BTreeMap::new();
+        for t in &self.tracks {
+            let Some((key, label)) = group_of(category, t) else {
+                continue;
+            };
+            groups
+                .entry(key.clone())
+                .or_insert_with(|| LibraryEntry { label, key, count: 0 })
+                .count += 1;
+        }
+        groups.into_values().collect()
+    }
+
+    /// Indices of tracks in `entry` (or all tracks), in album/disc/track order.
+    pub fn tracks_for(&self, category: LibraryCategory, entry: Option<&LibraryEntry>) -> Vec<usize> {
+        let mut out: Vec<usize> = (0..self.tracks.len())
+            .filter(|&i| match entry {
+                None => true,
+                Some(e) => group_of(category, &self.tracks[i]).is_none_or(|(key, _)| key == e.key),
+            })
+            .collect();
+
+        out.sort_by_cached_key(|&i| {
+            let t = &self.tracks[i];
+            (
+                t.grouping_artist().to_lowercase(),
+                t.year,
+                t.album.to_lowercase(),
+                t.disc_no.unwrap_or(0),
+                t.track_no.unwrap_or(0),
+                t.path.as_path(),
+            )
+        });
+        out
+    }
+
+    /// Case-insensitive substring match on title, artist, album and file name.
+    pub fn track_matches(&self, idx: usize, query: &str) -> bool {
+        let Some(t) = self.tracks.get(idx) else {
+            return false;
+        };
+        let q = query.trim().to_lowercase();
+        if q.is_empty() {
+            return true;
+        }
+        let file = t.path.file_name().and_then(|s| s.to_str()).unwrap_or("");
+        [t.title.as_str(), t.artist.as_str(), t.album.as_str(), t.album_artist.as_str(), file]
+            .iter()
+            .any(|s| s.to_lowercase().contains(&q))
+    }
+
+    pub fn playlist_items(&self, indices: &[usize]) -> Vec<PlaylistItem> {
+        indices
+            .iter()
+            .filter_map(|&i| self.tracks.get(i))
+            .map(|t| PlaylistItem {
+                path: t.path.clone(),
+                title: t.display_title(),
+                missing: false,
+            })
+            .collect()
+    }
+}
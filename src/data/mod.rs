@@ -1,5 +1,8 @@
 pub mod assets;
 pub mod config;
 pub mod about;
+pub mod keymap;
+pub mod library;
 pub mod playlist;
+pub mod playlist_file;
 pub mod theme_loader;
@@ -1,19 +1,55 @@
-use std::path::PathBuf;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 #[derive(Debug, Clone)]
 pub struct PlaylistItem {
     pub path: PathBuf,
     pub title: String,
+    /// Listed in a playlist file but not playable here (file gone, drive unmounted).
+    /// Kept so saving the playlist writes it back; playback passes over it.
+    pub missing: bool,
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Clone)]
 pub struct Playlist {
     pub items: Vec<PlaylistItem>,
     pub selected: usize,
     pub current: Option<usize>,
+    /// Changes whenever `items` is edited; clones share it until one of them is edited.
+    /// Code that changes `items` directly calls `edited()`.
+    rev: u64,
+}
+
+impl Default for Playlist {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+fn next_rev() -> u64 {
+    static REV: AtomicU64 = AtomicU64::new(1);
+    REV.fetch_add(1, Ordering::Relaxed)
 }
 
 impl Playlist {
+    pub fn new(items: Vec<PlaylistItem>) -> Self {
+        Self {
+            items,
+            selected: 0,
+            current: None,
+            rev: next_rev(),
+        }
+    }
+
+    /// Note an edit of `items` made from outside (lets `ShuffleState` rebuild its order).
+    pub fn edited(&mut self) {
+        self.rev = next_rev();
+    }
+
     pub fn len(&self) -> usize {
         self.items.len()
     }
@@ -48,6 +84,7 @@ impl Playlist {
         }
         let s = self.selected;
         self.items.swap(s, s - 1);
+        self.edited();
         self.selected = s - 1;
 
         if let Some(cur) = self.current {
@@ -69,6 +106,7 @@ impl Playlist {
             return false;
         }
         self.items.swap(s, s + 1);
+        self.edited();
         self.selected = s + 1;
 
         if let Some(cur) = self.current {
@@ -81,6 +119,114 @@ impl Playlist {
         true
     }
 
+    /// Remove the item at `idx`, keeping `selected`/`current` pointing at the same tracks.
+    /// If the current track itself is removed, `current` becomes `None`.
+    pub fn remove_at(&mut self, idx: usize) -> Option<PlaylistItem> {
+        if idx >= self.items.len() {
+            return None;
+        }
+        let item = self.items.remove(idx);
+        self.edited();
+
+        self.current = match self.current {
+            Some(cur) if cur == idx => None,
+            Some(cur) if cur > idx => Some(cur - 1),
+            other => other,
+        };
+        if self.selected > idx {
+            self.selected -= 1;
+        }
+        self.clamp_selected();
+        Some(item)
+    }
+
+    /// Remove `range` (clamped to the list), keeping `selected`/`current` on the same tracks.
+    /// The current track is never removed: it is still playing, so it stays (at the start of
+    /// the range) and `current` keeps pointing at it.
+    pub fn remove_range(&mut self, range: std::ops::Range<usize>) -> Vec<PlaylistItem> {
+        let end = range.end.min(self.items.len());
+        let start = range.start.min(end);
+        if start == end {
+            return Vec::new();
+        }
+        let mut removed: Vec<PlaylistItem> = self.items.drain(start..end).collect();
+        self.edited();
+        match self.current {
+            Some(cur) if (start..end).contains(&cur) => {
+                self.items.insert(start, removed.remove(cur - start));
+                self.current = Some(start);
+            }
+            Some(cur) if cur >= end => self.current = Some(cur - removed.len()),
+            _ => {}
+        }
+        let n = removed.len();
+        if self.selected >= end {
+            self.selected -= n;
+        } else if self.selected > start {
+            self.selected = start;
+        }
+        self.clamp_selected();
+        removed
+    }
+
+    /// Insert `items` before `idx` (appended if past the end) and select the first of them.
+    /// `current` keeps pointing at the same track.
+    pub fn insert_at(&mut self, idx: usize, items: Vec<PlaylistItem>) -> bool {
+        if items.is_empty() {
+            return false;
+        }
+        let idx = idx.min(self.items.len());
+        let n = items.len();
+        self.items.splice(idx..idx, items);
+        self.edited();
+
+        if let Some(cur) = self.current {
+            if cur >= idx {
+                self.current = Some(cur + n);
+            }
+        }
+        self.selected = idx;
+        true
+    }
+
+    /// Move the block `range` so that it starts at `to` (an index in the list without the block).
+    /// The selection follows the block; `current` follows its track.
+    pub fn move_range(&mut self, range: std::ops::Range<usize>, to: usize) -> bool {
+        let len = self.items.len();
+        if range.start >= range.end || range.end > len {
+            return false;
+        }
+        let n = range.end - range.start;
+        let to = to.min(len - n);
+        if to == range.start {
+            return false;
+        }
+        let block: Vec<PlaylistItem> = self.items.drain(range.clone()).collect();
+        self.items.splice(to..to, block);
+        self.edited();
+
+        // Where an old index ends up after the move.
+        let map = |i: usize| -> usize {
+            if range.contains(&i) {
+                to + (i - range.start)
+            } else {
+                let without = if i >= range.end { i - n } else { i };
+                if without >= to {
+                    without + n
+                } else {
+                    without
+                }
+            }
+        };
+        self.current = self.current.map(map);
+        self.selected = if range.contains(&self.selected) {
+            map(self.selected)
+        } else {
+            to
+        };
+        true
+    }
+
     pub fn current_path(&self) -> Option<&PathBuf> {
         self.current.and_then(|i| self.items.get(i)).map(|it| &it.path)
     }
@@ -98,44 +244,493 @@ impl Playlist {
     }
 
     pub fn next_index_sequence(&self) -> Option<usize> {
-        let cur = self.current?;
-        if self.items.is_empty() {
-            None
-        } else {
-            Some((cur + 1) % self.items.len())
-        }
+        self.step_playable(true, true)
     }
 
     pub fn next_index_no_wrap(&self) -> Option<usize> {
+        self.step_playable(true, false)
+    }
+
+    pub fn prev_index_sequence(&self) -> Option<usize> {
+        self.step_playable(false, true)
+    }
+
+    pub fn prev_index_no_wrap(&self) -> Option<usize> {
+        self.step_playable(false, false)
+    }
+
+    /// `idx` itself, or the first playable entry after it.
+    pub fn first_playable_from(&self, idx: usize) -> Option<usize> {
+        (idx..self.items.len()).find(|&i| !self.items[i].missing)
+    }
+
+    /// The nearest entry before / after `current` that isn't missing.
+    fn step_playable(&self, forward: bool, wrap: bool) -> Option<usize> {
         let cur = self.current?;
-        if self.items.is_empty() {
+        let len = self.items.len();
+        for k in 1..=len {
+            let i = if forward {
+                if !wrap && cur + k >= len {
+                    return None;
+                }
+                (cur + k) % len
+            } else {
+                if !wrap && k > cur {
+                    return None;
+                }
+                (cur + len - k) % len
+            };
+            if !self.items[i].missing {
+                return Some(i);
+            }
+        }
+        None
+    }
+}
+
+// Keep Prev useful for a long session without growing forever.
+const SHUFFLE_HISTORY_LIMIT: usize = 500;
+
+/// Shuffle order and play history for `RepeatMode::Shuffle`.
+///
+/// The order is a seeded Fisher–Yates permutation of the playlist; every track is played
+/// once before a new round starts. Any edit of the playlist (add/remove/reorder, other
+/// folder) rebuilds the order; tracks already played in this round stay played. History
+/// stores paths so it survives such rebuilds.
+#[derive(Debug, Default, Clone)]
+pub struct ShuffleState {
+    rev: u64,
+    key: u64,
+    order: Vec<usize>,
+    round: HashSet<PathBuf>,
+    pos: usize,
+    rng: u64,
+    history: Vec<PathBuf>,
+    playing: Option<PathBuf>,
+    album_order: Vec<usize>,
+    album_pos: usize,
+}
+
+impl ShuffleState {
+    /// Same seed, same orders (the default seeds from the clock on first use).
+    pub fn seeded(seed: u64) -> Self {
+        Self {
+            rng: seed.max(1),
+            ..Default::default()
+        }
+    }
+
+    /// Index to play after the current track (does not consume it). Missing files are passed over.
+    pub fn peek_next(&mut self, pl: &Playlist) -> Option<usize> {
+        if pl.items.is_empty() {
             return None;
         }
-        if cur + 1 >= self.items.len() {
-            None
-        } else {
-            Some(cur + 1)
+        self.sync(pl);
+        // Second pass only when the rest of this round was all missing files.
+        for _ in 0..2 {
+            if self.pos >= self.order.len() {
+                // Round finished: new order, but don't repeat the track that just played.
+                self.new_round(pl.items.len());
+                if self.order.len() > 1 && Some(self.order[0]) == pl.current {
+                    let last = self.order.len() - 1;
+                    self.order.swap(0, last);
+                }
+            }
+            while self.pos < self.order.len() && pl.items[self.order[self.pos]].missing {
+                self.pos += 1;
+            }
+            if let Some(&i) = self.order.get(self.pos) {
+                return Some(i);
+            }
         }
+        None
     }
 
-    pub fn prev_index_sequence(&self) -> Option<usize> {
-        let cur = self.current?;
-        if self.items.is_empty() {
-            None
-        } else {
-            Some((cur + self.items.len() - 1) % self.items.len())
+    /// Record that `path` started playing. `idx` is its index in `pl` (if it is part of it),
+    /// which is then consumed from the current round.
+    pub fn record(&mut self, pl: &Playlist, path: &Path, idx: Option<usize>) {
+        if let Some(prev) = self.playing.take() {
+            if prev != path {
+                self.history.push(prev);
+                if self.history.len() > SHUFFLE_HISTORY_LIMIT {
+                    self.history.remove(0);
+                }
+            }
         }
+        self.playing = Some(path.to_path_buf());
+
+        let Some(idx) = idx else {
+            return;
+        };
+        self.sync(pl);
+        if self.pos >= self.order.len() {
+            self.new_round(pl.items.len());
+        }
+        self.consume(idx);
+        self.round.insert(path.to_path_buf());
     }
 
-    pub fn prev_index_no_wrap(&self) -> Option<usize> {
-        let cur = self.current?;
-        if self.items.is_empty() {
+    /// Step back through what actually played. The track being left becomes the next one again.
+    pub fn back(&mut self, pl: &Playlist) -> Option<PathBuf> {
+        let path = self.history.pop()?;
+        self.sync(pl);
+        if self.pos > 0 && Some(self.order[self.pos - 1]) == pl.current {
+            self.pos -= 1;
+            if let Some(cur) = pl.current_path() {
+                self.round.remove(cur);
+            }
+        }
+        self.playing = Some(path.clone());
+        Some(path)
+    }
+
+    /// Next album index for album-level shuffle (each album once per round).
+    pub fn next_album(&mut self, count: usize, current: usize) -> Option<usize> {
+        if count == 0 {
             return None;
         }
-        if cur == 0 {
-            None
-        } else {
-            Some(cur - 1)
+        if self.album_order.len() != count || self.album_pos >= count {
+            self.album_order = (0..count).collect();
+            self.fisher_yates_albums();
+            if let Some(off) = self.album_order.iter().position(|&i| i == current) {
+                // The album that is playing counts as already played in this round.
+                self.album_order.swap(0, off);
+                self.album_pos = 1;
+            } else {
+                self.album_pos = 0;
+            }
+            if self.album_pos >= count {
+                // Single album: just replay it.
+                self.album_pos = 0;
+            }
+        }
+        let next = self.album_order.get(self.album_pos).copied();
+        self.album_pos += 1;
+        next
+    }
+
+    fn sync(&mut self, pl: &Playlist) {
+        if pl.rev == self.rev && self.order.len() == pl.items.len() {
+            return;
+        }
+        // Edited (or another playlist): only hash it then, and keep the order if nothing moved.
+        self.rev = pl.rev;
+        let key = playlist_key(pl);
+        if key == self.key && self.order.len() == pl.items.len() {
+            return;
+        }
+        self.key = key;
+        self.reshuffle(pl.items.len());
+        // The playing track and what already played in this round are "used" in the new order.
+        for (i, it) in pl.items.iter().enumerate() {
+            if Some(i) == pl.current || self.round.contains(&it.path) {
+                self.consume(i);
+            }
+        }
+    }
+
+    // Move `idx` to the played part of the order.
+    fn consume(&mut self, idx: usize) {
+        if let Some(off) = self.order[self.pos..].iter().position(|&i| i == idx) {
+            self.order.swap(self.pos, self.pos + off);
+            self.pos += 1;
+        }
+    }
+
+    fn new_round(&mut self, len: usize) {
+        self.round.clear();
+        self.reshuffle(len);
+    }
+
+    fn reshuffle(&mut self, len: usize) {
+        self.order = (0..len).collect();
+        self.pos = 0;
+        for i in (1..len).rev() {
+            let j = (self.next_rand() % (i as u64 + 1)) as usize;
+            self.order.swap(i, j);
+        }
+    }
+
+    fn fisher_yates_albums(&mut self) {
+        for i in (1..self.album_order.len()).rev() {
+            let j = (self.next_rand() % (i as u64 + 1)) as usize;
+            self.album_order.swap(i, j);
+        }
+    }
+
+    // splitmix64, seeded from the clock on first use.
+    fn next_rand(&mut self) -> u64 {
+        if self.rng == 0 {
+            self.rng = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_nanos() as u64)
+                .unwrap_or(0x9e37_79b9_7f4a_7c15)
+                | 1;
+        }
+        self.rng = self.rng.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.rng;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+fn playlist_key(pl: &Playlist) -> u64 {
+    let mut h = DefaultHasher::new();
+    for it in &pl.items {
+        it.path.hash(&mut h);
+    }
+    h.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn playlist(n: usize) -> Playlist {
+        Playlist {
+            items: (0..n)
+                .map(|i| PlaylistItem {
+                    path: PathBuf::from(format!("/m/{i}.mp3")),
+                    title: i.to_string(),
+                    missing: false,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn titles(pl: &Playlist) -> Vec<&str> {
+        pl.items.iter().map(|it| it.title.as_str()).collect()
+    }
+
+    fn title_at(pl: &Playlist, idx: Option<usize>) -> Option<&str> {
+        idx.and_then(|i| pl.items.get(i)).map(|it| it.title.as_str())
+    }
+
+    #[test]
+    fn remove_range_keeps_current_and_selected_on_their_tracks() {
+        let mut pl = playlist(6);
+        pl.current = Some(4);
+        pl.selected = 1;
+        let cut = pl.remove_range(1..3);
+        assert_eq!(cut.iter().map(|it| it.title.as_str()).collect::<Vec<_>>(), ["1", "2"]);
+        assert_eq!(titles(&pl), ["0", "3", "4", "5"]);
+        assert_eq!(title_at(&pl, pl.current), Some("4"));
+        assert_eq!(title_at(&pl, Some(pl.selected)), Some("3"));
+
+        // Clamped to the list.
+        assert_eq!(pl.remove_range(3..10).len(), 1);
+        assert_eq!(pl.selected, 1);
+        assert!(pl.remove_range(7..9).is_empty());
+    }
+
+    #[test]
+    fn remove_range_never_removes_the_playing_track() {
+        let mut pl = playlist(6);
+        pl.current = Some(2);
+        pl.selected = 1;
+        let cut = pl.remove_range(1..4);
+        assert_eq!(cut.len(), 2);
+        assert_eq!(titles(&pl), ["0", "2", "4", "5"]);
+        assert_eq!(title_at(&pl, pl.current), Some("2"));
+        assert_eq!(pl.selected, 1);
+
+        assert!(pl.remove_range(1..2).is_empty());
+        assert_eq!(title_at(&pl, pl.current), Some("2"));
+    }
+
+    #[test]
+    fn insert_at_selects_the_new_items_and_shifts_current() {
+        let mut pl = playlist(3);
+        pl.current = Some(1);
+        let extra = playlist(2).items.into_iter().map(|mut it| {
+            it.title = format!("new{}", it.title);
+            it
+        });
+        assert!(pl.insert_at(1, extra.collect()));
+        assert_eq!(titles(&pl), ["0", "new0", "new1", "1", "2"]);
+        assert_eq!(title_at(&pl, pl.current), Some("1"));
+        assert_eq!(pl.selected, 1);
+
+        // Past the end appends; current before the insert point stays.
+        assert!(pl.insert_at(99, playlist(1).items));
+        assert_eq!(pl.selected, 5);
+        assert_eq!(title_at(&pl, pl.current), Some("1"));
+        assert!(!pl.insert_at(0, Vec::new()));
+    }
+
+    #[test]
+    fn move_range_carries_current_and_selected() {
+        let mut pl = playlist(5);
+        pl.current = Some(2);
+        pl.selected = 1;
+        assert!(pl.move_range(1..3, 3));
+        assert_eq!(titles(&pl), ["0", "3", "4", "1", "2"]);
+        assert_eq!(title_at(&pl, pl.current), Some("2"));
+        assert_eq!(title_at(&pl, Some(pl.selected)), Some("1"));
+
+        pl.current = Some(1);
+        pl.selected = 3;
+        assert!(pl.move_range(3..5, 0));
+        assert_eq!(titles(&pl), ["1", "2", "0", "3", "4"]);
+        assert_eq!(title_at(&pl, pl.current), Some("3"));
+        assert_eq!(title_at(&pl, Some(pl.selected)), Some("1"));
+
+        assert!(!pl.move_range(0..2, 0));
+        assert!(!pl.move_range(4..6, 0));
+    }
+
+    #[test]
+    fn next_and_prev_skip_missing_entries() {
+        let mut pl = playlist(5);
+        pl.items[1].missing = true;
+        pl.items[2].missing = true;
+        pl.current = Some(0);
+        assert_eq!(pl.next_index_no_wrap(), Some(3));
+        pl.current = Some(3);
+        assert_eq!(pl.prev_index_no_wrap(), Some(0));
+        pl.items[0].missing = true;
+        assert_eq!(pl.prev_index_no_wrap(), None);
+        assert_eq!(pl.prev_index_sequence(), Some(4));
+        pl.current = Some(4);
+        assert_eq!(pl.next_index_no_wrap(), None);
+        assert_eq!(pl.next_index_sequence(), Some(3));
+        assert_eq!(pl.first_playable_from(0), Some(3));
+    }
+
+    #[test]
+    fn shuffle_never_picks_missing_entries() {
+        let mut pl = playlist(6);
+        for i in [0, 2, 4] {
+            pl.items[i].missing = true;
+        }
+        pl.current = Some(1);
+        let mut sh = ShuffleState::default();
+        for _ in 0..20 {
+            let next = sh.peek_next(&pl).unwrap();
+            assert!(!pl.items[next].missing);
+            let path = pl.items[next].path.clone();
+            pl.current = Some(next);
+            sh.record(&pl, &path, Some(next));
+        }
+
+        for it in &mut pl.items {
+            it.missing = true;
+        }
+        assert_eq!(ShuffleState::default().peek_next(&pl), None);
+    }
+
+    // What the event loop does when a shuffled track ends: play the peeked one and record it.
+    fn play_next(sh: &mut ShuffleState, pl: &mut Playlist) -> PathBuf {
+        let next = sh.peek_next(pl).unwrap();
+        let path = pl.items[next].path.clone();
+        pl.current = Some(next);
+        sh.record(pl, &path, Some(next));
+        path
+    }
+
+    fn item(name: &str) -> PlaylistItem {
+        PlaylistItem {
+            path: PathBuf::from(format!("/m/{name}.mp3")),
+            title: name.to_string(),
+            missing: false,
+        }
+    }
+
+    #[test]
+    fn shuffle_plays_every_track_once_per_round() {
+        let mut pl = playlist(8);
+        let mut sh = ShuffleState::seeded(7);
+        let mut last = None;
+        for _ in 0..3 {
+            let round: Vec<PathBuf> = (0..8).map(|_| play_next(&mut sh, &mut pl)).collect();
+            let mut distinct = round.clone();
+            distinct.sort();
+            distinct.dedup();
+            assert_eq!(distinct.len(), 8);
+            // Nor twice in a row across the start of a new round.
+            assert_ne!(last.as_ref(), round.first());
+            last = round.last().cloned();
+        }
+    }
+
+    #[test]
+    fn shuffle_order_follows_the_seed() {
+        let order = |seed| {
+            let mut pl = playlist(12);
+            let mut sh = ShuffleState::seeded(seed);
+            (0..24).map(|_| play_next(&mut sh, &mut pl)).collect::<Vec<_>>()
+        };
+        assert_eq!(order(42), order(42));
+        assert_ne!(order(42), order(43));
+    }
+
+    #[test]
+    fn shuffle_back_walks_the_history() {
+        let mut pl = playlist(6);
+        let mut sh = ShuffleState::seeded(5);
+        let played: Vec<PathBuf> = (0..4).map(|_| play_next(&mut sh, &mut pl)).collect();
+        let index_of = |pl: &Playlist, p: &PathBuf| pl.items.iter().position(|it| &it.path == p);
+
+        let back = sh.back(&pl).unwrap();
+        assert_eq!(back, played[2]);
+        // Prev plays it again; the track that was left comes next again.
+        pl.current = index_of(&pl, &back);
+        sh.record(&pl, &back, pl.current);
+        assert_eq!(sh.peek_next(&pl), index_of(&pl, &played[3]));
+
+        assert_eq!(sh.back(&pl), Some(played[1].clone()));
+        assert_eq!(sh.back(&pl), Some(played[0].clone()));
+        assert_eq!(sh.back(&pl), None);
+    }
+
+    #[test]
+    fn shuffle_edit_reshuffles_without_replaying_the_round() {
+        let mut pl = playlist(10);
+        let mut sh = ShuffleState::seeded(11);
+        let played: Vec<PathBuf> = (0..4).map(|_| play_next(&mut sh, &mut pl)).collect();
+
+        // Edits through the methods and one made directly (marked with `edited()`).
+        let unplayed = pl.items.iter().position(|it| !played.contains(&it.path)).unwrap();
+        pl.remove_at(unplayed);
+        pl.insert_at(0, vec![item("new")]);
+        pl.items.push(item("late"));
+        pl.edited();
+
+        let mut rest: Vec<PathBuf> = (0..7).map(|_| play_next(&mut sh, &mut pl)).collect();
+        let mut expect: Vec<PathBuf> = pl
+            .items
+            .iter()
+            .map(|it| it.path.clone())
+            .filter(|p| !played.contains(p))
+            .collect();
+        rest.sort();
+        expect.sort();
+        assert_eq!(rest, expect);
+    }
+
+    #[test]
+    fn album_shuffle_plays_each_album_once_and_its_tracks_in_order() {
+        let mut sh = ShuffleState::seeded(3);
+        let mut current = 0;
+        for _ in 0..3 {
+            let mut round = vec![current];
+            while round.len() < 4 {
+                current = sh.next_album(4, current).unwrap();
+                assert!(!round.contains(&current), "album {current} twice in {round:?}");
+                round.push(current);
+            }
+        }
+
+        // Inside an album the tracks play in listing order; at its end the album switches.
+        let mut pl = playlist(4);
+        pl.current = Some(0);
+        let mut order = vec![0];
+        while let Some(i) = pl.next_index_no_wrap() {
+            order.push(i);
+            pl.current = Some(i);
         }
+        assert_eq!(order, [0, 1, 2, 3]);
     }
 }
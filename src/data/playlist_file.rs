@@ -0,0 +1,436 @@
+use crate::data::assets;
+use crate::data::playlist::PlaylistItem;
+use anyhow::{anyhow, Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Extension used for playlists created from inside the app.
+pub const DEFAULT_PLAYLIST_EXT: &str = "m3u8";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaylistFormat {
+    M3u,
+    Pls,
+    Xspf,
+}
+
+impl PlaylistFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "m3u" | "m3u8" => Some(Self::M3u),
+            "pls" => Some(Self::Pls),
+            "xspf" => Some(Self::Xspf),
+            _ => None,
+        }
+    }
+}
+
+pub fn is_playlist_file(path: &Path) -> bool {
+    path.is_file() && PlaylistFormat::from_path(path).is_some()
+}
+
+/// `<asset root>/playlists`, where named user playlists live.
+pub fn playlists_dir() -> PathBuf {
+    assets::resolve_asset_path(Path::new("playlists"))
+}
+
+/// All playlist files in `playlists_dir()`, sorted by name.
+pub fn list_playlists() -> Vec<PathBuf> {
+    let Ok(rd) = fs::read_dir(playlists_dir()) else {
+        return Vec::new();
+    };
+    let mut out: Vec<PathBuf> = rd
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| is_playlist_file(p))
+        .collect();
+    out.sort_by_key(|p| playlist_name(p).to_lowercase());
+    out
+}
+
+pub fn playlist_name(path: &Path) -> String {
+    path.file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("Untitled")
+        .to_string()
+}
+
+/// `<asset root>/queue.m3u8`: the up-next queue, kept across restarts.
+pub fn up_next_path() -> PathBuf {
+    assets::resolve_asset_path(Path::new("queue.m3u8"))
+}
+
+/// Saved up-next queue; entries whose files are gone are dropped.
+pub fn read_up_next() -> Vec<PlaylistItem> {
+    let path = up_next_path();
+    if !path.is_file() {
+        return Vec::new();
+    }
+    read_playlist(&path)
+        .map(|items| items.into_iter().filter(|it| it.path.is_file()).collect())
+        .unwrap_or_default()
+}
+
+pub fn write_up_next(items: &[PlaylistItem]) -> Result<()> {
+    let path = up_next_path();
+    if items.is_empty() {
+        if path.exists() {
+            fs::remove_file(&path).with_context(|| format!("remove {}", path.display()))?;
+        }
+        return Ok(());
+    }
+    write_playlist(&path, items)
+}
+
+/// Path for a playlist named `name` in `playlists_dir()`.
+/// Keeps an explicit playlist extension (`.pls`, `.xspf`, ...) if the name has one.
+pub fn playlist_path_for_name(name: &str) -> Result<PathBuf> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(anyhow!("empty playlist name"));
+    }
+    if name.contains('/') || name.contains('\\') || name.starts_with('.') {
+        return Err(anyhow!("invalid playlist name"));
+    }
+    let p = playlists_dir().join(name);
+    if PlaylistFormat::from_path(&p).is_some() {
+        Ok(p)
+    } else {
+        Ok(playlists_dir().join(format!("{name}.{DEFAULT_PLAYLIST_EXT}")))
+    }
+}
+
+pub fn read_playlist(path: &Path) -> Result<Vec<PlaylistItem>> {
+    let format = PlaylistFormat::from_path(path).ok_or_else(|| anyhow!("unsupported playlist format"))?;
+    let raw = fs::read(path).with_context(|| format!("read {}", path.display()))?;
+    let raw = String::from_utf8_lossy(&raw);
+    let raw = raw.trim_start_matches('\u{feff}');
+    let base = path.parent().unwrap_or(Path::new("."));
+
+    let entries = match format {
+        PlaylistFormat::M3u => parse_m3u(raw),
+        PlaylistFormat::Pls => parse_pls(raw),
+        PlaylistFormat::Xspf => parse_xspf(raw),
+    };
+
+    Ok(entries
+        .into_iter()
+        .filter_map(|(loc, title)| {
+            let path = resolve_location(base, &loc)?;
+            let title = title
+                .filter(|t| !t.trim().is_empty())
+                .unwrap_or_else(|| {
+                    path.file_stem()
+                        .and_then(|s| s.to_str())
+                        .unwrap_or("Unknown")
+                        .to_string()
+                });
+            let missing = !path.is_file();
+            Some(PlaylistItem { path, title, missing })
+        })
+        .collect())
+}
+
+pub fn write_playlist(path: &Path, items: &[PlaylistItem]) -> Result<()> {
+    let format = PlaylistFormat::from_path(path).ok_or_else(|| anyhow!("unsupported playlist format"))?;
+    let content = match format {
+        PlaylistFormat::M3u => write_m3u(items),
+        PlaylistFormat::Pls => write_pls(items),
+        PlaylistFormat::Xspf => write_xspf(items),
+    };
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("mkdir {}", parent.display()))?;
+    }
+    // Written aside and renamed over the old file, so a crash never leaves it half-written.
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    fs::write(&tmp, content).with_context(|| format!("write {}", tmp.display()))?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+fn resolve_location(base: &Path, loc: &str) -> Option<PathBuf> {
+    let loc = loc.trim();
+    if loc.is_empty() {
+        return None;
+    }
+    let p = if let Some(rest) = loc.strip_prefix("file://") {
+        // file:///abs/path or file://localhost/abs/path
+        let rest = rest.strip_prefix("localhost").unwrap_or(rest);
+        PathBuf::from(percent_decode(rest))
+    } else if loc.contains("://") {
+        // Streams are not supported by the local player.
+        return None;
+    } else {
+        PathBuf::from(loc)
+    };
+    Some(if p.is_absolute() { p } else { base.join(p) })
+}
+
+fn parse_m3u(raw: &str) -> Vec<(String, Option<String>)> {
+    let mut out = Vec::new();
+    let mut pending_title: Option<String> = None;
+    for line in raw.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(info) = line.strip_prefix("#EXTINF:") {
+            pending_title = info.split_once(',').map(|(_, t)| t.trim().to_string());
+            continue;
+        }
+        if line.starts_with('#') {
+            continue;
+        }
+        out.push((line.to_string(), pending_title.take()));
+    }
+    out
+}
+
+fn parse_pls(raw: &str) -> Vec<(String, Option<String>)> {
+    let mut files: BTreeMap<u32, String> = BTreeMap::new();
+    let mut titles: BTreeMap<u32, String> = BTreeMap::new();
+    for line in raw.lines() {
+        let Some((k, v)) = line.trim().split_once('=') else {
+            continue;
+        };
+        let k = k.trim().to_ascii_lowercase();
+        if let Some(n) = k.strip_prefix("file").and_then(|n| n.parse::<u32>().ok()) {
+            files.insert(n, v.trim().to_string());
+        } else if let Some(n) = k.strip_prefix("title").and_then(|n| n.parse::<u32>().ok()) {
+            titles.insert(n, v.trim().to_string());
+        }
+    }
+    files
+        .into_iter()
+        .map(|(n, f)| (f, titles.remove(&n)))
+        .collect()
+}
+
+fn parse_xspf(raw: &str) -> Vec<(String, Option<String>)> {
+    use quick_xml::events::Event;
+    use quick_xml::Reader;
+
+    let mut reader = Reader::from_str(raw);
+    let mut out = Vec::new();
+    // Local names of the open elements, and the fields of the track being read.
+    let mut open: Vec<Vec<u8>> = Vec::new();
+    let mut location = String::new();
+    let mut title = String::new();
+    loop {
+        let text = match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                let name = e.local_name().as_ref().to_vec();
+                if name == b"track" {
+                    location.clear();
+                    title.clear();
+                }
+                open.push(name);
+                continue;
+            }
+            Ok(Event::End(_)) => {
+                if open.pop().as_deref() == Some(b"track".as_slice()) && !location.trim().is_empty() {
+                    let t = Some(title.trim().to_string()).filter(|t| !t.is_empty());
+                    out.push((location.trim().to_string(), t));
+                }
+                continue;
+            }
+            Ok(Event::Text(t)) => t
+                .unescape()
+                .map(|s| s.into_owned())
+                .unwrap_or_else(|_| String::from_utf8_lossy(&t).into_owned()),
+            Ok(Event::CData(c)) => String::from_utf8_lossy(&c).into_owned(),
+            // A broken file keeps the tracks read so far.
+            Ok(Event::Eof) | Err(_) => break,
+            Ok(_) => continue,
+        };
+        match open.as_slice() {
+            [.., track, field] if track == b"track" && field == b"location" => location.push_str(&text),
+            [.., track, field] if track == b"track" && field == b"title" => title.push_str(&text),
+            _ => {}
+        }
+    }
+    out
+}
+
+fn write_m3u(items: &[PlaylistItem]) -> String {
+    let mut out = String::from("#EXTM3U\n");
+    for it in items {
+        out.push_str(&format!("#EXTINF:-1,{}\n{}\n", it.title, it.path.to_string_lossy()));
+    }
+    out
+}
+
+fn write_pls(items: &[PlaylistItem]) -> String {
+    let mut out = String::from("[playlist]\n");
+    for (i, it) in items.iter().enumerate() {
+        let n = i + 1;
+        out.push_str(&format!("File{n}={}\nTitle{n}={}\nLength{n}=-1\n", it.path.to_string_lossy(), it.title));
+    }
+    out.push_str(&format!("NumberOfEntries={}\nVersion=2\n", items.len()));
+    out
+}
+
+fn write_xspf(items: &[PlaylistItem]) -> String {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<playlist version=\"1\" xmlns=\"http://xspf.org/ns/0/\">\n  <trackList>\n",
+    );
+    for it in items {
+        out.push_str("    <track>\n");
+        out.push_str(&format!(
+            "      <location>file://{}</location>\n",
+            xml_escape(&percent_encode_path(&it.path.to_string_lossy()))
+        ));
+        out.push_str(&format!("      <title>{}</title>\n", xml_escape(&it.title)));
+        out.push_str("    </track>\n");
+    }
+    out.push_str("  </trackList>\n</playlist>\n");
+    out
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+pub fn percent_encode_path(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric() || b"/-_.~".contains(&b) {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    out
+}
+
+pub fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out: Vec<u8> = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = |b: u8| (b as char).to_digit(16);
+            if let (Some(h), Some(l)) = (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                out.push((h * 16 + l) as u8);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(path: &str, title: &str) -> PlaylistItem {
+        PlaylistItem {
+            path: PathBuf::from(path),
+            title: title.to_string(),
+            missing: false,
+        }
+    }
+
+    fn round_trip(ext: &str) {
+        let dir = tempfile::tempdir().unwrap();
+        let song = dir.path().join("a song #1.mp3");
+        fs::write(&song, b"").unwrap();
+        let items = vec![
+            item(song.to_str().unwrap(), "First & <best>"),
+            item("/mnt/gone/Ünïcode 100%.flac", "Gone"),
+        ];
+        let path = dir.path().join(format!("list.{ext}"));
+        write_playlist(&path, &items).unwrap();
+
+        let read = read_playlist(&path).unwrap();
+        assert_eq!(read.len(), 2, "{ext}");
+        assert_eq!(read[0].path, song, "{ext}");
+        assert_eq!(read[0].title, "First & <best>", "{ext}");
+        assert!(!read[0].missing, "{ext}");
+        assert_eq!(read[1].path, PathBuf::from("/mnt/gone/Ünïcode 100%.flac"), "{ext}");
+        assert!(read[1].missing, "{ext}");
+
+        // Saving again keeps the missing entry.
+        write_playlist(&path, &read).unwrap();
+        assert_eq!(read_playlist(&path).unwrap().len(), 2, "{ext}");
+    }
+
+    #[test]
+    fn m3u_pls_xspf_round_trip_keeps_missing_entries() {
+        round_trip("m3u8");
+        round_trip("pls");
+        round_trip("xspf");
+    }
+
+    #[test]
+    fn m3u_reads_extinf_titles_and_relative_paths() {
+        let raw = "#EXTM3U\n#EXTINF:123,Artist - Song\nsub/song.mp3\n\n# comment\nother.ogg\nhttp://stream/x\n";
+        let entries = parse_m3u(raw);
+        assert_eq!(
+            entries,
+            vec![
+                ("sub/song.mp3".to_string(), Some("Artist - Song".to_string())),
+                ("other.ogg".to_string(), None),
+                ("http://stream/x".to_string(), None),
+            ]
+        );
+        let base = Path::new("/music");
+        assert_eq!(resolve_location(base, "sub/song.mp3"), Some(PathBuf::from("/music/sub/song.mp3")));
+        assert_eq!(resolve_location(base, "http://stream/x"), None);
+        assert_eq!(
+            resolve_location(base, "file://localhost/a%20b.mp3"),
+            Some(PathBuf::from("/a b.mp3"))
+        );
+    }
+
+    #[test]
+    fn pls_pairs_files_with_titles_by_number() {
+        let raw = "[playlist]\nFile2=b.mp3\nTitle1=One\nfile1=a.mp3\nNumberOfEntries=2\n";
+        assert_eq!(
+            parse_pls(raw),
+            vec![("a.mp3".to_string(), Some("One".to_string())), ("b.mp3".to_string(), None)]
+        );
+    }
+
+    #[test]
+    fn xspf_decodes_entities_cdata_and_prefixes() {
+        let raw = r#"<?xml version="1.0"?>
+<x:playlist xmlns:x="http://xspf.org/ns/0/"><x:title>Not a track</x:title>
+  <x:trackList>
+    <x:track><x:location>file:///m/Caf%C3%A9.mp3</x:location><x:title>Caf&#233; &amp; Bar</x:title></x:track>
+    <x:track>
+      <x:title><![CDATA[Raw <title>]]></x:title>
+      <x:location>/m/b&apos;s.mp3</x:location>
+    </x:track>
+    <x:track><x:title>No location</x:title></x:track>
+  </x:trackList>
+</x:playlist>"#;
+        assert_eq!(
+            parse_xspf(raw),
+            vec![
+                ("file:///m/Caf%C3%A9.mp3".to_string(), Some("Café & Bar".to_string())),
+                ("/m/b's.mp3".to_string(), Some("Raw <title>".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn percent_encoding_round_trips() {
+        let s = "/music/A b#c%d/Ünïcode?.mp3";
+        let enc = percent_encode_path(s);
+        assert!(!enc.contains(' ') && !enc.contains('#') && enc.is_ascii());
+        assert_eq!(percent_decode(&enc), s);
+    }
+}
@@ -1,14 +1,25 @@
 use crate::data::assets;
-use crate::ui::theme::{detect_color_capability, Theme, ThemeName, ThemePalette};
-use anyhow::Result;
+use crate::ui::theme::{detect_color_capability, Theme, ThemePalette};
+use anyhow::{anyhow, Context, Result};
 use serde::Deserialize;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub struct ThemeLoader;
 
+// Built-in themes: (config key, label, file under themes/). Listed first, in this order;
+// any other themes/*.toml is a user theme keyed by its file stem.
+const BUILTIN_THEMES: &[(&str, &str, &str)] = &[
+    ("system", "System", "system.toml"),
+    ("latte", "Latte", "catppuccin_latte.toml"),
+    ("frappe", "Frappe", "catppuccin_frappe.toml"),
+    ("macchiato", "Macchiato", "catppuccin_macchiato.toml"),
+    ("mocha", "Mocha", "catppuccin_mocha.toml"),
+];
+
 #[derive(Debug, Deserialize)]
 struct ThemeToml {
+    #[serde(default)]
     name: String,
     text: String,
     subtext: String,
@@ -17,46 +28,177 @@ struct ThemeToml {
     accent: String,
     accent2: String,
     accent3: String,
+    // Optional slots; see `ThemePalette` for what they color and `palette_from` for fallbacks.
+    #[serde(default)]
+    spectrum: Vec<String>,
+    progress: Option<String>,
+    lyric_highlight: Option<String>,
+    border: Option<String>,
+    selection: Option<String>,
+}
+
+/// A theme file found in the themes directory.
+#[derive(Debug, Clone)]
+pub struct ThemeEntry {
+    pub key: String,
+    pub label: String,
+    pub path: PathBuf,
 }
 
 impl ThemeLoader {
-    pub fn load(name: &str) -> Result<Theme> {
+    /// Load a theme by its config key. Unknown keys fall back to System.
+    pub fn load(key: &str) -> Result<Theme> {
         let _ = assets::ensure_assets_ready();
-        let name = ThemeName::from_str_or_system(name);
-
-        let rel = match name {
-            ThemeName::System => PathBuf::from("themes/system.toml"),
-            ThemeName::Latte => PathBuf::from("themes/catppuccin_latte.toml"),
-            ThemeName::Frappe => PathBuf::from("themes/catppuccin_frappe.toml"),
-            ThemeName::Macchiato => PathBuf::from("themes/catppuccin_macchiato.toml"),
-            ThemeName::Mocha => PathBuf::from("themes/catppuccin_mocha.toml"),
-        };
+        let themes = Self::list();
+        let key = key.to_lowercase();
+        let entry = themes
+            .iter()
+            .find(|t| t.key == key)
+            .or_else(|| themes.iter().find(|t| t.key == "system"))
+            .ok_or_else(|| anyhow!("no themes found"))?;
+        Self::load_entry(entry)
+    }
+
+    /// Built-in themes first, then user themes sorted by file name.
+    pub fn list() -> Vec<ThemeEntry> {
+        let dir = assets::resolve_asset_path(Path::new("themes"));
+        let mut out: Vec<ThemeEntry> = BUILTIN_THEMES
+            .iter()
+            .map(|(key, label, file)| ThemeEntry {
+                key: key.to_string(),
+                label: label.to_string(),
+                path: dir.join(file),
+            })
+            .collect();
 
-        let path = assets::resolve_asset_path(&rel);
-        let raw = fs::read_to_string(path)?;
-        let t: ThemeToml = toml::from_str(&raw)?;
-        let capability = detect_color_capability();
+        let mut files: Vec<PathBuf> = fs::read_dir(&dir)
+            .map(|rd| rd.flatten().map(|e| e.path()).collect())
+            .unwrap_or_default();
+        files.sort();
+        for path in files {
+            if path.extension().and_then(|e| e.to_str()) != Some("toml") || out.iter().any(|t| t.path == path) {
+                continue;
+            }
+            let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            let key = stem.to_lowercase();
+            if out.iter().any(|t| t.key == key) {
+                continue;
+            }
+            // The file's `name` is the label; reading it is cheap, and a broken file still gets listed.
+            let label = fs::read_to_string(&path)
+                .ok()
+                .and_then(|raw| toml::from_str::<toml::Table>(&raw).ok())
+                .and_then(|t| t.get("name").and_then(|v| v.as_str()).map(str::to_string))
+                .filter(|n| !n.trim().is_empty())
+                .unwrap_or_else(|| stem.to_string());
+            out.push(ThemeEntry { key, label, path });
+        }
+        out
+    }
+
+    pub fn load_entry(entry: &ThemeEntry) -> Result<Theme> {
+        let raw = fs::read_to_string(&entry.path).with_context(|| format!("read {}", entry.path.display()))?;
+        let t: ThemeToml = toml::from_str(&raw).with_context(|| format!("parse {}", entry.path.display()))?;
+        let palette = palette_from(&t).map_err(|e| anyhow!("{}: {e}", entry.path.display()))?;
         Ok(Theme {
-            name,
-            palette: ThemePalette {
-                text: parse_hex(&t.text),
-                subtext: parse_hex(&t.subtext),
-                base: parse_hex(&t.base),
-                surface: parse_hex(&t.surface),
-                accent: parse_hex(&t.accent),
-                accent2: parse_hex(&t.accent2),
-                accent3: parse_hex(&t.accent3),
-            },
-            capability,
+            key: entry.key.clone(),
+            label: entry.label.clone(),
+            palette: palette.clone(),
+            file_palette: palette,
+            capability: detect_color_capability(),
+            path: entry.path.clone(),
+            modified: modified_time(&entry.path),
         })
     }
+
+    /// The System theme as compiled in, for when the themes directory can't be used.
+    /// Its path still points at themes/system.toml, so a fixed file is picked up on reload.
+    pub fn builtin() -> Theme {
+        let t: ThemeToml = toml::from_str(assets::THEME_SYSTEM_TOML).expect("built-in theme parses");
+        let palette = palette_from(&t).expect("built-in theme colors are valid");
+        Theme {
+            key: "system".to_string(),
+            label: "System".to_string(),
+            palette: palette.clone(),
+            file_palette: palette,
+            capability: detect_color_capability(),
+            path: assets::resolve_asset_path(Path::new("themes/system.toml")),
+            modified: None,
+        }
+    }
+
+    /// The theme reloaded from its file if the file changed since it was loaded.
+    pub fn reload_if_changed(theme: &Theme) -> Option<Result<Theme>> {
+        let modified = modified_time(&theme.path);
+        if modified.is_none() || modified == theme.modified {
+            return None;
+        }
+        let entry = ThemeEntry {
+            key: theme.key.clone(),
+            label: theme.label.clone(),
+            path: theme.path.clone(),
+        };
+        Some(Self::load_entry(&entry))
+    }
+}
+
+fn palette_from(t: &ThemeToml) -> Result<ThemePalette, String> {
+    let color = |slot: &str, s: &str| parse_hex(s).ok_or_else(|| format!("{slot}: invalid color \"{s}\" (expected #RRGGBB)"));
+    let optional = |slot: &str, s: &Option<String>, fallback: (u8, u8, u8)| match s {
+        Some(s) => color(slot, s),
+        None => Ok(fallback),
+    };
+
+    let subtext = color("subtext", &t.subtext)?;
+    let accent = color("accent", &t.accent)?;
+    let accent2 = color("accent2", &t.accent2)?;
+    let accent3 = color("accent3", &t.accent3)?;
+    let spectrum = match t.spectrum.as_slice() {
+        [] => vec![accent2, accent3],
+        [_] => return Err("spectrum: needs at least two colors".to_string()),
+        stops => stops.iter().map(|s| color("spectrum", s)).collect::<Result<Vec<_>, _>>()?,
+    };
+    Ok(ThemePalette {
+        text: color("text", &t.text)?,
+        subtext,
+        base: color("base", &t.base)?,
+        surface: color("surface", &t.surface)?,
+        accent,
+        accent2,
+        accent3,
+        spectrum,
+        progress: optional("progress", &t.progress, accent2)?,
+        lyric_highlight: optional("lyric_highlight", &t.lyric_highlight, accent)?,
+        border: optional("border", &t.border, subtext)?,
+        selection: optional("selection", &t.selection, accent)?,
+    })
 }
 
-fn parse_hex(s: &str) -> (u8, u8, u8) {
-    let s = s.trim_start_matches('#');
-    let r = u8::from_str_radix(&s[0..2], 16).unwrap_or(255);
-    let g = u8::from_str_radix(&s[2..4], 16).unwrap_or(255);
-    let b = u8::from_str_radix(&s[4..6], 16).unwrap_or(255);
-    (r, g, b)
+fn modified_time(path: &Path) -> Option<std::time::SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
 }
 
+fn parse_hex(s: &str) -> Option<(u8, u8, u8)> {
+    let s = s.trim().trim_start_matches('#');
+    if s.len() != 6 || !s.is_ascii() {
+        return None;
+    }
+    let r = u8::from_str_radix(&s[0..2], 16).ok()?;
+    let g = u8::from_str_radix(&s[2..4], 16).ok()?;
+    let b = u8::from_str_radix(&s[4..6], 16).ok()?;
+    Some((r, g, b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_theme_parses() {
+        let theme = ThemeLoader::builtin();
+        assert_eq!(theme.key, "system");
+        assert!(theme.palette.spectrum.len() >= 2);
+    }
+}
@@ -0,0 +1,7 @@
+pub mod app;
+pub mod audio;
+pub mod data;
+pub mod playback;
+pub mod render;
+pub mod ui;
+pub mod utils;
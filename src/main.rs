@@ -1,24 +1,38 @@
-mod app;
-mod audio;
-mod data;
-mod playback;
-mod render;
-mod ui;
-mod utils;
-
 use anyhow::Result;
+use cli_music_player::{app, data, utils};
 
 fn main() -> Result<()> {
     env_logger::init();
 
+    // `cli-music-player ctl <cmd>` talks to a running instance and exits without a TUI.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("ctl") {
+        return app::control::run_ctl(&args[1..]);
+    }
+
     // Filter noisy ALSA(libasound) underrun logs from stderr to avoid corrupting the TUI.
     // Keeps other stderr output (including env_logger) intact.
     utils::stderr_filter::install_alsa_stderr_filter();
 
     let config = data::config::Config::load_or_default()?;
-    let theme = data::theme_loader::ThemeLoader::load(&config.theme)?;
+    // A broken theme file shouldn't keep the player from starting.
+    let (theme, theme_error) = match data::theme_loader::ThemeLoader::load(&config.theme) {
+        Ok(theme) => (theme, None),
+        Err(e) => {
+            log::warn!("theme {:?}: {e:#}; using the built-in theme", config.theme);
+            (data::theme_loader::ThemeLoader::builtin(), Some(e))
+        }
+    };
+    // Fail before the TUI starts so keys.toml mistakes are readable.
+    let keymap = data::keymap::Keymap::load()?;
 
     let mut app = app::state::AppState::new(config, theme);
+    // The DA1 probe reads stdin, so it runs here, before the event loop reads keys.
+    app.graphics_protocol = utils::graphics::detect_graphics_protocol(app.config.image_protocol);
+    app.keymap = keymap;
+    if let Some(e) = theme_error {
+        app.set_toast(format!("Theme error: {e:#}"));
+    }
     // Initialize EQ from config (persisted per user).
     app.eq.bands_db = app.config.eq_bands_db;
     app::event_loop::run(&mut app)
@@ -0,0 +1,165 @@
+use crate::data::library::{Library, LibraryTrack};
+use crate::playback::local_player::is_audio;
+use crate::playback::metadata::read_library_tags;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::UNIX_EPOCH;
+
+pub enum LibraryScanEvent {
+    /// Files visited so far / files whose tags had to be (re)read.
+    Progress { seen: usize, updated: usize },
+    Done { library: Library, updated: usize, removed: usize },
+}
+
+/// Background recursive scanner. Files whose mtime and size match the previous index
+/// are reused as-is, so rescans of a large collection only read changed files.
+#[derive(Debug)]
+pub struct LibraryScanner {
+    rx: Receiver<LibraryScanEvent>,
+}
+
+impl LibraryScanner {
+    pub fn start(roots: Vec<PathBuf>, previous: &Library) -> Self {
+        let (tx, rx) = mpsc::channel::<LibraryScanEvent>();
+        let mut old: HashMap<PathBuf, LibraryTrack> =
+            previous.tracks.iter().map(|t| (t.path.clone(), t.clone())).collect();
+
+        thread::spawn(move || {
+            let mut files: Vec<PathBuf> = Vec::new();
+            let mut visited: HashSet<PathBuf> = HashSet::new();
+            for root in &roots {
+                collect_audio_files(root, &mut files, &mut visited);
+            }
+            files.sort();
+            files.dedup();
+
+            let mut tracks: Vec<LibraryTrack> = Vec::with_capacity(files.len());
+            let mut updated = 0usize;
+            for (i, path) in files.iter().enumerate() {
+                let (mtime, size) = file_stamp(path);
+                match old.remove(path) {
+                    Some(t) if t.mtime == mtime && t.size == size => tracks.push(t),
+                    _ => {
+                        let mut t = read_library_tags(path).unwrap_or_else(|e| {
+                            log::info!("library: tags unreadable for {}: {e}", path.display());
+                            LibraryTrack {
+                                path: path.clone(),
+                                ..Default::default()
+                            }
+                        });
+                        t.mtime = mtime;
+                        t.size = size;
+                        tracks.push(t);
+                        updated += 1;
+                    }
+                }
+                if i % 250 == 0 {
+                    let _ = tx.send(LibraryScanEvent::Progress { seen: i, updated });
+                }
+            }
+
+            // Whatever is left in `old` no longer exists under the roots.
+            let removed = old.len();
+            let library = Library { roots, tracks };
+            if updated > 0 || removed > 0 {
+                if let Err(e) = library.save() {
+                    log::warn!("library save failed: {e}");
+                }
+            }
+            let _ = tx.send(LibraryScanEvent::Done { library, updated, removed });
+        });
+
+        Self { rx }
+    }
+
+    pub fn drain(&self) -> Vec<LibraryScanEvent> {
+        self.rx.try_iter().collect()
+    }
+}
+
+pub fn file_stamp(path: &Path) -> (u64, u64) {
+    let Ok(md) = fs::metadata(path) else {
+        return (0, 0);
+    };
+    let mtime = md
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    (mtime, md.len())
+}
+
+fn collect_audio_files(dir: &Path, out: &mut Vec<PathBuf>, visited: &mut HashSet<PathBuf>) {
+    // Guard against symlink loops.
+    let Ok(canon) = fs::canonicalize(dir) else {
+        return;
+    };
+    if !visited.insert(canon) {
+        return;
+    }
+    let Ok(rd) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in rd.flatten() {
+        let p = entry.path();
+        let hidden = p
+            .file_name()
+            .and_then(|s| s.to_str())
+            .map(|s| s.starts_with('.'))
+            .unwrap_or(false);
+        if hidden {
+            continue;
+        }
+        if p.is_dir() {
+            collect_audio_files(&p, out, visited);
+        } else if is_audio(&p) {
+            out.push(p);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rescan_rereads_only_changed_files_and_drops_deleted_ones() {
+        crate::data::assets::isolate_for_tests();
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        let indexed = |name: &str, title: &str| {
+            let path = root.join(name);
+            fs::write(&path, b"not really audio").unwrap();
+            let (mtime, size) = file_stamp(&path);
+            LibraryTrack { path, title: title.to_string(), mtime, size, ..Default::default() }
+        };
+        let same = indexed("same.mp3", "From the index");
+        let mut changed = indexed("changed.mp3", "Old title");
+        changed.size += 1;
+        let gone = LibraryTrack { path: root.join("gone.mp3"), title: "Gone".to_string(), ..Default::default() };
+        fs::write(root.join("new.flac"), b"fresh").unwrap();
+        let previous = Library { roots: vec![root.clone()], tracks: vec![same, changed, gone] };
+
+        let scanner = LibraryScanner::start(vec![root.clone()], &previous);
+        let (library, updated, removed) = loop {
+            if let Some(LibraryScanEvent::Done { library, updated, removed }) =
+                scanner.drain().into_iter().find(|e| matches!(e, LibraryScanEvent::Done { .. }))
+            {
+                break (library, updated, removed);
+            }
+            thread::sleep(std::time::Duration::from_millis(5));
+        };
+
+        // Changed and new files are re-read (their tags are unreadable here, so untitled).
+        assert_eq!((updated, removed), (2, 1));
+        let title = |name: &str| library.tracks.iter().find(|t| t.path == root.join(name)).map(|t| t.title.as_str());
+        assert_eq!(title("same.mp3"), Some("From the index"));
+        assert_eq!(title("changed.mp3"), Some(""));
+        assert_eq!(title("new.flac"), Some(""));
+        assert_eq!(title("gone.mp3"), None);
+    }
+}
@@ -1,15 +1,18 @@
 use crate::app::state::{EQ_BANDS, EQ_FREQS_HZ, EqSettings, LocalFolderKind, PlaybackState, TrackMetadata};
+use crate::data::config::ReplayGainMode;
 use crate::data::playlist::{Playlist, PlaylistItem};
 use crate::playback::metadata::read_metadata;
 use crate::playback::metadata::read_cover_from_folder;
+use crate::playback::loudness::{gain_factor, replay_gain_from_loudness, LoudnessScanner};
+use crate::render::cover_cache::CoverKey;
 use anyhow::{anyhow, Result};
-use rodio::{OutputStream, Sink, Source};
+use rodio::{OutputStream, OutputStreamHandle, Sink, Source};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fs::File;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
-use std::sync::atomic::{AtomicI32, AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU32, AtomicUsize, Ordering};
 use std::time::{Duration, Instant};
 
 use symphonia::core::audio::SampleBuffer;
@@ -47,12 +50,20 @@ impl EqParams {
     fn load_db_x10(&self) -> [i32; EQ_BANDS] {
         std::array::from_fn(|i| self.bands_db_x10[i].load(Ordering::Relaxed))
     }
+
+    fn is_flat(&self) -> bool {
+        self.bands_db_x10.iter().all(|b| b.load(Ordering::Relaxed) == 0)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
 struct OrderFile {
     order: Vec<String>,
 
+    // Tracks cut from the folder's listing (`dd`), keyed like `order`; a rescan leaves them out.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    removed: Vec<String>,
+
     #[serde(default)]
     last_opened_song: Option<String>,
 
@@ -69,49 +80,87 @@ struct OrderFile {
     // Stored here to speed up subsequent loads without re-decoding/resizing images.
     #[serde(default)]
     cover: HashMap<String, String>,
+
+    // Measured EBU R128 loudness for files without ReplayGain tags, keyed like `order`.
+    #[serde(default)]
+    loudness: HashMap<String, LoudnessEntry>,
+
+    // Lyric timing corrections (ms, positive = lyrics earlier), keyed like `order`.
+    #[serde(default)]
+    lyrics_offset: HashMap<String, i64>,
 }
 
-fn cover_key(hash: u64, width: u16, height: u16) -> String {
-    format!("{hash}:{width}x{height}")
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct LoudnessEntry {
+    lufs: f32,
+    peak: f32,
+}
+
+fn cover_key(key: CoverKey) -> String {
+    let base = format!("{}:{}x{}", key.hash, key.width, key.height);
+    match key.style.tag() {
+        Some(tag) => format!("{base}:{tag}"),
+        None => base,
+    }
 }
 
-pub fn read_cover_ascii_cache(folder: &Path, hash: u64, width: u16, height: u16) -> Option<String> {
+pub fn read_cover_ascii_cache(folder: &Path, key: CoverKey) -> Option<String> {
     let p = folder.join(".order.toml");
     if !p.exists() {
         return None;
     }
     let of = read_order_file(folder)?;
-    of.cover.get(&cover_key(hash, width, height)).cloned()
+    of.cover.get(&cover_key(key)).cloned()
 }
 
-pub fn write_cover_ascii_cache(
-    folder: &Path,
-    hash: u64,
-    width: u16,
-    height: u16,
-    ascii: &str,
-) -> Result<bool> {
+pub fn write_cover_ascii_cache(folder: &Path, key: CoverKey, ascii: &str) -> Result<bool> {
     // If the file exists but is unreadable/unparseable, avoid clobbering it.
-    let p = folder.join(".order.toml");
-    let mut of = if p.exists() {
-        match read_order_file(folder) {
-            Some(v) => v,
-            None => return Ok(false),
+    update_order_file(folder, false, |of| {
+        let k = cover_key(key);
+        if of.cover.get(&k).is_some_and(|existing| existing == ascii) {
+            return false;
         }
-    } else {
-        OrderFile::default()
-    };
+        of.cover.insert(k, ascii.to_string());
+        true
+    })
+}
 
-    let k = cover_key(hash, width, height);
-    if let Some(existing) = of.cover.get(&k) {
-        if existing == ascii {
-            return Ok(false);
-        }
-    }
+/// Cached (integrated LUFS, sample peak) for an audio file, if it was scanned before.
+pub fn read_loudness_cache(folder: &Path, song_path: &Path) -> Option<(f32, f32)> {
+    let of = read_order_file(folder)?;
+    of.loudness
+        .get(&order_key(folder, song_path))
+        .map(|e| (e.lufs, e.peak))
+}
 
-    of.cover.insert(k, ascii.to_string());
-    write_order_file_struct(folder, &of)?;
-    Ok(true)
+/// Called from the loudness scanner thread; `update_order_file` keeps it from racing the UI.
+pub fn write_loudness_cache(folder: &Path, song_path: &Path, lufs: f32, peak: f32) -> Result<()> {
+    // If the file exists but is unreadable/unparseable, avoid clobbering it.
+    update_order_file(folder, false, |of| {
+        of.loudness
+            .insert(order_key(folder, song_path), LoudnessEntry { lufs, peak });
+        true
+    })?;
+    Ok(())
+}
+
+pub fn read_lyrics_offset(folder: &Path, song_path: &Path) -> Option<i64> {
+    let of = read_order_file(folder)?;
+    of.lyrics_offset.get(&order_key(folder, song_path)).copied()
+}
+
+/// Store a song's lyric offset; zero removes the entry.
+pub fn write_lyrics_offset(folder: &Path, song_path: &Path, offset_ms: i64) -> Result<()> {
+    // If the file exists but is unreadable/unparseable, avoid clobbering it.
+    update_order_file(folder, false, |of| {
+        let key = order_key(folder, song_path);
+        if offset_ms == 0 {
+            of.lyrics_offset.remove(&key).is_some()
+        } else {
+            of.lyrics_offset.insert(key, offset_ms) != Some(offset_ms)
+        }
+    })?;
+    Ok(())
 }
 
 fn order_key(folder: &Path, path: &Path) -> String {
@@ -127,14 +176,32 @@ fn read_order_file(folder: &Path) -> Option<OrderFile> {
     toml::from_str(&s).ok()
 }
 
+/// Held across every read-modify-write of a `.order.toml`: the UI thread and the loudness
+/// scanner both update them.
+static ORDER_FILE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Read `folder`'s `.order.toml`, let `update` change it and write it back if it returns
+/// true. A file that exists but doesn't parse is only replaced when `clobber` is set.
+fn update_order_file(folder: &Path, clobber: bool, update: impl FnOnce(&mut OrderFile) -> bool) -> Result<bool> {
+    let _guard = ORDER_FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut of = match read_order_file(folder) {
+        Some(of) => of,
+        None if clobber || !folder.join(".order.toml").exists() => OrderFile::default(),
+        None => return Ok(false),
+    };
+    if !update(&mut of) {
+        return Ok(false);
+    }
+    write_order_file_struct(folder, &of)?;
+    Ok(true)
+}
+
 fn write_order_file_struct(folder: &Path, of: &OrderFile) -> Result<()> {
     let content = toml::to_string_pretty(of)?;
 
     let tmp = folder.join(".order.toml.tmp");
     let dst = folder.join(".order.toml");
     std::fs::write(&tmp, content)?;
-    // Best-effort atomic replace.
-    let _ = std::fs::remove_file(&dst);
     std::fs::rename(&tmp, &dst)?;
     Ok(())
 }
@@ -152,7 +219,13 @@ fn apply_order_file(folder: &Path, playlist: &mut Playlist, order: &OrderFile) {
         key_to_index.insert(order_key(folder, &it.path), i);
     }
 
+    // Cut tracks count as placed already, so neither pass below lists them.
     let mut used = vec![false; playlist.items.len()];
+    for k in &order.removed {
+        if let Some(&idx) = key_to_index.get(k) {
+            used[idx] = true;
+        }
+    }
     let mut new_items: Vec<PlaylistItem> = Vec::with_capacity(playlist.items.len());
 
     for k in &order.order {
@@ -171,6 +244,7 @@ fn apply_order_file(folder: &Path, playlist: &mut Playlist, order: &OrderFile) {
     }
 
     playlist.items = new_items;
+    playlist.edited();
 
     // Restore selection/current by path (best-effort).
     if let Some(sp) = selected_path {
@@ -178,42 +252,52 @@ fn apply_order_file(folder: &Path, playlist: &mut Playlist, order: &OrderFile) {
             playlist.selected = i;
         }
     }
-    if let Some(cp) = current_path {
-        if let Some(i) = playlist.items.iter().position(|it| it.path == cp) {
-            playlist.current = Some(i);
-        }
-    }
+    playlist.current = current_path.and_then(|cp| playlist.items.iter().position(|it| it.path == cp));
     playlist.clamp_selected();
 }
 
-pub fn write_order_file(folder: &Path, playlist: &Playlist) -> Result<()> {
-    let mut of = read_order_file(folder).unwrap_or_default();
-    of.order = playlist
-        .items
-        .iter()
-        .map(|it| order_key(folder, &it.path))
-        .collect::<Vec<_>>();
-    write_order_file_struct(folder, &of)
+/// Save the folder's listing. `cut` tracks are remembered as removed so a rescan doesn't
+/// bring them back; a removed track that is listed again (pasted back) is restored.
+pub fn write_order_file(folder: &Path, playlist: &Playlist, cut: &[PlaylistItem]) -> Result<()> {
+    update_order_file(folder, true, |of| {
+        of.order = playlist
+            .items
+            .iter()
+            .map(|it| order_key(folder, &it.path))
+            .collect::<Vec<_>>();
+        of.removed.extend(cut.iter().map(|it| order_key(folder, &it.path)));
+        of.removed.retain(|k| !of.order.contains(k));
+        of.removed.sort();
+        of.removed.dedup();
+        true
+    })?;
+    Ok(())
 }
 
 pub fn write_last_opened_song(folder: &Path, song_path: &Path) -> Result<()> {
-    let mut of = read_order_file(folder).unwrap_or_default();
-    of.last_opened_song = Some(order_key(folder, song_path));
-    write_order_file_struct(folder, &of)
+    update_order_file(folder, true, |of| {
+        of.last_opened_song = Some(order_key(folder, song_path));
+        true
+    })?;
+    Ok(())
 }
 
 pub fn write_last_album(root: &Path, album_folder: &Path) -> Result<()> {
-    let mut of = read_order_file(root).unwrap_or_default();
-    let rel = album_folder.strip_prefix(root).unwrap_or(album_folder);
-    of.last_album = Some(rel.to_string_lossy().replace('\\', "/"));
-    write_order_file_struct(root, &of)
+    update_order_file(root, true, |of| {
+        let rel = album_folder.strip_prefix(root).unwrap_or(album_folder);
+        of.last_album = Some(rel.to_string_lossy().replace('\\', "/"));
+        true
+    })?;
+    Ok(())
 }
 
 pub fn write_last_position(folder: &Path, song_path: &Path, position: Duration) -> Result<()> {
-    let mut of = read_order_file(folder).unwrap_or_default();
-    of.last_position_song = Some(order_key(folder, song_path));
-    of.last_position_sec = Some(position.as_secs());
-    write_order_file_struct(folder, &of)
+    update_order_file(folder, true, |of| {
+        of.last_position_song = Some(order_key(folder, song_path));
+        of.last_position_sec = Some(position.as_secs());
+        true
+    })?;
+    Ok(())
 }
 
 pub fn read_last_position_for_song(folder: &Path, song_path: &Path) -> Option<u64> {
@@ -331,10 +415,44 @@ fn detect_folder_kind(folder: &Path) -> (LocalFolderKind, Vec<PathBuf>) {
     (LocalFolderKind::Plain, Vec::new())
 }
 
+/// Gapless: the next track is appended to the sink this long before the current one ends.
+const PRELOAD_WINDOW: Duration = Duration::from_secs(3);
+
+/// Whether `pos` into a track of length `dur` is within the preload window (the crossfade
+/// length when one is set).
+fn in_preload_window(pos: Duration, dur: Duration, crossfade: Duration) -> bool {
+    if dur.is_zero() {
+        return false;
+    }
+    let window = if crossfade.is_zero() { PRELOAD_WINDOW } else { crossfade };
+    dur.saturating_sub(pos) <= window
+}
+
+/// A track that has been appended to the sink behind the current one (gapless).
+struct QueuedTrack {
+    path: PathBuf,
+    meta: TrackMetadata,
+    ctl: Arc<TrackCtl>,
+}
+
 pub struct LocalPlayer {
     _stream: OutputStream,
+    handle: OutputStreamHandle,
     sink: Sink,
 
+    // gapless / crossfade
+    crossfade: Duration,
+    track_ctl: Arc<TrackCtl>,
+    queued: Option<QueuedTrack>,
+    // previous track fading out on its own sink while the next one fades in
+    fading: Option<Sink>,
+
+    // loudness normalization
+    replay_gain: ReplayGainMode,
+    loudness_scan: bool,
+    scanner: Option<LoudnessScanner>,
+    scan_requested: HashSet<PathBuf>,
+
     current_path: Option<PathBuf>,
     duration: Option<Duration>,
 
@@ -347,6 +465,8 @@ pub struct LocalPlayer {
     base_seek: Duration,
     started_at: Option<Instant>,
     paused_acc: Duration,
+    /// Seeks applied so far (MPRIS announces each one).
+    seeks: u64,
 
     // visualization tap (last ~16384 samples)
     viz_samples: Arc<VizRing>,
@@ -364,7 +484,16 @@ impl LocalPlayer {
         let eq_params = Arc::new(EqParams::new());
         Self {
             _stream,
+            handle,
             sink,
+            crossfade: Duration::from_secs(0),
+            track_ctl: Arc::new(TrackCtl::new()),
+            queued: None,
+            fading: None,
+            replay_gain: ReplayGainMode::Off,
+            loudness_scan: false,
+            scanner: None,
+            scan_requested: HashSet::new(),
             current_path: None,
             duration: None,
             volume: 0.0,
@@ -374,6 +503,7 @@ impl LocalPlayer {
             base_seek: Duration::from_secs(0),
             started_at: None,
             paused_acc: Duration::from_secs(0),
+            seeks: 0,
             viz_samples: Arc::new(VizRing::new(16384)),
 
             meta_cache: HashMap::new(),
@@ -392,7 +522,15 @@ impl LocalPlayer {
             return m.clone();
         }
 
-        let meta = read_metadata(path).unwrap_or_default();
+        let mut meta = read_metadata(path).unwrap_or_default();
+        if meta.replay_gain.is_empty() {
+            if let Some((lufs, peak)) = path.parent().and_then(|folder| read_loudness_cache(folder, path)) {
+                meta.replay_gain = replay_gain_from_loudness(lufs, peak);
+            }
+        }
+        if let Some(ms) = path.parent().and_then(|folder| read_lyrics_offset(folder, path)) {
+            meta.lyrics_offset_ms = ms;
+        }
         let key = path.to_path_buf();
         self.meta_cache.insert(key.clone(), meta.clone());
         self.meta_order.push_back(key);
@@ -406,6 +544,28 @@ impl LocalPlayer {
         meta
     }
 
+    /// Overwrite the cached metadata of `path` (after lyrics were edited in the app).
+    /// Metadata of `path` if it was read before; never touches the file.
+    pub fn peek_cached_metadata(&self, path: &Path) -> Option<&TrackMetadata> {
+        self.meta_cache.get(path)
+    }
+
+    pub fn replace_cached_metadata(&mut self, path: &Path, meta: &TrackMetadata) {
+        if let Some(m) = self.meta_cache.get_mut(path) {
+            *m = meta.clone();
+        }
+    }
+
+    /// Change the cached (and preloaded) metadata of `path` in place, e.g. after a tag edit.
+    pub fn patch_cached_metadata(&mut self, path: &Path, patch: impl Fn(&mut TrackMetadata)) {
+        if let Some(m) = self.meta_cache.get_mut(path) {
+            patch(m);
+        }
+        if let Some(q) = self.queued.as_mut().filter(|q| q.path == path) {
+            patch(&mut q.meta);
+        }
+    }
+
     pub fn update_cached_metadata(&mut self, path: &Path, update: &crate::playback::remote_fetch::RemoteFetchResult) {
         if let Some(m) = self.meta_cache.get_mut(path) {
             update.apply_to(m);
@@ -449,7 +609,7 @@ impl LocalPlayer {
                 .and_then(|s| s.to_str())
                 .unwrap_or("Unknown")
                 .to_string();
-            playlist.items.push(PlaylistItem { path, title });
+            playlist.items.push(PlaylistItem { path, title, missing: false });
         }
 
         // Optional persisted order (local folder only). If it fails to parse, keep default order.
@@ -549,7 +709,7 @@ impl LocalPlayer {
                 .and_then(|s| s.to_str())
                 .unwrap_or("Unknown")
                 .to_string();
-            playlist.items.push(PlaylistItem { path, title });
+            playlist.items.push(PlaylistItem { path, title, missing: false });
         }
 
         if let Some(order) = read_order_file(&p) {
@@ -563,8 +723,90 @@ impl LocalPlayer {
         Ok(playlist)
     }
 
+    pub fn set_crossfade(&mut self, crossfade: Duration) {
+        self.crossfade = crossfade;
+    }
+
+    pub fn set_replay_gain(&mut self, mode: ReplayGainMode, scan: bool) {
+        if mode == self.replay_gain && scan == self.loudness_scan {
+            return;
+        }
+        self.replay_gain = mode;
+        self.loudness_scan = scan;
+
+        // Re-apply to what's already playing/queued.
+        if let Some(path) = self.current_path.clone() {
+            let meta = self.cached_metadata(&path);
+            let g = self.track_gain(&path, &meta);
+            self.track_ctl.set_gain(g);
+        }
+        if let Some(q) = self.queued.as_ref() {
+            q.ctl.set_gain(gain_factor(&q.meta.replay_gain, self.replay_gain));
+        }
+    }
+
+    /// Linear ReplayGain factor for a track; queues a loudness scan for untagged files.
+    fn track_gain(&mut self, path: &Path, meta: &TrackMetadata) -> f32 {
+        if meta.replay_gain.is_empty()
+            && self.loudness_scan
+            && self.replay_gain != ReplayGainMode::Off
+            && self.scan_requested.insert(path.to_path_buf())
+        {
+            self.scanner.get_or_insert_with(LoudnessScanner::start).request(path);
+        }
+        gain_factor(&meta.replay_gain, self.replay_gain)
+    }
+
+    /// Apply finished background loudness scans.
+    pub fn poll_loudness(&mut self) {
+        let Some(scanner) = self.scanner.as_ref() else {
+            return;
+        };
+        for res in scanner.drain() {
+            if let Some(m) = self.meta_cache.get_mut(&res.path) {
+                m.replay_gain = res.replay_gain;
+            }
+            let g = gain_factor(&res.replay_gain, self.replay_gain);
+            if self.current_path.as_deref() == Some(res.path.as_path()) {
+                self.track_ctl.set_gain(g);
+            }
+            if let Some(q) = self.queued.as_mut().filter(|q| q.path == res.path) {
+                q.meta.replay_gain = res.replay_gain;
+                q.ctl.set_gain(g);
+            }
+        }
+    }
+
+    /// Build the decode -> EQ -> gain/limiter -> fade -> visualization tap chain for one track.
+    fn open_track(
+        &self,
+        path: &Path,
+        start: Duration,
+        duration: Option<Duration>,
+        fade_in: Duration,
+        ctl: &Arc<TrackCtl>,
+    ) -> Result<TapSource<FadeSource<GainSource<EqSource<SymphoniaSource>>>>> {
+        let src = SymphoniaSource::open(path, start, duration)?;
+        // ensure params reflect current state
+        self.eq_params.set_from(self.eq);
+        let eqd = EqSource::new(src, Arc::clone(&self.eq_params));
+        // The limiter sits after the EQ so band boosts can't push the gained signal into clipping.
+        let gained = GainSource::new(eqd, Arc::clone(ctl), Arc::clone(&self.eq_params));
+        let faded = FadeSource::new(gained, fade_in, Arc::clone(ctl));
+        Ok(TapSource::new(faded, Arc::clone(&self.viz_samples), Arc::clone(ctl)))
+    }
+
+    /// Drop any preloaded/fading track (manual skip, seek, folder change).
+    fn reset_transition(&mut self) {
+        self.queued = None;
+        if let Some(old) = self.fading.take() {
+            old.stop();
+        }
+    }
+
     pub fn play_file(&mut self, path: &Path) -> Result<TrackMetadata> {
         // stop current (avoid blocking rebuilds; keep the sink and just clear sources)
+        self.reset_transition();
         self.sink.clear();
 
         // metadata
@@ -581,16 +823,120 @@ impl LocalPlayer {
         self.sink.set_volume(self.volume);
 
         self.viz_samples.clear();
-        let src = SymphoniaSource::open(path, Duration::from_secs(0), Some(meta.duration))?;
-        // ensure params reflect current state
-        self.eq_params.set_from(self.eq);
-        let eqd = EqSource::new(src, Arc::clone(&self.eq_params));
-        let tapped = TapSource::new(eqd, Arc::clone(&self.viz_samples));
+        self.track_ctl = Arc::new(TrackCtl::new());
+        let gain = self.track_gain(path, &meta);
+        self.track_ctl.set_gain(gain);
+        let tapped = self.open_track(path, Duration::from_secs(0), Some(meta.duration), Duration::from_secs(0), &self.track_ctl)?;
         self.sink.append(tapped);
         self.sink.play();
         Ok(meta)
     }
 
+    /// True when the current track is close enough to its end that the next one should be
+    /// handed to `preload_next` (and nothing has been preloaded yet).
+    pub fn wants_preload(&self) -> bool {
+        if self.queued.is_some() || self.started_at.is_none() || self.sink.empty() {
+            return false;
+        }
+        let (Some(pos), Some(dur)) = (self.position(), self.duration) else {
+            return false;
+        };
+        in_preload_window(pos, dur, self.crossfade)
+    }
+
+    /// Pre-open the next track.
+    ///
+    /// Without crossfade the track is appended to the sink and starts gaplessly once the
+    /// current one drains (reported later by `poll_advance`). With crossfade it starts
+    /// immediately on a second sink and the metadata is returned right away.
+    pub fn preload_next(&mut self, path: &Path) -> Result<Option<TrackMetadata>> {
+        let meta = self.cached_metadata(path);
+        let remaining = match (self.position(), self.duration) {
+            (Some(pos), Some(dur)) => dur.saturating_sub(pos),
+            _ => Duration::from_secs(0),
+        };
+
+        if self.crossfade.is_zero() {
+            let ctl = Arc::new(TrackCtl::new());
+            ctl.set_gain(self.track_gain(path, &meta));
+            // stay silent in the visualizer until it actually starts playing
+            ctl.tap.store(false, Ordering::Relaxed);
+            let src = self.open_track(path, Duration::from_secs(0), Some(meta.duration), Duration::from_secs(0), &ctl)?;
+            self.sink.append(src);
+            self.queued = Some(QueuedTrack {
+                path: path.to_path_buf(),
+                meta,
+                ctl,
+            });
+            return Ok(None);
+        }
+
+        // Crossfade: fade the current track out over what's left of it, and fade the
+        // next one in over the same span (bounded so very short tracks still work).
+        let fade = remaining.min(self.crossfade).min(meta.duration / 2);
+        let ctl = Arc::new(TrackCtl::new());
+        ctl.set_gain(self.track_gain(path, &meta));
+        let src = self.open_track(path, Duration::from_secs(0), Some(meta.duration), fade, &ctl)?;
+        let sink = Sink::try_new(&self.handle)?;
+        sink.set_volume(self.volume);
+        sink.append(src);
+        sink.play();
+
+        self.track_ctl.begin_fade_out(fade);
+        if let Some(old) = self.fading.take() {
+            old.stop();
+        }
+        self.fading = Some(std::mem::replace(&mut self.sink, sink));
+        self.track_ctl = ctl;
+        self.viz_samples.clear();
+
+        self.start_track(path, &meta);
+        Ok(Some(meta))
+    }
+
+    /// Path of the gapless successor waiting in the sink, if any.
+    pub fn preloaded_path(&self) -> Option<&Path> {
+        self.queued.as_ref().map(|q| q.path.as_path())
+    }
+
+    /// Forget the gapless successor (what comes next changed since it was preloaded). It stays
+    /// in the sink but ends as soon as it is reached, and `wants_preload` asks again.
+    pub fn drop_preloaded(&mut self) {
+        if let Some(q) = self.queued.take() {
+            q.ctl.dropped.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Reports a gapless transition into the preloaded track (once).
+    pub fn poll_advance(&mut self) -> Option<(PathBuf, TrackMetadata)> {
+        // finished crossfade tail
+        if self.fading.as_ref().map(|s| s.empty()).unwrap_or(false) {
+            self.fading = None;
+        }
+
+        if self.queued.is_none() || self.sink.len() > 1 {
+            return None;
+        }
+        let q = self.queued.take()?;
+        if self.sink.empty() {
+            // queued source failed/ended immediately; let poll_end handle it
+            return None;
+        }
+        q.ctl.tap.store(true, Ordering::Relaxed);
+        self.track_ctl = q.ctl;
+        self.viz_samples.clear();
+        self.start_track(&q.path, &q.meta);
+        Some((q.path, q.meta))
+    }
+
+    fn start_track(&mut self, path: &Path, meta: &TrackMetadata) {
+        self.current_path = Some(path.to_path_buf());
+        self.duration = Some(meta.duration);
+        self.base_seek = Duration::from_secs(0);
+        self.paused_acc = Duration::from_secs(0);
+        self.started_at = if self.sink.is_paused() { None } else { Some(Instant::now()) };
+    }
+
     pub fn pause(&mut self) -> Result<()> {
         if self.started_at.is_some() {
             // paused_acc is accumulated time *after* base_seek.
@@ -601,12 +947,29 @@ impl LocalPlayer {
             self.started_at = None;
         }
         self.sink.pause();
+        if let Some(old) = self.fading.as_ref() {
+            old.pause();
+        }
         Ok(())
     }
 
+    /// Stop playback and rewind to the start; the track stays loaded so play restarts it.
+    pub fn stop(&mut self) {
+        self.reset_transition();
+        self.sink.clear();
+        self.sink.pause();
+        self.viz_samples.clear();
+        self.base_seek = Duration::from_secs(0);
+        self.paused_acc = Duration::from_secs(0);
+        self.started_at = None;
+    }
+
     pub fn toggle_play_pause(&mut self) -> Result<()> {
         if self.sink.is_paused() {
             self.sink.play();
+            if let Some(old) = self.fading.as_ref() {
+                old.play();
+            }
             self.started_at = Some(Instant::now());
         } else {
             self.pause()?;
@@ -617,6 +980,9 @@ impl LocalPlayer {
     pub fn set_volume(&mut self, v: f32) {
         self.volume = v.clamp(0.0, 1.0);
         self.sink.set_volume(self.volume);
+        if let Some(old) = self.fading.as_ref() {
+            old.set_volume(self.volume);
+        }
     }
 
     pub fn volume(&self) -> f32 {
@@ -665,7 +1031,8 @@ impl LocalPlayer {
         // and the sink becomes empty OR we reached the known duration.
         if self.started_at.is_some() {
             let mut finished = self.sink.empty();
-            if !finished {
+            // With a gapless successor queued, the sink itself tells us when the track ends.
+            if !finished && self.queued.is_none() {
                 if let Some(dur) = self.duration {
                     // Some formats may not flip sink.empty reliably; use duration as fallback.
                     if dur > Duration::from_millis(0) {
@@ -698,6 +1065,19 @@ impl LocalPlayer {
         self.duration
     }
 
+    /// Seek relative to the current position (works the same while paused).
+    /// Clamped so a long forward jump lands just before the end instead of skipping the track.
+    pub fn seek_by(&mut self, delta_ms: i64) -> Result<Duration> {
+        let cur = self.position().unwrap_or_default().as_millis() as i64;
+        let mut target = (cur + delta_ms).max(0) as u64;
+        if let Some(dur) = self.duration {
+            target = target.min((dur.as_millis() as u64).saturating_sub(1000));
+        }
+        let target = Duration::from_millis(target);
+        self.seek(target)?;
+        Ok(target)
+    }
+
     pub fn seek(&mut self, pos: Duration) -> Result<()> {
         let Some(path) = self.current_path.clone() else {
             return Ok(());
@@ -706,14 +1086,16 @@ impl LocalPlayer {
         let was_paused = self.sink.is_paused();
 
         // Replace source without rebuilding the output sink (prevents UI stalls on some systems).
+        self.reset_transition();
         self.sink.clear();
         self.sink.set_volume(self.volume);
 
         self.viz_samples.clear();
-        let src = SymphoniaSource::open(&path, pos, self.duration)?;
-        self.eq_params.set_from(self.eq);
-        let eqd = EqSource::new(src, Arc::clone(&self.eq_params));
-        let tapped = TapSource::new(eqd, Arc::clone(&self.viz_samples));
+        self.track_ctl = Arc::new(TrackCtl::new());
+        let meta = self.cached_metadata(&path);
+        let gain = self.track_gain(&path, &meta);
+        self.track_ctl.set_gain(gain);
+        let tapped = self.open_track(&path, pos, self.duration, Duration::from_secs(0), &self.track_ctl)?;
         self.sink.append(tapped);
 
         if was_paused {
@@ -725,12 +1107,22 @@ impl LocalPlayer {
         self.base_seek = pos;
         self.paused_acc = Duration::from_secs(0);
         self.started_at = if was_paused { None } else { Some(Instant::now()) };
+        self.seeks += 1;
         Ok(())
     }
 
+    pub fn seeks(&self) -> u64 {
+        self.seeks
+    }
+
     pub fn latest_samples(&self, n: usize) -> Vec<f32> {
         self.viz_samples.latest_samples(n)
     }
+
+    /// (channels, sample_rate) of the samples returned by `latest_samples`.
+    pub fn latest_samples_format(&self) -> (u16, u32) {
+        self.viz_samples.format()
+    }
 }
 
 /// Lock-free fixed-size ring buffer for visualization samples.
@@ -742,6 +1134,9 @@ struct VizRing {
     cap: usize,
     write_idx: AtomicUsize,
     data: Vec<AtomicU32>,
+    // Interleaving of the samples currently being written (set by TapSource).
+    channels: AtomicU32,
+    sample_rate: AtomicU32,
 }
 
 impl VizRing {
@@ -751,7 +1146,25 @@ impl VizRing {
         for _ in 0..cap {
             data.push(AtomicU32::new(0));
         }
-        Self { cap, write_idx: AtomicUsize::new(0), data }
+        Self {
+            cap,
+            write_idx: AtomicUsize::new(0),
+            data,
+            channels: AtomicU32::new(2),
+            sample_rate: AtomicU32::new(44_100),
+        }
+    }
+
+    fn set_format(&self, channels: u16, sample_rate: u32) {
+        self.channels.store(channels.max(1) as u32, Ordering::Relaxed);
+        self.sample_rate.store(sample_rate, Ordering::Relaxed);
+    }
+
+    fn format(&self) -> (u16, u32) {
+        (
+            self.channels.load(Ordering::Relaxed) as u16,
+            self.sample_rate.load(Ordering::Relaxed),
+        )
     }
 
     fn clear(&self) {
@@ -764,13 +1177,18 @@ impl VizRing {
         self.data[pos].store(s.to_bits(), Ordering::Relaxed);
     }
 
+    /// The last `n` samples, as whole frames: a frame still being written is left out, so
+    /// the first sample is always the first channel.
     fn latest_samples(&self, n: usize) -> Vec<f32> {
+        let ch = self.channels.load(Ordering::Relaxed).max(1) as usize;
         let end = self.write_idx.load(Ordering::Relaxed);
+        let end = end - end % ch;
         if end == 0 {
             return Vec::new();
         }
 
         let n = n.min(self.cap).min(end);
+        let n = n - n % ch;
         let start = end - n;
 
         let mut out = Vec::with_capacity(n);
@@ -783,7 +1201,7 @@ impl VizRing {
     }
 }
 
-struct SymphoniaSource {
+pub(crate) struct SymphoniaSource {
     format: Box<dyn symphonia::core::formats::FormatReader>,
     decoder: Box<dyn symphonia::core::codecs::Decoder>,
     track_id: u32,
@@ -797,7 +1215,7 @@ struct SymphoniaSource {
 }
 
 impl SymphoniaSource {
-    fn open(path: &Path, start: Duration, total_duration: Option<Duration>) -> Result<Self> {
+    pub(crate) fn open(path: &Path, start: Duration, total_duration: Option<Duration>) -> Result<Self> {
         let file = Box::new(File::open(path)?);
         let mss = MediaSourceStream::new(file, Default::default());
         let hint = Hint::new();
@@ -915,20 +1333,226 @@ impl Source for SymphoniaSource {
     }
 }
 
+/// Per-track control shared between the player and the audio thread.
+struct TrackCtl {
+    // > 0 once a fade-out was requested (crossfade into the next track)
+    fade_out_ms: AtomicU32,
+    // whether this track feeds the visualization ring
+    tap: AtomicBool,
+    // ReplayGain factor (f32 bits)
+    gain: AtomicU32,
+    // set when a preloaded track is no longer next; its source ends as soon as it is reached
+    dropped: AtomicBool,
+}
+
+impl TrackCtl {
+    fn new() -> Self {
+        Self {
+            fade_out_ms: AtomicU32::new(0),
+            tap: AtomicBool::new(true),
+            gain: AtomicU32::new(1.0f32.to_bits()),
+            dropped: AtomicBool::new(false),
+        }
+    }
+
+    fn set_gain(&self, g: f32) {
+        self.gain.store(g.to_bits(), Ordering::Relaxed);
+    }
+
+    fn begin_fade_out(&self, fade: Duration) {
+        self.tap.store(false, Ordering::Relaxed);
+        self.fade_out_ms
+            .store((fade.as_millis() as u32).max(1), Ordering::Relaxed);
+    }
+}
+
+/// Output ceiling of the limiter (about -0.1 dBFS).
+const LIMITER_CEILING: f32 = 0.989;
+/// Time the limiter takes to recover from full reduction.
+const LIMITER_RELEASE: Duration = Duration::from_millis(200);
+
+/// ReplayGain stage: scales samples by the per-track factor in `TrackCtl`, then limits peaks
+/// (instant attack, linear release) so gain plus EQ boost never clip. With unity gain and a
+/// flat EQ nothing is boosted, so the samples pass through untouched.
+struct GainSource<S>
+where
+    S: Source<Item = f32>,
+{
+    inner: S,
+    ctl: Arc<TrackCtl>,
+    eq: Arc<EqParams>,
+    /// Current limiter gain, 1.0 when idle.
+    reduction: f32,
+    release_step: f32,
+}
+
+impl<S> GainSource<S>
+where
+    S: Source<Item = f32>,
+{
+    fn new(inner: S, ctl: Arc<TrackCtl>, eq: Arc<EqParams>) -> Self {
+        let release_samples = duration_to_samples(&inner, LIMITER_RELEASE).max(1);
+        Self {
+            inner,
+            ctl,
+            eq,
+            reduction: 1.0,
+            release_step: 1.0 / release_samples as f32,
+        }
+    }
+}
+
+impl<S> Iterator for GainSource<S>
+where
+    S: Source<Item = f32>,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let gain = f32::from_bits(self.ctl.gain.load(Ordering::Relaxed));
+        let s = self.inner.next()? * gain;
+        // Bypassed only once a past reduction has fully released.
+        if gain == 1.0 && self.reduction >= 1.0 && self.eq.is_flat() {
+            return Some(s);
+        }
+        let peak = s.abs();
+        self.reduction = (self.reduction + self.release_step).min(1.0);
+        if peak * self.reduction > LIMITER_CEILING {
+            self.reduction = LIMITER_CEILING / peak;
+        }
+        Some(s * self.reduction)
+    }
+}
+
+impl<S> Source for GainSource<S>
+where
+    S: Source<Item = f32>,
+{
+    fn current_frame_len(&self) -> Option<usize> {
+        self.inner.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+}
+
+/// Linear fade-in at the start and (on request) fade-out that ends the source.
+struct FadeSource<S>
+where
+    S: Source<Item = f32>,
+{
+    inner: S,
+    ctl: Arc<TrackCtl>,
+    pos: u64,
+    fade_in_len: u64,
+    fade_out_len: u64,
+    fade_out_pos: u64,
+}
+
+impl<S> FadeSource<S>
+where
+    S: Source<Item = f32>,
+{
+    fn new(inner: S, fade_in: Duration, ctl: Arc<TrackCtl>) -> Self {
+        let fade_in_len = duration_to_samples(&inner, fade_in);
+        Self {
+            inner,
+            ctl,
+            pos: 0,
+            fade_in_len,
+            fade_out_len: 0,
+            fade_out_pos: 0,
+        }
+    }
+}
+
+fn duration_to_samples<S: Source<Item = f32>>(src: &S, d: Duration) -> u64 {
+    let per_sec = src.sample_rate() as u64 * src.channels().max(1) as u64;
+    (d.as_millis() as u64).saturating_mul(per_sec) / 1000
+}
+
+impl<S> Iterator for FadeSource<S>
+where
+    S: Source<Item = f32>,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.fade_out_len == 0 {
+            let ms = self.ctl.fade_out_ms.load(Ordering::Relaxed);
+            if ms > 0 {
+                self.fade_out_len = duration_to_samples(&self.inner, Duration::from_millis(ms as u64)).max(1);
+            }
+        }
+        if self.fade_out_len > 0 && self.fade_out_pos >= self.fade_out_len {
+            return None;
+        }
+
+        let s = self.inner.next()?;
+        let mut gain = 1.0f32;
+        if self.pos < self.fade_in_len {
+            gain *= self.pos as f32 / self.fade_in_len as f32;
+        }
+        if self.fade_out_len > 0 {
+            gain *= 1.0 - self.fade_out_pos as f32 / self.fade_out_len as f32;
+            self.fade_out_pos += 1;
+        }
+        self.pos += 1;
+        Some(s * gain)
+    }
+}
+
+impl<S> Source for FadeSource<S>
+where
+    S: Source<Item = f32>,
+{
+    fn current_frame_len(&self) -> Option<usize> {
+        self.inner.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+}
+
 struct TapSource<S>
 where
     S: Source<Item = f32>,
 {
     inner: S,
     buf: Arc<VizRing>,
+    ctl: Arc<TrackCtl>,
+    format_set: bool,
 }
 
 impl<S> TapSource<S>
 where
     S: Source<Item = f32>,
 {
-    fn new(inner: S, buf: Arc<VizRing>) -> Self {
-        Self { inner, buf }
+    fn new(inner: S, buf: Arc<VizRing>, ctl: Arc<TrackCtl>) -> Self {
+        Self {
+            inner,
+            buf,
+            ctl,
+            format_set: false,
+        }
     }
 }
 
@@ -939,8 +1563,17 @@ where
     type Item = f32;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if self.ctl.dropped.load(Ordering::Relaxed) {
+            return None;
+        }
         let s = self.inner.next()?;
-        self.buf.push(s);
+        if self.ctl.tap.load(Ordering::Relaxed) {
+            if !self.format_set {
+                self.buf.set_format(self.inner.channels(), self.inner.sample_rate());
+                self.format_set = true;
+            }
+            self.buf.push(s);
+        }
         Some(s)
     }
 }
@@ -1111,7 +1744,7 @@ where
     }
 }
 
-fn is_audio(p: &Path) -> bool {
+pub fn is_audio(p: &Path) -> bool {
     let Some(ext) = p.extension().and_then(|s| s.to_str()) else {
         return false;
     };
@@ -1120,3 +1753,191 @@ fn is_audio(p: &Path) -> bool {
         "mp3" | "flac" | "wav" | "ogg" | "aac" | "m4a"
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scanned(folder: &Path, names: &[&str]) -> Playlist {
+        Playlist::new(
+            names
+                .iter()
+                .map(|n| PlaylistItem {
+                    path: folder.join(n),
+                    title: n.to_string(),
+                    missing: false,
+                })
+                .collect(),
+        )
+    }
+
+    fn reload(folder: &Path, names: &[&str]) -> Vec<String> {
+        let mut pl = scanned(folder, names);
+        apply_order_file(folder, &mut pl, &read_order_file(folder).unwrap());
+        pl.items.into_iter().map(|it| it.title).collect()
+    }
+
+    #[test]
+    fn cut_tracks_stay_out_of_the_folder_listing() {
+        let dir = tempfile::tempdir().unwrap();
+        let folder = dir.path();
+        let all = ["a.mp3", "b.mp3", "c.mp3", "d.mp3"];
+
+        let mut pl = scanned(folder, &all);
+        let cut = pl.remove_range(1..2);
+        write_order_file(folder, &pl, &cut).unwrap();
+        // A rescan lists every file again; the cut one stays out, a new one is appended.
+        assert_eq!(reload(folder, &["a.mp3", "b.mp3", "c.mp3", "d.mp3", "e.mp3"]), ["a.mp3", "c.mp3", "d.mp3", "e.mp3"]);
+
+        // Pasting it back restores it.
+        pl.insert_at(0, cut);
+        write_order_file(folder, &pl, &[]).unwrap();
+        assert_eq!(reload(folder, &all), ["b.mp3", "a.mp3", "c.mp3", "d.mp3"]);
+        assert!(read_order_file(folder).unwrap().removed.is_empty());
+    }
+
+    #[test]
+    fn applying_the_order_drops_current_when_its_track_was_cut() {
+        let dir = tempfile::tempdir().unwrap();
+        let folder = dir.path();
+        let mut pl = scanned(folder, &["a.mp3", "b.mp3"]);
+        let cut = pl.remove_range(0..1);
+        write_order_file(folder, &pl, &cut).unwrap();
+
+        let mut fresh = scanned(folder, &["a.mp3", "b.mp3"]);
+        fresh.current = Some(0);
+        apply_order_file(folder, &mut fresh, &read_order_file(folder).unwrap());
+        assert_eq!(fresh.current, None);
+        assert_eq!(fresh.items.len(), 1);
+    }
+
+    #[test]
+    fn order_file_writers_from_two_threads_keep_each_others_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let folder = dir.path().to_path_buf();
+        let songs: Vec<PathBuf> = (0..40).map(|i| folder.join(format!("{i}.flac"))).collect();
+        let scanner = {
+            let (folder, songs) = (folder.clone(), songs.clone());
+            std::thread::spawn(move || {
+                for s in &songs {
+                    write_loudness_cache(&folder, s, -14.0, 0.9).unwrap();
+                }
+            })
+        };
+        for s in &songs {
+            write_last_position(&folder, s, Duration::from_secs(7)).unwrap();
+        }
+        scanner.join().unwrap();
+
+        let of = read_order_file(&folder).unwrap();
+        assert_eq!(of.loudness.len(), songs.len());
+        assert_eq!(of.last_position_song.as_deref(), Some("39.flac"));
+    }
+
+    #[test]
+    fn viz_ring_returns_whole_frames() {
+        let ring = VizRing::new(7);
+        ring.set_format(2, 48_000);
+        for i in 0..11 {
+            ring.push(i as f32);
+        }
+        // Sample 10 starts a frame that isn't finished; 7 rounds down to 6.
+        assert_eq!(ring.latest_samples(7), [4.0, 5.0, 6.0, 7.0, 8.0, 9.0]);
+        assert_eq!(ring.latest_samples(3), [8.0, 9.0]);
+        ring.clear();
+        ring.push(1.0);
+        assert!(ring.latest_samples(4).is_empty());
+    }
+
+    #[test]
+    fn limiter_keeps_boosted_peaks_under_full_scale() {
+        let ctl = Arc::new(TrackCtl::new());
+        ctl.set_gain(2.0);
+        let input: Vec<f32> = (0..4800).map(|i| if i % 2 == 0 { 0.8 } else { -0.8 }).collect();
+        let src = rodio::buffer::SamplesBuffer::new(2, 48_000, input);
+        let out: Vec<f32> = GainSource::new(src, ctl, Arc::new(EqParams::new())).collect();
+        assert!(out.iter().all(|s| s.abs() <= LIMITER_CEILING + 1e-6));
+        assert!(out.last().unwrap().abs() > 0.9);
+    }
+
+    #[test]
+    fn limiter_is_bypassed_without_gain_or_eq_boost() {
+        let hot = || rodio::buffer::SamplesBuffer::new(2, 48_000, vec![0.999f32, -0.999, 0.5, -0.5]);
+        let eq = Arc::new(EqParams::new());
+        let out: Vec<f32> = GainSource::new(hot(), Arc::new(TrackCtl::new()), Arc::clone(&eq)).collect();
+        assert_eq!(out, [0.999, -0.999, 0.5, -0.5]);
+
+        // Any EQ band moved brings the limiter back.
+        let mut boosted = EqSettings::default();
+        boosted.bands_db[0] = 3.0;
+        eq.set_from(boosted);
+        let out: Vec<f32> = GainSource::new(hot(), Arc::new(TrackCtl::new()), eq).collect();
+        assert!(out.iter().all(|s| s.abs() <= LIMITER_CEILING + 1e-6));
+    }
+
+    #[test]
+    fn dropped_preload_ends_when_reached() {
+        let ctl = Arc::new(TrackCtl::new());
+        let input = rodio::buffer::SamplesBuffer::new(2, 48_000, vec![0.5f32; 96]);
+        let mut src = TapSource::new(input, Arc::new(VizRing::new(64)), Arc::clone(&ctl));
+        assert_eq!(src.by_ref().take(4).count(), 4);
+        ctl.dropped.store(true, Ordering::Relaxed);
+        assert_eq!(src.next(), None);
+    }
+
+    // A constant 1.0 at 1 kHz mono: one sample per millisecond.
+    fn constant(ms: usize) -> rodio::buffer::SamplesBuffer<f32> {
+        rodio::buffer::SamplesBuffer::new(1, 1000, vec![1.0f32; ms])
+    }
+
+    #[test]
+    fn fade_in_ramps_up_from_silence() {
+        let src = FadeSource::new(constant(20), Duration::from_millis(10), Arc::new(TrackCtl::new()));
+        let out: Vec<f32> = src.collect();
+        assert_eq!(out.len(), 20);
+        for (i, s) in out[..10].iter().enumerate() {
+            assert!((s - i as f32 / 10.0).abs() < 1e-6);
+        }
+        assert!(out[10..].iter().all(|s| *s == 1.0));
+    }
+
+    #[test]
+    fn fade_out_ends_the_source_after_the_crossfade() {
+        let ctl = Arc::new(TrackCtl::new());
+        let mut src = FadeSource::new(constant(1000), Duration::ZERO, Arc::clone(&ctl));
+        assert_eq!(src.by_ref().take(100).count(), 100);
+
+        ctl.begin_fade_out(Duration::from_millis(50));
+        let out: Vec<f32> = src.collect();
+        // 50 ms of the 900 left, falling from full level towards silence.
+        assert_eq!(out.len(), 50);
+        assert_eq!(out[0], 1.0);
+        assert!(out.windows(2).all(|w| w[1] < w[0]));
+        assert!(out[49] <= 1.0 / 50.0 + 1e-6);
+
+        // The length follows the channels and rate of the track.
+        let ctl = Arc::new(TrackCtl::new());
+        let stereo = rodio::buffer::SamplesBuffer::new(2, 48_000, vec![0.5f32; 96_000]);
+        let src = FadeSource::new(stereo, Duration::ZERO, Arc::clone(&ctl));
+        ctl.begin_fade_out(Duration::from_millis(250));
+        assert_eq!(src.count(), 24_000);
+    }
+
+    #[test]
+    fn preload_starts_only_inside_the_window() {
+        let dur = Duration::from_secs(200);
+        let at = |s: f32| Duration::from_secs_f32(s);
+        assert!(!in_preload_window(at(196.9), dur, Duration::ZERO));
+        assert!(in_preload_window(dur - PRELOAD_WINDOW, dur, Duration::ZERO));
+        assert!(in_preload_window(at(199.5), dur, Duration::ZERO));
+        assert!(in_preload_window(at(250.0), dur, Duration::ZERO));
+
+        // A crossfade replaces the window.
+        let crossfade = Duration::from_secs(8);
+        assert!(!in_preload_window(at(191.9), dur, crossfade));
+        assert!(in_preload_window(at(192.0), dur, crossfade));
+
+        // Unknown length: never.
+        assert!(!in_preload_window(Duration::ZERO, Duration::ZERO, Duration::ZERO));
+    }
+}
@@ -0,0 +1,325 @@
+// Persistent cache of remote lookups, keyed by `TrackKey`.
+// Hits (lyrics text, cover, identification) and misses are stored with the time they
+// were checked, so restarts don't re-ask every provider and cached songs work offline.
+// `<asset root>/lookup_cache/index.json` plus one file per cover (by hash) under `covers/`.
+// The index is written at most every `SAVE_INTERVAL` while lookups come in, and once more
+// when they stop (`flush`).
+
+use crate::data::assets;
+use crate::data::config::ProviderKind;
+use crate::playback::provider::Identification;
+use crate::playback::remote_fetch::TrackKey;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const CACHE_VERSION: u32 = 1;
+
+const DAY_SECS: u64 = 24 * 60 * 60;
+
+/// Least time between two index writes while lookups keep changing it.
+pub const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long hits and misses stay valid, in days. A miss TTL of 0 keeps no misses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheTtl {
+    pub hit_days: u32,
+    pub miss_days: u32,
+}
+
+/// One lookup: when it was made and what it found (None = a miss).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checked<T> {
+    pub at: u64,
+    pub found: Option<T>,
+}
+
+impl<T> Checked<T> {
+    pub fn now(found: Option<T>) -> Self {
+        Self { at: unix_now(), found }
+    }
+
+    /// The cached answer while it is still valid.
+    pub fn fresh(&self, ttl: CacheTtl, now: u64) -> Option<Option<&T>> {
+        is_fresh(self.at, self.found.is_some(), ttl, now).then_some(self.found.as_ref())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedLyrics {
+    pub provider: ProviderKind,
+    /// LRC or plain text, as the provider returned it.
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedCover {
+    pub hash: u64,
+    pub content_type: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LookupEntry {
+    pub key: TrackKey,
+    #[serde(default)]
+    pub identity: Option<Checked<Identification>>,
+    #[serde(default)]
+    pub lyrics: Option<Checked<CachedLyrics>>,
+    #[serde(default)]
+    pub cover: Option<Checked<CachedCover>>,
+}
+
+impl LookupEntry {
+    pub fn new(key: TrackKey) -> Self {
+        Self { key, identity: None, lyrics: None, cover: None }
+    }
+
+    fn parts(&self) -> [Option<(u64, bool)>; 3] {
+        [
+            self.identity.as_ref().map(|c| (c.at, c.found.is_some())),
+            self.lyrics.as_ref().map(|c| (c.at, c.found.is_some())),
+            self.cover.as_ref().map(|c| (c.at, c.found.is_some())),
+        ]
+    }
+}
+
+/// Shown in the Local Audio settings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LookupCacheStats {
+    pub songs: usize,
+    pub hits: usize,
+    pub misses: usize,
+    pub cover_bytes: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheFile {
+    #[serde(default)]
+    version: u32,
+    #[serde(default)]
+    entries: Vec<LookupEntry>,
+}
+
+fn is_fresh(at: u64, hit: bool, ttl: CacheTtl, now: u64) -> bool {
+    let days = if hit { ttl.hit_days } else { ttl.miss_days };
+    now.saturating_sub(at) < days as u64 * DAY_SECS
+}
+
+#[derive(Debug, Default)]
+pub struct LookupCache {
+    entries: HashMap<TrackKey, LookupEntry>,
+    cover_bytes: u64,
+    /// Hit and miss counts over all entries, kept up to date by `put`.
+    hits: usize,
+    misses: usize,
+    /// Changes not written to the index yet.
+    dirty: bool,
+    last_save: Option<Instant>,
+}
+
+impl LookupCache {
+    /// `<asset root>/lookup_cache`
+    pub fn default_dir() -> PathBuf {
+        assets::resolve_asset_path(Path::new("lookup_cache"))
+    }
+
+    fn covers_dir() -> PathBuf {
+        Self::default_dir().join("covers")
+    }
+
+    fn cover_file_name(hash: u64) -> String {
+        format!("{hash:016x}")
+    }
+
+    fn cover_path(hash: u64) -> PathBuf {
+        Self::covers_dir().join(Self::cover_file_name(hash))
+    }
+
+    /// Load the index, dropping entries whose every lookup has expired and cover files
+    /// that no fresh entry refers to any more.
+    pub fn load(ttl: CacheTtl) -> Self {
+        let Ok(raw) = fs::read(Self::default_dir().join("index.json")) else {
+            return Self::default();
+        };
+        let Ok(file) = serde_json::from_slice::<CacheFile>(&raw) else {
+            return Self::default();
+        };
+        if file.version != CACHE_VERSION {
+            return Self::default();
+        }
+        let now = unix_now();
+        let mut cache = Self::default();
+        for e in file.entries {
+            if e.parts().into_iter().flatten().any(|(at, hit)| is_fresh(at, hit, ttl, now)) {
+                cache.put(e);
+            }
+        }
+        cache.dirty = false;
+
+        let live: HashSet<String> = cache
+            .entries
+            .values()
+            .filter_map(|e| e.cover.as_ref().and_then(|c| c.fresh(ttl, now)).flatten())
+            .map(|c| Self::cover_file_name(c.hash))
+            .collect();
+        if let Ok(rd) = fs::read_dir(Self::covers_dir()) {
+            for e in rd.flatten() {
+                if live.contains(e.file_name().to_string_lossy().as_ref()) {
+                    cache.cover_bytes += e.metadata().map(|m| m.len()).unwrap_or(0);
+                } else {
+                    let _ = fs::remove_file(e.path());
+                }
+            }
+        }
+        cache
+    }
+
+    /// Write the index now (if anything changed since the last write).
+    pub fn flush(&mut self) -> Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        let dir = Self::default_dir();
+        fs::create_dir_all(&dir)?;
+        let path = dir.join("index.json");
+        let file = CacheFile {
+            version: CACHE_VERSION,
+            entries: self.entries.values().cloned().collect(),
+        };
+        // Pretty-printed so it can be inspected by hand.
+        let data = serde_json::to_vec_pretty(&file)?;
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, data).with_context(|| format!("write {}", tmp.display()))?;
+        fs::rename(&tmp, &path)?;
+        self.dirty = false;
+        self.last_save = Some(Instant::now());
+        Ok(())
+    }
+
+    /// `flush`, unless the index was written less than `SAVE_INTERVAL` ago.
+    pub fn save_if_due(&mut self) -> Result<()> {
+        if self.last_save.is_some_and(|at| at.elapsed() < SAVE_INTERVAL) {
+            return Ok(());
+        }
+        self.flush()
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    pub fn get(&self, key: &TrackKey) -> Option<&LookupEntry> {
+        self.entries.get(key)
+    }
+
+    pub fn put(&mut self, entry: LookupEntry) {
+        self.tally(&entry, true);
+        if let Some(old) = self.entries.insert(entry.key.clone(), entry) {
+            self.tally(&old, false);
+        }
+        self.dirty = true;
+    }
+
+    fn tally(&mut self, entry: &LookupEntry, add: bool) {
+        for (_, hit) in entry.parts().into_iter().flatten() {
+            let n = if hit { &mut self.hits } else { &mut self.misses };
+            *n = if add { *n + 1 } else { n.saturating_sub(1) };
+        }
+    }
+
+    pub fn read_cover(&self, cover: &CachedCover) -> Option<Vec<u8>> {
+        fs::read(Self::cover_path(cover.hash)).ok().filter(|b| !b.is_empty())
+    }
+
+    /// Store cover bytes (shared by every song with the same cover).
+    pub fn write_cover(&mut self, hash: u64, bytes: &[u8]) -> Result<()> {
+        let path = Self::cover_path(hash);
+        if path.is_file() {
+            return Ok(());
+        }
+        fs::create_dir_all(Self::covers_dir())?;
+        fs::write(&path, bytes)?;
+        self.cover_bytes += bytes.len() as u64;
+        Ok(())
+    }
+
+    pub fn stats(&self) -> LookupCacheStats {
+        LookupCacheStats {
+            songs: self.entries.len(),
+            hits: self.hits,
+            misses: self.misses,
+            cover_bytes: self.cover_bytes,
+        }
+    }
+
+    /// Forget everything, on disk too.
+    pub fn purge(&mut self) -> Result<()> {
+        self.entries.clear();
+        self.cover_bytes = 0;
+        (self.hits, self.misses) = (0, 0);
+        self.dirty = false;
+        let dir = Self::default_dir();
+        if dir.exists() {
+            fs::remove_dir_all(&dir).with_context(|| format!("remove {}", dir.display()))?;
+        }
+        Ok(())
+    }
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(title: &str) -> TrackKey {
+        TrackKey {
+            path: None,
+            title: title.into(),
+            artist: "Band".into(),
+            album: "Record".into(),
+            duration_secs: 200,
+        }
+    }
+
+    fn with_cover(title: &str, hash: u64, at: u64) -> LookupEntry {
+        LookupEntry {
+            cover: Some(Checked { at, found: Some(CachedCover { hash, content_type: None }) }),
+            ..LookupEntry::new(key(title))
+        }
+    }
+
+    #[test]
+    fn load_drops_covers_of_expired_entries_and_keeps_stats() {
+        crate::data::assets::isolate_for_tests();
+        let ttl = CacheTtl { hit_days: 30, miss_days: 7 };
+        let now = unix_now();
+
+        let mut cache = LookupCache::default();
+        cache.write_cover(1, b"fresh").unwrap();
+        cache.write_cover(2, b"stale").unwrap();
+        cache.put(with_cover("a", 1, now));
+        cache.put(with_cover("b", 2, now - 40 * DAY_SECS));
+        cache.put(LookupEntry {
+            lyrics: Some(Checked { at: now, found: None }),
+            ..LookupEntry::new(key("c"))
+        });
+        // Replacing an entry doesn't count it twice.
+        cache.put(with_cover("a", 1, now));
+        assert_eq!((cache.stats().hits, cache.stats().misses), (2, 1));
+        cache.flush().unwrap();
+        assert!(!cache.is_dirty());
+
+        let loaded = LookupCache::load(ttl);
+        let stats = loaded.stats();
+        assert_eq!((stats.songs, stats.hits, stats.misses), (2, 1, 1));
+        assert_eq!(stats.cover_bytes, 5);
+        assert!(LookupCache::cover_path(1).is_file());
+        assert!(!LookupCache::cover_path(2).exists());
+    }
+}
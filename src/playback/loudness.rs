@@ -0,0 +1,261 @@
+use crate::app::state::ReplayGain;
+use crate::data::config::ReplayGainMode;
+use crate::playback::local_player::{write_loudness_cache, SymphoniaSource};
+use anyhow::{anyhow, Result};
+use rodio::Source;
+use std::f64::consts::PI;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+/// ReplayGain 2.0 reference loudness.
+pub const REFERENCE_LUFS: f32 = -18.0;
+
+/// Linear gain to apply for `mode`, limited so the tagged peak never exceeds full scale.
+pub fn gain_factor(rg: &ReplayGain, mode: ReplayGainMode) -> f32 {
+    let (gain_db, peak) = match mode {
+        ReplayGainMode::Off => return 1.0,
+        ReplayGainMode::Track => (
+            rg.track_gain_db.or(rg.album_gain_db),
+            if rg.track_gain_db.is_some() { rg.track_peak } else { rg.album_peak },
+        ),
+        ReplayGainMode::Album => (
+            rg.album_gain_db.or(rg.track_gain_db),
+            if rg.album_gain_db.is_some() { rg.album_peak } else { rg.track_peak },
+        ),
+    };
+
+    let Some(gain_db) = gain_db else {
+        return 1.0;
+    };
+    let mut g = 10f32.powf(gain_db.clamp(-30.0, 20.0) / 20.0);
+    if let Some(peak) = peak.filter(|p| *p > 0.0) {
+        // clipping protection
+        g = g.min(1.0 / peak);
+    }
+    g
+}
+
+/// ReplayGain values derived from a measured integrated loudness.
+pub fn replay_gain_from_loudness(lufs: f32, peak: f32) -> ReplayGain {
+    ReplayGain {
+        track_gain_db: Some(REFERENCE_LUFS - lufs),
+        track_peak: Some(peak),
+        album_gain_db: None,
+        album_peak: None,
+    }
+}
+
+/// Measure EBU R128 integrated loudness (LUFS) and sample peak of a file.
+pub fn measure_r128(path: &Path) -> Result<(f32, f32)> {
+    let mut src = SymphoniaSource::open(path, Duration::from_secs(0), None)?;
+    let channels = src.channels().max(1) as usize;
+    let fs = src.sample_rate() as f64;
+
+    let mut filters: Vec<KWeighting> = (0..channels).map(|_| KWeighting::new(fs)).collect();
+
+    // 100ms sub-blocks; a 400ms gating block is 4 consecutive sub-blocks (75% overlap).
+    let sub_len = (fs / 10.0).round().max(1.0) as usize;
+    let mut sub_energies: Vec<f64> = Vec::new();
+    let mut acc = 0.0f64;
+    let mut frames_in_sub = 0usize;
+    let mut peak = 0.0f32;
+
+    let mut ch = 0usize;
+    for s in src.by_ref() {
+        peak = peak.max(s.abs());
+        let y = filters[ch].process(s as f64);
+        acc += y * y;
+        ch += 1;
+        if ch == channels {
+            ch = 0;
+            frames_in_sub += 1;
+            if frames_in_sub == sub_len {
+                sub_energies.push(acc / sub_len as f64);
+                acc = 0.0;
+                frames_in_sub = 0;
+            }
+        }
+    }
+
+    let blocks: Vec<f64> = sub_energies
+        .windows(4)
+        .map(|w| w.iter().sum::<f64>() / 4.0)
+        .collect();
+    if blocks.is_empty() {
+        return Err(anyhow!("track too short to measure"));
+    }
+
+    let loudness = |e: f64| -0.691 + 10.0 * e.max(1e-12).log10();
+
+    // absolute gate at -70 LUFS, then relative gate 10 LU below the gated mean
+    let abs_gated: Vec<f64> = blocks.iter().copied().filter(|e| loudness(*e) > -70.0).collect();
+    if abs_gated.is_empty() {
+        return Err(anyhow!("track is silent"));
+    }
+    let rel_threshold = loudness(abs_gated.iter().sum::<f64>() / abs_gated.len() as f64) - 10.0;
+    let rel_gated: Vec<f64> = abs_gated.into_iter().filter(|e| loudness(*e) > rel_threshold).collect();
+    if rel_gated.is_empty() {
+        return Err(anyhow!("track is silent"));
+    }
+
+    let integrated = loudness(rel_gated.iter().sum::<f64>() / rel_gated.len() as f64);
+    Ok((integrated as f32, peak))
+}
+
+/// BS.1770 K-weighting: high shelf followed by a high-pass (coefficients for any sample rate).
+struct KWeighting {
+    shelf: Biquad,
+    highpass: Biquad,
+}
+
+impl KWeighting {
+    fn new(fs: f64) -> Self {
+        let f0 = 1681.974450955533;
+        let g = 3.999843853973347;
+        let q = 0.7071752369554196;
+        let k = (PI * f0 / fs).tan();
+        let vh = 10f64.powf(g / 20.0);
+        let vb = vh.powf(0.4996667741545416);
+        let a0 = 1.0 + k / q + k * k;
+        let shelf = Biquad {
+            b: [
+                (vh + vb * k / q + k * k) / a0,
+                2.0 * (k * k - vh) / a0,
+                (vh - vb * k / q + k * k) / a0,
+            ],
+            a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+            z: [0.0; 2],
+        };
+
+        let f0 = 38.13547087602444;
+        let q = 0.5003270373238773;
+        let k = (PI * f0 / fs).tan();
+        let a0 = 1.0 + k / q + k * k;
+        let highpass = Biquad {
+            b: [1.0, -2.0, 1.0],
+            a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+            z: [0.0; 2],
+        };
+
+        Self { shelf, highpass }
+    }
+
+    fn process(&mut self, x: f64) -> f64 {
+        self.highpass.process(self.shelf.process(x))
+    }
+}
+
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    z: [f64; 2],
+}
+
+impl Biquad {
+    // transposed direct form II
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.z[0];
+        self.z[0] = self.b[1] * x - self.a[0] * y + self.z[1];
+        self.z[1] = self.b[2] * x - self.a[1] * y;
+        y
+    }
+}
+
+pub struct LoudnessResult {
+    pub path: PathBuf,
+    pub replay_gain: ReplayGain,
+}
+
+/// Background R128 scanner for untagged files. Results are cached in the folder's `.order.toml`.
+pub struct LoudnessScanner {
+    tx: Sender<PathBuf>,
+    rx: Receiver<LoudnessResult>,
+}
+
+impl LoudnessScanner {
+    pub fn start() -> Self {
+        let (req_tx, req_rx) = mpsc::channel::<PathBuf>();
+        let (res_tx, res_rx) = mpsc::channel::<LoudnessResult>();
+
+        thread::spawn(move || {
+            while let Ok(path) = req_rx.recv() {
+                let (lufs, peak) = match measure_r128(&path) {
+                    Ok(v) => v,
+                    Err(e) => {
+                        log::info!("loudness scan failed for {}: {e}", path.display());
+                        continue;
+                    }
+                };
+                if let Some(folder) = path.parent() {
+                    let _ = write_loudness_cache(folder, &path, lufs, peak);
+                }
+                let replay_gain = replay_gain_from_loudness(lufs, peak);
+                if res_tx.send(LoudnessResult { path, replay_gain }).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self { tx: req_tx, rx: res_rx }
+    }
+
+    pub fn request(&self, path: &Path) {
+        let _ = self.tx.send(path.to_path_buf());
+    }
+
+    pub fn drain(&self) -> Vec<LoudnessResult> {
+        self.rx.try_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn gain_is_limited_by_the_peak_of_the_chosen_mode() {
+        let rg = ReplayGain {
+            track_gain_db: Some(6.0),
+            track_peak: Some(0.8),
+            album_gain_db: Some(-6.0),
+            album_peak: Some(0.99),
+        };
+        assert_eq!(gain_factor(&rg, ReplayGainMode::Off), 1.0);
+        // +6 dB would be ~2.0; the 0.8 track peak caps it at 1.25.
+        assert!(close(gain_factor(&rg, ReplayGainMode::Track), 1.25));
+        assert!(close(gain_factor(&rg, ReplayGainMode::Album), 10f32.powf(-6.0 / 20.0)));
+
+        // Album peak limits album gain; a quiet track peak doesn't.
+        let rg = ReplayGain { album_gain_db: Some(3.0), album_peak: Some(0.9), ..rg };
+        assert!(close(gain_factor(&rg, ReplayGainMode::Album), 1.0 / 0.9));
+    }
+
+    #[test]
+    fn missing_gain_falls_back_to_the_other_mode_with_its_peak() {
+        let track_only = ReplayGain {
+            track_gain_db: Some(6.0),
+            track_peak: Some(0.8),
+            ..ReplayGain::default()
+        };
+        assert!(close(gain_factor(&track_only, ReplayGainMode::Album), 1.25));
+
+        let album_only = ReplayGain {
+            album_gain_db: Some(20.0),
+            album_peak: Some(0.5),
+            track_peak: Some(0.1),
+            ..ReplayGain::default()
+        };
+        assert!(close(gain_factor(&album_only, ReplayGainMode::Track), 2.0));
+
+        // No peak tag: gain as tagged, clamped to +20 dB.
+        let unpeaked = ReplayGain { track_gain_db: Some(40.0), ..ReplayGain::default() };
+        assert!(close(gain_factor(&unpeaked, ReplayGainMode::Track), 10.0));
+        assert_eq!(gain_factor(&ReplayGain::default(), ReplayGainMode::Track), 1.0);
+    }
+}
@@ -1,10 +1,11 @@
-use crate::app::state::{LyricLine, TrackMetadata};
-use anyhow::Result;
+use crate::app::state::{LyricLine, LyricTrack, LyricTrackKind, LyricWord, ReplayGain, TrackMetadata};
+use crate::data::library::LibraryTrack;
+use anyhow::{anyhow, Result};
 use lofty::{Accessor, AudioFile, ItemKey, Tag, TaggedFileExt};
 use std::collections::hash_map::DefaultHasher;
 use std::fs;
 use std::hash::{Hash, Hasher};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 pub fn read_metadata(path: &Path) -> Result<TrackMetadata> {
     let mut meta = TrackMetadata::default();
@@ -26,6 +27,9 @@ pub fn read_metadata(path: &Path) -> Result<TrackMetadata> {
 
     }
 
+    // Primary tag first, then any other tag present in the file.
+    meta.replay_gain = read_replay_gain(tagged.primary_tag().into_iter().chain(tagged.tags()));
+
     // Embedded cover (prefer any embedded picture across all tags; best-effort)
     if meta.cover.is_none() {
         if let Some((bytes, hash)) = read_embedded_cover(&tagged) {
@@ -61,12 +65,78 @@ pub fn read_metadata(path: &Path) -> Result<TrackMetadata> {
         }
     }
 
-    // Embedded lyrics first; fallback to local .lrc.
-    meta.lyrics = read_embedded_lyrics(&tagged).or_else(|| read_lrc_for_audio(path));
+    // Embedded lyrics first; fallback to local .lrc. `<stem>.<lang>.lrc` side files add to either.
+    let local = read_lrc_for_audio(path);
+    meta.lyrics = match read_embedded_lyrics(&tagged) {
+        Some(mut tracks) => {
+            tracks.extend(local.into_iter().filter(|t| t.kind != LyricTrackKind::Original));
+            tracks
+        }
+        None => local,
+    };
 
     Ok(meta)
 }
 
+/// Tags needed for the library index only (no covers/lyrics, so scanning stays cheap).
+pub fn read_library_tags(path: &Path) -> Result<LibraryTrack> {
+    let tagged = lofty::read_from_path(path)?;
+    let mut t = LibraryTrack {
+        path: path.to_path_buf(),
+        duration_ms: tagged.properties().duration().as_millis() as u64,
+        ..Default::default()
+    };
+
+    if let Some(tag) = tagged.primary_tag().or_else(|| tagged.first_tag()) {
+        t.title = tag.title().map(|s| s.to_string()).unwrap_or_default();
+        t.artist = tag.artist().map(|s| s.to_string()).unwrap_or_default();
+        t.album = tag.album().map(|s| s.to_string()).unwrap_or_default();
+        t.genre = tag.genre().map(|s| s.to_string()).unwrap_or_default();
+        t.album_artist = tag.get_string(&ItemKey::AlbumArtist).unwrap_or_default().to_string();
+        t.year = tag.year().filter(|y| *y > 0);
+        t.track_no = tag.track();
+        t.disc_no = tag.disk();
+    }
+    Ok(t)
+}
+
+/// ReplayGain values from `tags`; the first tag that has a value wins.
+fn read_replay_gain<'a>(tags: impl IntoIterator<Item = &'a Tag>) -> ReplayGain {
+    let mut rg = ReplayGain::default();
+    for tag in tags {
+        let get = |key: &ItemKey| tag.get_string(key).and_then(parse_gain_value);
+        rg.track_gain_db = rg.track_gain_db.or_else(|| get(&ItemKey::ReplayGainTrackGain));
+        rg.track_peak = rg.track_peak.or_else(|| get(&ItemKey::ReplayGainTrackPeak));
+        rg.album_gain_db = rg.album_gain_db.or_else(|| get(&ItemKey::ReplayGainAlbumGain));
+        rg.album_peak = rg.album_peak.or_else(|| get(&ItemKey::ReplayGainAlbumPeak));
+
+        // Opus/Vorbis R128 tags: Q7.8 fixed point relative to -23 LUFS.
+        // ReplayGain references -18 LUFS, hence the +5 dB.
+        let r128 = |name: &str| {
+            tag.get_string(&ItemKey::Unknown(name.to_string()))
+                .and_then(|v| v.trim().parse::<i32>().ok())
+                .map(|q| q as f32 / 256.0 + 5.0)
+        };
+        rg.track_gain_db = rg.track_gain_db.or_else(|| r128("R128_TRACK_GAIN"));
+        rg.album_gain_db = rg.album_gain_db.or_else(|| r128("R128_ALBUM_GAIN"));
+    }
+
+    rg
+}
+
+/// Parses values like "-6.54 dB", "+1.2dB" or "0.988553".
+fn parse_gain_value(v: &str) -> Option<f32> {
+    let v = v.trim();
+    let v = v
+        .strip_suffix("dB")
+        .or_else(|| v.strip_suffix("db"))
+        .or_else(|| v.strip_suffix("DB"))
+        .unwrap_or(v)
+        .trim();
+    let v = v.strip_prefix('+').unwrap_or(v);
+    v.parse::<f32>().ok().filter(|x| x.is_finite())
+}
+
 fn read_embedded_cover(tagged: &lofty::TaggedFile) -> Option<(Vec<u8>, u64)> {
     // Try primary tag first, then other tags.
     if let Some(t) = tagged.primary_tag() {
@@ -119,7 +189,7 @@ pub fn read_cover_from_folder(dir: &Path) -> Option<(Vec<u8>, u64)> {
     None
 }
 
-fn read_cover_for_audio(audio_path: &Path) -> Option<(Vec<u8>, u64)> {
+pub fn read_cover_for_audio(audio_path: &Path) -> Option<(Vec<u8>, u64)> {
     let Some(folder) = audio_path.parent() else {
         return None;
     };
@@ -140,65 +210,166 @@ fn read_cover_for_audio(audio_path: &Path) -> Option<(Vec<u8>, u64)> {
     None
 }
 
-fn read_embedded_lyrics(tagged: &lofty::TaggedFile) -> Option<Vec<LyricLine>> {
+fn read_embedded_lyrics(tagged: &lofty::TaggedFile) -> Option<Vec<LyricTrack>> {
     // Try primary tag first, then other tags.
     if let Some(t) = tagged.primary_tag() {
-        if let Some(lines) = read_lyrics_from_tag(t) {
-            return Some(lines);
+        if let Some(tracks) = read_lyrics_from_tag(t) {
+            return Some(tracks);
         }
     }
     for t in tagged.tags() {
-        if let Some(lines) = read_lyrics_from_tag(t) {
-            return Some(lines);
+        if let Some(tracks) = read_lyrics_from_tag(t) {
+            return Some(tracks);
         }
     }
     None
 }
 
-fn read_lyrics_from_tag(tag: &Tag) -> Option<Vec<LyricLine>> {
+fn read_lyrics_from_tag(tag: &Tag) -> Option<Vec<LyricTrack>> {
     let raw = tag.get_string(&ItemKey::Lyrics)?.trim();
     if raw.is_empty() {
         return None;
     }
+    parse_lyric_tracks(raw)
+}
 
-    // If it's LRC-like, parse timestamps.
-    if let Some(parsed) = parse_lrc(raw) {
-        return Some(parsed);
+/// Local lyrics: `<stem>.lrc` (or `lrc/<stem>.lrc`, or plain `<stem>.txt`) as the original, plus
+/// `<stem>.<lang>.lrc` side files in either place as translations / romanizations.
+fn read_lrc_for_audio(audio_path: &Path) -> Vec<LyricTrack> {
+    let mut out: Vec<LyricTrack> = read_lyrics_text_for_audio(audio_path)
+        .and_then(|content| parse_lyric_tracks(&content))
+        .unwrap_or_default();
+    let folder = audio_path.parent().unwrap_or(Path::new("."));
+    let stem = audio_path.file_stem().and_then(|s| s.to_str());
+
+    let Some(stem) = stem else {
+        return out;
+    };
+    let prefix = format!("{stem}.");
+    let mut side: Vec<(String, std::path::PathBuf)> = Vec::new();
+    for dir in [folder.to_path_buf(), folder.join("lrc")] {
+        let Ok(rd) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for e in rd.flatten() {
+            let name = e.file_name().to_string_lossy().to_string();
+            let Some(base) = name.strip_suffix(".lrc").or_else(|| name.strip_suffix(".LRC")) else {
+                continue;
+            };
+            let Some(tag) = base.strip_prefix(&prefix) else {
+                continue;
+            };
+            if tag.is_empty() || tag.contains('.') || side.iter().any(|(t, _)| t == tag) {
+                continue;
+            }
+            side.push((tag.to_string(), e.path()));
+        }
     }
+    side.sort();
 
-    // Otherwise treat it as unsynchronized lyrics: show first 1-2 lines statically.
-    let mut non_empty = raw.lines().map(str::trim).filter(|l| !l.is_empty());
-    let first = non_empty.next()?.to_string();
-    let second = non_empty.next().map(|s| s.to_string());
-
-    let mut out = Vec::new();
-    out.push(LyricLine { start_ms: 0, text: first });
-    if let Some(s2) = second {
-        out.push(LyricLine { start_ms: u64::MAX, text: s2 });
+    for (tag, p) in side {
+        let Ok(content) = fs::read_to_string(&p) else {
+            continue;
+        };
+        let Some(first) = parse_lyric_tracks(&content).and_then(|t| t.into_iter().next()) else {
+            continue;
+        };
+        let kind = if is_romanization_tag(&tag) {
+            LyricTrackKind::Romanization
+        } else {
+            LyricTrackKind::Translation
+        };
+        out.push(LyricTrack {
+            kind,
+            lang: Some(tag),
+            lines: first.lines,
+            timing: first.timing,
+        });
     }
-    Some(out)
+    out
 }
 
-fn read_lrc_for_audio(audio_path: &Path) -> Option<Vec<LyricLine>> {
+/// Text of the song's own lyrics file: `<stem>.lrc`, `lrc/<stem>.lrc`, then `<stem>.txt`.
+pub fn read_lyrics_text_for_audio(audio_path: &Path) -> Option<String> {
+    let folder = audio_path.parent().unwrap_or(Path::new("."));
     let mut candidates = Vec::new();
     candidates.push(audio_path.with_extension("lrc"));
-
-    if let (Some(folder), Some(stem)) = (audio_path.parent(), audio_path.file_stem().and_then(|s| s.to_str())) {
+    if let Some(stem) = audio_path.file_stem().and_then(|s| s.to_str()) {
         candidates.push(folder.join("lrc").join(format!("{stem}.lrc")));
     }
+    // Plain-text lyrics (unsynced) only when there is no LRC.
+    candidates.push(audio_path.with_extension("txt"));
+
+    candidates
+        .into_iter()
+        .filter_map(|p| fs::read_to_string(p).ok())
+        .find(|content| parse_lyric_tracks(content).is_some())
+}
+
+fn is_romanization_tag(tag: &str) -> bool {
+    let t = tag.to_ascii_lowercase();
+    matches!(t.as_str(), "romaji" | "roma" | "rom" | "romanized" | "romanization" | "pinyin" | "jyutping")
+        || t.ends_with("-latn")
+}
+
+/// Lyrics from LRC (or plain) text. LRC files that repeat most timestamps carry
+/// extra layers (original, then translation, then romanization) and are split into tracks.
+pub fn parse_lyric_tracks(content: &str) -> Option<Vec<LyricTrack>> {
+    let Some(lines) = parse_lrc(content) else {
+        let lines = parse_plain_lyrics(content)?;
+        return Some(vec![LyricTrack::unsynced(LyricTrackKind::Original, lines)]);
+    };
+    Some(split_lyric_layers(lines))
+}
 
-    for p in candidates {
-        if let Ok(content) = fs::read_to_string(&p) {
-            if let Some(lines) = parse_lrc(&content).or_else(|| parse_plain_lyrics(&content)) {
-                return Some(lines);
+fn split_lyric_layers(lines: Vec<LyricLine>) -> Vec<LyricTrack> {
+    const MAX_LAYERS: usize = 3;
+
+    let mut layers: Vec<Vec<LyricLine>> = Vec::new();
+    let (mut groups, mut paired) = (0usize, 0usize);
+    let mut i = 0;
+    while i < lines.len() {
+        let start = lines[i].start_ms;
+        let j = i + lines[i..].iter().take_while(|l| l.start_ms == start).count();
+        groups += 1;
+        if j - i > 1 {
+            paired += 1;
+        }
+        for (k, l) in lines[i..j].iter().take(MAX_LAYERS).enumerate() {
+            if layers.len() <= k {
+                layers.push(Vec::new());
+            }
+            if k == 0 || !l.text.trim().is_empty() {
+                layers[k].push(l.clone());
             }
         }
+        i = j;
     }
-    None
+
+    // A few duplicate timestamps are just repeated lines, not a second language.
+    if layers.len() < 2 || paired * 2 < groups {
+        return vec![LyricTrack::new(LyricTrackKind::Original, lines)];
+    }
+
+    layers
+        .into_iter()
+        .enumerate()
+        .filter(|(_, l)| !l.is_empty())
+        .map(|(k, l)| {
+            let kind = match k {
+                0 => LyricTrackKind::Original,
+                1 => LyricTrackKind::Translation,
+                _ => LyricTrackKind::Romanization,
+            };
+            LyricTrack::new(kind, l)
+        })
+        .collect()
 }
 
 pub fn parse_lrc(content: &str) -> Option<Vec<LyricLine>> {
     let mut out: Vec<LyricLine> = Vec::new();
+    // `[offset:+/-ms]`: positive values make lyrics show up earlier.
+    let mut offset_ms: i64 = 0;
 
     for raw in content.lines() {
         let mut s = raw.trim();
@@ -215,6 +386,8 @@ pub fn parse_lrc(content: &str) -> Option<Vec<LyricLine>> {
             let tag = &rest[..end];
             if let Some(ms) = parse_lrc_time_tag(tag) {
                 times.push(ms);
+            } else if let Some(v) = tag.trim().strip_prefix("offset:") {
+                offset_ms = v.trim().parse().unwrap_or(offset_ms);
             }
             s = &rest[end + 1..];
         }
@@ -223,11 +396,21 @@ pub fn parse_lrc(content: &str) -> Option<Vec<LyricLine>> {
             continue;
         }
 
-        let text = s.trim().to_string();
+        let (text, words) = parse_word_tags(s);
+        // Word tags are absolute; a line repeated under several time tags shifts them along.
+        let base = times.iter().copied().min().unwrap_or(0);
         for t in times {
+            let words = words
+                .iter()
+                .map(|w| LyricWord {
+                    start_ms: (w.start_ms.max(base) - base) + t,
+                    text: w.text.clone(),
+                })
+                .collect();
             out.push(LyricLine {
                 start_ms: t,
                 text: text.clone(),
+                words,
             });
         }
     }
@@ -235,21 +418,148 @@ pub fn parse_lrc(content: &str) -> Option<Vec<LyricLine>> {
     if out.is_empty() {
         return None;
     }
+    if offset_ms != 0 {
+        let shift = |t: u64| (t as i64 - offset_ms).max(0) as u64;
+        for l in &mut out {
+            l.start_ms = shift(l.start_ms);
+            for w in &mut l.words {
+                w.start_ms = shift(w.start_ms);
+            }
+        }
+    }
     out.sort_by_key(|l| l.start_ms);
     Some(out)
 }
 
+/// `mm:ss.xx`, as used in LRC time tags.
+pub fn format_lrc_time(ms: u64) -> String {
+    format!("{:02}:{:02}.{:02}", ms / 60_000, (ms / 1000) % 60, (ms % 1000) / 10)
+}
+
+/// A complete LRC document (ID tags + one time-tagged row per line). Extra `layers`
+/// (translation, then romanization) repeat the original's time tags, the way
+/// `parse_lyric_tracks` reads them back.
+pub fn format_lrc(meta: &TrackMetadata, layers: &[Vec<LyricLine>]) -> String {
+    let mut out = String::new();
+    for (tag, v) in [("ti", &meta.title), ("ar", &meta.artist), ("al", &meta.album)] {
+        if !v.trim().is_empty() && v != "Unknown" {
+            out.push_str(&format!("[{tag}:{}]\n", v.trim()));
+        }
+    }
+    if !meta.duration.is_zero() {
+        let secs = meta.duration.as_secs();
+        out.push_str(&format!("[length:{:02}:{:02}]\n", secs / 60, secs % 60));
+    }
+    let mut rows: Vec<(u64, usize, &str)> = layers
+        .iter()
+        .enumerate()
+        .flat_map(|(k, lines)| lines.iter().map(move |l| (l.start_ms, k, l.text.as_str())))
+        .collect();
+    // Stable: lines of one layer keep their order under the same time tag.
+    rows.sort_by_key(|&(start, k, _)| (start, k));
+    for (start, _, text) in rows {
+        out.push_str(&format!("[{}]{}\n", format_lrc_time(start), text));
+    }
+    out
+}
+
+/// Write LRC text for an audio file: over `<stem>.lrc` if it exists, otherwise into
+/// `lrc/<stem>.lrc` (where downloaded lyrics go). Returns the path written.
+pub fn write_lrc_for_audio(audio_path: &Path, content: &str) -> Result<PathBuf> {
+    let folder = audio_path.parent().unwrap_or(Path::new("."));
+    let stem = audio_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| anyhow!("no file name"))?;
+    let beside = audio_path.with_extension("lrc");
+    let p = if beside.exists() {
+        beside
+    } else {
+        let dir = folder.join("lrc");
+        fs::create_dir_all(&dir)?;
+        dir.join(format!("{stem}.lrc"))
+    };
+    fs::write(&p, content.as_bytes())?;
+    Ok(p)
+}
+
+/// Rewrite an existing `<stem>.<lang>.lrc` side file (beside the song or in `lrc/`).
+/// Returns None when the song has no side file for `lang`.
+pub fn write_side_lrc(audio_path: &Path, lang: &str, content: &str) -> Option<Result<PathBuf>> {
+    let folder = audio_path.parent().unwrap_or(Path::new("."));
+    let name = format!("{}.{lang}.lrc", audio_path.file_stem()?.to_str()?);
+    let p = [folder.join(&name), folder.join("lrc").join(&name)].into_iter().find(|p| p.is_file())?;
+    Some(fs::write(&p, content.as_bytes()).map(|_| p).map_err(Into::into))
+}
+
+/// Plain (untimed) lyrics, kept whole. Runs of blank lines collapse into one empty line so
+/// stanza breaks survive.
 pub fn parse_plain_lyrics(content: &str) -> Option<Vec<LyricLine>> {
-    let mut non_empty = content.lines().map(str::trim).filter(|l| !l.is_empty());
-    let first = non_empty.next()?.to_string();
-    let second = non_empty.next().map(|s| s.to_string());
+    let mut out: Vec<LyricLine> = Vec::new();
+    for l in content.lines().map(str::trim) {
+        if l.is_empty() && out.last().is_none_or(|p| p.text.is_empty()) {
+            continue;
+        }
+        out.push(LyricLine { start_ms: 0, text: l.to_string(), words: Vec::new() });
+    }
+    while out.last().is_some_and(|l| l.text.is_empty()) {
+        out.pop();
+    }
+    if out.is_empty() {
+        None
+    } else {
+        Some(out)
+    }
+}
 
-    let mut out = Vec::new();
-    out.push(LyricLine { start_ms: 0, text: first });
-    if let Some(s2) = second {
-        out.push(LyricLine { start_ms: u64::MAX, text: s2 });
+/// Split enhanced-LRC text (`<00:12.30>word <00:12.80>word`) into plain text and timed words.
+/// Text without word tags yields no words.
+fn parse_word_tags(s: &str) -> (String, Vec<LyricWord>) {
+    let mut words: Vec<LyricWord> = Vec::new();
+    let mut lead = String::new();
+    let mut rest = s;
+    while let Some(open) = rest.find('<') {
+        let Some(close) = rest[open..].find('>').map(|c| open + c) else {
+            break;
+        };
+        let before = &rest[..open];
+        match words.last_mut() {
+            Some(w) => w.text.push_str(before),
+            None => lead.push_str(before),
+        }
+        match parse_lrc_time_tag(&rest[open + 1..close]) {
+            Some(ms) => words.push(LyricWord { start_ms: ms, text: String::new() }),
+            // Not a timestamp: keep it as text.
+            None => match words.last_mut() {
+                Some(w) => w.text.push_str(&rest[open..=close]),
+                None => lead.push_str(&rest[open..=close]),
+            },
+        }
+        rest = &rest[close + 1..];
     }
-    Some(out)
+    match words.last_mut() {
+        Some(w) => w.text.push_str(rest),
+        None => lead.push_str(rest),
+    }
+
+    if words.is_empty() {
+        return (lead.trim().to_string(), words);
+    }
+    if !lead.trim().is_empty() {
+        // Untimed text before the first tag starts with the first word.
+        let start_ms = words[0].start_ms;
+        words.insert(0, LyricWord { start_ms, text: lead });
+    }
+
+    // Trim the line ends while keeping the spacing between words.
+    if let Some(first) = words.iter_mut().find(|w| !w.text.trim().is_empty()) {
+        first.text = first.text.trim_start().to_string();
+    }
+    if let Some(last) = words.iter_mut().rev().find(|w| !w.text.trim().is_empty()) {
+        last.text = last.text.trim_end().to_string();
+    }
+    let text: String = words.iter().map(|w| w.text.as_str()).collect();
+    (text, words)
 }
 
 fn parse_lrc_time_tag(tag: &str) -> Option<u64> {
@@ -293,3 +603,108 @@ fn hash_bytes(bytes: &[u8]) -> u64 {
     bytes.hash(&mut h);
     h.finish()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(track: &LyricTrack) -> Vec<(u64, &str)> {
+        track.lines.iter().map(|l| (l.start_ms, l.text.as_str())).collect()
+    }
+
+    #[test]
+    fn lrc_time_tags_offset_and_repeats() {
+        let lines = parse_lrc("[ti:Song]\n[offset:500]\n[00:02.00][00:10.50]chorus\n[00:01.000]verse\n").unwrap();
+        let got: Vec<(u64, &str)> = lines.iter().map(|l| (l.start_ms, l.text.as_str())).collect();
+        assert_eq!(got, [(500, "verse"), (1500, "chorus"), (10_000, "chorus")]);
+        assert!(parse_lrc("no tags here\n[ar:Band]\n").is_none());
+        assert_eq!(parse_lrc_time_tag("01:02.5"), Some(62_500));
+        assert_eq!(parse_lrc_time_tag("ti:1"), None);
+    }
+
+    #[test]
+    fn enhanced_lrc_words_follow_repeated_lines() {
+        let lines = parse_lrc("[00:01.00][00:05.00]<00:01.00>a <00:01.50>b\n").unwrap();
+        assert_eq!(lines[0].text, "a b");
+        let starts: Vec<u64> = lines[1].words.iter().map(|w| w.start_ms).collect();
+        assert_eq!(starts, [5000, 5500]);
+    }
+
+    #[test]
+    fn repeated_time_tags_split_into_layers() {
+        let tracks = parse_lyric_tracks("[00:01.00]hola\n[00:01.00]hello\n[00:02.00]mundo\n[00:02.00]world\n").unwrap();
+        assert_eq!(tracks.len(), 2);
+        assert_eq!(tracks[0].kind, LyricTrackKind::Original);
+        assert_eq!(texts(&tracks[0]), [(1000, "hola"), (2000, "mundo")]);
+        assert_eq!(tracks[1].kind, LyricTrackKind::Translation);
+        assert_eq!(texts(&tracks[1]), [(1000, "hello"), (2000, "world")]);
+
+        // One repeated line among many is just a repeat, not a translation.
+        let tracks = parse_lyric_tracks("[00:01.00]a\n[00:01.00]a\n[00:02.00]b\n[00:03.00]c\n[00:04.00]d\n").unwrap();
+        assert_eq!(tracks.len(), 1);
+        assert_eq!(tracks[0].lines.len(), 5);
+    }
+
+    #[test]
+    fn plain_lyrics_keep_stanza_breaks() {
+        let tracks = parse_lyric_tracks("\n\nfirst\n\n\n\nsecond\n\n").unwrap();
+        assert_eq!(tracks.len(), 1);
+        assert!(!tracks[0].is_synced());
+        let lines: Vec<&str> = tracks[0].lines.iter().map(|l| l.text.as_str()).collect();
+        assert_eq!(lines, ["first", "", "second"]);
+    }
+
+    #[test]
+    fn formatted_layers_parse_back_into_the_same_tracks() {
+        let line = |start_ms, text: &str| LyricLine { start_ms, text: text.into(), words: Vec::new() };
+        let meta = TrackMetadata { title: "Song".into(), ..TrackMetadata::default() };
+        let content = format_lrc(
+            &meta,
+            &[
+                vec![line(1000, "hola"), line(2500, "mundo")],
+                vec![line(1000, "hello"), line(2500, "world")],
+                vec![line(1000, "o-la"), line(2500, "mun-do")],
+            ],
+        );
+        assert!(content.starts_with("[ti:Song]\n"));
+        let tracks = parse_lyric_tracks(&content).unwrap();
+        let kinds: Vec<LyricTrackKind> = tracks.iter().map(|t| t.kind).collect();
+        assert_eq!(kinds, [LyricTrackKind::Original, LyricTrackKind::Translation, LyricTrackKind::Romanization]);
+        assert_eq!(texts(&tracks[1]), [(1000, "hello"), (2500, "world")]);
+    }
+
+    #[test]
+    fn gain_values_parse_with_or_without_db() {
+        assert_eq!(parse_gain_value("-6.5 dB"), Some(-6.5));
+        assert_eq!(parse_gain_value("+1.2dB"), Some(1.2));
+        assert_eq!(parse_gain_value(" 0.988553 "), Some(0.988553));
+        assert_eq!(parse_gain_value("-3 db"), Some(-3.0));
+        assert_eq!(parse_gain_value("loud"), None);
+        assert_eq!(parse_gain_value("NaN dB"), None);
+    }
+
+    #[test]
+    fn replay_gain_prefers_the_first_tag_and_converts_r128() {
+        let mut vorbis = Tag::new(lofty::TagType::VorbisComments);
+        vorbis.insert_text(ItemKey::ReplayGainTrackPeak, "0.5".into());
+        // -1.5 dB at -23 LUFS in Q7.8, i.e. +3.5 dB against the ReplayGain reference.
+        for (key, q) in [("R128_TRACK_GAIN", "-384"), ("R128_ALBUM_GAIN", "256")] {
+            let value = lofty::ItemValue::Text(q.into());
+            vorbis.insert_unchecked(lofty::TagItem::new(ItemKey::Unknown(key.into()), value));
+        }
+        let mut id3 = Tag::new(lofty::TagType::Id3v2);
+        id3.insert_text(ItemKey::ReplayGainAlbumGain, "-6.5 dB".into());
+        id3.insert_text(ItemKey::ReplayGainAlbumPeak, "0.9".into());
+
+        let rg = read_replay_gain([&vorbis, &id3]);
+        assert_eq!(rg.track_gain_db, Some(3.5));
+        assert_eq!(rg.track_peak, Some(0.5));
+        // The R128 album gain of the first tag is taken before the second tag is looked at.
+        assert_eq!(rg.album_gain_db, Some(6.0));
+        assert_eq!(rg.album_peak, Some(0.9));
+
+        let rg = read_replay_gain([&id3, &vorbis]);
+        assert_eq!(rg.album_gain_db, Some(-6.5));
+        assert!(read_replay_gain([&Tag::new(lofty::TagType::Id3v2)]).is_empty());
+    }
+}
@@ -1,4 +1,22 @@
+pub mod library_scanner;
 pub mod local_player;
+pub mod lookup_cache;
+pub mod loudness;
 pub mod metadata;
 pub mod mpris_client;
+pub mod mpris_server;
+pub mod provider;
 pub mod remote_fetch;
+pub mod tag_editor;
+pub mod tagger;
+
+pub mod providers {
+    pub mod acoustid;
+    pub mod coverartarchive;
+    pub mod genius;
+    pub mod local;
+    pub mod lrclib;
+    pub mod musicbrainz;
+    pub mod netease;
+    pub mod qqmusic;
+}
@@ -2,7 +2,8 @@
 mod imp {
     use crate::app::state::{PlaybackState, TrackMetadata};
     use anyhow::Result;
-    use mpris::{PlaybackStatus, PlayerFinder, TrackID};
+    use crate::playback::mpris_server::BUS_NAME;
+    use mpris::{PlaybackStatus, Player, PlayerFinder, TrackID};
     use std::collections::hash_map::DefaultHasher;
     use std::hash::{Hash, Hasher};
     use std::path::PathBuf;
@@ -32,10 +33,29 @@ mod imp {
             }
         }
 
+        /// Like `PlayerFinder::find_active`, but never picks our own MPRIS server.
+        fn find_active(&self) -> Option<Player> {
+            let players = self.finder.iter_players().ok()?;
+            let mut first_paused: Option<Player> = None;
+            let mut first_found: Option<Player> = None;
+            for player in players.flatten() {
+                if player.bus_name().starts_with(BUS_NAME) {
+                    continue;
+                }
+                match player.get_playback_status() {
+                    Ok(PlaybackStatus::Playing) => return Some(player),
+                    Ok(PlaybackStatus::Paused) if first_paused.is_none() => first_paused = Some(player),
+                    _ if first_found.is_none() => first_found = Some(player),
+                    _ => {}
+                }
+            }
+            first_paused.or(first_found)
+        }
+
         pub fn poll_snapshot(&mut self) -> Result<Option<MprisSnapshot>> {
-            let player = match self.finder.find_active() {
-                Ok(p) => p,
-                Err(_) => return Ok(None),
+            let player = match self.find_active() {
+                Some(p) => p,
+                None => return Ok(None),
             };
 
             let status = player.get_playback_status().unwrap_or(PlaybackStatus::Stopped);
@@ -83,35 +103,35 @@ mod imp {
         }
 
         pub fn toggle_play_pause(&mut self) -> Result<()> {
-            if let Ok(p) = self.finder.find_active() {
+            if let Some(p) = self.find_active() {
                 let _ = p.play_pause();
             }
             Ok(())
         }
 
         pub fn pause(&mut self) -> Result<()> {
-            if let Ok(p) = self.finder.find_active() {
+            if let Some(p) = self.find_active() {
                 let _ = p.pause();
             }
             Ok(())
         }
 
         pub fn next(&mut self) -> Result<()> {
-            if let Ok(p) = self.finder.find_active() {
+            if let Some(p) = self.find_active() {
                 let _ = p.next();
             }
             Ok(())
         }
 
         pub fn prev(&mut self) -> Result<()> {
-            if let Ok(p) = self.finder.find_active() {
+            if let Some(p) = self.find_active() {
                 let _ = p.previous();
             }
             Ok(())
         }
 
         pub fn seek_to(&mut self, pos: Duration) -> Result<()> {
-            if let Ok(p) = self.finder.find_active() {
+            if let Some(p) = self.find_active() {
                 if let Some(id) = self.last_track_id.clone() {
                     let _ = p.set_position(id, &pos);
                 } else {
@@ -125,8 +145,30 @@ mod imp {
             Ok(())
         }
 
+        /// Relative seek; returns the player's position afterwards when it reports one.
+        /// MPRIS `Seek` is relative already, so it also works while the player is paused.
+        pub fn seek_by(&mut self, delta_ms: i64) -> Result<Option<Duration>> {
+            let Some(p) = self.find_active() else {
+                return Ok(None);
+            };
+            let cur = p.get_position().unwrap_or(Duration::from_secs(0));
+            let mut delta_us = delta_ms.saturating_mul(1000);
+            if delta_us < 0 {
+                // Some players ignore seeks before the start instead of clamping to 0.
+                delta_us = delta_us.max(-(cur.as_micros() as i64));
+            } else if let Some(len) = p.get_metadata().ok().and_then(|m| m.length()) {
+                // Past the end, `Seek` behaves like `Next`; stop just short of it instead.
+                let room = len.saturating_sub(cur).saturating_sub(Duration::from_secs(1));
+                delta_us = delta_us.min(room.as_micros() as i64);
+            }
+            if delta_us != 0 {
+                let _ = p.seek(delta_us);
+            }
+            Ok(p.get_position().ok())
+        }
+
         pub fn set_volume_delta(&mut self, delta: f32) -> Result<()> {
-            if let Ok(p) = self.finder.find_active() {
+            if let Some(p) = self.find_active() {
                 let v = p.get_volume().unwrap_or(0.0) as f32;
                 let nv = (v + delta).clamp(0.0, 1.0);
                 let _ = p.set_volume(nv as f64);
@@ -197,6 +239,10 @@ mod imp {
             Ok(())
         }
 
+        pub fn seek_by(&mut self, _delta_ms: i64) -> Result<Option<Duration>> {
+            Ok(None)
+        }
+
         pub fn set_volume_delta(&mut self, _delta: f32) -> Result<()> {
             Ok(())
         }
@@ -0,0 +1,574 @@
+// MPRIS2 server: publishes local playback on the session bus so media keys,
+// `playerctl` and status bars can see and control us.
+// Commands are forwarded to the event loop as `ControlCommand`s (same path as the control socket).
+
+/// Well-known name; a second instance appends `.instance<pid>` as the spec suggests.
+pub const BUS_NAME: &str = "org.mpris.MediaPlayer2.cli_music_player";
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use super::BUS_NAME;
+    use crate::app::control::{self, ControlCommand, ControlRequest, SeekTarget};
+    use crate::app::state::{AppState, PlayMode, PlaybackState, RepeatMode};
+    use crate::data::playlist_file;
+    use anyhow::{anyhow, Result};
+    use dbus::arg::{PropMap, RefArg, Variant};
+    use dbus::blocking::Connection;
+    use dbus::channel::{MatchingReceiver, Sender as _};
+    use dbus::message::{MatchRule, MessageType};
+    use dbus::strings::{ErrorName, Interface, Member};
+    use dbus::Message;
+    use std::collections::hash_map::DefaultHasher;
+    use std::ffi::CString;
+    use std::hash::{Hash, Hasher};
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::path::PathBuf;
+    use std::sync::mpsc::{self, Receiver, Sender};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+
+    const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+    const ROOT_IFACE: &str = "org.mpris.MediaPlayer2";
+    const PLAYER_IFACE: &str = "org.mpris.MediaPlayer2.Player";
+    const PROPS_IFACE: &str = "org.freedesktop.DBus.Properties";
+    const NO_TRACK: &str = "/org/mpris/MediaPlayer2/TrackList/NoTrack";
+
+    const INTROSPECT_XML: &str = r#"<!DOCTYPE node PUBLIC "-//freedesktop//DTD D-BUS Object Introspection 1.0//EN" "http://www.freedesktop.org/standards/dbus/1.0/introspect.dtd">
+<node>
+  <interface name="org.freedesktop.DBus.Introspectable">
+    <method name="Introspect"><arg name="xml" type="s" direction="out"/></method>
+  </interface>
+  <interface name="org.freedesktop.DBus.Properties">
+    <method name="Get"><arg type="s" direction="in"/><arg type="s" direction="in"/><arg type="v" direction="out"/></method>
+    <method name="GetAll"><arg type="s" direction="in"/><arg type="a{sv}" direction="out"/></method>
+    <method name="Set"><arg type="s" direction="in"/><arg type="s" direction="in"/><arg type="v" direction="in"/></method>
+    <signal name="PropertiesChanged"><arg type="s"/><arg type="a{sv}"/><arg type="as"/></signal>
+  </interface>
+  <interface name="org.mpris.MediaPlayer2">
+    <method name="Raise"/>
+    <method name="Quit"/>
+    <property name="CanQuit" type="b" access="read"/>
+    <property name="CanRaise" type="b" access="read"/>
+    <property name="HasTrackList" type="b" access="read"/>
+    <property name="Identity" type="s" access="read"/>
+    <property name="SupportedUriSchemes" type="as" access="read"/>
+    <property name="SupportedMimeTypes" type="as" access="read"/>
+  </interface>
+  <interface name="org.mpris.MediaPlayer2.Player">
+    <method name="Next"/>
+    <method name="Previous"/>
+    <method name="Pause"/>
+    <method name="PlayPause"/>
+    <method name="Stop"/>
+    <method name="Play"/>
+    <method name="Seek"><arg name="Offset" type="x" direction="in"/></method>
+    <method name="SetPosition"><arg name="TrackId" type="o" direction="in"/><arg name="Position" type="x" direction="in"/></method>
+    <method name="OpenUri"><arg name="Uri" type="s" direction="in"/></method>
+    <signal name="Seeked"><arg name="Position" type="x"/></signal>
+    <property name="PlaybackStatus" type="s" access="read"/>
+    <property name="LoopStatus" type="s" access="readwrite"/>
+    <property name="Rate" type="d" access="readwrite"/>
+    <property name="Shuffle" type="b" access="readwrite"/>
+    <property name="Metadata" type="a{sv}" access="read"/>
+    <property name="Volume" type="d" access="readwrite"/>
+    <property name="Position" type="x" access="read"/>
+    <property name="MinimumRate" type="d" access="read"/>
+    <property name="MaximumRate" type="d" access="read"/>
+    <property name="CanGoNext" type="b" access="read"/>
+    <property name="CanGoPrevious" type="b" access="read"/>
+    <property name="CanPlay" type="b" access="read"/>
+    <property name="CanPause" type="b" access="read"/>
+    <property name="CanSeek" type="b" access="read"/>
+    <property name="CanControl" type="b" access="read"/>
+  </interface>
+</node>"#;
+
+    /// What the bus sees; refreshed by the UI thread every frame.
+    #[derive(Debug, Clone, Default, PartialEq)]
+    struct PlayerInfo {
+        /// Only local playback is published; system monitor mode reports Stopped.
+        active: bool,
+        playing: bool,
+        paused: bool,
+        repeat: Option<RepeatMode>,
+        volume: f64,
+        position_us: i64,
+        /// Bumped by every seek; a change is announced as `Seeked`.
+        seeks: u64,
+        track_id: String,
+        length_us: i64,
+        title: String,
+        artist: String,
+        album: String,
+        url: Option<String>,
+        art_url: Option<String>,
+    }
+
+    impl PlayerInfo {
+        fn playback_status(&self) -> &'static str {
+            if !self.active {
+                "Stopped"
+            } else if self.playing {
+                "Playing"
+            } else if self.paused {
+                "Paused"
+            } else {
+                "Stopped"
+            }
+        }
+
+        fn loop_status(&self) -> &'static str {
+            match self.repeat {
+                Some(RepeatMode::LoopOne) => "Track",
+                Some(RepeatMode::LoopAll) => "Playlist",
+                _ => "None",
+            }
+        }
+
+        fn shuffle(&self) -> bool {
+            self.repeat == Some(RepeatMode::Shuffle)
+        }
+
+        fn metadata(&self) -> PropMap {
+            let mut m = PropMap::new();
+            let track_id = if self.active && !self.track_id.is_empty() { &self.track_id } else { NO_TRACK };
+            m.insert("mpris:trackid".into(), var(dbus::Path::from(track_id.to_string())));
+            if !self.active {
+                return m;
+            }
+            if self.length_us > 0 {
+                m.insert("mpris:length".into(), var(self.length_us));
+            }
+            if !self.title.is_empty() {
+                m.insert("xesam:title".into(), var(self.title.clone()));
+            }
+            if !self.artist.is_empty() {
+                m.insert("xesam:artist".into(), var(vec![self.artist.clone()]));
+            }
+            if !self.album.is_empty() {
+                m.insert("xesam:album".into(), var(self.album.clone()));
+            }
+            if let Some(url) = self.url.as_ref() {
+                m.insert("xesam:url".into(), var(url.clone()));
+            }
+            if let Some(art) = self.art_url.as_ref() {
+                m.insert("mpris:artUrl".into(), var(art.clone()));
+            }
+            m
+        }
+
+        /// Player properties that are announced through PropertiesChanged (Position is not, per spec).
+        fn player_props(&self) -> PropMap {
+            let mut m = PropMap::new();
+            m.insert("PlaybackStatus".into(), var(self.playback_status().to_string()));
+            m.insert("LoopStatus".into(), var(self.loop_status().to_string()));
+            m.insert("Shuffle".into(), var(self.shuffle()));
+            m.insert("Metadata".into(), var(self.metadata()));
+            m.insert("Volume".into(), var(self.volume));
+            m.extend(self.capabilities());
+            m
+        }
+
+        fn capabilities(&self) -> PropMap {
+            let mut m = PropMap::new();
+            m.insert("CanGoNext".into(), var(self.active));
+            m.insert("CanGoPrevious".into(), var(self.active));
+            m.insert("CanPlay".into(), var(self.active));
+            m.insert("CanPause".into(), var(self.active));
+            m.insert("CanSeek".into(), var(self.active && self.length_us > 0));
+            m
+        }
+
+        /// The subset of `player_props` that differs from `prev`.
+        fn changed_since(&self, prev: &PlayerInfo) -> PropMap {
+            let mut m = PropMap::new();
+            if self.playback_status() != prev.playback_status() {
+                m.insert("PlaybackStatus".into(), var(self.playback_status().to_string()));
+            }
+            if self.loop_status() != prev.loop_status() {
+                m.insert("LoopStatus".into(), var(self.loop_status().to_string()));
+            }
+            if self.shuffle() != prev.shuffle() {
+                m.insert("Shuffle".into(), var(self.shuffle()));
+            }
+            if (self.volume - prev.volume).abs() > 1e-4 {
+                m.insert("Volume".into(), var(self.volume));
+            }
+            let meta = |i: &PlayerInfo| {
+                (
+                    i.active,
+                    i.track_id.clone(),
+                    i.length_us,
+                    i.title.clone(),
+                    i.artist.clone(),
+                    i.album.clone(),
+                    i.url.clone(),
+                    i.art_url.clone(),
+                )
+            };
+            if meta(self) != meta(prev) {
+                m.insert("Metadata".into(), var(self.metadata()));
+                m.extend(self.capabilities());
+            }
+            m
+        }
+
+        fn all_player_props(&self) -> PropMap {
+            let mut m = self.player_props();
+            m.insert("Rate".into(), var(1.0f64));
+            m.insert("MinimumRate".into(), var(1.0f64));
+            m.insert("MaximumRate".into(), var(1.0f64));
+            m.insert("Position".into(), var(self.position_us));
+            m.insert("CanControl".into(), var(true));
+            m
+        }
+    }
+
+    fn root_props() -> PropMap {
+        let mut m = PropMap::new();
+        m.insert("CanQuit".into(), var(false));
+        m.insert("CanRaise".into(), var(false));
+        m.insert("HasTrackList".into(), var(false));
+        m.insert("Identity".into(), var("CLI Music Player".to_string()));
+        m.insert("SupportedUriSchemes".into(), var(vec!["file".to_string()]));
+        m.insert(
+            "SupportedMimeTypes".into(),
+            var(
+                ["audio/mpeg", "audio/flac", "audio/ogg", "audio/wav", "audio/mp4", "audio/aac", "audio/x-m4a"]
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect::<Vec<_>>(),
+            ),
+        );
+        m
+    }
+
+    fn var<T: RefArg + 'static>(v: T) -> Variant<Box<dyn RefArg>> {
+        Variant(Box::new(v))
+    }
+
+    pub struct MprisServer {
+        info: Arc<Mutex<PlayerInfo>>,
+        rx: Receiver<ControlRequest>,
+        // Cover written for `mpris:artUrl`, keyed by cover hash.
+        art_file: Option<(u64, PathBuf)>,
+    }
+
+    impl MprisServer {
+        pub fn start() -> Result<Self> {
+            let conn = Connection::new_session()?;
+            let mut name = BUS_NAME.to_string();
+            let reply = conn.request_name(name.as_str(), false, false, true)?;
+            if reply != dbus::blocking::stdintf::org_freedesktop_dbus::RequestNameReply::PrimaryOwner {
+                name = format!("{BUS_NAME}.instance{}", std::process::id());
+                conn.request_name(name.as_str(), false, false, true)?;
+            }
+
+            let info = Arc::new(Mutex::new(PlayerInfo::default()));
+            let (tx, rx) = mpsc::channel::<ControlRequest>();
+
+            let shared = info.clone();
+            conn.start_receive(
+                MatchRule::new_method_call(),
+                Box::new(move |msg, conn| {
+                    let reply = handle_call(&msg, &shared, &tx)
+                        .or_else(|| dbus::channel::default_reply(&msg));
+                    if let Some(reply) = reply {
+                        if !msg.get_no_reply() {
+                            let _ = conn.send(reply);
+                        }
+                    }
+                    true
+                }),
+            );
+
+            let shared = info.clone();
+            thread::Builder::new()
+                .name("mpris-server".into())
+                .spawn(move || serve(conn, shared))
+                .map_err(|e| anyhow!("spawn mpris server: {e}"))?;
+
+            log::info!("mpris server listening as {name}");
+            Ok(Self {
+                info,
+                rx,
+                art_file: None,
+            })
+        }
+
+        /// Publish the current state (cheap; the bus thread emits signals only on change).
+        pub fn update(&mut self, app: &AppState) {
+            let p = &app.player;
+            let active = p.mode == PlayMode::LocalPlayback;
+            let path = if active { app.playlist.current_path().cloned() } else { None };
+            let art_url = if active { self.art_url(app) } else { None };
+
+            let info = PlayerInfo {
+                active,
+                playing: p.playback == PlaybackState::Playing,
+                paused: p.playback == PlaybackState::Paused,
+                repeat: active.then_some(p.repeat_mode),
+                volume: p.volume.clamp(0.0, 1.0) as f64,
+                position_us: p.position.as_micros() as i64,
+                seeks: p.seeks,
+                track_id: path.as_ref().map(|p| track_id(p)).unwrap_or_default(),
+                length_us: p.track.duration.as_micros() as i64,
+                title: p.track.title.clone(),
+                artist: p.track.artist.clone(),
+                album: p.track.album.clone(),
+                url: path.map(|p| file_url(&p)),
+                art_url,
+            };
+            if let Ok(mut shared) = self.info.lock() {
+                *shared = info;
+            }
+        }
+
+        pub fn drain(&self) -> Vec<ControlRequest> {
+            self.rx.try_iter().collect()
+        }
+
+        fn art_url(&mut self, app: &AppState) -> Option<String> {
+            let track = &app.player.track;
+            let (Some(bytes), Some(hash)) = (track.cover.as_ref(), track.cover_hash) else {
+                return None;
+            };
+            if let Some((h, path)) = self.art_file.as_ref() {
+                if *h == hash {
+                    return Some(file_url(path));
+                }
+            }
+            if let Some((_, old)) = self.art_file.take() {
+                let _ = std::fs::remove_file(old);
+            }
+            let ext = if bytes.starts_with(b"\x89PNG") { "png" } else { "jpg" };
+            // Owner-only directory and file: a name in the shared temp dir could be planted
+            // beforehand as a link to someone's file.
+            let path = control::runtime_dir().ok()?.join(format!(
+                "cli-music-player-cover-{}-{hash:016x}.{ext}",
+                std::process::id()
+            ));
+            let _ = std::fs::remove_file(&path);
+            let written = std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .mode(0o600)
+                .open(&path)
+                .and_then(|mut f| f.write_all(bytes));
+            if written.is_err() {
+                let _ = std::fs::remove_file(&path);
+                return None;
+            }
+            let url = file_url(&path);
+            self.art_file = Some((hash, path));
+            Some(url)
+        }
+    }
+
+    impl Drop for MprisServer {
+        fn drop(&mut self) {
+            if let Some((_, path)) = self.art_file.take() {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+
+    fn file_url(path: &std::path::Path) -> String {
+        format!("file://{}", playlist_file::percent_encode_path(&path.to_string_lossy()))
+    }
+
+    fn track_id(path: &std::path::Path) -> String {
+        let mut h = DefaultHasher::new();
+        path.hash(&mut h);
+        format!("/org/mpris/MediaPlayer2/cli_music_player/track/t{:016x}", h.finish())
+    }
+
+    /// Bus thread: dispatch calls and announce property changes / seeks.
+    fn serve(conn: Connection, shared: Arc<Mutex<PlayerInfo>>) {
+        let mut last: Option<PlayerInfo> = None;
+        loop {
+            if conn.process(Duration::from_millis(50)).is_err() {
+                log::warn!("mpris server: lost the session bus");
+                return;
+            }
+            let Some(now) = shared.lock().ok().map(|i| i.clone()) else {
+                return;
+            };
+
+            if let Some(prev) = last.as_ref() {
+                let changed = now.changed_since(prev);
+                if !changed.is_empty() {
+                    let _ = conn.send(properties_changed(PLAYER_IFACE, changed));
+                }
+
+                // Seeks from the bus, the control socket and the UI all land here.
+                if now.active && now.seeks != prev.seeks {
+                    let _ = conn.send(signal(PLAYER_IFACE, "Seeked").append1(now.position_us));
+                }
+            }
+            last = Some(now);
+        }
+    }
+
+    fn signal(iface: &str, member: &str) -> Message {
+        Message::signal(
+            &dbus::Path::from(OBJECT_PATH),
+            &Interface::from(iface),
+            &Member::from(member),
+        )
+    }
+
+    fn properties_changed(iface: &str, changed: PropMap) -> Message {
+        signal(PROPS_IFACE, "PropertiesChanged").append3(iface, changed, Vec::<String>::new())
+    }
+
+    fn error(msg: &Message, name: &str, text: &str) -> Message {
+        let text = CString::new(text).unwrap_or_default();
+        msg.error(&ErrorName::from(name), &text)
+    }
+
+    fn handle_call(msg: &Message, shared: &Arc<Mutex<PlayerInfo>>, tx: &Sender<ControlRequest>) -> Option<Message> {
+        if msg.msg_type() != MessageType::MethodCall {
+            return None;
+        }
+        let path = msg.path()?;
+        let iface = msg.interface()?;
+        let member = msg.member()?;
+        let (iface, member) = (&*iface, &*member);
+
+        if iface == "org.freedesktop.DBus.Introspectable" && member == "Introspect" {
+            let xml = if &*path == OBJECT_PATH {
+                INTROSPECT_XML.to_string()
+            } else {
+                // Let tools walk down from "/".
+                let child = OBJECT_PATH
+                    .strip_prefix(&*path)
+                    .and_then(|rest| rest.trim_start_matches('/').split('/').next())
+                    .filter(|c| !c.is_empty())?;
+                format!("<node><node name=\"{child}\"/></node>")
+            };
+            return Some(msg.method_return().append1(xml));
+        }
+        if &*path != OBJECT_PATH {
+            return None;
+        }
+
+        let info = shared.lock().ok()?.clone();
+        let send = |command: ControlCommand| {
+            let _ = tx.send(ControlRequest::detached(command));
+        };
+
+        match (iface, member) {
+            (PROPS_IFACE, "Get") => {
+                let (i, name) = msg.read2::<&str, &str>().ok()?;
+                let mut props = if i == ROOT_IFACE { root_props() } else { info.all_player_props() };
+                match props.remove(name) {
+                    Some(v) => Some(msg.method_return().append1(v)),
+                    None => Some(error(msg, "org.freedesktop.DBus.Error.UnknownProperty", "no such property")),
+                }
+            }
+            (PROPS_IFACE, "GetAll") => {
+                let i = msg.read1::<&str>().ok()?;
+                let props = match i {
+                    ROOT_IFACE => root_props(),
+                    PLAYER_IFACE => info.all_player_props(),
+                    _ => PropMap::new(),
+                };
+                Some(msg.method_return().append1(props))
+            }
+            (PROPS_IFACE, "Set") => {
+                let (_, name, value) = msg.read3::<&str, &str, Variant<Box<dyn RefArg>>>().ok()?;
+                match name {
+                    "Volume" => {
+                        let v = value.0.as_f64()?;
+                        send(ControlCommand::Volume {
+                            value: (v.clamp(0.0, 1.0) * 100.0) as f32,
+                            relative: false,
+                        });
+                    }
+                    "LoopStatus" => {
+                        let mode = match value.0.as_str()? {
+                            "Track" => RepeatMode::LoopOne,
+                            "Playlist" => RepeatMode::LoopAll,
+                            _ => RepeatMode::Sequence,
+                        };
+                        send(ControlCommand::Repeat(mode));
+                    }
+                    "Shuffle" => {
+                        let on = value.0.as_i64()? != 0;
+                        if on {
+                            send(ControlCommand::Repeat(RepeatMode::Shuffle));
+                        } else if info.shuffle() {
+                            send(ControlCommand::Repeat(RepeatMode::Sequence));
+                        }
+                    }
+                    // Fixed at 1.0.
+                    "Rate" => {}
+                    _ => {
+                        return Some(error(msg, "org.freedesktop.DBus.Error.PropertyReadOnly", "property is read-only"));
+                    }
+                }
+                Some(msg.method_return())
+            }
+            (ROOT_IFACE, "Raise") | (ROOT_IFACE, "Quit") => Some(msg.method_return()),
+            (PLAYER_IFACE, _) => {
+                let command = match member {
+                    "Next" => ControlCommand::Next,
+                    "Previous" => ControlCommand::Prev,
+                    "Play" => ControlCommand::Play,
+                    "Pause" => ControlCommand::Pause,
+                    "Stop" => ControlCommand::Stop,
+                    "PlayPause" => ControlCommand::Toggle,
+                    "Seek" => {
+                        let offset = msg.read1::<i64>().ok()?;
+                        ControlCommand::Seek(SeekTarget::Relative(offset as f32 / 1_000_000.0))
+                    }
+                    "SetPosition" => {
+                        let (id, pos) = msg.read2::<dbus::Path, i64>().ok()?;
+                        // Stale track ids and out-of-range positions are ignored, per spec.
+                        if &*id != info.track_id.as_str() || pos < 0 || pos > info.length_us {
+                            return Some(msg.method_return());
+                        }
+                        ControlCommand::Seek(SeekTarget::Absolute(pos as f32 / 1_000_000.0))
+                    }
+                    "OpenUri" => {
+                        let uri = msg.read1::<&str>().ok()?;
+                        let Some(path) = uri.strip_prefix("file://") else {
+                            return Some(error(msg, "org.freedesktop.DBus.Error.NotSupported", "only file:// URIs"));
+                        };
+                        ControlCommand::Open(PathBuf::from(playlist_file::percent_decode(path)))
+                    }
+                    _ => return None,
+                };
+                send(command);
+                Some(msg.method_return())
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod imp {
+    use crate::app::control::ControlRequest;
+    use crate::app::state::AppState;
+    use anyhow::{anyhow, Result};
+
+    pub struct MprisServer;
+
+    impl MprisServer {
+        pub fn start() -> Result<Self> {
+            Err(anyhow!("MPRIS is only supported on Linux"))
+        }
+
+        pub fn update(&mut self, _app: &AppState) {}
+
+        pub fn drain(&self) -> Vec<ControlRequest> {
+            Vec::new()
+        }
+    }
+}
+
+pub use imp::MprisServer;
@@ -0,0 +1,231 @@
+// Remote (and local-folder) sources for lyrics, covers and track identification.
+// `remote_fetch` asks them in the configured order; each source lives in
+// `playback::providers` and only implements what it can answer.
+
+use crate::data::config::{ProviderKind, ProvidersConfig};
+use crate::playback::providers::{acoustid, coverartarchive, genius, local, lrclib, musicbrainz, netease, qqmusic};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const USER_AGENT: &str = "cli-music-player/0.1.0 (https://github.com)";
+
+/// Covers larger than this are ignored.
+pub const MAX_COVER_BYTES: u64 = 5 * 1024 * 1024;
+
+/// What is known about the song when a provider is asked. Identification results are
+/// merged in before lyrics and covers are looked up.
+#[derive(Debug, Clone)]
+pub struct Query {
+    pub path: Option<PathBuf>,
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub duration_secs: u64,
+    /// MusicBrainz release, once an identification provider found one.
+    pub release_mbid: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Identification {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub release_mbid: Option<String>,
+    /// Measured from the audio (fingerprinting decodes the whole file).
+    pub duration_secs: Option<u64>,
+}
+
+impl Identification {
+    /// Fill the fields still missing from `other`.
+    pub fn merge(&mut self, other: Identification) {
+        self.title = self.title.take().or(other.title);
+        self.artist = self.artist.take().or(other.artist);
+        self.album = self.album.take().or(other.album);
+        self.release_mbid = self.release_mbid.take().or(other.release_mbid);
+        self.duration_secs = self.duration_secs.take().or(other.duration_secs);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Cover {
+    pub bytes: Vec<u8>,
+    pub content_type: Option<String>,
+}
+
+pub trait Provider: Send {
+    fn kind(&self) -> ProviderKind;
+
+    /// LRC or plain-text lyrics.
+    fn lyrics(&self, _query: &Query) -> Option<String> {
+        None
+    }
+
+    fn cover(&self, _query: &Query) -> Option<Cover> {
+        None
+    }
+
+    fn identify(&self, _query: &Query) -> Option<Identification> {
+        None
+    }
+}
+
+/// Everything the worker needs to build the provider chain for a request.
+#[derive(Debug, Clone)]
+pub struct ProviderChainConfig {
+    pub order: Vec<ProviderKind>,
+    pub providers: ProvidersConfig,
+    pub lyrics_folders: Vec<PathBuf>,
+    /// AcoustID is only built with a key and fingerprinting turned on.
+    pub acoustid_api_key: Option<String>,
+}
+
+/// Enabled providers in the configured order (each at most once).
+pub fn build_chain(cfg: &ProviderChainConfig) -> Vec<Box<dyn Provider>> {
+    let mut seen: Vec<ProviderKind> = Vec::new();
+    let mut out: Vec<Box<dyn Provider>> = Vec::new();
+    for &kind in &cfg.order {
+        if seen.contains(&kind) {
+            continue;
+        }
+        seen.push(kind);
+        let settings = cfg.providers.get(kind);
+        if !settings.enabled(kind) {
+            continue;
+        }
+        let base = settings.base_url(kind);
+        let p: Box<dyn Provider> = match kind {
+            ProviderKind::Local => {
+                if cfg.lyrics_folders.is_empty() {
+                    continue;
+                }
+                Box::new(local::LocalLyrics::new(cfg.lyrics_folders.clone()))
+            }
+            ProviderKind::Lrclib => Box::new(lrclib::Lrclib::new(base)),
+            ProviderKind::Netease => Box::new(netease::Netease::new(base)),
+            ProviderKind::Qqmusic => Box::new(qqmusic::QqMusic::new(base)),
+            ProviderKind::Genius => Box::new(genius::Genius::new(base)),
+            ProviderKind::Musicbrainz => Box::new(musicbrainz::MusicBrainz::new(base)),
+            ProviderKind::Coverartarchive => Box::new(coverartarchive::CoverArtArchive::new(base)),
+            ProviderKind::Acoustid => match cfg.acoustid_api_key.as_deref() {
+                Some(key) if !key.trim().is_empty() => Box::new(acoustid::AcoustId::new(base, key.to_string())),
+                _ => continue,
+            },
+        };
+        out.push(p);
+    }
+    out
+}
+
+pub fn http_agent() -> ureq::Agent {
+    ureq::AgentBuilder::new()
+        .timeout(Duration::from_secs(8))
+        .build()
+}
+
+pub fn is_unknown(s: &str) -> bool {
+    let t = s.trim();
+    t.is_empty() || t.eq_ignore_ascii_case("unknown")
+}
+
+/// Lowercase letters and digits only, for loose title / artist comparison.
+pub fn normalize(s: &str) -> String {
+    s.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+/// How well a search hit fits the query; None when it is clearly another song.
+/// Titles must match loosely, the artist (if known) must appear among the hit's artists,
+/// and durations (if both known) must be within 10 seconds.
+pub fn match_score(query: &Query, title: &str, artists: &[&str], duration_secs: Option<u64>) -> Option<u32> {
+    let (qt, t) = (normalize(&query.title), normalize(title));
+    if qt.is_empty() || t.is_empty() || !(t.contains(&qt) || qt.contains(&t)) {
+        return None;
+    }
+    let mut score = if t == qt { 2 } else { 1 };
+    if !is_unknown(&query.artist) {
+        let qa = normalize(&query.artist);
+        let hit = artists.iter().map(|a| normalize(a)).any(|a| !a.is_empty() && (a.contains(&qa) || qa.contains(&a)));
+        if !hit {
+            return None;
+        }
+        score += 2;
+    }
+    if let Some(d) = duration_secs.filter(|&d| d > 0 && query.duration_secs > 0) {
+        if d.abs_diff(query.duration_secs) > 10 {
+            return None;
+        }
+        score += 1;
+    }
+    Some(score)
+}
+
+/// Decode the HTML entities that show up in scraped or escaped lyrics.
+pub fn decode_entities(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let Some(semi) = rest.char_indices().take(12).find(|&(_, c)| c == ';').map(|(i, _)| i) else {
+            out.push('&');
+            rest = &rest[1..];
+            continue;
+        };
+        let entity = &rest[1..semi];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ => entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+                .and_then(|h| u32::from_str_radix(h, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|d| d.parse().ok()))
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Read a response body up to `MAX_COVER_BYTES`.
+pub fn read_cover(resp: ureq::Response) -> Option<Cover> {
+    use std::io::Read;
+    if resp.status() != 200 {
+        return None;
+    }
+    if let Some(n) = resp.header("Content-Length").and_then(|len| len.parse::<u64>().ok()) {
+        if n > MAX_COVER_BYTES {
+            return None;
+        }
+    }
+    let content_type = resp.header("Content-Type").map(|s| s.to_string());
+    let mut bytes = Vec::new();
+    resp.into_reader().take(MAX_COVER_BYTES + 1).read_to_end(&mut bytes).ok()?;
+    if bytes.is_empty() || bytes.len() as u64 > MAX_COVER_BYTES {
+        return None;
+    }
+    Some(Cover { bytes, content_type })
+}
+
+/// File name stem for `<artist> - <title>` style lookups, without path separators.
+pub fn file_safe(s: &str) -> String {
+    s.trim().chars().map(|c| if matches!(c, '/' | '\\' | ':') { '_' } else { c }).collect()
+}
+
+pub fn audio_stem(path: Option<&Path>) -> Option<String> {
+    path?.file_stem()?.to_str().map(|s| s.to_string())
+}
@@ -0,0 +1,204 @@
+use crate::data::config::ProviderKind;
+use crate::playback::provider::{http_agent, is_unknown, Identification, Provider, Query, USER_AGENT};
+use chromaprint::Chromaprint;
+use serde::Deserialize;
+use std::path::Path;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::DecoderOptions;
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+/// AcoustID: identifies untagged files by their Chromaprint fingerprint.
+pub struct AcoustId {
+    base: String,
+    api_key: String,
+}
+
+impl AcoustId {
+    pub fn new(base: String, api_key: String) -> Self {
+        Self { base, api_key }
+    }
+}
+
+impl Provider for AcoustId {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::Acoustid
+    }
+
+    fn identify(&self, q: &Query) -> Option<Identification> {
+        // Fingerprinting decodes the whole file; only worth it when the tags say nothing.
+        if !(is_unknown(&q.artist) && is_unknown(&q.album)) {
+            return None;
+        }
+        let (fp, fp_dur) = chromaprint_fingerprint(q.path.as_deref()?)?;
+        let ac = acoustid_lookup(&self.base, &self.api_key, &fp, fp_dur)?;
+        Some(Identification {
+            title: ac.title,
+            artist: ac.artist,
+            album: ac.album,
+            release_mbid: ac.release_mbid,
+            duration_secs: Some(fp_dur as u64),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct AcoustidResponse {
+    status: String,
+    results: Option<Vec<AcoustidResult>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AcoustidResult {
+    recordings: Option<Vec<AcoustidRecording>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AcoustidRecording {
+    title: Option<String>,
+    artists: Option<Vec<AcoustidArtist>>,
+    releases: Option<Vec<AcoustidRelease>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AcoustidArtist {
+    name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AcoustidRelease {
+    id: Option<String>,
+    title: Option<String>,
+}
+
+struct AcoustidLookup {
+    title: Option<String>,
+    artist: Option<String>,
+    album: Option<String>,
+    release_mbid: Option<String>,
+}
+
+fn acoustid_lookup(base: &str, api_key: &str, fingerprint: &str, duration_secs: u32) -> Option<AcoustidLookup> {
+    if api_key.trim().is_empty() {
+        return None;
+    }
+
+    let resp = http_agent()
+        .get(&format!("{base}/v2/lookup"))
+        .set("User-Agent", USER_AGENT)
+        .query("client", api_key)
+        .query("meta", "recordings+releases")
+        .query("duration", &duration_secs.to_string())
+        .query("fingerprint", fingerprint)
+        .query("format", "json")
+        .call()
+        .ok()?;
+
+    if resp.status() != 200 {
+        return None;
+    }
+
+    let body: AcoustidResponse = resp.into_json().ok()?;
+    if body.status != "ok" {
+        return None;
+    }
+    let result = body.results?.into_iter().next()?;
+    let rec = result.recordings?.into_iter().next()?;
+
+    let title = rec.title;
+    let artist = rec.artists.and_then(|mut a| a.pop()).and_then(|a| a.name);
+    let (album, release_mbid) = if let Some(mut releases) = rec.releases {
+        if let Some(r) = releases.pop() {
+            (r.title, r.id)
+        } else {
+            (None, None)
+        }
+    } else {
+        (None, None)
+    };
+
+    Some(AcoustidLookup {
+        title,
+        artist,
+        album,
+        release_mbid,
+    })
+}
+
+fn chromaprint_fingerprint(path: &Path) -> Option<(String, u32)> {
+    let file = std::fs::File::open(path).ok()?;
+    let mss = MediaSourceStream::new(Box::new(file), Default::default());
+    let hint = Hint::new();
+    let format_opts: FormatOptions = Default::default();
+    let metadata_opts: MetadataOptions = Default::default();
+    let decoder_opts: DecoderOptions = Default::default();
+
+    let probed = symphonia::default::get_probe()
+        .format(&hint, mss, &format_opts, &metadata_opts)
+        .ok()?;
+    let mut format = probed.format;
+
+    let track = format.default_track()?;
+    if track.codec_params.codec == symphonia::core::codecs::CODEC_TYPE_NULL {
+        return None;
+    }
+
+    let track_id = track.id;
+    let channels = track
+        .codec_params
+        .channels
+        .map(|c| c.count() as u16)
+        .unwrap_or(2)
+        .max(1);
+    let sample_rate = track.codec_params.sample_rate.unwrap_or(44100).max(1);
+
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &decoder_opts)
+        .ok()?;
+
+    let mut chroma = Chromaprint::new();
+    if !chroma.start(sample_rate as i32, channels as i32) {
+        return None;
+    }
+
+    let mut total_frames: u64 = 0;
+    let mut sample_buf: Option<SampleBuffer<i16>> = None;
+
+    while let Ok(packet) = format.next_packet() {
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        match decoder.decode(&packet) {
+            Ok(audio_buf) => {
+                if sample_buf.is_none() {
+                    let spec = *audio_buf.spec();
+                    let duration = audio_buf.capacity() as u64;
+                    sample_buf = Some(SampleBuffer::<i16>::new(duration, spec));
+                }
+                if let Some(sb) = &mut sample_buf {
+                    sb.copy_interleaved_ref(audio_buf);
+                    let samples = sb.samples();
+                    if !chroma.feed(samples) {
+                        return None;
+                    }
+                    let frames = samples.len() as u64 / channels as u64;
+                    total_frames = total_frames.saturating_add(frames);
+                }
+            }
+            Err(SymphoniaError::DecodeError(_)) => continue,
+            Err(_) => break,
+        }
+    }
+
+    if !chroma.finish() {
+        return None;
+    }
+
+    let fp = chroma.fingerprint()?;
+    let duration_secs = (total_frames as f64 / sample_rate as f64).round() as u32;
+    Some((fp, duration_secs))
+}
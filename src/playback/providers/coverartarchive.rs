@@ -0,0 +1,26 @@
+use crate::data::config::ProviderKind;
+use crate::playback::provider::{http_agent, read_cover, Cover, Provider, Query, USER_AGENT};
+
+/// Cover Art Archive: front covers of MusicBrainz releases.
+pub struct CoverArtArchive {
+    base: String,
+}
+
+impl CoverArtArchive {
+    pub fn new(base: String) -> Self {
+        Self { base }
+    }
+}
+
+impl Provider for CoverArtArchive {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::Coverartarchive
+    }
+
+    fn cover(&self, q: &Query) -> Option<Cover> {
+        let mbid = q.release_mbid.as_deref()?;
+        let url = format!("{}/release/{mbid}/front-500", self.base);
+        let resp = http_agent().get(&url).set("User-Agent", USER_AGENT).call().ok()?;
+        read_cover(resp)
+    }
+}
@@ -0,0 +1,158 @@
+use crate::data::config::ProviderKind;
+use crate::playback::provider::{decode_entities, http_agent, is_unknown, match_score, Provider, Query, USER_AGENT};
+use serde::Deserialize;
+
+/// Genius: plain-text lyrics scraped from the song page (no timing).
+pub struct Genius {
+    base: String,
+}
+
+impl Genius {
+    pub fn new(base: String) -> Self {
+        Self { base }
+    }
+
+    /// Path of the best matching song page, e.g. `/Artist-title-lyrics`.
+    fn search(&self, q: &Query) -> Option<String> {
+        let resp = http_agent()
+            .get(&format!("{}/api/search/song", self.base))
+            .set("User-Agent", USER_AGENT)
+            .query("q", &format!("{} {}", q.title, q.artist))
+            .query("per_page", "10")
+            .call()
+            .ok()?;
+        if resp.status() != 200 {
+            return None;
+        }
+        let body: SearchResponse = resp.into_json().ok()?;
+        body.response
+            .sections
+            .into_iter()
+            .flat_map(|s| s.hits)
+            .filter_map(|h| {
+                let artist = h.result.primary_artist.name;
+                let score = match_score(q, &h.result.title, &[artist.as_str()], None)?;
+                Some((score, h.result.path))
+            })
+            .max_by_key(|(score, _)| *score)
+            .map(|(_, path)| path)
+    }
+}
+
+impl Provider for Genius {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::Genius
+    }
+
+    fn lyrics(&self, q: &Query) -> Option<String> {
+        if is_unknown(&q.title) || is_unknown(&q.artist) {
+            return None;
+        }
+        let path = self.search(q)?;
+        let resp = http_agent()
+            .get(&format!("{}{path}", self.base))
+            .set("User-Agent", USER_AGENT)
+            .call()
+            .ok()?;
+        if resp.status() != 200 {
+            return None;
+        }
+        let text = lyrics_from_page(&resp.into_string().ok()?);
+        (!text.trim().is_empty()).then_some(text)
+    }
+}
+
+/// Text of every `data-lyrics-container` element. Section headers (`[Chorus]`) become
+/// blank lines so stanzas stay apart.
+fn lyrics_from_page(html: &str) -> String {
+    const MARKER: &str = "data-lyrics-container=\"true\"";
+    let mut out = String::new();
+    let mut rest = html;
+    while let Some(at) = rest.find(MARKER) {
+        let Some(open_end) = rest[at..].find('>').map(|i| at + i + 1) else {
+            break;
+        };
+        let body = &rest[open_end..];
+        let end = container_end(body);
+        out.push_str(&html_to_text(&body[..end]));
+        out.push('\n');
+        rest = &body[end..];
+    }
+    out.lines()
+        .map(str::trim)
+        .map(|l| if l.starts_with('[') && l.ends_with(']') { "" } else { l })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Byte offset of the `</div>` closing the container whose content starts `body`.
+fn container_end(body: &str) -> usize {
+    let mut depth = 0usize;
+    let mut i = 0;
+    while let Some(lt) = body[i..].find('<').map(|k| i + k) {
+        let tag = &body[lt..];
+        if tag.starts_with("</div") {
+            if depth == 0 {
+                return lt;
+            }
+            depth -= 1;
+        } else if tag.starts_with("<div") {
+            depth += 1;
+        }
+        i = lt + 1;
+    }
+    body.len()
+}
+
+fn html_to_text(fragment: &str) -> String {
+    let mut out = String::new();
+    let mut rest = fragment;
+    while let Some(lt) = rest.find('<') {
+        out.push_str(&rest[..lt]);
+        let Some(gt) = rest[lt..].find('>').map(|k| lt + k) else {
+            rest = "";
+            break;
+        };
+        let tag = rest[lt + 1..gt].trim_start_matches('/').to_ascii_lowercase();
+        if tag.starts_with("br") {
+            out.push('\n');
+        }
+        rest = &rest[gt + 1..];
+    }
+    out.push_str(rest);
+    decode_entities(&out)
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    response: SearchSections,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchSections {
+    #[serde(default)]
+    sections: Vec<Section>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Section {
+    #[serde(default)]
+    hits: Vec<Hit>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Hit {
+    result: SongResult,
+}
+
+#[derive(Debug, Deserialize)]
+struct SongResult {
+    title: String,
+    path: String,
+    primary_artist: PrimaryArtist,
+}
+
+#[derive(Debug, Deserialize)]
+struct PrimaryArtist {
+    name: String,
+}
@@ -0,0 +1,72 @@
+use crate::data::config::ProviderKind;
+use crate::playback::provider::{audio_stem, file_safe, is_unknown, Provider, Query};
+use std::fs;
+use std::path::PathBuf;
+
+/// Lyrics collections on disk (`lyrics_folders`). Looks for `<artist> - <title>`,
+/// `<artist>/<title>`, `<title>` and the audio file's own name, as `.lrc` then `.txt`,
+/// ignoring case.
+pub struct LocalLyrics {
+    folders: Vec<PathBuf>,
+}
+
+impl LocalLyrics {
+    pub fn new(folders: Vec<PathBuf>) -> Self {
+        Self { folders }
+    }
+}
+
+impl Provider for LocalLyrics {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::Local
+    }
+
+    fn lyrics(&self, q: &Query) -> Option<String> {
+        let mut names: Vec<String> = Vec::new();
+        if !is_unknown(&q.title) {
+            let title = file_safe(&q.title);
+            if !is_unknown(&q.artist) {
+                let artist = file_safe(&q.artist);
+                names.push(format!("{artist} - {title}"));
+                names.push(format!("{artist}/{title}"));
+            }
+            names.push(title);
+        }
+        names.extend(audio_stem(q.path.as_deref()));
+
+        for folder in &self.folders {
+            for name in &names {
+                for ext in ["lrc", "txt"] {
+                    let Some(p) = find_ignore_case(folder, &format!("{name}.{ext}")) else {
+                        continue;
+                    };
+                    if let Ok(text) = fs::read_to_string(p) {
+                        if !text.trim().is_empty() {
+                            return Some(text);
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/// `folder/rel` with every component matched case-insensitively.
+fn find_ignore_case(folder: &std::path::Path, rel: &str) -> Option<PathBuf> {
+    let mut dir = folder.to_path_buf();
+    for part in rel.split('/') {
+        let exact = dir.join(part);
+        if exact.exists() {
+            dir = exact;
+            continue;
+        }
+        let want = part.to_lowercase();
+        dir = fs::read_dir(&dir)
+            .ok()?
+            .flatten()
+            .find(|e| e.file_name().to_string_lossy().to_lowercase() == want)?
+            .path();
+    }
+    Some(dir)
+}
@@ -0,0 +1,53 @@
+use crate::data::config::ProviderKind;
+use crate::playback::provider::{http_agent, is_unknown, Provider, Query, USER_AGENT};
+use serde::Deserialize;
+
+/// LRCLIB: exact lookup by title, artist, album and duration.
+pub struct Lrclib {
+    base: String,
+}
+
+impl Lrclib {
+    pub fn new(base: String) -> Self {
+        Self { base }
+    }
+}
+
+impl Provider for Lrclib {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::Lrclib
+    }
+
+    fn lyrics(&self, q: &Query) -> Option<String> {
+        if q.duration_secs == 0 || is_unknown(&q.title) || is_unknown(&q.artist) || is_unknown(&q.album) {
+            return None;
+        }
+        let resp = http_agent()
+            .get(&format!("{}/api/get", self.base))
+            .set("User-Agent", USER_AGENT)
+            .query("track_name", &q.title)
+            .query("artist_name", &q.artist)
+            .query("album_name", &q.album)
+            .query("duration", &q.duration_secs.to_string())
+            .call()
+            .ok()?;
+
+        if resp.status() != 200 {
+            return None;
+        }
+
+        let body: LrclibResponse = resp.into_json().ok()?;
+        [body.synced_lyrics, body.plain_lyrics]
+            .into_iter()
+            .flatten()
+            .find(|s| !s.trim().is_empty())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct LrclibResponse {
+    #[serde(default, rename = "syncedLyrics")]
+    synced_lyrics: Option<String>,
+    #[serde(default, rename = "plainLyrics")]
+    plain_lyrics: Option<String>,
+}
@@ -0,0 +1,73 @@
+use crate::data::config::ProviderKind;
+use crate::playback::provider::{http_agent, is_unknown, Identification, Provider, Query, USER_AGENT};
+use serde::Deserialize;
+
+/// MusicBrainz recording search: finds the release a cover can be fetched for.
+pub struct MusicBrainz {
+    base: String,
+}
+
+impl MusicBrainz {
+    pub fn new(base: String) -> Self {
+        Self { base }
+    }
+}
+
+impl Provider for MusicBrainz {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::Musicbrainz
+    }
+
+    fn identify(&self, q: &Query) -> Option<Identification> {
+        if q.release_mbid.is_some() || is_unknown(&q.title) || is_unknown(&q.artist) {
+            return None;
+        }
+
+        let mut query = format!("recording:\"{}\" AND artist:\"{}\"", sanitize_mb(&q.title), sanitize_mb(&q.artist));
+        if !is_unknown(&q.album) {
+            query.push_str(&format!(" AND release:\"{}\"", sanitize_mb(&q.album)));
+        }
+
+        let resp = http_agent()
+            .get(&format!("{}/ws/2/recording/", self.base))
+            .set("User-Agent", USER_AGENT)
+            .query("query", &query)
+            .query("fmt", "json")
+            .query("limit", "1")
+            .query("inc", "releases")
+            .call()
+            .ok()?;
+
+        if resp.status() != 200 {
+            return None;
+        }
+
+        let body: MbRecordingResponse = resp.into_json().ok()?;
+        let rec = body.recordings?.into_iter().next()?;
+        let release = rec.releases?.into_iter().next()?;
+        Some(Identification {
+            release_mbid: Some(release.id),
+            ..Default::default()
+        })
+    }
+}
+
+fn sanitize_mb(s: &str) -> String {
+    s.replace('"', " ").trim().to_string()
+}
+
+#[derive(Debug, Deserialize)]
+struct MbRecordingResponse {
+    recordings: Option<Vec<MbRecording>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MbRecording {
+    #[serde(default)]
+    releases: Option<Vec<MbRelease>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MbRelease {
+    id: String,
+}
@@ -0,0 +1,113 @@
+use crate::data::config::ProviderKind;
+use crate::playback::provider::{http_agent, is_unknown, match_score, Provider, Query, USER_AGENT};
+use serde::Deserialize;
+
+/// NetEase Cloud Music: song search, then the LRC and its translation (if any).
+pub struct Netease {
+    base: String,
+}
+
+impl Netease {
+    pub fn new(base: String) -> Self {
+        Self { base }
+    }
+
+    fn search(&self, q: &Query) -> Option<u64> {
+        let keywords = if is_unknown(&q.artist) {
+            q.title.clone()
+        } else {
+            format!("{} {}", q.title, q.artist)
+        };
+        let resp = http_agent()
+            .get(&format!("{}/api/search/get/web", self.base))
+            .set("User-Agent", USER_AGENT)
+            .set("Referer", &self.base)
+            .query("s", &keywords)
+            .query("type", "1")
+            .query("limit", "10")
+            .call()
+            .ok()?;
+        if resp.status() != 200 {
+            return None;
+        }
+        let body: SearchResponse = resp.into_json().ok()?;
+        body.result?
+            .songs?
+            .into_iter()
+            .filter_map(|s| {
+                let artists: Vec<&str> = s.artists.iter().filter_map(|a| a.name.as_deref()).collect();
+                let score = match_score(q, &s.name, &artists, s.duration.map(|ms| ms / 1000))?;
+                Some((score, s.id))
+            })
+            .max_by_key(|(score, _)| *score)
+            .map(|(_, id)| id)
+    }
+}
+
+impl Provider for Netease {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::Netease
+    }
+
+    fn lyrics(&self, q: &Query) -> Option<String> {
+        if is_unknown(&q.title) {
+            return None;
+        }
+        let id = self.search(q)?;
+        let resp = http_agent()
+            .get(&format!("{}/api/song/lyric", self.base))
+            .set("User-Agent", USER_AGENT)
+            .set("Referer", &self.base)
+            .query("id", &id.to_string())
+            .query("lv", "1")
+            .query("tv", "-1")
+            .call()
+            .ok()?;
+        if resp.status() != 200 {
+            return None;
+        }
+        let body: LyricResponse = resp.into_json().ok()?;
+        let lrc = body.lrc.and_then(|l| l.lyric).filter(|s| !s.trim().is_empty())?;
+        // The translation carries the same time tags; appended, it loads as a second track.
+        match body.tlyric.and_then(|l| l.lyric).filter(|s| !s.trim().is_empty()) {
+            Some(tr) => Some(format!("{lrc}\n{tr}")),
+            None => Some(lrc),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    result: Option<SearchResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResult {
+    songs: Option<Vec<Song>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Song {
+    id: u64,
+    name: String,
+    #[serde(default)]
+    artists: Vec<Artist>,
+    /// Milliseconds.
+    duration: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Artist {
+    name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LyricResponse {
+    lrc: Option<LyricBody>,
+    tlyric: Option<LyricBody>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LyricBody {
+    lyric: Option<String>,
+}
@@ -0,0 +1,127 @@
+use crate::data::config::ProviderKind;
+use crate::playback::provider::{decode_entities, http_agent, is_unknown, match_score, Provider, Query, USER_AGENT};
+use serde::Deserialize;
+
+/// QQ Music: song search, then the LRC and its translation (if any).
+pub struct QqMusic {
+    base: String,
+}
+
+impl QqMusic {
+    pub fn new(base: String) -> Self {
+        Self { base }
+    }
+
+    fn search(&self, q: &Query) -> Option<String> {
+        let keywords = if is_unknown(&q.artist) {
+            q.title.clone()
+        } else {
+            format!("{} {}", q.title, q.artist)
+        };
+        let resp = http_agent()
+            .get(&format!("{}/soso/fcgi-bin/client_search_cp", self.base))
+            .set("User-Agent", USER_AGENT)
+            .query("w", &keywords)
+            .query("format", "json")
+            .query("p", "1")
+            .query("n", "10")
+            .call()
+            .ok()?;
+        if resp.status() != 200 {
+            return None;
+        }
+        let body: SearchResponse = serde_json::from_str(strip_jsonp(&resp.into_string().ok()?)).ok()?;
+        body.data?
+            .song?
+            .list
+            .into_iter()
+            .filter_map(|s| {
+                let artists: Vec<&str> = s.singer.iter().filter_map(|a| a.name.as_deref()).collect();
+                let score = match_score(q, &s.songname, &artists, s.interval)?;
+                Some((score, s.songmid))
+            })
+            .max_by_key(|(score, _)| *score)
+            .map(|(_, mid)| mid)
+    }
+}
+
+impl Provider for QqMusic {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::Qqmusic
+    }
+
+    fn lyrics(&self, q: &Query) -> Option<String> {
+        if is_unknown(&q.title) {
+            return None;
+        }
+        let mid = self.search(q)?;
+        // The lyric endpoint refuses requests that don't come from the player page.
+        let resp = http_agent()
+            .get(&format!("{}/lyric/fcgi-bin/fcg_query_lyric_new.fcg", self.base))
+            .set("User-Agent", USER_AGENT)
+            .set("Referer", "https://y.qq.com/")
+            .query("songmid", &mid)
+            .query("format", "json")
+            .query("nobase64", "1")
+            .call()
+            .ok()?;
+        if resp.status() != 200 {
+            return None;
+        }
+        let body: LyricResponse = serde_json::from_str(strip_jsonp(&resp.into_string().ok()?)).ok()?;
+        let lrc = body.lyric.map(|s| decode_entities(&s)).filter(|s| !s.trim().is_empty())?;
+        match body.trans.map(|s| decode_entities(&s)).filter(|s| !s.trim().is_empty()) {
+            Some(tr) => Some(format!("{lrc}\n{tr}")),
+            None => Some(lrc),
+        }
+    }
+}
+
+/// Some endpoints wrap JSON in a callback (`MusicJsonCallback({...})`) even when asked not to.
+fn strip_jsonp(s: &str) -> &str {
+    let s = s.trim();
+    if s.starts_with('{') {
+        return s;
+    }
+    match (s.find('('), s.rfind(')')) {
+        (Some(a), Some(b)) if a < b => &s[a + 1..b],
+        _ => s,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    data: Option<SearchData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchData {
+    song: Option<SongList>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SongList {
+    #[serde(default)]
+    list: Vec<Song>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Song {
+    songmid: String,
+    songname: String,
+    #[serde(default)]
+    singer: Vec<Singer>,
+    /// Seconds.
+    interval: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Singer {
+    name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LyricResponse {
+    lyric: Option<String>,
+    trans: Option<String>,
+}
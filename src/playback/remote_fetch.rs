@@ -1,22 +1,17 @@
-use crate::app::state::{LyricLine, TrackMetadata};
-use crate::playback::metadata::{parse_lrc, parse_plain_lyrics};
-use chromaprint::Chromaprint;
-use symphonia::core::audio::SampleBuffer;
-use symphonia::core::codecs::DecoderOptions;
-use symphonia::core::errors::Error as SymphoniaError;
-use symphonia::core::formats::FormatOptions;
-use symphonia::core::io::MediaSourceStream;
-use symphonia::core::meta::MetadataOptions;
-use symphonia::core::probe::Hint;
-use serde::Deserialize;
+use crate::app::state::{LyricTrack, TrackMetadata};
+use crate::data::config::ProviderKind;
+use crate::playback::metadata::parse_lyric_tracks;
+use crate::playback::lookup_cache::{unix_now, CacheTtl, CachedCover, CachedLyrics, Checked, LookupCache, LookupEntry, SAVE_INTERVAL};
+use crate::playback::provider::{build_chain, is_unknown, Identification, Provider, ProviderChainConfig, Query};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
-use std::io::Read;
 use std::path::{Path, PathBuf};
 use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct TrackKey {
     pub path: Option<PathBuf>,
     pub title: String,
@@ -41,8 +36,8 @@ impl TrackKey {
 pub struct FetchOptions {
     pub enable_fetch: bool,
     pub download: bool,
-    pub enable_fingerprint: bool,
-    pub acoustid_api_key: Option<String>,
+    pub providers: ProviderChainConfig,
+    pub cache_ttl: CacheTtl,
 }
 
 #[derive(Debug, Clone)]
@@ -58,6 +53,17 @@ pub struct RemoteFetchRequest {
     pub options: FetchOptions,
 }
 
+/// What a lookup found, before it is turned into a `RemoteFetchResult` (or tag proposals).
+#[derive(Debug, Default)]
+pub struct Lookup {
+    pub identity: Option<Identification>,
+    pub lyrics: Option<CachedLyrics>,
+    /// Image bytes and content type.
+    pub cover: Option<(Vec<u8>, Option<String>)>,
+    /// Some part wasn't answered by the cache, so providers were asked.
+    pub queried: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct RemoteFetchResult {
     pub key: TrackKey,
@@ -65,7 +71,7 @@ pub struct RemoteFetchResult {
     pub title: Option<String>,
     pub artist: Option<String>,
     pub album: Option<String>,
-    pub lyrics: Option<Vec<LyricLine>>,
+    pub lyrics: Option<Vec<LyricTrack>>,
     pub cover: Option<Vec<u8>>,
     pub cover_hash: Option<u64>,
     pub cover_folder: Option<PathBuf>,
@@ -88,9 +94,9 @@ impl RemoteFetchResult {
                 track.album = al.clone();
             }
         }
-        if track.lyrics.is_none() {
-            if let Some(lines) = self.lyrics.as_ref() {
-                track.lyrics = Some(lines.clone());
+        if track.lyrics.is_empty() {
+            if let Some(tracks) = self.lyrics.as_ref() {
+                track.lyrics = tracks.clone();
             }
         }
         if track.cover.is_none() {
@@ -105,15 +111,15 @@ impl RemoteFetchResult {
     }
 }
 
-pub fn start_remote_fetch_worker() -> (Sender<RemoteFetchRequest>, Receiver<RemoteFetchResult>) {
+pub fn start_remote_fetch_worker(cache: Arc<Mutex<LookupCache>>) -> (Sender<RemoteFetchRequest>, Receiver<RemoteFetchResult>) {
     let (tx, rx) = mpsc::channel::<RemoteFetchRequest>();
     let (res_tx, res_rx) = mpsc::channel::<RemoteFetchResult>();
 
-    std::thread::spawn(move || worker_loop(rx, res_tx));
+    std::thread::spawn(move || worker_loop(rx, res_tx, cache));
     (tx, res_rx)
 }
 
-fn worker_loop(rx: Receiver<RemoteFetchRequest>, res_tx: Sender<RemoteFetchResult>) {
+fn worker_loop(rx: Receiver<RemoteFetchRequest>, res_tx: Sender<RemoteFetchResult>, cache: Arc<Mutex<LookupCache>>) {
     let debounce = Duration::from_millis(700);
     let throttle = Duration::from_secs(120);
     let mut pending: Option<RemoteFetchRequest> = None;
@@ -122,9 +128,9 @@ fn worker_loop(rx: Receiver<RemoteFetchRequest>, res_tx: Sender<RemoteFetchResul
     loop {
         let mut req = match pending.take() {
             Some(r) => r,
-            None => match rx.recv() {
-                Ok(r) => r,
-                Err(_) => break,
+            None => match next_request(&rx, &cache) {
+                Some(r) => r,
+                None => break,
             },
         };
 
@@ -147,23 +153,40 @@ fn worker_loop(rx: Receiver<RemoteFetchRequest>, res_tx: Sender<RemoteFetchResul
         }
         last_attempt.insert(req.key.clone(), now);
 
-        if let Some(res) = process_request(req) {
+        if let Some(res) = process_request(req, &cache) {
             let _ = res_tx.send(res);
         }
     }
 }
 
-fn process_request(req: RemoteFetchRequest) -> Option<RemoteFetchResult> {
+/// Wait for the next request; lookups the debounced index save skipped are written once
+/// things go quiet. None when the app is gone.
+fn next_request(rx: &Receiver<RemoteFetchRequest>, cache: &Mutex<LookupCache>) -> Option<RemoteFetchRequest> {
+    if cache.lock().is_ok_and(|c| c.is_dirty()) {
+        match rx.recv_timeout(SAVE_INTERVAL) {
+            Ok(r) => return Some(r),
+            Err(mpsc::RecvTimeoutError::Disconnected) => return None,
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                if let Ok(mut c) = cache.lock() {
+                    if let Err(e) = c.flush() {
+                        log::warn!("lookup cache save failed: {e}");
+                    }
+                }
+            }
+        }
+    }
+    rx.recv().ok()
+}
+
+fn process_request(req: RemoteFetchRequest, cache: &Mutex<LookupCache>) -> Option<RemoteFetchResult> {
     if !req.options.enable_fetch {
         return None;
     }
+    if req.has_lyrics && req.has_cover {
+        return None;
+    }
 
-    let mut title = req.title.clone();
-    let mut artist = req.artist.clone();
-    let mut album = req.album.clone();
-    let mut duration_secs = req.duration_secs;
-    let mut release_mbid: Option<String> = None;
-
+    let found = lookup(&req, cache);
     let mut out = RemoteFetchResult {
         key: req.key.clone(),
         path: req.path.clone(),
@@ -176,364 +199,200 @@ fn process_request(req: RemoteFetchRequest) -> Option<RemoteFetchResult> {
         cover_folder: None,
     };
 
-    let metadata_missing = is_unknown(&artist) && is_unknown(&album);
-    let need_lyrics = !req.has_lyrics;
-    let need_cover = !req.has_cover;
+    if let Some(id) = found.identity {
+        out.title = id.title;
+        out.artist = id.artist;
+        out.album = id.album;
+    }
 
-    if metadata_missing && req.options.enable_fingerprint && (need_lyrics || need_cover) {
-        if let (Some(path), Some(key)) = (req.path.as_deref(), req.options.acoustid_api_key.as_deref()) {
-            if let Some((fp, fp_dur)) = chromaprint_fingerprint(path) {
-                if duration_secs == 0 {
-                    duration_secs = fp_dur as u64;
-                }
-                if let Some(ac) = acoustid_lookup(key, &fp, fp_dur as u32) {
-                    if let Some(t) = ac.title {
-                        if is_unknown(&title) {
-                            title = t.clone();
-                        }
-                        out.title = Some(t);
-                    }
-                    if let Some(a) = ac.artist {
-                        if is_unknown(&artist) {
-                            artist = a.clone();
-                        }
-                        out.artist = Some(a);
-                    }
-                    if let Some(al) = ac.album {
-                        if is_unknown(&album) {
-                            album = al.clone();
-                        }
-                        out.album = Some(al);
-                    }
-                    if let Some(mbid) = ac.release_mbid {
-                        release_mbid = Some(mbid);
-                    }
-                }
+    if let Some(l) = found.lyrics {
+        out.lyrics = parse_lyric_tracks(&l.text);
+        // Lyrics from the local collection are already on disk.
+        if req.options.download && l.provider != ProviderKind::Local {
+            if let Some(path) = req.path.as_deref() {
+                let _ = save_lrc(path, &l.text);
             }
         }
     }
 
-    if need_lyrics {
-        if duration_secs > 0 && !is_unknown(&title) && !is_unknown(&artist) && !is_unknown(&album) {
-            if let Some(lrc) = lrclib_fetch(&title, &artist, &album, duration_secs) {
-                if let Some(lines) = parse_lrc(&lrc).or_else(|| parse_plain_lyrics(&lrc)) {
-                    out.lyrics = Some(lines);
-                }
-                if req.options.download {
-                    if let Some(path) = req.path.as_deref() {
-                        let _ = save_lrc(path, &lrc);
-                    }
-                }
+    if let Some((bytes, content_type)) = found.cover {
+        out.cover_hash = Some(hash_bytes(&bytes));
+        if let Some(path) = req.path.as_deref() {
+            if let Some(folder) = path.parent() {
+                out.cover_folder = Some(folder.to_path_buf());
             }
         }
-    }
-
-    if need_cover {
-        if let Some(mbid) = release_mbid.clone().or_else(|| musicbrainz_release_id(&title, &artist, &album)) {
-            if let Some((bytes, content_type)) = cover_art_archive_fetch(&mbid) {
-                out.cover_hash = Some(hash_bytes(&bytes));
-                out.cover = Some(bytes.clone());
-                if let Some(path) = req.path.as_deref() {
-                    if let Some(folder) = path.parent() {
-                        out.cover_folder = Some(folder.to_path_buf());
-                    }
-                }
 
-                if req.options.download {
-                    if let Some(path) = req.path.as_deref() {
-                        let _ = save_cover(path, &bytes, content_type.as_deref());
-                    }
-                }
+        if req.options.download {
+            if let Some(path) = req.path.as_deref() {
+                let _ = save_cover(path, &bytes, content_type.as_deref());
             }
         }
+        out.cover = Some(bytes);
     }
 
     let changed = out.title.is_some() || out.artist.is_some() || out.album.is_some() || out.lyrics.is_some() || out.cover.is_some();
     if changed { Some(out) } else { None }
 }
 
-fn is_unknown(s: &str) -> bool {
-    let t = s.trim();
-    t.is_empty() || t.eq_ignore_ascii_case("unknown")
-}
-
-fn lrclib_fetch(title: &str, artist: &str, album: &str, duration_secs: u64) -> Option<String> {
-    let agent = "cli-music-player/0.1.0 (https://github.com)";
-    let resp = http_agent()
-        .get("https://lrclib.net/api/get")
-        .set("User-Agent", agent)
-        .query("track_name", title)
-        .query("artist_name", artist)
-        .query("album_name", album)
-        .query("duration", &duration_secs.to_string())
-        .call()
-        .ok()?;
-
-    if resp.status() != 200 {
-        return None;
-    }
+/// What the providers (or the lookup cache) know about the song in `req`: identification
+/// always, lyrics unless `has_lyrics`, a cover unless `has_cover`. Nothing is written next
+/// to the audio file.
+pub fn lookup(req: &RemoteFetchRequest, cache: &Mutex<LookupCache>) -> Lookup {
+    let need_lyrics = !req.has_lyrics;
+    let need_cover = !req.has_cover;
 
-    let body: LrclibResponse = resp.into_json().ok()?;
-    if let Some(s) = body.synced_lyrics {
-        if !s.trim().is_empty() {
-            return Some(s);
-        }
-    }
-    if let Some(p) = body.plain_lyrics {
-        if !p.trim().is_empty() {
-            return Some(p);
+    let chain = build_chain(&req.options.providers);
+    let mut q = Query {
+        path: req.path.clone(),
+        title: req.title.clone(),
+        artist: req.artist.clone(),
+        album: req.album.clone(),
+        duration_secs: req.duration_secs,
+        release_mbid: None,
+    };
+    let mut found = Lookup::default();
+
+    // Lookups still valid in the cache are answered from it; only the rest hit the network.
+    // A failed refresh keeps serving an expired hit (the network may just be down).
+    let ttl = req.options.cache_ttl;
+    let now = unix_now();
+    let cached = cache.lock().ok().and_then(|c| c.get(&req.key).cloned());
+    let mut entry = cached.clone().unwrap_or_else(|| LookupEntry::new(req.key.clone()));
+
+    // Identification: fill in what the tags don't say (and a release for the cover).
+    let identified = |q: &Query| !is_unknown(&q.title) && !is_unknown(&q.artist) && (!need_cover || q.release_mbid.is_some());
+    let identity = match entry.identity.as_ref().and_then(|c| c.fresh(ttl, now)) {
+        Some(hit) => hit.cloned(),
+        None if identified(&q) => None,
+        None => {
+            found.queried = true;
+            let mut id_found: Option<Identification> = None;
+            for p in &chain {
+                let mut probe = q.clone();
+                if let Some(id) = id_found.as_ref() {
+                    apply_identity(&mut probe, id);
+                }
+                if identified(&probe) {
+                    break;
+                }
+                if let Some(id) = p.identify(&probe) {
+                    id_found.get_or_insert_with(Identification::default).merge(id);
+                }
+            }
+            refresh(&mut entry.identity, id_found, ttl.miss_days)
         }
+    };
+    if let Some(id) = identity.as_ref() {
+        apply_identity(&mut q, id);
     }
-    None
-}
-
-#[derive(Debug, Deserialize)]
-struct LrclibResponse {
-    #[serde(default, rename = "syncedLyrics")]
-    synced_lyrics: Option<String>,
-    #[serde(default, rename = "plainLyrics")]
-    plain_lyrics: Option<String>,
-}
-
-fn musicbrainz_release_id(title: &str, artist: &str, album: &str) -> Option<String> {
-    if is_unknown(title) || is_unknown(artist) {
-        return None;
-    }
-
-    let mut query = format!("recording:\"{}\" AND artist:\"{}\"", sanitize_mb(title), sanitize_mb(artist));
-    if !is_unknown(album) {
-        query.push_str(&format!(" AND release:\"{}\"", sanitize_mb(album)));
-    }
+    found.identity = identity;
 
-    let agent = "cli-music-player/0.1.0 (https://github.com)";
-    let resp = http_agent()
-        .get("https://musicbrainz.org/ws/2/recording/")
-        .set("User-Agent", agent)
-        .query("query", &query)
-        .query("fmt", "json")
-        .query("limit", "1")
-        .query("inc", "releases")
-        .call()
-        .ok()?;
-
-    if resp.status() != 200 {
-        return None;
+    if need_lyrics {
+        found.lyrics = match entry.lyrics.as_ref().and_then(|c| c.fresh(ttl, now)) {
+            Some(hit) => hit.cloned(),
+            None => {
+                found.queried = true;
+                refresh(&mut entry.lyrics, fetch_lyrics(&chain, &q), ttl.miss_days)
+            }
+        };
     }
 
-    let body: MbRecordingResponse = resp.into_json().ok()?;
-    let rec = body.recordings?.into_iter().next()?;
-    if let Some(releases) = rec.releases {
-        return releases.into_iter().next().map(|r| r.id);
+    if need_cover {
+        let fresh = entry.cover.as_ref().and_then(|c| c.fresh(ttl, now)).map(|hit| hit.cloned());
+        found.cover = match fresh {
+            Some(None) => None,
+            // A fresh hit whose file went missing is looked up again.
+            hit => match hit.flatten().and_then(|c| read_cached_cover(cache, &c)) {
+                Some(c) => Some(c),
+                None => {
+                    found.queried = true;
+                    match chain.iter().find_map(|p| p.cover(&q)) {
+                        Some(c) => {
+                            let hash = hash_bytes(&c.bytes);
+                            if let Ok(mut cache) = cache.lock() {
+                                let _ = cache.write_cover(hash, &c.bytes);
+                            }
+                            let stored = CachedCover { hash, content_type: c.content_type.clone() };
+                            refresh(&mut entry.cover, Some(stored), ttl.miss_days);
+                            Some((c.bytes, c.content_type))
+                        }
+                        None => refresh(&mut entry.cover, None, ttl.miss_days).and_then(|c| read_cached_cover(cache, &c)),
+                    }
+                }
+            },
+        };
     }
-    None
-}
-
-fn sanitize_mb(s: &str) -> String {
-    s.replace('"', " ").trim().to_string()
-}
-
-#[derive(Debug, Deserialize)]
-struct MbRecordingResponse {
-    recordings: Option<Vec<MbRecording>>,
-}
-
-#[derive(Debug, Deserialize)]
-struct MbRecording {
-    #[serde(default)]
-    releases: Option<Vec<MbRelease>>,
-}
 
-#[derive(Debug, Deserialize)]
-struct MbRelease {
-    id: String,
-}
-
-fn cover_art_archive_fetch(release_id: &str) -> Option<(Vec<u8>, Option<String>)> {
-    let agent = "cli-music-player/0.1.0 (https://github.com)";
-    let url = format!("https://coverartarchive.org/release/{}/front-500", release_id);
-    let resp = http_agent().get(&url).set("User-Agent", agent).call().ok()?;
-    if resp.status() != 200 {
-        return None;
-    }
-    if let Some(len) = resp.header("Content-Length") {
-        if let Ok(n) = len.parse::<u64>() {
-            if n > 5 * 1024 * 1024 {
-                return None;
+    if cached.as_ref() != Some(&entry) {
+        if let Ok(mut cache) = cache.lock() {
+            cache.put(entry);
+            if let Err(e) = cache.save_if_due() {
+                log::warn!("lookup cache save failed: {e}");
             }
         }
     }
-    let content_type = resp.header("Content-Type").map(|s| s.to_string());
-    let mut reader = resp.into_reader();
-    let mut bytes = Vec::new();
-    reader.read_to_end(&mut bytes).ok()?;
-    if bytes.is_empty() {
-        return None;
-    }
-    Some((bytes, content_type))
-}
-
-#[derive(Debug, Deserialize)]
-struct AcoustidResponse {
-    status: String,
-    results: Option<Vec<AcoustidResult>>,
+    found
 }
 
-#[derive(Debug, Deserialize)]
-struct AcoustidResult {
-    recordings: Option<Vec<AcoustidRecording>>,
-}
-
-#[derive(Debug, Deserialize)]
-struct AcoustidRecording {
-    title: Option<String>,
-    artists: Option<Vec<AcoustidArtist>>,
-    releases: Option<Vec<AcoustidRelease>>,
-}
-
-#[derive(Debug, Deserialize)]
-struct AcoustidArtist {
-    name: Option<String>,
-}
-
-#[derive(Debug, Deserialize)]
-struct AcoustidRelease {
-    id: Option<String>,
-    title: Option<String>,
-}
-
-struct AcoustidLookup {
-    title: Option<String>,
-    artist: Option<String>,
-    album: Option<String>,
-    release_mbid: Option<String>,
-}
-
-fn acoustid_lookup(api_key: &str, fingerprint: &str, duration_secs: u32) -> Option<AcoustidLookup> {
-    if api_key.trim().is_empty() {
-        return None;
-    }
-
-    let agent = "cli-music-player/0.1.0 (https://github.com)";
-    let resp = http_agent()
-        .get("https://api.acoustid.org/v2/lookup")
-        .set("User-Agent", agent)
-        .query("client", api_key)
-        .query("meta", "recordings+releases")
-        .query("duration", &duration_secs.to_string())
-        .query("fingerprint", fingerprint)
-        .query("format", "json")
-        .call()
-        .ok()?;
-
-    if resp.status() != 200 {
-        return None;
-    }
-
-    let body: AcoustidResponse = resp.into_json().ok()?;
-    if body.status != "ok" {
-        return None;
-    }
-    let result = body.results?.into_iter().next()?;
-    let rec = result.recordings?.into_iter().next()?;
-
-    let title = rec.title;
-    let artist = rec.artists.and_then(|mut a| a.pop()).and_then(|a| a.name);
-    let (album, release_mbid) = if let Some(mut releases) = rec.releases {
-        if let Some(r) = releases.pop() {
-            (r.title, r.id)
-        } else {
-            (None, None)
+/// First hit wins, except that synced lyrics from a later provider beat plain ones.
+fn fetch_lyrics(chain: &[Box<dyn Provider>], q: &Query) -> Option<CachedLyrics> {
+    let mut plain: Option<CachedLyrics> = None;
+    for p in chain {
+        let Some(text) = p.lyrics(q) else {
+            continue;
+        };
+        let Some(tracks) = parse_lyric_tracks(&text) else {
+            continue;
+        };
+        let found = CachedLyrics { provider: p.kind(), text };
+        if tracks.first().is_some_and(|t| t.is_synced()) {
+            return Some(found);
         }
-    } else {
-        (None, None)
-    };
-
-    Some(AcoustidLookup {
-        title,
-        artist,
-        album,
-        release_mbid,
-    })
-}
-
-fn chromaprint_fingerprint(path: &Path) -> Option<(String, u32)> {
-    let file = std::fs::File::open(path).ok()?;
-    let mss = MediaSourceStream::new(Box::new(file), Default::default());
-    let hint = Hint::new();
-    let format_opts: FormatOptions = Default::default();
-    let metadata_opts: MetadataOptions = Default::default();
-    let decoder_opts: DecoderOptions = Default::default();
-
-    let probed = symphonia::default::get_probe()
-        .format(&hint, mss, &format_opts, &metadata_opts)
-        .ok()?;
-    let mut format = probed.format;
-
-    let track = format.default_track()?;
-    if track.codec_params.codec == symphonia::core::codecs::CODEC_TYPE_NULL {
-        return None;
+        plain.get_or_insert(found);
     }
+    plain
+}
 
-    let track_id = track.id;
-    let channels = track
-        .codec_params
-        .channels
-        .map(|c| c.count() as u16)
-        .unwrap_or(2)
-        .max(1);
-    let sample_rate = track.codec_params.sample_rate.unwrap_or(44100).max(1);
-
-    let mut decoder = symphonia::default::get_codecs()
-        .make(&track.codec_params, &decoder_opts)
-        .ok()?;
-
-    let mut chroma = Chromaprint::new();
-    if !chroma.start(sample_rate as i32, channels as i32) {
-        return None;
+/// Record a new lookup in `slot` and return what to use. A miss never replaces a hit: the
+/// old hit is kept (and returned) so it is retried next time. Misses aren't stored when
+/// their TTL is 0.
+fn refresh<T: Clone>(slot: &mut Option<Checked<T>>, found: Option<T>, miss_days: u32) -> Option<T> {
+    match found {
+        Some(v) => {
+            *slot = Some(Checked::now(Some(v.clone())));
+            Some(v)
+        }
+        None => match slot.as_ref().and_then(|c| c.found.clone()) {
+            Some(stale) => Some(stale),
+            None => {
+                *slot = (miss_days > 0).then(|| Checked::now(None));
+                None
+            }
+        },
     }
+}
 
-    let mut total_frames: u64 = 0;
-    let mut sample_buf: Option<SampleBuffer<i16>> = None;
-
-    loop {
-        let packet = match format.next_packet() {
-            Ok(p) => p,
-            Err(_) => break,
-        };
-
-        if packet.track_id() != track_id {
-            continue;
-        }
+fn read_cached_cover(cache: &Mutex<LookupCache>, cover: &CachedCover) -> Option<(Vec<u8>, Option<String>)> {
+    let bytes = cache.lock().ok()?.read_cover(cover)?;
+    Some((bytes, cover.content_type.clone()))
+}
 
-        match decoder.decode(&packet) {
-            Ok(audio_buf) => {
-                if sample_buf.is_none() {
-                    let spec = *audio_buf.spec();
-                    let duration = audio_buf.capacity() as u64;
-                    sample_buf = Some(SampleBuffer::<i16>::new(duration, spec));
-                }
-                if let Some(sb) = &mut sample_buf {
-                    sb.copy_interleaved_ref(audio_buf);
-                    let samples = sb.samples();
-                    if !chroma.feed(samples) {
-                        return None;
-                    }
-                    let frames = samples.len() as u64 / channels as u64;
-                    total_frames = total_frames.saturating_add(frames);
-                }
+/// Merge identification into the query, only where the tags say nothing.
+fn apply_identity(q: &mut Query, id: &Identification) {
+    for (found, known) in [(&id.title, &mut q.title), (&id.artist, &mut q.artist), (&id.album, &mut q.album)] {
+        if let Some(v) = found {
+            if is_unknown(known) {
+                *known = v.clone();
             }
-            Err(SymphoniaError::DecodeError(_)) => continue,
-            Err(_) => break,
         }
     }
-
-    if !chroma.finish() {
-        return None;
+    if q.release_mbid.is_none() {
+        q.release_mbid = id.release_mbid.clone();
+    }
+    if q.duration_secs == 0 {
+        q.duration_secs = id.duration_secs.unwrap_or(0);
     }
-
-    let fp = chroma.fingerprint()?;
-    let duration_secs = (total_frames as f64 / sample_rate as f64).round() as u32;
-    Some((fp, duration_secs))
 }
 
 fn save_lrc(audio_path: &Path, lrc: &str) -> std::io::Result<()> {
@@ -574,9 +433,3 @@ fn hash_bytes(bytes: &[u8]) -> u64 {
     bytes.hash(&mut h);
     h.finish()
 }
-
-fn http_agent() -> ureq::Agent {
-    ureq::AgentBuilder::new()
-        .timeout(Duration::from_secs(8))
-        .build()
-}
@@ -0,0 +1,269 @@
+// Manual tag edits (title, artist, album, album artist, track / disc number, year, genre)
+// written back with lofty. MP3s go through their ID3v2 tag like the batch tagger, so
+// frames the generic tag can't hold are kept. Fields the file's tag format has no slot
+// for are left out and reported. The files are written on a worker thread (`EditJob`).
+
+use crate::data::library::LibraryTrack;
+use crate::playback::tagger::{is_mpeg, library_tags, read_mpeg};
+use anyhow::{anyhow, Context, Result};
+use lofty::{Accessor, ItemKey, MergeTag, SplitTag, Tag, TagExt, TagType, TaggedFileExt};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+/// Highest track / disc number accepted.
+const MAX_NUMBER: u32 = 999;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditField {
+    Title,
+    Artist,
+    Album,
+    AlbumArtist,
+    TrackNo,
+    DiscNo,
+    Year,
+    Genre,
+}
+
+impl EditField {
+    pub const ALL: [EditField; 8] = [
+        EditField::Title,
+        EditField::Artist,
+        EditField::Album,
+        EditField::AlbumArtist,
+        EditField::TrackNo,
+        EditField::DiscNo,
+        EditField::Year,
+        EditField::Genre,
+    ];
+
+    /// Fields offered when a whole album is edited (title and track number stay per track).
+    pub const ALBUM: [EditField; 6] = [
+        EditField::Album,
+        EditField::AlbumArtist,
+        EditField::Artist,
+        EditField::DiscNo,
+        EditField::Year,
+        EditField::Genre,
+    ];
+
+    pub fn as_label(self) -> &'static str {
+        match self {
+            EditField::Title => "Title",
+            EditField::Artist => "Artist",
+            EditField::Album => "Album",
+            EditField::AlbumArtist => "Album artist",
+            EditField::TrackNo => "Track #",
+            EditField::DiscNo => "Disc #",
+            EditField::Year => "Year",
+            EditField::Genre => "Genre",
+        }
+    }
+
+    pub fn numeric(self) -> bool {
+        matches!(self, EditField::TrackNo | EditField::DiscNo | EditField::Year)
+    }
+
+    /// The field as shown in the editor ("" when unset).
+    pub fn value_of(self, t: &LibraryTrack) -> String {
+        let num = |n: Option<u32>| n.map(|n| n.to_string()).unwrap_or_default();
+        match self {
+            EditField::Title => t.title.clone(),
+            EditField::Artist => t.artist.clone(),
+            EditField::Album => t.album.clone(),
+            EditField::AlbumArtist => t.album_artist.clone(),
+            EditField::TrackNo => num(t.track_no),
+            EditField::DiscNo => num(t.disc_no),
+            EditField::Year => num(t.year),
+            EditField::Genre => t.genre.clone(),
+        }
+    }
+
+    fn item_keys(self) -> &'static [ItemKey] {
+        match self {
+            EditField::Title => &[ItemKey::TrackTitle],
+            EditField::Artist => &[ItemKey::TrackArtist],
+            EditField::Album => &[ItemKey::AlbumTitle],
+            EditField::AlbumArtist => &[ItemKey::AlbumArtist],
+            EditField::TrackNo => &[ItemKey::TrackNumber],
+            EditField::DiscNo => &[ItemKey::DiscNumber],
+            // Formats without a year item keep it in the recording date.
+            EditField::Year => &[ItemKey::Year, ItemKey::RecordingDate],
+            EditField::Genre => &[ItemKey::Genre],
+        }
+    }
+}
+
+/// Check a value before anything is written. Empty clears the field.
+pub fn validate(field: EditField, value: &str) -> Result<(), String> {
+    let v = value.trim();
+    if v.is_empty() || !field.numeric() {
+        return Ok(());
+    }
+    let n: u32 = v
+        .parse()
+        .map_err(|_| format!("{} must be a whole number", field.as_label()))?;
+    let (min, max) = match field {
+        EditField::Year => (1000, 9999),
+        _ => (1, MAX_NUMBER),
+    };
+    if n < min || n > max {
+        return Err(format!("{} must be between {min} and {max}", field.as_label()));
+    }
+    Ok(())
+}
+
+/// Fields left out of one file because its tag format has no slot for them.
+#[derive(Debug, Default)]
+pub struct EditReport {
+    pub unsupported: Vec<(EditField, TagType)>,
+}
+
+/// Write validated edits into one file. Returns what the tag format could not take.
+pub fn write_edits(path: &Path, edits: &[(EditField, String)]) -> Result<EditReport> {
+    let mut report = EditReport::default();
+    let mut apply = |tag: &mut Tag| {
+        let tag_type = tag.tag_type();
+        for (field, value) in edits {
+            let value = value.trim();
+            if !field.item_keys().iter().any(|k| k.map_key(tag_type, false).is_some()) {
+                report.unsupported.push((*field, tag_type));
+                continue;
+            }
+            if value.is_empty() {
+                for key in field.item_keys() {
+                    tag.remove_key(key);
+                }
+                continue;
+            }
+            match (field, value.parse::<u32>()) {
+                (EditField::TrackNo, Ok(n)) => tag.set_track(n),
+                (EditField::DiscNo, Ok(n)) => tag.set_disk(n),
+                (EditField::Year, Ok(n)) => tag.set_year(n),
+                _ => {
+                    tag.insert_text(field.item_keys()[0].clone(), value.to_string());
+                }
+            }
+        }
+    };
+
+    if is_mpeg(path)? {
+        let mut mpeg = read_mpeg(path)?;
+        let (rest, mut tag) = mpeg.remove_id3v2().unwrap_or_default().split_tag();
+        apply(&mut tag);
+        let id3 = rest.merge_tag(tag);
+        id3.save_to_path(path).with_context(|| format!("write {}", path.display()))?;
+    } else {
+        let mut tagged = lofty::read_from_path(path)?;
+        let tag_type = tagged.primary_tag_type();
+        if tagged.tag(tag_type).is_none() {
+            tagged.insert_tag(Tag::new(tag_type));
+        }
+        let tag = tagged.tag_mut(tag_type).ok_or_else(|| anyhow!("no {tag_type:?} tag"))?;
+        apply(tag);
+        tag.save_to_path(path).with_context(|| format!("write {}", path.display()))?;
+    }
+    Ok(report)
+}
+
+/// What writing the edits into every file of the editor did.
+#[derive(Debug, Default)]
+pub struct EditOutcome {
+    pub written: usize,
+    /// Fields some file's tag format had no slot for, as "Disc # (Id3v1)", once each.
+    pub unsupported: Vec<String>,
+    pub failed: Vec<(PathBuf, String)>,
+    /// Tags before / after of the written files.
+    pub retagged: Vec<(LibraryTrack, LibraryTrack)>,
+}
+
+/// Edits being written in the background; `poll` once per frame.
+#[derive(Debug)]
+pub struct EditJob {
+    rx: Receiver<EditOutcome>,
+}
+
+impl EditJob {
+    /// Write `edits` into each of `tracks` (the tags they had when the editor opened).
+    pub fn spawn(tracks: Vec<LibraryTrack>, edits: Vec<(EditField, String)>) -> Self {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let mut outcome = EditOutcome::default();
+            for old in tracks {
+                match write_edits(&old.path, &edits) {
+                    Ok(report) => {
+                        outcome.written += 1;
+                        for (field, tag_type) in report.unsupported {
+                            let note = format!("{} ({tag_type:?})", field.as_label());
+                            if !outcome.unsupported.contains(&note) {
+                                outcome.unsupported.push(note);
+                            }
+                        }
+                        if let Some(new) = library_tags(&old.path) {
+                            outcome.retagged.push((old, new));
+                        }
+                    }
+                    Err(e) => outcome.failed.push((old.path.clone(), format!("{e:#}"))),
+                }
+            }
+            let _ = tx.send(outcome);
+        });
+        Self { rx }
+    }
+
+    /// The outcome once every file has been handled.
+    pub fn poll(&self) -> Option<EditOutcome> {
+        match self.rx.try_recv() {
+            Ok(outcome) => Some(outcome),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(EditOutcome {
+                failed: vec![(PathBuf::new(), "tag writer stopped".to_string())],
+                ..Default::default()
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_are_checked_and_text_is_not() {
+        assert_eq!(validate(EditField::Title, "  anything 123 "), Ok(()));
+        assert_eq!(validate(EditField::TrackNo, ""), Ok(()));
+        assert_eq!(validate(EditField::TrackNo, " 12 "), Ok(()));
+        assert_eq!(validate(EditField::DiscNo, "999"), Ok(()));
+        assert_eq!(validate(EditField::Year, "1969"), Ok(()));
+
+        assert_eq!(validate(EditField::TrackNo, "0"), Err("Track # must be between 1 and 999".to_string()));
+        assert_eq!(validate(EditField::DiscNo, "1000"), Err("Disc # must be between 1 and 999".to_string()));
+        assert_eq!(validate(EditField::Year, "99"), Err("Year must be between 1000 and 9999".to_string()));
+        assert_eq!(validate(EditField::Year, "1969-07"), Err("Year must be a whole number".to_string()));
+        assert_eq!(validate(EditField::TrackNo, "-3"), Err("Track # must be a whole number".to_string()));
+    }
+
+    #[test]
+    fn unreadable_files_are_reported_without_stopping_the_rest() {
+        let dir = tempfile::tempdir().unwrap();
+        let tracks: Vec<LibraryTrack> = ["a.flac", "b.mp3"]
+            .iter()
+            .map(|n| {
+                let path = dir.path().join(n);
+                std::fs::write(&path, b"not audio").unwrap();
+                LibraryTrack { path, ..Default::default() }
+            })
+            .collect();
+        let job = EditJob::spawn(tracks, vec![(EditField::Title, "New".to_string())]);
+        let outcome = loop {
+            if let Some(o) = job.poll() {
+                break o;
+            }
+            thread::sleep(std::time::Duration::from_millis(5));
+        };
+        assert_eq!(outcome.written, 0);
+        assert_eq!(outcome.failed.len(), 2);
+        assert!(outcome.retagged.is_empty());
+    }
+}
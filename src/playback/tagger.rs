@@ -0,0 +1,826 @@
+// Batch tagger ("fix my library"): look songs up the way the remote fetch worker does,
+// propose tag changes for review, then write them into the files with lofty (title /
+// artist / album, a front cover, lyrics as USLT plus SYLT for synced MP3 lyrics).
+// Only empty fields are filled. Every write is recorded in `<asset root>/tagger/journal.json`
+// so a batch can be undone.
+
+use crate::data::assets;
+use crate::data::config::ProviderKind;
+use crate::data::library::LibraryTrack;
+use crate::playback::library_scanner::file_stamp;
+use crate::playback::lookup_cache::{unix_now, LookupCache};
+use crate::playback::metadata::{parse_lrc, read_cover_for_audio, read_cover_from_folder, read_library_tags, read_lyrics_text_for_audio};
+use crate::playback::provider::is_unknown;
+use crate::playback::remote_fetch::{lookup, FetchOptions, RemoteFetchRequest, TrackKey};
+use anyhow::{anyhow, Context, Result};
+use lofty::id3::v2::{Frame, FrameFlags, FrameId, FrameValue, SyncTextContentType, SynchronizedText, TimestampFormat};
+use lofty::mpeg::MpegFile;
+use lofty::{AudioFile, FileType, ItemKey, MergeTag, ParseOptions, Picture, PictureType, SplitTag, Tag, TagExt, TaggedFileExt, TextEncoding};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Undo keeps this many batches.
+const MAX_JOURNAL_BATCHES: usize = 20;
+
+/// MusicBrainz asks for at most one request per second.
+const LOOKUP_PACE: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagField {
+    Title,
+    Artist,
+    Album,
+    Lyrics,
+}
+
+impl TagField {
+    pub fn as_label(self) -> &'static str {
+        match self {
+            TagField::Title => "Title",
+            TagField::Artist => "Artist",
+            TagField::Album => "Album",
+            TagField::Lyrics => "Lyrics",
+        }
+    }
+
+    fn item_key(self) -> ItemKey {
+        match self {
+            TagField::Title => ItemKey::TrackTitle,
+            TagField::Artist => ItemKey::TrackArtist,
+            TagField::Album => ItemKey::AlbumTitle,
+            TagField::Lyrics => ItemKey::Lyrics,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: TagField,
+    pub before: Option<String>,
+    pub after: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProposedLyrics {
+    pub text: String,
+    /// None for the song's own `.lrc` / `.txt` file.
+    pub provider: Option<ProviderKind>,
+    pub synced: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProposedCover {
+    pub bytes: Vec<u8>,
+    /// Found next to the song rather than fetched.
+    pub local: bool,
+}
+
+/// What the tagger would write into one file.
+#[derive(Debug, Clone)]
+pub struct TagProposal {
+    pub path: PathBuf,
+    /// Title / artist / album, only where the file's tag is empty or "Unknown".
+    pub fields: Vec<FieldChange>,
+    pub lyrics: Option<ProposedLyrics>,
+    pub cover: Option<ProposedCover>,
+    /// Included when the batch is written.
+    pub accepted: bool,
+}
+
+impl TagProposal {
+    /// Diff rows for the review modal: (removed?, text).
+    pub fn diff(&self) -> Vec<(bool, String)> {
+        let mut out = Vec::new();
+        for c in &self.fields {
+            if let Some(b) = c.before.as_deref().filter(|b| !b.trim().is_empty()) {
+                out.push((true, format!("{}: {b}", c.field.as_label())));
+            }
+            out.push((false, format!("{}: {}", c.field.as_label(), c.after)));
+        }
+        if let Some(l) = self.lyrics.as_ref() {
+            let lines = l.text.lines().filter(|s| !s.trim().is_empty()).count();
+            let kind = if l.synced { "synced" } else { "unsynced" };
+            let from = l.provider.map_or("lyrics file", ProviderKind::as_label);
+            out.push((false, format!("Lyrics: {lines} lines, {kind} ({from})")));
+        }
+        if let Some(c) = self.cover.as_ref() {
+            let from = if c.local { "cover file" } else { "fetched" };
+            out.push((false, format!("Cover: {} KB ({from})", c.bytes.len().div_ceil(1024))));
+        }
+        out
+    }
+}
+
+/// Outcome of a write, dry run or undo.
+#[derive(Debug, Default)]
+pub struct ApplyReport {
+    pub written: usize,
+    /// Nothing left to do (already filled, or changed since the write being undone).
+    pub skipped: usize,
+    pub failed: Vec<(PathBuf, String)>,
+    pub dry_run: bool,
+    /// Library tags before and after, for every file that was changed.
+    pub retagged: Vec<(LibraryTrack, LibraryTrack)>,
+}
+
+pub enum TaggerEvent {
+    Progress { done: usize, total: usize },
+    Proposal(TagProposal),
+    /// Every file has been looked up.
+    Scanned,
+    Applied(ApplyReport),
+    Undone(ApplyReport),
+}
+
+/// One background pass (lookups, a write or an undo). Dropping it stops a lookup pass;
+/// writes always run to the end so the journal matches the files.
+#[derive(Debug)]
+pub struct TaggerJob {
+    rx: Receiver<TaggerEvent>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl TaggerJob {
+    /// Propose changes for `paths`, one file at a time.
+    pub fn scan(paths: Vec<PathBuf>, options: FetchOptions, cache: Arc<Mutex<LookupCache>>) -> Self {
+        Self::spawn(move |tx, cancel| {
+            let total = paths.len();
+            for (i, path) in paths.iter().enumerate() {
+                if cancel.load(Ordering::Relaxed) {
+                    flush_lookups(&cache);
+                    return;
+                }
+                let _ = tx.send(TaggerEvent::Progress { done: i, total });
+                let (proposal, queried) = propose(path, &options, &cache);
+                if let Some(p) = proposal {
+                    let _ = tx.send(TaggerEvent::Proposal(p));
+                }
+                if queried {
+                    thread::sleep(LOOKUP_PACE);
+                }
+            }
+            flush_lookups(&cache);
+            let _ = tx.send(TaggerEvent::Progress { done: total, total });
+            let _ = tx.send(TaggerEvent::Scanned);
+        })
+    }
+
+    /// Write the proposals (or, on a dry run, check that they could be written).
+    pub fn apply(proposals: Vec<TagProposal>, dry_run: bool) -> Self {
+        Self::spawn(move |tx, _| {
+            let total = proposals.len();
+            let mut report = ApplyReport { dry_run, ..Default::default() };
+            // Each change is journaled before its file is written, so quitting (or crashing)
+            // halfway still leaves an undoable batch.
+            let mut journal = (!dry_run).then(Journal::begin);
+            for (i, p) in proposals.iter().enumerate() {
+                let _ = tx.send(TaggerEvent::Progress { done: i, total });
+                let before = journal.as_ref().and_then(|_| library_tags(&p.path));
+                let journaled = |entry: &JournalEntry| match journal.as_mut() {
+                    Some(j) => j.record(entry.clone()).context("journal"),
+                    None => Ok(()),
+                };
+                match write_proposal(p, dry_run, journaled) {
+                    Ok(true) => {
+                        report.written += 1;
+                        if let (Some(before), Some(after)) = (before, journal.as_ref().and_then(|_| library_tags(&p.path))) {
+                            report.retagged.push((before, after));
+                        }
+                    }
+                    Ok(false) => report.skipped += 1,
+                    Err(e) => {
+                        // The file wasn't written; its journal entry (if any) would undo nothing.
+                        if let Some(j) = journal.as_mut() {
+                            j.forget(&p.path);
+                        }
+                        report.failed.push((p.path.clone(), format!("{e:#}")));
+                    }
+                }
+            }
+            let _ = tx.send(TaggerEvent::Applied(report));
+        })
+    }
+
+    /// Revert the last written batch. Entries leave the journal only once they are undone;
+    /// failed ones stay for another try.
+    pub fn undo() -> Self {
+        Self::spawn(move |tx, _| {
+            let mut report = ApplyReport::default();
+            let mut journal = Journal::load();
+            journal.batches.retain(|b| !b.entries.is_empty());
+            let Some(batch) = journal.batches.last_mut() else {
+                let _ = tx.send(TaggerEvent::Undone(report));
+                return;
+            };
+            let entries = std::mem::take(&mut batch.entries);
+            let total = entries.len();
+            for (i, e) in entries.into_iter().enumerate() {
+                let _ = tx.send(TaggerEvent::Progress { done: i, total });
+                let before = library_tags(&e.path);
+                match undo_entry(&e) {
+                    Ok(true) => {
+                        report.written += 1;
+                        if let (Some(before), Some(after)) = (before, library_tags(&e.path)) {
+                            report.retagged.push((before, after));
+                        }
+                    }
+                    Ok(false) => report.skipped += 1,
+                    Err(err) => {
+                        report.failed.push((e.path.clone(), format!("{err:#}")));
+                        batch.entries.push(e);
+                    }
+                }
+            }
+            if batch.entries.is_empty() {
+                journal.batches.pop();
+            }
+            // Undone entries that stay listed (the save failed) are skipped next time: their
+            // fields no longer hold what the tagger wrote.
+            if let Err(e) = journal.save() {
+                log::warn!("tagger journal save failed: {e}");
+            }
+            let _ = tx.send(TaggerEvent::Undone(report));
+        })
+    }
+
+    fn spawn(work: impl FnOnce(Sender<TaggerEvent>, Arc<AtomicBool>) + Send + 'static) -> Self {
+        let (tx, rx) = mpsc::channel::<TaggerEvent>();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let flag = cancel.clone();
+        thread::spawn(move || work(tx, flag));
+        Self { rx, cancel }
+    }
+
+    pub fn drain(&self) -> Vec<TaggerEvent> {
+        self.rx.try_iter().collect()
+    }
+}
+
+impl Drop for TaggerJob {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Write what the scan added to the lookup cache (its index saves are debounced).
+fn flush_lookups(cache: &Mutex<LookupCache>) {
+    if let Ok(mut c) = cache.lock() {
+        if let Err(e) = c.flush() {
+            log::warn!("lookup cache save failed: {e}");
+        }
+    }
+}
+
+/// Tags of `path` as the library indexes them.
+pub(crate) fn library_tags(path: &Path) -> Option<LibraryTrack> {
+    let mut t = read_library_tags(path).ok()?;
+    (t.mtime, t.size) = file_stamp(path);
+    Some(t)
+}
+
+/// Look one file up. Lyrics and cover files next to the song are used before any provider.
+/// Returns the proposal (None when there is nothing to fill) and whether providers were asked.
+fn propose(path: &Path, options: &FetchOptions, cache: &Mutex<LookupCache>) -> (Option<TagProposal>, bool) {
+    let Ok(tagged) = lofty::read_from_path(path) else {
+        return (None, false);
+    };
+    let tag = tagged.primary_tag().or_else(|| tagged.first_tag());
+    let current = |key: &ItemKey| tag.and_then(|t| t.get_string(key)).map(str::trim).filter(|s| !is_unknown(s));
+    let has_lyrics = tagged.tags().iter().any(|t| t.get_string(&ItemKey::Lyrics).is_some_and(|s| !s.trim().is_empty()));
+    let has_cover = tagged.tags().iter().any(|t| !t.pictures().is_empty());
+
+    let mut lyrics = if has_lyrics {
+        None
+    } else {
+        read_lyrics_text_for_audio(path).map(|text| ProposedLyrics { synced: parse_lrc(&text).is_some(), text, provider: None })
+    };
+    let mut cover = if has_cover {
+        None
+    } else {
+        read_cover_for_audio(path)
+            .or_else(|| read_cover_from_folder(path.parent()?))
+            .map(|(bytes, _)| ProposedCover { bytes, local: true })
+    };
+
+    // Same key as the player uses, so lookups made while listening are reused.
+    let title = current(&ItemKey::TrackTitle)
+        .map(str::to_string)
+        .or_else(|| path.file_stem().and_then(|s| s.to_str()).map(str::to_string))
+        .unwrap_or_else(|| "Unknown".to_string());
+    let key = TrackKey {
+        path: Some(path.to_path_buf()),
+        title,
+        artist: current(&ItemKey::TrackArtist).unwrap_or("Unknown").to_string(),
+        album: current(&ItemKey::AlbumTitle).unwrap_or("Unknown").to_string(),
+        duration_secs: tagged.properties().duration().as_secs(),
+    };
+
+    let mut fields = Vec::new();
+    let mut queried = false;
+    if options.enable_fetch {
+        let req = RemoteFetchRequest {
+            key: key.clone(),
+            path: key.path.clone(),
+            title: key.title.clone(),
+            artist: key.artist.clone(),
+            album: key.album.clone(),
+            duration_secs: key.duration_secs,
+            has_lyrics: has_lyrics || lyrics.is_some(),
+            has_cover: has_cover || cover.is_some(),
+            options: options.clone(),
+        };
+        let found = lookup(&req, cache);
+        queried = found.queried;
+
+        if let Some(id) = found.identity {
+            for (field, value) in [(TagField::Title, id.title), (TagField::Artist, id.artist), (TagField::Album, id.album)] {
+                let Some(after) = value.map(|v| v.trim().to_string()).filter(|v| !is_unknown(v)) else {
+                    continue;
+                };
+                if current(&field.item_key()).is_none() {
+                    let before = tag.and_then(|t| t.get_string(&field.item_key())).map(str::to_string);
+                    fields.push(FieldChange { field, before, after });
+                }
+            }
+        }
+        if lyrics.is_none() {
+            lyrics = found.lyrics.map(|l| ProposedLyrics {
+                synced: parse_lrc(&l.text).is_some(),
+                text: l.text,
+                provider: Some(l.provider),
+            });
+        }
+        if cover.is_none() {
+            cover = found.cover.map(|(bytes, _)| ProposedCover { bytes, local: false });
+        }
+    }
+
+    if fields.is_empty() && lyrics.is_none() && cover.is_none() {
+        return (None, queried);
+    }
+    let proposal = TagProposal {
+        path: path.to_path_buf(),
+        fields,
+        lyrics,
+        cover,
+        accepted: true,
+    };
+    (Some(proposal), queried)
+}
+
+/// Write one proposal. Fields filled in since the lookup are left alone; returns false when
+/// nothing was left to write. `journal` sees the change before the file is saved, and an
+/// error from it aborts the write. A dry run does everything except journaling and saving.
+fn write_proposal(p: &TagProposal, dry_run: bool, journal: impl FnOnce(&JournalEntry) -> Result<()>) -> Result<bool> {
+    let mut entry = JournalEntry {
+        path: p.path.clone(),
+        fields: Vec::new(),
+        cover_added: false,
+        cover_hash: None,
+        sylt_added: false,
+    };
+
+    let mut changes = p.fields.clone();
+    if let Some(l) = p.lyrics.as_ref() {
+        changes.push(FieldChange { field: TagField::Lyrics, before: None, after: l.text.clone() });
+    }
+    let fill = |tag: &mut Tag, entry: &mut JournalEntry| -> Result<()> {
+        for c in &changes {
+            let key = c.field.item_key();
+            let before = tag.get_string(&key).map(str::to_string);
+            if before.as_deref().is_some_and(|b| !is_unknown(b)) {
+                continue;
+            }
+            tag.insert_text(key, c.after.clone());
+            entry.fields.push(FieldChange { field: c.field, before, after: c.after.clone() });
+        }
+        if let Some(cover) = p.cover.as_ref() {
+            if tag.pictures().is_empty() {
+                let mut pic = Picture::from_reader(&mut cover.bytes.as_slice()).context("cover image")?;
+                pic.set_pic_type(PictureType::CoverFront);
+                entry.cover_hash = Some(picture_hash(&pic));
+                tag.push_picture(pic);
+                entry.cover_added = true;
+            }
+        }
+        Ok(())
+    };
+
+    if is_mpeg(&p.path)? {
+        let mut mpeg = read_mpeg(&p.path)?;
+        let (rest, mut tag) = mpeg.remove_id3v2().unwrap_or_default().split_tag();
+        fill(&mut tag, &mut entry)?;
+        let mut id3 = rest.merge_tag(tag);
+        if let Some(l) = p.lyrics.as_ref().filter(|l| l.synced && entry.fields.iter().any(|c| c.field == TagField::Lyrics)) {
+            if id3.get(&sylt_id()).is_none() {
+                id3.insert(sylt_frame(&l.text)?);
+                entry.sylt_added = true;
+            }
+        }
+        if entry.is_empty() {
+            return Ok(false);
+        }
+        if !dry_run {
+            journal(&entry)?;
+            id3.save_to_path(&p.path).with_context(|| format!("write {}", p.path.display()))?;
+        }
+    } else {
+        let mut tagged = lofty::read_from_path(&p.path)?;
+        let tag_type = tagged.primary_tag_type();
+        if tagged.tag(tag_type).is_none() {
+            tagged.insert_tag(Tag::new(tag_type));
+        }
+        let tag = tagged.tag_mut(tag_type).ok_or_else(|| anyhow!("no {tag_type:?} tag"))?;
+        fill(tag, &mut entry)?;
+        if entry.is_empty() {
+            return Ok(false);
+        }
+        if !dry_run {
+            journal(&entry)?;
+            tag.save_to_path(&p.path).with_context(|| format!("write {}", p.path.display()))?;
+        }
+    }
+    Ok(true)
+}
+
+/// Put back what a journal entry changed. Fields edited since then are kept as they are.
+/// Returns false when nothing had to be restored.
+fn undo_entry(e: &JournalEntry) -> Result<bool> {
+    let mut restored = false;
+    let restore = |tag: &mut Tag, restored: &mut bool| {
+        for c in &e.fields {
+            let key = c.field.item_key();
+            if tag.get_string(&key) != Some(c.after.as_str()) {
+                continue;
+            }
+            match c.before.clone() {
+                Some(b) => {
+                    tag.insert_text(key, b);
+                }
+                None => tag.remove_key(&key),
+            }
+            *restored = true;
+        }
+        // Only the picture the tagger added; entries without its hash remove nothing.
+        if e.cover_added {
+            if let Some(i) = tag.pictures().iter().position(|pic| Some(picture_hash(pic)) == e.cover_hash) {
+                tag.remove_picture(i);
+                *restored = true;
+            }
+        }
+    };
+
+    if is_mpeg(&e.path)? {
+        let mut mpeg = read_mpeg(&e.path)?;
+        let Some(id3) = mpeg.remove_id3v2() else {
+            return Ok(false);
+        };
+        let (rest, mut tag) = id3.split_tag();
+        restore(&mut tag, &mut restored);
+        let mut id3 = rest.merge_tag(tag);
+        if e.sylt_added && id3.remove(&sylt_id()).count() > 0 {
+            restored = true;
+        }
+        if restored {
+            id3.save_to_path(&e.path).with_context(|| format!("write {}", e.path.display()))?;
+        }
+    } else {
+        let mut tagged = lofty::read_from_path(&e.path)?;
+        let tag_type = tagged.primary_tag_type();
+        let Some(tag) = tagged.tag_mut(tag_type) else {
+            return Ok(false);
+        };
+        restore(tag, &mut restored);
+        if restored {
+            tag.save_to_path(&e.path).with_context(|| format!("write {}", e.path.display()))?;
+        }
+    }
+    Ok(restored)
+}
+
+/// Identifies the cover the tagger added, so undo leaves other pictures alone.
+fn picture_hash(pic: &Picture) -> u64 {
+    let mut h = DefaultHasher::new();
+    pic.data().hash(&mut h);
+    h.finish()
+}
+
+/// MP3s are edited through their ID3v2 tag directly, so frames the generic tag can't hold
+/// (SYLT among them) survive the write.
+pub fn is_mpeg(path: &Path) -> Result<bool> {
+    let probe = lofty::Probe::open(path)?.guess_file_type()?;
+    Ok(probe.file_type() == Some(FileType::Mpeg))
+}
+
+pub fn read_mpeg(path: &Path) -> Result<MpegFile> {
+    let mut f = fs::File::open(path)?;
+    Ok(MpegFile::read_from(&mut f, ParseOptions::new())?)
+}
+
+fn sylt_id() -> FrameId<'static> {
+    FrameId::Valid(Cow::Borrowed("SYLT"))
+}
+
+/// SYLT frame (millisecond timestamps) for the first layer of LRC text.
+fn sylt_frame(lrc: &str) -> Result<Frame<'static>> {
+    let mut content: Vec<(u32, String)> = Vec::new();
+    for l in parse_lrc(lrc).unwrap_or_default() {
+        // Translation layers repeat the timestamp; SYLT only carries the original.
+        if content.last().is_some_and(|(t, _)| *t as u64 == l.start_ms) {
+            continue;
+        }
+        content.push((l.start_ms.min(u32::MAX as u64) as u32, l.text));
+    }
+    let sylt = SynchronizedText {
+        encoding: TextEncoding::UTF8,
+        language: *b"XXX",
+        timestamp_format: TimestampFormat::MS,
+        content_type: SyncTextContentType::Lyrics,
+        description: None,
+        content,
+    };
+    Ok(Frame::new("SYLT", FrameValue::Binary(sylt.as_bytes()?), FrameFlags::default())?)
+}
+
+/// Written batches that can still be undone.
+pub fn undo_depth() -> usize {
+    Journal::load().batches.iter().filter(|b| !b.entries.is_empty()).count()
+}
+
+/// What one write changed in one file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct JournalEntry {
+    path: PathBuf,
+    #[serde(default)]
+    fields: Vec<FieldChange>,
+    #[serde(default)]
+    cover_added: bool,
+    /// `picture_hash` of the added cover.
+    #[serde(default)]
+    cover_hash: Option<u64>,
+    #[serde(default)]
+    sylt_added: bool,
+}
+
+impl JournalEntry {
+    fn is_empty(&self) -> bool {
+        self.fields.is_empty() && !self.cover_added && !self.sylt_added
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JournalBatch {
+    at: u64,
+    entries: Vec<JournalEntry>,
+}
+
+/// Written batches, oldest first.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Journal {
+    #[serde(default)]
+    batches: Vec<JournalBatch>,
+}
+
+impl Journal {
+    fn path() -> PathBuf {
+        assets::resolve_asset_path(Path::new("tagger")).join("journal.json")
+    }
+
+    fn load() -> Self {
+        fs::read(Self::path())
+            .ok()
+            .and_then(|raw| serde_json::from_slice(&raw).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) -> Result<()> {
+        let path = Self::path();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_vec_pretty(self)?).with_context(|| format!("write {}", tmp.display()))?;
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    /// Start a new batch (dropping the oldest beyond `MAX_JOURNAL_BATCHES`).
+    fn begin() -> Self {
+        let mut journal = Self::load();
+        journal.batches.retain(|b| !b.entries.is_empty());
+        journal.batches.push(JournalBatch { at: unix_now(), entries: Vec::new() });
+        let excess = journal.batches.len().saturating_sub(MAX_JOURNAL_BATCHES);
+        journal.batches.drain(..excess);
+        journal
+    }
+
+    /// Add an entry to the open batch and save; on error the entry is dropped again.
+    fn record(&mut self, entry: JournalEntry) -> Result<()> {
+        let Some(batch) = self.batches.last_mut() else {
+            return Err(anyhow!("no open batch"));
+        };
+        batch.entries.push(entry);
+        let saved = self.save();
+        if saved.is_err() {
+            if let Some(batch) = self.batches.last_mut() {
+                batch.entries.pop();
+            }
+        }
+        saved
+    }
+
+    /// Drop the open batch's entry for `path` (its write failed after it was journaled).
+    fn forget(&mut self, path: &Path) {
+        let Some(batch) = self.batches.last_mut() else {
+            return;
+        };
+        let before = batch.entries.len();
+        batch.entries.retain(|e| e.path != path);
+        if batch.entries.len() != before {
+            if let Err(e) = self.save() {
+                log::warn!("tagger journal save failed: {e}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The journal is one file under the asset root: these tests take turns.
+    static JOURNAL_LOCK: Mutex<()> = Mutex::new(());
+
+    fn fresh_journal() -> std::sync::MutexGuard<'static, ()> {
+        let guard = JOURNAL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        assets::isolate_for_tests();
+        let _ = fs::remove_file(Journal::path());
+        guard
+    }
+
+    /// A FLAC file with STREAMINFO and padding but no audio, tagged with `title`.
+    fn flac_fixture(dir: &Path, title: &str) -> PathBuf {
+        let mut raw = b"fLaC".to_vec();
+        raw.extend_from_slice(&[0, 0, 0, 34]);
+        raw.extend_from_slice(&4096u16.to_be_bytes());
+        raw.extend_from_slice(&4096u16.to_be_bytes());
+        raw.extend_from_slice(&[0; 6]);
+        // 44.1 kHz, mono, 16 bit, no samples
+        raw.extend_from_slice(&((44_100u64 << 44) | (15u64 << 36)).to_be_bytes());
+        raw.extend_from_slice(&[0; 16]);
+        raw.extend_from_slice(&[0x81, 0, 1, 0]);
+        raw.extend_from_slice(&[0; 256]);
+        let path = dir.join(format!("{title}.flac"));
+        fs::write(&path, raw).unwrap();
+
+        let mut tagged = lofty::read_from_path(&path).unwrap();
+        let tag_type = tagged.primary_tag_type();
+        tagged.insert_tag(Tag::new(tag_type));
+        let tag = tagged.tag_mut(tag_type).unwrap();
+        tag.insert_text(ItemKey::TrackTitle, title.to_string());
+        tag.save_to_path(&path).unwrap();
+        path
+    }
+
+    fn png(marker: u8) -> Vec<u8> {
+        let mut bytes = b"\x89PNG\r\n\x1a\n".to_vec();
+        bytes.extend_from_slice(&[marker; 16]);
+        bytes
+    }
+
+    fn tag_of(path: &Path) -> Tag {
+        let tagged = lofty::read_from_path(path).unwrap();
+        tagged.primary_tag().unwrap().clone()
+    }
+
+    fn proposal(path: &Path) -> TagProposal {
+        let change = |field, after: &str| FieldChange { field, before: None, after: after.to_string() };
+        TagProposal {
+            path: path.to_path_buf(),
+            fields: vec![change(TagField::Artist, "Band"), change(TagField::Album, "Record")],
+            lyrics: None,
+            cover: Some(ProposedCover { bytes: png(1), local: true }),
+            accepted: true,
+        }
+    }
+
+    fn finish(job: TaggerJob) -> ApplyReport {
+        loop {
+            for ev in job.drain() {
+                if let TaggerEvent::Applied(r) | TaggerEvent::Undone(r) = ev {
+                    return r;
+                }
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn undo_restores_fields_and_removes_only_the_added_cover() {
+        let _journal = fresh_journal();
+        let dir = tempfile::tempdir().unwrap();
+        let path = flac_fixture(dir.path(), "Song");
+
+        let report = finish(TaggerJob::apply(vec![proposal(&path)], false));
+        assert_eq!((report.written, report.failed.len()), (1, 0));
+        assert_eq!(report.retagged[0].1.artist, "Band");
+        let tag = tag_of(&path);
+        assert_eq!(tag.get_string(&ItemKey::AlbumTitle), Some("Record"));
+        assert_eq!(tag.pictures().len(), 1);
+        assert_eq!(undo_depth(), 1);
+
+        // A picture added by hand afterwards is not the tagger's to take back.
+        let mut tagged = lofty::read_from_path(&path).unwrap();
+        let mut own = Picture::from_reader(&mut png(2).as_slice()).unwrap();
+        own.set_pic_type(PictureType::Artist);
+        tagged.primary_tag_mut().unwrap().push_picture(own);
+        tagged.save_to_path(&path).unwrap();
+
+        let report = finish(TaggerJob::undo());
+        assert_eq!(report.written, 1);
+        let tag = tag_of(&path);
+        assert_eq!(tag.get_string(&ItemKey::TrackTitle), Some("Song"));
+        assert_eq!(tag.get_string(&ItemKey::TrackArtist), None);
+        assert_eq!(tag.get_string(&ItemKey::AlbumTitle), None);
+        assert_eq!(tag.pictures().len(), 1);
+        assert_eq!(tag.pictures()[0].pic_type(), PictureType::Artist);
+        assert_eq!(undo_depth(), 0);
+    }
+
+    #[test]
+    fn dry_run_touches_neither_file_nor_journal() {
+        let _journal = fresh_journal();
+        let dir = tempfile::tempdir().unwrap();
+        let path = flac_fixture(dir.path(), "Song");
+        let before = fs::read(&path).unwrap();
+
+        let report = finish(TaggerJob::apply(vec![proposal(&path)], true));
+        assert!(report.dry_run);
+        assert_eq!(report.written, 1);
+        assert!(report.retagged.is_empty());
+        assert_eq!(fs::read(&path).unwrap(), before);
+        assert!(!Journal::path().exists());
+    }
+
+    #[test]
+    fn undo_keeps_fields_edited_after_the_write() {
+        let _journal = fresh_journal();
+        let dir = tempfile::tempdir().unwrap();
+        let path = flac_fixture(dir.path(), "Song");
+        finish(TaggerJob::apply(vec![proposal(&path)], false));
+
+        let mut tagged = lofty::read_from_path(&path).unwrap();
+        tagged.primary_tag_mut().unwrap().insert_text(ItemKey::TrackArtist, "Someone else".to_string());
+        tagged.save_to_path(&path).unwrap();
+
+        finish(TaggerJob::undo());
+        let tag = tag_of(&path);
+        assert_eq!(tag.get_string(&ItemKey::TrackArtist), Some("Someone else"));
+        assert_eq!(tag.get_string(&ItemKey::AlbumTitle), None);
+        assert!(tag.pictures().is_empty());
+    }
+
+    #[test]
+    fn forget_drops_a_failed_write_from_the_batch() {
+        let _journal = fresh_journal();
+        let entry = |name: &str| JournalEntry {
+            path: PathBuf::from(name),
+            fields: vec![FieldChange { field: TagField::Title, before: None, after: "x".to_string() }],
+            cover_added: false,
+            cover_hash: None,
+            sylt_added: false,
+        };
+        let mut journal = Journal::begin();
+        journal.record(entry("/music/a.flac")).unwrap();
+        journal.record(entry("/music/b.flac")).unwrap();
+        journal.forget(Path::new("/music/b.flac"));
+
+        let saved = Journal::load();
+        let paths: Vec<&Path> = saved.batches.last().unwrap().entries.iter().map(|e| e.path.as_path()).collect();
+        assert_eq!(paths, vec![Path::new("/music/a.flac")]);
+
+        // A batch whose only write failed leaves nothing to undo.
+        journal.forget(Path::new("/music/a.flac"));
+        assert_eq!(undo_depth(), 0);
+    }
+
+    #[test]
+    fn sylt_keeps_one_line_per_timestamp() {
+        let frame = sylt_frame("[00:01.00]Hello\n[00:01.00]Hallo\n[00:02.50]World\n[00:02.50]Welt\n").unwrap();
+        let FrameValue::Binary(raw) = frame.content() else {
+            panic!("SYLT is written as binary");
+        };
+        let sylt = SynchronizedText::parse(raw).unwrap();
+        assert_eq!(sylt.content, vec![(1000, "Hello".to_string()), (2500, "World".to_string())]);
+    }
+}
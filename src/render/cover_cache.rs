@@ -1,3 +1,4 @@
+use crate::render::cover_renderer::CoverStyle;
 use std::collections::{HashMap, VecDeque};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -5,6 +6,7 @@ pub struct CoverKey {
     pub hash: u64,
     pub width: u16,
     pub height: u16,
+    pub style: CoverStyle,
 }
 
 #[derive(Debug, Default)]
@@ -1,7 +1,66 @@
+use crate::data::config::CoverMode;
+use crate::ui::theme::ColorCapability;
 use image::imageops::FilterType;
+use image::RgbImage;
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span, Text};
+use std::fmt::Write as _;
 
 pub const COVER_CHARSET: &str = "⠀░▒▓█";
 
+/// 4x4 Bayer matrix for ordered dithering of the braille dots.
+const BAYER4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Levels of the xterm 6x6x6 color cube.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// A text cover style: the mode picked in Settings, resolved against the terminal's colors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CoverStyle {
+    pub mode: CoverMode,
+    pub colors: ColorCapability,
+}
+
+impl CoverStyle {
+    pub fn new(mode: CoverMode, colors: ColorCapability) -> Self {
+        match (mode, colors) {
+            // The charset is drawn in the theme's text color; half blocks are nothing
+            // without colors.
+            (CoverMode::Ascii, _) | (CoverMode::Halfblock, ColorCapability::NoColor) => Self {
+                mode: CoverMode::Ascii,
+                colors: ColorCapability::NoColor,
+            },
+            _ => Self { mode, colors },
+        }
+    }
+
+    /// Suffix of the `.order.toml` cache key; None for the original charset, which keeps
+    /// the old keys valid.
+    pub fn tag(self) -> Option<String> {
+        let mode = match self.mode {
+            CoverMode::Ascii => return None,
+            CoverMode::Halfblock => "halfblock",
+            CoverMode::Braille => "braille",
+        };
+        let colors = match self.colors {
+            ColorCapability::TrueColor => "",
+            ColorCapability::Ansi256 => "-256",
+            ColorCapability::NoColor => "-mono",
+        };
+        Some(format!("{mode}{colors}"))
+    }
+}
+
+/// Render a cover as `height` lines of `width` cells in the given style. Colored styles
+/// embed SGR color escapes; `cover_cells` reads them back.
+pub fn render_cover_text(image_bytes: &[u8], width: u16, height: u16, style: CoverStyle) -> Option<String> {
+    match style.mode {
+        CoverMode::Ascii => render_cover_ascii(image_bytes, width, height),
+        CoverMode::Halfblock => render_cover_halfblock(image_bytes, width, height, style.colors),
+        CoverMode::Braille => render_cover_braille(image_bytes, width, height, style.colors),
+    }
+}
+
 pub fn render_cover_ascii(image_bytes: &[u8], width: u16, height: u16) -> Option<String> {
     let img = image::load_from_memory(image_bytes).ok()?;
     // Performance: large covers + Lanczos can be very expensive; Triangle is much faster.
@@ -25,3 +84,330 @@ pub fn render_cover_ascii(image_bytes: &[u8], width: u16, height: u16) -> Option
 
     Some(out)
 }
+
+/// `▀` with the upper pixel as foreground and the lower one as background: two pixels per cell.
+fn render_cover_halfblock(image_bytes: &[u8], width: u16, height: u16, colors: ColorCapability) -> Option<String> {
+    let rgb = resized_rgb(image_bytes, width as u32, height as u32 * 2)?;
+    let mut out = String::new();
+    for y in 0..height as u32 {
+        let mut sgr = SgrRow::new(colors);
+        for x in 0..width as u32 {
+            let top = rgb.get_pixel(x, y * 2).0;
+            let bottom = rgb.get_pixel(x, y * 2 + 1).0;
+            sgr.push(&mut out, '▀', Some(top), Some(bottom));
+        }
+        out.push('\n');
+    }
+    Some(out)
+}
+
+/// 2x4 dots per cell, lit by ordered dithering on brightness and colored with the average
+/// of the lit pixels (no color when the terminal has none).
+fn render_cover_braille(image_bytes: &[u8], width: u16, height: u16, colors: ColorCapability) -> Option<String> {
+    // Braille dot bit for (x, y) inside the cell.
+    const DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+    let rgb = resized_rgb(image_bytes, width as u32 * 2, height as u32 * 4)?;
+    let mut out = String::new();
+    for cy in 0..height as u32 {
+        let mut sgr = SgrRow::new(colors);
+        for cx in 0..width as u32 {
+            let mut bits = 0u32;
+            let mut sum = [0u32; 3];
+            let mut lit = 0u32;
+            for (dy, row) in DOTS.iter().enumerate() {
+                for (dx, bit) in row.iter().enumerate() {
+                    let (x, y) = (cx * 2 + dx as u32, cy * 4 + dy as u32);
+                    let [r, g, b] = rgb.get_pixel(x, y).0;
+                    let luma = (r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000;
+                    let threshold = (BAYER4[y as usize % 4][x as usize % 4] as u32 * 2 + 1) * 255 / 32;
+                    if luma > threshold {
+                        bits |= bit;
+                        sum = [sum[0] + r as u32, sum[1] + g as u32, sum[2] + b as u32];
+                        lit += 1;
+                    }
+                }
+            }
+            let ch = char::from_u32(0x2800 + bits).unwrap_or(' ');
+            let fg = (lit > 0).then(|| [(sum[0] / lit) as u8, (sum[1] / lit) as u8, (sum[2] / lit) as u8]);
+            sgr.push(&mut out, ch, fg, None);
+        }
+        out.push('\n');
+    }
+    Some(out)
+}
+
+fn resized_rgb(image_bytes: &[u8], w: u32, h: u32) -> Option<RgbImage> {
+    let img = image::load_from_memory(image_bytes).ok()?;
+    Some(img.resize_exact(w.max(1), h.max(1), FilterType::Triangle).to_rgb8())
+}
+
+/// Writes cells of one row, emitting an SGR escape only when the colors change.
+struct SgrRow {
+    colors: ColorCapability,
+    last: Option<(Option<String>, Option<String>)>,
+}
+
+impl SgrRow {
+    fn new(colors: ColorCapability) -> Self {
+        Self { colors, last: None }
+    }
+
+    fn push(&mut self, out: &mut String, ch: char, fg: Option<[u8; 3]>, bg: Option<[u8; 3]>) {
+        if self.colors != ColorCapability::NoColor {
+            let fg = fg.map(|c| self.code(c));
+            let bg = bg.map(|c| self.code(c));
+            let next = (fg, bg);
+            if self.last.as_ref() != Some(&next) {
+                let mut params = vec!["0".to_string()];
+                if let Some(c) = &next.0 {
+                    params.push(format!("38;{c}"));
+                }
+                if let Some(c) = &next.1 {
+                    params.push(format!("48;{c}"));
+                }
+                let _ = write!(out, "\x1b[{}m", params.join(";"));
+                self.last = Some(next);
+            }
+        }
+        out.push(ch);
+    }
+
+    fn code(&self, [r, g, b]: [u8; 3]) -> String {
+        match self.colors {
+            ColorCapability::Ansi256 => format!("5;{}", ansi256(r, g, b)),
+            _ => format!("2;{r};{g};{b}"),
+        }
+    }
+}
+
+/// Nearest xterm-256 color, from the color cube or the gray ramp.
+fn ansi256(r: u8, g: u8, b: u8) -> u8 {
+    let nearest_level = |v: u8| {
+        CUBE_LEVELS
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, l)| (**l as i32 - v as i32).abs())
+            .map(|(i, _)| i as u8)
+            .unwrap_or(0)
+    };
+    let (ri, gi, bi) = (nearest_level(r), nearest_level(g), nearest_level(b));
+    let cube = (CUBE_LEVELS[ri as usize], CUBE_LEVELS[gi as usize], CUBE_LEVELS[bi as usize]);
+
+    let avg = (r as u32 + g as u32 + b as u32) / 3;
+    let gi_ramp = ((avg.saturating_sub(3)) / 10).min(23) as u8;
+    let gray_v = 8 + 10 * gi_ramp;
+
+    let dist = |(cr, cg, cb): (u8, u8, u8)| {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        d(cr, r) + d(cg, g) + d(cb, b)
+    };
+    if dist((gray_v, gray_v, gray_v)) < dist(cube) {
+        232 + gi_ramp
+    } else {
+        16 + 36 * ri + 6 * gi + bi
+    }
+}
+
+/// One cell of a text cover. Plain charset covers have no colors and take the panel's style.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoverCell {
+    pub ch: char,
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+}
+
+impl CoverCell {
+    pub fn plain(ch: char) -> Self {
+        Self { ch, fg: None, bg: None }
+    }
+}
+
+/// Split a rendered cover into exactly `rows` rows of cells, reading the SGR color escapes
+/// written by the colored renderers.
+pub fn cover_cells(s: &str, rows: usize) -> Vec<Vec<CoverCell>> {
+    let mut out: Vec<Vec<CoverCell>> = Vec::with_capacity(rows);
+    for line in s.lines().take(rows) {
+        let (mut fg, mut bg) = (None, None);
+        let mut row = Vec::new();
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                let params: String = chars.by_ref().skip(1).take_while(|&p| p != 'm').collect();
+                apply_sgr(&params, &mut fg, &mut bg);
+                continue;
+            }
+            row.push(CoverCell { ch: c, fg, bg });
+        }
+        out.push(row);
+    }
+    out.resize_with(rows, Vec::new);
+    out
+}
+
+fn apply_sgr(params: &str, fg: &mut Option<Color>, bg: &mut Option<Color>) {
+    let nums: Vec<u8> = params.split(';').map(|p| p.parse().unwrap_or(0)).collect();
+    let mut i = 0;
+    while i < nums.len() {
+        match nums[i] {
+            0 => {
+                *fg = None;
+                *bg = None;
+            }
+            39 => *fg = None,
+            49 => *bg = None,
+            n @ (38 | 48) => {
+                let color = match nums.get(i + 1) {
+                    Some(2) if i + 4 < nums.len() => {
+                        let c = Color::Rgb(nums[i + 2], nums[i + 3], nums[i + 4]);
+                        i += 3;
+                        Some(c)
+                    }
+                    Some(5) if i + 2 < nums.len() => {
+                        let c = Color::Indexed(nums[i + 2]);
+                        i += 1;
+                        Some(c)
+                    }
+                    _ => None,
+                };
+                i += 1;
+                if n == 38 {
+                    *fg = color;
+                } else {
+                    *bg = color;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+}
+
+/// Rows of cells as ratatui text, one span per run of equal colors.
+pub fn cover_text(rows: Vec<Vec<CoverCell>>) -> Text<'static> {
+    let lines: Vec<Line<'static>> = rows
+        .into_iter()
+        .map(|row| {
+            let mut spans: Vec<Span<'static>> = Vec::new();
+            let mut run = String::new();
+            let mut colors = None;
+            for cell in row {
+                if colors != Some((cell.fg, cell.bg)) && !run.is_empty() {
+                    spans.push(styled_run(std::mem::take(&mut run), colors));
+                }
+                colors = Some((cell.fg, cell.bg));
+                run.push(cell.ch);
+            }
+            if !run.is_empty() {
+                spans.push(styled_run(run, colors));
+            }
+            Line::from(spans)
+        })
+        .collect();
+    Text::from(lines)
+}
+
+fn styled_run(text: String, colors: Option<(Option<Color>, Option<Color>)>) -> Span<'static> {
+    let mut style = Style::default();
+    if let Some((fg, bg)) = colors {
+        if let Some(fg) = fg {
+            style = style.fg(fg);
+        }
+        if let Some(bg) = bg {
+            style = style.bg(bg);
+        }
+    }
+    Span::styled(text, style)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png(w: u32, h: u32, pixel: impl Fn(u32, u32) -> [u8; 3]) -> Vec<u8> {
+        let img = RgbImage::from_fn(w, h, |x, y| image::Rgb(pixel(x, y)));
+        let mut bytes = std::io::Cursor::new(Vec::new());
+        image::DynamicImage::ImageRgb8(img)
+            .write_to(&mut bytes, image::ImageOutputFormat::Png)
+            .unwrap();
+        bytes.into_inner()
+    }
+
+    fn cells(bytes: &[u8], mode: CoverMode, colors: ColorCapability) -> Vec<Vec<CoverCell>> {
+        let s = render_cover_text(bytes, 2, 1, CoverStyle::new(mode, colors)).unwrap();
+        cover_cells(&s, 2)
+    }
+
+    #[test]
+    fn halfblock_cells_carry_both_pixels_in_either_palette() {
+        // Red over blue, two cells wide.
+        let bytes = png(2, 2, |_, y| if y == 0 { [255, 0, 0] } else { [0, 0, 255] });
+        let cell = |fg, bg| CoverCell { ch: '▀', fg: Some(fg), bg: Some(bg) };
+
+        let rows = cells(&bytes, CoverMode::Halfblock, ColorCapability::TrueColor);
+        let (red, blue) = (Color::Rgb(255, 0, 0), Color::Rgb(0, 0, 255));
+        assert_eq!(rows, [vec![cell(red, blue); 2], vec![]]);
+
+        let rows = cells(&bytes, CoverMode::Halfblock, ColorCapability::Ansi256);
+        assert_eq!(rows[0], vec![cell(Color::Indexed(196), Color::Indexed(21)); 2]);
+    }
+
+    #[test]
+    fn braille_cells_light_bright_dots_in_their_color() {
+        // Per cell: left column white, right column black.
+        let bytes = png(4, 4, |x, _| if x % 2 == 0 { [255, 255, 255] } else { [0, 0, 0] });
+        let cell = |fg| CoverCell { ch: '⡇', fg, bg: None };
+
+        let rows = cells(&bytes, CoverMode::Braille, ColorCapability::TrueColor);
+        assert_eq!(rows[0], vec![cell(Some(Color::Rgb(255, 255, 255))); 2]);
+        let rows = cells(&bytes, CoverMode::Braille, ColorCapability::Ansi256);
+        assert_eq!(rows[0], vec![cell(Some(Color::Indexed(231))); 2]);
+        let rows = cells(&bytes, CoverMode::Braille, ColorCapability::NoColor);
+        assert_eq!(rows[0], vec![cell(None); 2]);
+
+        // All dark: blank braille cells without a color.
+        let dark = png(4, 4, |_, _| [0, 0, 0]);
+        let rows = cells(&dark, CoverMode::Braille, ColorCapability::TrueColor);
+        assert_eq!(rows[0], vec![CoverCell::plain('⠀'); 2]);
+    }
+
+    #[test]
+    fn sgr_resets_and_defaults_clear_colors() {
+        let rows = cover_cells("\x1b[0;38;5;9;48;2;1;2;3ma\x1b[39mb\x1b[0mc\n", 1);
+        let bg = Some(Color::Rgb(1, 2, 3));
+        assert_eq!(
+            rows[0],
+            [
+                CoverCell { ch: 'a', fg: Some(Color::Indexed(9)), bg },
+                CoverCell { ch: 'b', fg: None, bg },
+                CoverCell::plain('c'),
+            ]
+        );
+    }
+
+    #[test]
+    fn ansi256_picks_cube_corners_and_the_gray_ramp() {
+        assert_eq!(ansi256(0, 0, 0), 16);
+        assert_eq!(ansi256(255, 255, 255), 231);
+        assert_eq!(ansi256(255, 0, 0), 196);
+        assert_eq!(ansi256(128, 128, 128), 244);
+        assert!((232..=255).contains(&ansi256(40, 40, 40)));
+    }
+
+    #[test]
+    fn cache_tags_differ_per_mode_and_palette() {
+        let colors = [ColorCapability::TrueColor, ColorCapability::Ansi256, ColorCapability::NoColor];
+        let mut tags = Vec::new();
+        for mode in [CoverMode::Ascii, CoverMode::Halfblock, CoverMode::Braille] {
+            for c in colors {
+                tags.push(CoverStyle::new(mode, c).tag());
+            }
+        }
+        // The charset keeps the old keys; monochrome half blocks fall back to it.
+        assert_eq!(tags[..4], [None, None, None, Some("halfblock".to_string())]);
+        let mut colored: Vec<String> = tags.into_iter().flatten().collect();
+        colored.sort();
+        colored.dedup();
+        assert_eq!(colored, ["braille", "braille-256", "braille-mono", "halfblock", "halfblock-256"]);
+    }
+}
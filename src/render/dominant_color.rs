@@ -1,6 +1,6 @@
-use image::imageops;
+use image::{imageops, RgbaImage};
 
-pub fn dominant_rgb_from_image_bytes(image_bytes: &[u8]) -> Option<(u8, u8, u8)> {
+fn thumbnail(image_bytes: &[u8]) -> Option<RgbaImage> {
     let img = image::load_from_memory(image_bytes).ok()?;
     let mut rgba = img.to_rgba8();
 
@@ -15,6 +15,11 @@ pub fn dominant_rgb_from_image_bytes(image_bytes: &[u8]) -> Option<(u8, u8, u8)>
         let new_h = ((h as f32) * scale).round().max(8.0) as u32;
         rgba = imageops::resize(&rgba, new_w, new_h, imageops::FilterType::Triangle);
     }
+    Some(rgba)
+}
+
+pub fn dominant_rgb_from_image_bytes(image_bytes: &[u8]) -> Option<(u8, u8, u8)> {
+    let rgba = thumbnail(image_bytes)?;
 
     // Quantize into 5-bit buckets per channel (32^3 = 32768 buckets).
     // Use a weighted count to prefer more saturated colors and de-emphasize very dark/bright pixels.
@@ -62,3 +67,73 @@ pub fn dominant_rgb_from_image_bytes(image_bytes: &[u8]) -> Option<(u8, u8, u8)>
     let to_8 = |v5: u8| (v5 << 3) | (v5 >> 2);
     Some((to_8(ri), to_8(gi), to_8(bi)))
 }
+
+/// Median-cut palette: up to `count` (color, pixel count) swatches, most common first.
+/// Unlike `dominant_rgb_from_image_bytes` this keeps blacks and whites, which the
+/// adaptive theme needs for its background tones.
+pub fn palette_from_image_bytes(image_bytes: &[u8], count: usize) -> Vec<((u8, u8, u8), u32)> {
+    let Some(rgba) = thumbnail(image_bytes) else {
+        return Vec::new();
+    };
+    let pixels: Vec<[u8; 3]> = rgba
+        .pixels()
+        .filter(|p| p.0[3] >= 16)
+        .map(|p| [p.0[0], p.0[1], p.0[2]])
+        .collect();
+    median_cut(pixels, count)
+}
+
+/// Quantize `pixels` to at most `count` colors, each with the number of pixels it stands for.
+pub fn median_cut(pixels: Vec<[u8; 3]>, count: usize) -> Vec<((u8, u8, u8), u32)> {
+    if pixels.is_empty() || count == 0 {
+        return Vec::new();
+    }
+
+    // Repeatedly split the box with the widest channel range at its median.
+    let mut boxes: Vec<Vec<[u8; 3]>> = vec![pixels];
+    while boxes.len() < count {
+        let widest = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.len() > 1)
+            .map(|(i, b)| (i, channel_range(b)))
+            .max_by_key(|&(_, (_, range))| range);
+        let Some((i, (channel, range))) = widest else {
+            break;
+        };
+        if range == 0 {
+            break;
+        }
+        let mut b = boxes.swap_remove(i);
+        b.sort_unstable_by_key(|p| p[channel]);
+        let upper = b.split_off(b.len() / 2);
+        boxes.push(b);
+        boxes.push(upper);
+    }
+
+    let mut out: Vec<((u8, u8, u8), u32)> = boxes
+        .iter()
+        .map(|b| {
+            let n = b.len() as u64;
+            let sum = b.iter().fold([0u64; 3], |acc, p| {
+                [acc[0] + p[0] as u64, acc[1] + p[1] as u64, acc[2] + p[2] as u64]
+            });
+            (((sum[0] / n) as u8, (sum[1] / n) as u8, (sum[2] / n) as u8), n as u32)
+        })
+        .collect();
+    out.sort_by_key(|c| std::cmp::Reverse(c.1));
+    out
+}
+
+/// (channel, max - min) of the channel with the widest spread.
+fn channel_range(pixels: &[[u8; 3]]) -> (usize, u8) {
+    (0..3)
+        .map(|c| {
+            let (lo, hi) = pixels
+                .iter()
+                .fold((255u8, 0u8), |(lo, hi), p| (lo.min(p[c]), hi.max(p[c])));
+            (c, hi.saturating_sub(lo))
+        })
+        .max_by_key(|&(_, r)| r)
+        .unwrap_or((0, 0))
+}
@@ -0,0 +1,24 @@
+use anyhow::Result;
+use crossterm::{cursor, queue, style::Print};
+use ratatui::layout::Rect;
+use std::io::{self, Write};
+
+/// Draw a base64 PNG (see `kitty_graphics::encode_image_bytes_to_png_base64`) into `rect`
+/// with the iTerm2 inline image protocol (OSC 1337). The terminal scales it to the cell
+/// size, keeping the aspect ratio.
+///
+/// Like Sixel there is no delete command; the image goes away when its cells are redrawn.
+pub fn place_image(rect: Rect, png_b64: &str) -> Result<()> {
+    if rect.width == 0 || rect.height == 0 || png_b64.is_empty() {
+        return Ok(());
+    }
+    let mut out = io::stdout();
+    queue!(out, cursor::MoveTo(rect.x, rect.y))?;
+    let esc = format!(
+        "\x1b]1337;File=inline=1;width={};height={};preserveAspectRatio=1;doNotMoveCursor=1:{png_b64}\x07",
+        rect.width, rect.height
+    );
+    queue!(out, Print(esc))?;
+    out.flush()?;
+    Ok(())
+}
@@ -1 +1,273 @@
-// MVP: lyric rendering handled in ui::panels::visual_panel.
+// Full-screen lyrics view: a window centered on the active line that scrolls
+// (eased via `AppState::lyrics_scroll`) as playback advances. Enhanced LRC word
+// timing highlights the active line progressively, character by character.
+// A stacked secondary track (translation / romanization) is drawn under each line.
+// Unsynced lyrics are scrolled by hand unless estimated sync spreads them over the song.
+// `render_sync` draws the tap-to-sync editor in the same area.
+
+use crate::app::state::{AppState, LyricLine, LyricTrack};
+use crate::playback::metadata::format_lrc_time;
+use crate::ui::borders::SOLID_BORDER;
+use ratatui::layout::{Alignment, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph};
+use ratatui::Frame;
+
+/// How long the last word of a line lasts when nothing follows it.
+const LAST_WORD_MS: u64 = 1500;
+
+/// How far apart (ms) a secondary line may start and still pair with a primary line.
+const COMPANION_TOLERANCE_MS: u64 = 1000;
+
+/// Index of the line being sung at `pos_ms` (the last one that has started).
+pub fn active_line(lines: &[LyricLine], pos_ms: u64) -> Option<usize> {
+    if lines.is_empty() {
+        return None;
+    }
+    Some(lines.partition_point(|l| l.start_ms <= pos_ms).saturating_sub(1))
+}
+
+/// The line of `track` that goes with a primary line starting at `start_ms`.
+pub fn companion_line(track: &LyricTrack, start_ms: u64) -> Option<&LyricLine> {
+    let lines = &track.lines;
+    let i = lines.partition_point(|l| l.start_ms < start_ms);
+    [i.checked_sub(1), Some(i)]
+        .into_iter()
+        .flatten()
+        .filter_map(|k| lines.get(k))
+        .filter(|l| l.start_ms.abs_diff(start_ms) <= COMPANION_TOLERANCE_MS)
+        .min_by_key(|l| l.start_ms.abs_diff(start_ms))
+}
+
+/// The line of `secondary` that goes with line `idx` of `primary`: by time, or by position
+/// when neither track has real timestamps.
+pub fn companion_at<'a>(primary: &LyricTrack, idx: usize, secondary: &'a LyricTrack) -> Option<&'a LyricLine> {
+    if !primary.is_synced() && !secondary.is_synced() {
+        return secondary.lines.get(idx);
+    }
+    companion_line(secondary, primary.lines.get(idx)?.start_ms)
+}
+
+/// Short marker for the lyrics' timing, shown next to titles.
+pub fn timing_label(app: &AppState, track: &LyricTrack) -> &'static str {
+    if track.is_synced() {
+        "synced"
+    } else if app.lyrics_follow_playback() {
+        "unsynced · estimated sync"
+    } else {
+        "unsynced"
+    }
+}
+
+pub fn render(f: &mut Frame, area: Rect, app: &AppState) {
+    let track = &app.player.track;
+    let (primary, secondary) = app.lyric_display();
+    let mut title = if track.artist.is_empty() {
+        format!(" Lyrics — {} ", track.title)
+    } else {
+        format!(" Lyrics — {} · {} ", track.title, track.artist)
+    };
+    if let Some(t) = primary.filter(|t| !t.lines.is_empty()) {
+        title.push_str(&format!("[{}] ", timing_label(app, t)));
+    }
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(SOLID_BORDER)
+        .border_style(Style::default().fg(app.theme.color_border()))
+        .title(title)
+        .style(Style::default().fg(app.theme.color_subtext()));
+    f.render_widget(block, area);
+
+    let inner = area.inner(&ratatui::layout::Margin { horizontal: 2, vertical: 1 });
+    if inner.height == 0 || inner.width == 0 {
+        return;
+    }
+
+    let (primary, lines) = match primary {
+        Some(t) if !t.lines.is_empty() => (t, &t.lines),
+        _ => {
+            let y = inner.y + inner.height / 2;
+            f.render_widget(
+                Paragraph::new("No lyrics")
+                    .style(Style::default().fg(app.theme.color_subtext()))
+                    .alignment(Alignment::Center),
+                Rect { x: inner.x, y, width: inner.width, height: 1 },
+            );
+            return;
+        }
+    };
+
+    let pos_ms = app.lyric_position_ms();
+    // None while unsynced lyrics are scrolled by hand: nothing is highlighted then.
+    let active = app.active_lyric_index();
+    let mut inner = inner;
+    if !primary.is_synced() && inner.height >= 6 {
+        inner.height -= 1;
+        f.render_widget(
+            Paragraph::new("j/k scroll · E estimated sync")
+                .style(Style::default().fg(app.theme.color_subtext()))
+                .alignment(Alignment::Center),
+            Rect { x: inner.x, y: inner.y + inner.height, width: inner.width, height: 1 },
+        );
+    }
+
+    // A blank row between lines when there is room; it also makes the easing visible.
+    // A stacked track needs a row of its own under each line.
+    let spacing: f32 = match (secondary.is_some(), inner.height >= 12) {
+        (true, true) => 3.0,
+        (true, false) | (false, true) => 2.0,
+        (false, false) => 1.0,
+    };
+    let center = inner.y as f32 + (inner.height / 2) as f32;
+    let reach = (inner.height as f32 / spacing).ceil() as i64 + 2;
+    let first = (app.lyrics_scroll.floor() as i64 - reach).max(0) as usize;
+    let last = ((app.lyrics_scroll.ceil() as i64 + reach).max(0) as usize).min(lines.len());
+
+    for i in first..last {
+        let row = (center + (i as f32 - app.lyrics_scroll) * spacing).round();
+        if row < inner.y as f32 || row >= (inner.y + inner.height) as f32 {
+            continue;
+        }
+        let line = &lines[i];
+        let content = match active {
+            Some(a) if i == a => karaoke_line(app, lines, i, pos_ms),
+            Some(a) => {
+                let color = if i > a && i - a <= 2 {
+                    app.theme.color_text()
+                } else {
+                    app.theme.color_subtext()
+                };
+                Line::styled(line.text.clone(), Style::default().fg(color))
+            }
+            None => Line::styled(line.text.clone(), Style::default().fg(app.theme.color_text())),
+        };
+        f.render_widget(
+            Paragraph::new(content).alignment(Alignment::Center),
+            Rect { x: inner.x, y: row as u16, width: inner.width, height: 1 },
+        );
+
+        let Some(sub) = secondary.and_then(|t| companion_at(primary, i, t)) else {
+            continue;
+        };
+        let sub_row = row as u16 + 1;
+        if sub_row >= inner.y + inner.height {
+            continue;
+        }
+        let color = if active == Some(i) {
+            app.theme.color_accent2()
+        } else {
+            app.theme.color_subtext()
+        };
+        f.render_widget(
+            Paragraph::new(sub.text.clone())
+                .style(Style::default().fg(color))
+                .alignment(Alignment::Center),
+            Rect { x: inner.x, y: sub_row, width: inner.width, height: 1 },
+        );
+    }
+}
+
+/// The active line: sung part in the accent color, the word in progress split at the
+/// character reached so far. Lines without word timing are highlighted as a whole.
+fn karaoke_line(app: &AppState, lines: &[LyricLine], idx: usize, pos_ms: u64) -> Line<'static> {
+    let sung = Style::default()
+        .fg(app.theme.color_lyric_highlight())
+        .add_modifier(Modifier::BOLD);
+    let unsung = Style::default().fg(app.theme.color_text());
+
+    let line = &lines[idx];
+    if line.words.is_empty() {
+        return Line::styled(line.text.clone(), sung);
+    }
+
+    let line_end = lines
+        .get(idx + 1)
+        .map(|l| l.start_ms)
+        .filter(|&t| t > line.start_ms);
+
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    for (k, w) in line.words.iter().enumerate() {
+        if w.text.is_empty() {
+            continue;
+        }
+        let end = line
+            .words
+            .get(k + 1)
+            .map(|n| n.start_ms)
+            .or(line_end)
+            .unwrap_or(w.start_ms + LAST_WORD_MS)
+            .max(w.start_ms + 1);
+
+        if pos_ms >= end {
+            spans.push(Span::styled(w.text.clone(), sung));
+        } else if pos_ms < w.start_ms {
+            spans.push(Span::styled(w.text.clone(), unsung));
+        } else {
+            let chars: Vec<char> = w.text.chars().collect();
+            let done = ((chars.len() as u64 * (pos_ms - w.start_ms)) / (end - w.start_ms)) as usize;
+            let (a, b) = chars.split_at(done.min(chars.len()));
+            spans.push(Span::styled(a.iter().collect::<String>(), sung));
+            spans.push(Span::styled(b.iter().collect::<String>(), unsung));
+        }
+    }
+    Line::from(spans)
+}
+
+/// Tap-to-sync editor: stamped lines with their times, the next line to stamp highlighted.
+pub fn render_sync(f: &mut Frame, area: Rect, app: &AppState) {
+    let Some(sync) = app.lyric_sync.as_ref() else {
+        return;
+    };
+    let title = format!(" Sync lyrics — {}/{} ", sync.cursor, sync.lines.len());
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(SOLID_BORDER)
+        .border_style(Style::default().fg(app.theme.color_border()))
+        .title(title)
+        .style(Style::default().fg(app.theme.color_subtext()));
+    f.render_widget(block, area);
+
+    let inner = area.inner(&ratatui::layout::Margin { horizontal: 2, vertical: 1 });
+    if inner.height < 3 || inner.width == 0 {
+        return;
+    }
+
+    let hint_y = inner.y + inner.height - 1;
+    f.render_widget(
+        Paragraph::new("Enter stamp · Backspace undo · ←/→ seek · Space play/pause · Ctrl+S save · Esc cancel")
+            .style(Style::default().fg(app.theme.color_subtext()))
+            .alignment(Alignment::Center),
+        Rect { x: inner.x, y: hint_y, width: inner.width, height: 1 },
+    );
+
+    let list_h = inner.height - 2;
+    let first = sync.cursor.saturating_sub(list_h as usize / 2);
+    let stamped = Style::default().fg(app.theme.color_text());
+    let pending = Style::default().fg(app.theme.color_subtext());
+    let next = Style::default()
+        .fg(app.theme.color_accent())
+        .add_modifier(Modifier::BOLD);
+
+    for (row, (i, (text, t))) in sync.lines.iter().enumerate().skip(first).take(list_h as usize).enumerate() {
+        let time = match t {
+            Some(ms) => format!("[{}] ", format_lrc_time(*ms)),
+            None => "[--:--.--] ".to_string(),
+        };
+        let style = if i == sync.cursor {
+            next
+        } else if t.is_some() {
+            stamped
+        } else {
+            pending
+        };
+        let line = Line::from(vec![
+            Span::styled(time, Style::default().fg(app.theme.color_subtext())),
+            Span::styled(text.clone(), style),
+        ]);
+        f.render_widget(
+            Paragraph::new(line),
+            Rect { x: inner.x, y: inner.y + row as u16, width: inner.width, height: 1 },
+        );
+    }
+}
@@ -1,7 +1,9 @@
 pub mod cover_cache;
 pub mod cover_renderer;
 pub mod dominant_color;
+pub mod iterm2_graphics;
 pub mod kitty_graphics;
 pub mod lyric_renderer;
 pub mod oscilloscope_renderer;
+pub mod sixel_graphics;
 pub mod spectrum_renderer;
@@ -0,0 +1,158 @@
+use crate::render::dominant_color::median_cut;
+use anyhow::Result;
+use crossterm::{cursor, queue, style::Print};
+use image::{imageops, RgbaImage};
+use ratatui::layout::Rect;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+/// Pixels sampled when building the palette; more only slows the median cut down.
+const PALETTE_SAMPLE: usize = 65536;
+
+/// Encode an image as a Sixel sequence scaled to fit `max_w_px` x `max_h_px`.
+///
+/// Unlike kitty/iTerm2 the terminal draws Sixel at its native pixel size, so the image is
+/// scaled up as well as down. `colors` is the palette size (2..=256).
+pub fn encode_image_bytes_to_sixel(image_bytes: &[u8], max_w_px: u32, max_h_px: u32, colors: usize) -> Option<String> {
+    let img = image::load_from_memory(image_bytes).ok()?;
+    let mut rgba = img.to_rgba8();
+    let (w, h) = rgba.dimensions();
+    if w == 0 || h == 0 || max_w_px == 0 || max_h_px == 0 {
+        return None;
+    }
+
+    let scale = (max_w_px as f32 / w as f32).min(max_h_px as f32 / h as f32);
+    let new_w = ((w as f32 * scale).round() as u32).clamp(1, max_w_px);
+    let new_h = ((h as f32 * scale).round() as u32).clamp(1, max_h_px);
+    if (new_w, new_h) != (w, h) {
+        rgba = imageops::resize(&rgba, new_w, new_h, imageops::FilterType::Triangle);
+    }
+
+    Some(encode_rgba(&rgba, colors.clamp(2, 256)))
+}
+
+fn encode_rgba(img: &RgbaImage, colors: usize) -> String {
+    let (w, h) = img.dimensions();
+    let (w, h) = (w as usize, h as usize);
+
+    let step = (w * h / PALETTE_SAMPLE).max(1);
+    let sample: Vec<[u8; 3]> = img
+        .pixels()
+        .step_by(step)
+        .filter(|p| p.0[3] >= 128)
+        .map(|p| [p.0[0], p.0[1], p.0[2]])
+        .collect();
+    let mut palette: Vec<(u8, u8, u8)> = median_cut(sample, colors).into_iter().map(|(c, _)| c).collect();
+    if palette.is_empty() {
+        palette.push((0, 0, 0));
+    }
+
+    // Palette index per pixel (None = transparent). Nearest-color lookups are cached per
+    // 15-bit color, which is plenty for a cover.
+    let mut lut = vec![u16::MAX; 1 << 15];
+    let indices: Vec<Option<u8>> = img
+        .pixels()
+        .map(|p| {
+            let [r, g, b, a] = p.0;
+            if a < 128 {
+                return None;
+            }
+            let key = ((r as usize >> 3) << 10) | ((g as usize >> 3) << 5) | (b as usize >> 3);
+            if lut[key] == u16::MAX {
+                lut[key] = nearest(&palette, (r, g, b)) as u16;
+            }
+            Some(lut[key] as u8)
+        })
+        .collect();
+
+    let mut out = String::with_capacity(w * h / 2);
+    // P2=1: pixels we don't paint stay transparent. Raster attributes: 1:1 aspect, w x h.
+    let _ = write!(out, "\x1bP0;1;0q\"1;1;{w};{h}");
+    let pct = |v: u8| (v as u32 * 100 + 127) / 255;
+    for (i, &(r, g, b)) in palette.iter().enumerate() {
+        let _ = write!(out, "#{i};2;{};{};{}", pct(r), pct(g), pct(b));
+    }
+
+    // Each band is six pixel rows; every color used in it gets one pass over the band,
+    // with `$` returning to the band start between colors and `-` moving to the next band.
+    let mut used = vec![false; palette.len()];
+    for top in (0..h).step_by(6) {
+        let rows = (h - top).min(6);
+        used.iter_mut().for_each(|u| *u = false);
+        for idx in indices[top * w..(top + rows) * w].iter().flatten() {
+            used[*idx as usize] = true;
+        }
+
+        let mut first = true;
+        for (color, _) in used.iter().enumerate().filter(|(_, u)| **u) {
+            if !first {
+                out.push('$');
+            }
+            first = false;
+            let _ = write!(out, "#{color}");
+
+            let mut run: Option<(char, usize)> = None;
+            for x in 0..w {
+                let mut bits = 0u8;
+                for dy in 0..rows {
+                    if indices[(top + dy) * w + x] == Some(color as u8) {
+                        bits |= 1 << dy;
+                    }
+                }
+                let ch = (63 + bits) as char;
+                run = match run {
+                    Some((c, n)) if c == ch => Some((c, n + 1)),
+                    prev => {
+                        if let Some((c, n)) = prev {
+                            push_run(&mut out, c, n);
+                        }
+                        Some((ch, 1))
+                    }
+                };
+            }
+            // A trailing empty run draws nothing; leave it out.
+            if let Some((c, n)) = run.filter(|(c, _)| *c != '?') {
+                push_run(&mut out, c, n);
+            }
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\");
+    out
+}
+
+fn push_run(out: &mut String, ch: char, n: usize) {
+    if n > 3 {
+        let _ = write!(out, "!{n}{ch}");
+    } else {
+        (0..n).for_each(|_| out.push(ch));
+    }
+}
+
+fn nearest(palette: &[(u8, u8, u8)], (r, g, b): (u8, u8, u8)) -> usize {
+    let dist = |&(pr, pg, pb): &(u8, u8, u8)| {
+        let dr = pr as i32 - r as i32;
+        let dg = pg as i32 - g as i32;
+        let db = pb as i32 - b as i32;
+        dr * dr + dg * dg + db * db
+    };
+    palette
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, c)| dist(c))
+        .map(|(i, _)| i)
+        .unwrap_or(0)
+}
+
+/// Draw an encoded Sixel image with its top-left corner at `rect`.
+///
+/// There is no way to delete a Sixel image; it stays until the cells under it are redrawn.
+pub fn place_image(rect: Rect, sixel: &str) -> Result<()> {
+    if rect.width == 0 || rect.height == 0 || sixel.is_empty() {
+        return Ok(());
+    }
+    let mut out = io::stdout();
+    queue!(out, cursor::MoveTo(rect.x, rect.y), Print(sixel))?;
+    out.flush()?;
+    Ok(())
+}
@@ -276,22 +276,6 @@ fn smooth_char(frac: f32) -> char {
 }
 
 fn vertical_gradient_color(app: &AppState, t: f32) -> Color {
-    // Top -> bottom
-    // Use the theme's accent range for a clear vertical gradient.
-    let top = app.theme.color_accent2();
-    let bottom = app.theme.color_accent3();
-    mix(top, bottom, t)
-}
-
-fn mix(a: Color, b: Color, t: f32) -> Color {
-    let t = t.clamp(0.0, 1.0);
-    match (a, b) {
-        (Color::Rgb(ar, ag, ab), Color::Rgb(br, bg, bb)) => {
-            let r = (ar as f32 + (br as f32 - ar as f32) * t) as u8;
-            let g = (ag as f32 + (bg as f32 - ag as f32) * t) as u8;
-            let b = (ab as f32 + (bb as f32 - ab as f32) * t) as u8;
-            Color::Rgb(r, g, b)
-        }
-        _ => a,
-    }
+    // Top -> bottom along the theme's spectrum stops.
+    app.theme.color_gradient(t)
 }
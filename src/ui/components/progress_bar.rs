@@ -25,14 +25,40 @@ pub fn render(f: &mut Frame, area: Rect, app: &AppState, pos: Duration, dur: Dur
         (ratio * (w as f32 - 1.0)).round() as usize
     };
 
-    let left = "─".repeat(knob);
-    let right = if w > 0 { "─".repeat(w.saturating_sub(1 + knob)) } else { String::new() };
+    let to_cell = |d: Duration| -> usize {
+        if dur.as_secs_f32() <= 0.0 || w <= 1 {
+            return 0;
+        }
+        ((d.as_secs_f32() / dur.as_secs_f32()).clamp(0.0, 1.0) * (w as f32 - 1.0)).round() as usize
+    };
+    // A–B loop: the looped stretch is tinted, with brackets at A and B.
+    let ab = app.ab_loop.as_ref().map(|ab| {
+        let a = to_cell(ab.a);
+        (a, ab.b.map(to_cell).unwrap_or(a))
+    });
+
+    let played = Style::default().fg(app.theme.color_progress());
+    let rest = Style::default().fg(app.theme.color_subtext());
+    let looped = Style::default().fg(app.theme.color_accent3());
 
-    let line = Line::from(vec![
-        Span::styled(left, Style::default().fg(app.theme.color_accent2())),
-        Span::styled("○", Style::default().fg(app.theme.color_accent())),
-        Span::styled(right, Style::default().fg(app.theme.color_subtext())),
-    ]);
+    let mut spans: Vec<Span> = Vec::with_capacity(w);
+    for i in 0..w {
+        let in_loop = ab.map(|(a, b)| i >= a && i <= b).unwrap_or(false);
+        let span = if i == knob {
+            Span::styled("○", Style::default().fg(app.theme.color_accent()))
+        } else if ab.map(|(a, _)| i == a).unwrap_or(false) {
+            Span::styled("[", looped)
+        } else if ab.map(|(a, b)| b > a && i == b).unwrap_or(false) {
+            Span::styled("]", looped)
+        } else if in_loop {
+            Span::styled("─", looped)
+        } else if i < knob {
+            Span::styled("─", played)
+        } else {
+            Span::styled("─", rest)
+        };
+        spans.push(span);
+    }
 
-    f.render_widget(Paragraph::new(line), area);
+    f.render_widget(Paragraph::new(Line::from(spans)), area);
 }
@@ -1,5 +1,5 @@
-use crate::app::state::{AppState, CoverSnapshot, Overlay, PlayMode};
-use crate::render::cover_cache::CoverKey;
+use crate::app::state::{AppState, CoverSnapshot, PlayMode};
+use crate::render::cover_renderer::{cover_cells, cover_text, CoverCell};
 use crate::ui::components::{control_buttons, progress_bar, volume_bar};
 use crate::ui::borders::SOLID_BORDER;
 use crate::utils::timefmt;
@@ -84,6 +84,7 @@ pub fn render(f: &mut Frame, area: Rect, app: &mut AppState) {
     let b = Block::default()
         .borders(Borders::ALL)
         .border_set(SOLID_BORDER)
+        .border_style(Style::default().fg(app.theme.color_border()))
         .title(" ")
         .style(Style::default().fg(app.theme.color_subtext()));
     f.render_widget(b, area);
@@ -94,8 +95,8 @@ pub fn render(f: &mut Frame, area: Rect, app: &mut AppState) {
     if l.cover.width > 0 && l.cover.height > 0 {
         let show_border = app.config.album_border;
 
-        let kitty_enabled = app.config.kitty_graphics
-            && app.kitty_graphics_supported
+        let kitty_enabled = app.config.cover_images
+            && app.graphics_protocol.is_some()
             && app.player.track.cover.is_some();
 
         let dominant_bg = if let (Some(bytes), Some(hash)) = (app.player.track.cover.as_deref(), app.player.track.cover_hash) {
@@ -108,7 +109,7 @@ pub fn render(f: &mut Frame, area: Rect, app: &mut AppState) {
 
         // Playlist overlay (including slide animation) should hide the song cover only in
         // kitty mode (otherwise the overlay will naturally cover the ASCII render).
-        let playlist_overlay_visible = app.overlay == Overlay::Playlist
+        let playlist_overlay_visible = app.overlay.shows_playlist()
             || app.playlist_slide_x != app.playlist_slide_target_x;
 
         if kitty_enabled {
@@ -119,6 +120,7 @@ pub fn render(f: &mut Frame, area: Rect, app: &mut AppState) {
                     let block = Block::default()
                         .borders(Borders::ALL)
                         .border_set(SOLID_BORDER)
+                        .border_style(Style::default().fg(app.theme.color_border()))
                         .style(Style::default().fg(app.theme.color_subtext()));
                     f.render_widget(block, l.cover);
                     let inner = l.cover.inner(&ratatui::layout::Margin { horizontal: 1, vertical: 1 });
@@ -142,11 +144,12 @@ pub fn render(f: &mut Frame, area: Rect, app: &mut AppState) {
                 let _ = cover_ascii_for_snapshot(&snap, inner_w, inner_h, app);
             } else {
                 // Draw border (optional) and keep the inside blank; the real image is painted
-                // after ratatui draw via kitty graphics protocol.
+                // after ratatui draw via the terminal image protocol.
                 if show_border {
                     let block = Block::default()
                         .borders(Borders::ALL)
                         .border_set(SOLID_BORDER)
+                        .border_style(Style::default().fg(app.theme.color_border()))
                         .style(Style::default().fg(app.theme.color_subtext()));
                     f.render_widget(block, l.cover);
                     let inner = l.cover.inner(&ratatui::layout::Margin { horizontal: 1, vertical: 1 });
@@ -200,7 +203,7 @@ pub fn render(f: &mut Frame, area: Rect, app: &mut AppState) {
 
                 let composed = compose_slide_cover(l.cover.width, l.cover.height, &from_box, &to_box, anim.dir, offset);
                 let fg = if to_fg == app.theme.color_text() { to_fg } else { from_fg };
-                f.render_widget(Paragraph::new(composed).style(Style::default().fg(fg)), l.cover);
+                f.render_widget(Paragraph::new(cover_text(composed)).style(Style::default().fg(fg)), l.cover);
 
                 // restore animation (lifetime managed in tick)
                 app.cover_anim = Some(anim);
@@ -213,7 +216,7 @@ pub fn render(f: &mut Frame, area: Rect, app: &mut AppState) {
                     show_border,
                     app,
                 );
-                f.render_widget(Paragraph::new(box_ascii).style(Style::default().fg(fg)), l.cover);
+                f.render_widget(Paragraph::new(cover_text(box_ascii)).style(Style::default().fg(fg)), l.cover);
             }
 
         }
@@ -270,7 +273,7 @@ pub fn render(f: &mut Frame, area: Rect, app: &mut AppState) {
     }
 
     // header right status (theme + mode)
-    let header = format!("[{}]  [Mode: {}]", app.theme.name.as_label(), mode_label(app.player.mode));
+    let header = format!("[{}]  [Mode: {}]", app.theme.label, mode_label(app.player.mode));
     let header_area = Rect { x: area.x + 2, y: area.y, width: area.width.saturating_sub(4), height: 1 };
     f.render_widget(
         Paragraph::new(header)
@@ -287,23 +290,24 @@ fn cover_box_ascii_for_snapshot(
     height: u16,
     show_border: bool,
     app: &mut AppState,
-) -> (String, ratatui::style::Color) {
+) -> (Vec<Vec<CoverCell>>, ratatui::style::Color) {
     if width == 0 || height == 0 {
-        return (String::new(), app.theme.color_subtext());
+        return (Vec::new(), app.theme.color_subtext());
     }
 
-    let mut grid: Vec<Vec<char>> = vec![vec![' '; width as usize]; height as usize];
+    let mut grid: Vec<Vec<CoverCell>> = vec![vec![CoverCell::plain(' '); width as usize]; height as usize];
 
     let (inner_x, inner_y, inner_w, inner_h) = if width >= 3 && height >= 3 {
         if show_border {
             // Border
-            let tl = SOLID_BORDER.top_left.chars().next().unwrap_or(' ');
-            let tr = SOLID_BORDER.top_right.chars().next().unwrap_or(' ');
-            let bl = SOLID_BORDER.bottom_left.chars().next().unwrap_or(' ');
-            let br = SOLID_BORDER.bottom_right.chars().next().unwrap_or(' ');
-            let hch = SOLID_BORDER.horizontal_top.chars().next().unwrap_or(' ');
-            let vl = SOLID_BORDER.vertical_left.chars().next().unwrap_or(' ');
-            let vr = SOLID_BORDER.vertical_right.chars().next().unwrap_or(' ');
+            let cell = |s: &str| CoverCell::plain(s.chars().next().unwrap_or(' '));
+            let tl = cell(SOLID_BORDER.top_left);
+            let tr = cell(SOLID_BORDER.top_right);
+            let bl = cell(SOLID_BORDER.bottom_left);
+            let br = cell(SOLID_BORDER.bottom_right);
+            let hch = cell(SOLID_BORDER.horizontal_top);
+            let vl = cell(SOLID_BORDER.vertical_left);
+            let vr = cell(SOLID_BORDER.vertical_right);
 
             grid[0][0] = tl;
             grid[0][(width - 1) as usize] = tr;
@@ -328,15 +332,9 @@ fn cover_box_ascii_for_snapshot(
     };
 
     let (inner_ascii, fg) = cover_ascii_for_snapshot(snap, inner_w as u16, inner_h as u16, app);
-    let inner_lines = split_lines(&inner_ascii, inner_h);
+    let inner_lines = cover_cells(&inner_ascii, inner_h);
     blit_xy(&mut grid, &inner_lines, inner_x as i16, inner_y as i16);
-
-    let mut out = String::with_capacity((width as usize + 1) * height as usize);
-    for row in grid {
-        out.extend(row);
-        out.push('\n');
-    }
-    (out, fg)
+    (grid, fg)
 }
 
 fn hash_track_seed(app: &AppState) -> u64 {
@@ -362,13 +360,13 @@ fn cover_ascii_for_snapshot(
     app: &mut AppState,
 ) -> (String, ratatui::style::Color) {
     if let (Some(bytes), Some(hash)) = (snap.cover.as_deref(), snap.cover_hash) {
-        let key = CoverKey { hash, width, height };
+        let key = app.cover_key(hash, width, height);
         let cached = { app.cover_cache.borrow_mut().get(key) };
         let ascii = match cached {
             Some(s) => s,
             None => {
                 if let Some(folder) = snap.cover_folder.as_deref() {
-                    if let Some(s) = crate::playback::local_player::read_cover_ascii_cache(folder, hash, width, height) {
+                    if let Some(s) = crate::playback::local_player::read_cover_ascii_cache(folder, key) {
                         app.cover_cache.borrow_mut().put(key, s.clone());
                         return (s, app.theme.color_text());
                     }
@@ -382,7 +380,7 @@ fn cover_ascii_for_snapshot(
         (ascii, app.theme.color_text())
     } else {
         let seed = hash_snapshot_seed(snap);
-        let key = CoverKey { hash: seed, width, height };
+        let key = app.cover_key(seed, width, height);
         let cached = { app.cover_cache.borrow_mut().get(key) };
         let ascii = match cached {
             Some(s) => s,
@@ -412,17 +410,15 @@ fn fill_ascii(width: u16, height: u16, ch: char) -> String {
 fn compose_slide_cover(
     width: u16,
     height: u16,
-    from_ascii: &str,
-    to_ascii: &str,
+    from_lines: &[Vec<CoverCell>],
+    to_lines: &[Vec<CoverCell>],
     dir: i8,
     offset: i16,
-) -> String {
+) -> Vec<Vec<CoverCell>> {
     let w = width as i16;
     let h = height as usize;
 
-    let mut grid: Vec<Vec<char>> = vec![vec![' '; width as usize]; h];
-    let from_lines = split_lines(from_ascii, h);
-    let to_lines = split_lines(to_ascii, h);
+    let mut grid: Vec<Vec<CoverCell>> = vec![vec![CoverCell::plain(' '); width as usize]; h];
 
     // Next: dir=-1, both move left. Prev: dir=+1, both move right.
     let (from_dx, to_dx) = if dir < 0 {
@@ -431,32 +427,12 @@ fn compose_slide_cover(
         (offset, -w + offset)
     };
 
-    blit(&mut grid, &from_lines, from_dx);
-    blit(&mut grid, &to_lines, to_dx);
-
-    let mut out = String::with_capacity((width as usize + 1) * h);
-    for row in grid {
-        out.extend(row);
-        out.push('\n');
-    }
-    out
-}
-
-fn split_lines(s: &str, expected: usize) -> Vec<Vec<char>> {
-    let mut out: Vec<Vec<char>> = Vec::with_capacity(expected);
-    for line in s.lines() {
-        out.push(line.chars().collect());
-        if out.len() == expected {
-            break;
-        }
-    }
-    while out.len() < expected {
-        out.push(Vec::new());
-    }
-    out
+    blit(&mut grid, from_lines, from_dx);
+    blit(&mut grid, to_lines, to_dx);
+    grid
 }
 
-fn blit(dst: &mut [Vec<char>], src: &[Vec<char>], dx: i16) {
+fn blit(dst: &mut [Vec<CoverCell>], src: &[Vec<CoverCell>], dx: i16) {
     let h = dst.len().min(src.len());
     if h == 0 {
         return;
@@ -472,7 +448,7 @@ fn blit(dst: &mut [Vec<char>], src: &[Vec<char>], dx: i16) {
     }
 }
 
-fn blit_xy(dst: &mut [Vec<char>], src: &[Vec<char>], dx: i16, dy: i16) {
+fn blit_xy(dst: &mut [Vec<CoverCell>], src: &[Vec<CoverCell>], dx: i16, dy: i16) {
     let dst_h = dst.len() as i16;
     if dst_h == 0 {
         return;
@@ -1,10 +1,10 @@
 use crate::app::state::AppState;
-use crate::app::state::{LocalFolderKind, Overlay, PlayMode};
+use crate::app::state::{LocalFolderKind, PlayMode};
+use crate::render::cover_renderer::{cover_cells, cover_text, CoverCell};
 use crate::ui::borders::SOLID_BORDER;
-use crate::render::cover_cache::CoverKey;
 use ratatui::layout::Rect;
 use ratatui::style::{Color, Modifier, Style};
-use ratatui::text::Line;
+use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
 use ratatui::Frame;
 use std::path::PathBuf;
@@ -84,12 +84,12 @@ fn render_album_cover(f: &mut Frame, area: Rect, app: &mut AppState) {
 
     // Performance: during slide in/out, avoid expensive cover ASCII rendering.
     // Only render the real cover when the playlist overlay is fully expanded.
-    let fully_expanded = app.overlay == Overlay::Playlist
+    let fully_expanded = app.overlay.shows_playlist()
         && app.playlist_slide_x == 0
         && app.playlist_slide_target_x == 0;
 
-    let kitty_enabled = app.config.kitty_graphics
-        && app.kitty_graphics_supported
+    let kitty_enabled = app.config.cover_images
+        && app.graphics_protocol.is_some()
         && app.local_view_album_cover.is_some();
 
     if kitty_enabled {
@@ -171,7 +171,7 @@ fn render_album_cover(f: &mut Frame, area: Rect, app: &mut AppState) {
 
         let composed = compose_slide_cover(cover.width, cover.height, &from_ascii, &to_ascii, anim.dir, offset);
         f.render_widget(
-            Paragraph::new(composed)
+            Paragraph::new(cover_text(composed))
                 .style(Style::default().bg(app.theme.color_surface()).fg(app.theme.color_text()))
                 .wrap(Wrap { trim: false }),
             cover,
@@ -194,7 +194,7 @@ fn render_album_cover(f: &mut Frame, area: Rect, app: &mut AppState) {
         );
         app.local_view_album_cover = current_cover;
         f.render_widget(
-            Paragraph::new(ascii)
+            Paragraph::new(cover_text(cover_cells(&ascii, cover.height as usize)))
                 .style(Style::default().bg(app.theme.color_surface()).fg(app.theme.color_text()))
                 .wrap(Wrap { trim: false }),
             cover,
@@ -251,15 +251,31 @@ fn render_playlist_list(f: &mut Frame, area: Rect, app: &AppState) {
     let footer_rows: u16 = 2;
     let list_rows = area.height.saturating_sub(footer_rows);
 
-    let total = app.playlist_view.items.len();
-    let selected = app.playlist_view.selected.min(total.saturating_sub(1));
+    let (items, selected, current) = if app.up_next_view {
+        (&app.up_next, app.up_next_selected, None)
+    } else {
+        (&app.playlist_view.items, app.playlist_view.selected, app.playlist_view.current)
+    };
+
+    // `/` search narrows the list to matches; rows keep their playlist numbering.
+    let search = app.playlist_search.as_ref().filter(|_| !app.up_next_view);
+    let rows: Vec<(usize, &[usize])> = match search {
+        Some(s) => s.matches.iter().map(|(i, pos)| (*i, pos.as_slice())).collect(),
+        None => (0..items.len()).map(|i| (i, &[][..])).collect(),
+    };
+    let total = rows.len();
+    let selected_row = match search {
+        Some(s) => s.selected,
+        None => selected,
+    }
+    .min(total.saturating_sub(1));
 
     let visible = list_rows as usize;
     let mut start = 0usize;
     if visible > 0 && total > visible {
         // Keep selection within the visible window.
-        if selected >= visible {
-            start = selected + 1 - visible;
+        if selected_row >= visible {
+            start = selected_row + 1 - visible;
         }
         // Also clamp to tail.
         start = start.min(total - visible);
@@ -269,36 +285,70 @@ fn render_playlist_list(f: &mut Frame, area: Rect, app: &AppState) {
     let mut lines: Vec<Line> = Vec::new();
 
     if total == 0 {
+        let empty = if search.is_some() {
+            "(no matches)"
+        } else if app.up_next_view {
+            "(queue empty)"
+        } else {
+            "(empty)"
+        };
         lines.push(Line::styled(
-            "(empty)",
+            empty,
             Style::default()
                 .fg(app.theme.color_subtext())
                 .bg(app.theme.color_surface()),
         ));
     } else {
-        for i in start..end {
-            let it = &app.playlist_view.items[i];
-            let prefix = if app.playlist_view.current == Some(i) { "[>]" } else { "   " };
-            let label = format!("{} {:02}. {}", prefix, i + 1, it.title);
+        for (row, &(i, matched)) in rows.iter().enumerate().take(end).skip(start) {
+            let it = &items[i];
+            let prefix = if current == Some(i) { "[>]" } else { "   " };
             let mut style = Style::default()
                 .fg(app.theme.color_text())
                 .bg(app.theme.color_surface());
-            if i == app.playlist_view.selected {
+            if row == selected_row {
                 style = Style::default()
                     .fg(app.theme.color_base())
-                    .bg(app.theme.color_accent())
+                    .bg(app.theme.color_selection())
                     .add_modifier(Modifier::BOLD);
+            } else if it.missing {
+                style = style.fg(app.theme.color_subtext()).add_modifier(Modifier::DIM);
             }
-            lines.push(Line::styled(label, style));
+            if matched.is_empty() {
+                let missing = if it.missing { " (missing)" } else { "" };
+                lines.push(Line::styled(format!("{} {:02}. {}{missing}", prefix, i + 1, it.title), style));
+                continue;
+            }
+            let hl = style.fg(app.theme.color_accent3()).add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+            let mut spans = vec![Span::styled(format!("{} {:02}. ", prefix, i + 1), style)];
+            for (ci, ch) in it.title.chars().enumerate() {
+                let st = if matched.contains(&ci) { hl } else { style };
+                spans.push(Span::styled(ch.to_string(), st));
+            }
+            lines.push(Line::from(spans));
         }
     }
 
-    // No in-panel shortcut hint; see Keys modal.
-
     let p = Paragraph::new(lines)
         .style(Style::default().bg(app.theme.color_surface()))
         .wrap(Wrap { trim: true });
     f.render_widget(p, area);
+
+    // No in-panel shortcut hint; see Keys modal. The footer only shows the search prompt.
+    if let Some(s) = search {
+        if area.height > footer_rows {
+            let prompt = Rect {
+                x: area.x,
+                y: area.y + area.height - 1,
+                width: area.width,
+                height: 1,
+            };
+            let text = format!("/{}  ({} of {})", s.query, s.matches.len(), items.len());
+            f.render_widget(
+                Paragraph::new(text).style(Style::default().fg(app.theme.color_accent()).bg(app.theme.color_surface())),
+                prompt,
+            );
+        }
+    }
 }
 
 fn album_cover_ascii(
@@ -311,7 +361,7 @@ fn album_cover_ascii(
     default_ch: char,
 ) -> String {
     if let (Some(bytes), Some(hash)) = (bytes, hash) {
-        let key = CoverKey { hash, width, height };
+        let key = app.cover_key(hash, width, height);
         let mut cache = app.cover_cache.borrow_mut();
         if let Some(s) = cache.get(key) {
             return s;
@@ -319,7 +369,7 @@ fn album_cover_ascii(
         drop(cache);
 
         if let Some(folder) = folder {
-            if let Some(s) = crate::playback::local_player::read_cover_ascii_cache(folder, hash, width, height) {
+            if let Some(s) = crate::playback::local_player::read_cover_ascii_cache(folder, key) {
                 app.cover_cache.borrow_mut().put(key, s.clone());
                 return s;
             }
@@ -353,13 +403,13 @@ fn warm_album_cover_ascii_cache(
         return;
     };
 
-    let key = CoverKey { hash, width, height };
+    let key = app.cover_key(hash, width, height);
     if app.cover_cache.borrow().contains(key) {
         return;
     }
 
     if let Some(folder) = folder {
-        if let Some(s) = crate::playback::local_player::read_cover_ascii_cache(folder, hash, width, height) {
+        if let Some(s) = crate::playback::local_player::read_cover_ascii_cache(folder, key) {
             app.cover_cache.borrow_mut().put(key, s);
             return;
         }
@@ -375,13 +425,13 @@ fn compose_slide_cover(
     to_ascii: &str,
     dir: i8,
     offset: i16,
-) -> String {
+) -> Vec<Vec<CoverCell>> {
     let w = width as i16;
     let h = height as usize;
 
-    let mut grid: Vec<Vec<char>> = vec![vec![' '; width as usize]; h];
-    let from_lines = split_lines(from_ascii, h);
-    let to_lines = split_lines(to_ascii, h);
+    let mut grid: Vec<Vec<CoverCell>> = vec![vec![CoverCell::plain(' '); width as usize]; h];
+    let from_lines = cover_cells(from_ascii, h);
+    let to_lines = cover_cells(to_ascii, h);
 
     // Next: dir=-1, both move left. Prev: dir=+1, both move right.
     let (from_dx, to_dx) = if dir < 0 {
@@ -392,30 +442,10 @@ fn compose_slide_cover(
 
     blit(&mut grid, &from_lines, from_dx);
     blit(&mut grid, &to_lines, to_dx);
-
-    let mut out = String::with_capacity((width as usize + 1) * h);
-    for row in grid {
-        out.extend(row);
-        out.push('\n');
-    }
-    out
+    grid
 }
 
-fn split_lines(s: &str, expected: usize) -> Vec<Vec<char>> {
-    let mut out: Vec<Vec<char>> = Vec::with_capacity(expected);
-    for line in s.lines() {
-        out.push(line.chars().collect());
-        if out.len() == expected {
-            break;
-        }
-    }
-    while out.len() < expected {
-        out.push(Vec::new());
-    }
-    out
-}
-
-fn blit(dst: &mut [Vec<char>], src: &[Vec<char>], dx: i16) {
+fn blit(dst: &mut [Vec<CoverCell>], src: &[Vec<CoverCell>], dx: i16) {
     let h = dst.len().min(src.len());
     if h == 0 {
         return;
@@ -435,11 +465,32 @@ pub fn render(f: &mut Frame, area: Rect, app: &mut AppState) {
     // solid background for playlist overlay
     f.render_widget(ratatui::widgets::Clear, area);
 
+    let title = match app.user_playlist.as_deref() {
+        _ if app.up_next_view => format!("Up Next ({} tracks)", app.up_next.len()),
+        Some(p) if app.local_view_album_folder.is_none() => format!(
+            "Playlist: {} ({} tracks)",
+            crate::data::playlist_file::playlist_name(p),
+            app.playlist_view.len()
+        ),
+        _ => format!("Playlist ({} tracks)", app.playlist_view.len()),
+    };
+    let title = if !app.up_next_view && !app.up_next.is_empty() {
+        format!("{title} · {} up next", app.up_next.len())
+    } else {
+        title
+    };
+    // Count / unfinished key sequence being typed ("5d").
+    let title = if app.key_pending.is_empty() {
+        title
+    } else {
+        format!("{title} · {}", app.key_pending.label())
+    };
     let block = Block::default()
         .borders(Borders::ALL)
         .border_set(SOLID_BORDER)
+        .border_style(Style::default().fg(app.theme.color_border()))
         .style(Style::default().fg(app.theme.color_subtext()).bg(app.theme.color_surface()))
-        .title(format!("Playlist ({} tracks)", app.playlist_view.len()));
+        .title(title);
     f.render_widget(block, area);
 
     let l = compute_layout(area, app);
@@ -1,6 +1,6 @@
 use crate::app::state::AppState;
 use crate::data::config::VisualizeMode;
-use crate::render::{oscilloscope_renderer, spectrum_renderer};
+use crate::render::{lyric_renderer, oscilloscope_renderer, spectrum_renderer};
 use crate::ui::borders::SOLID_BORDER;
 use ratatui::layout::{Alignment, Rect};
 use ratatui::style::Style;
@@ -15,10 +15,15 @@ pub fn render(f: &mut Frame, lyric_area: Rect, spectrum_area: Rect, app: &AppSta
         width: lyric_area.width,
         height: lyric_area.height.saturating_add(spectrum_area.height),
     };
-    let outer_block = Block::default()
+    let mut outer_block = Block::default()
         .borders(Borders::ALL)
         .border_set(SOLID_BORDER)
+        .border_style(Style::default().fg(app.theme.color_border()))
         .style(Style::default().fg(app.theme.color_subtext()));
+    // Mark lyrics that don't follow real timestamps.
+    if let Some(t) = app.lyric_display().0.filter(|t| !t.is_synced() && !t.lines.is_empty()) {
+        outer_block = outer_block.title(format!(" {} ", lyric_renderer::timing_label(app, t)));
+    }
     f.render_widget(outer_block, outer);
 
     let inner = outer.inner(&ratatui::layout::Margin { horizontal: 1, vertical: 1 });
@@ -59,24 +64,26 @@ pub fn render(f: &mut Frame, lyric_area: Rect, spectrum_area: Rect, app: &AppSta
 
 
 fn current_two_lines(app: &AppState) -> (String, String) {
-    let Some(lines) = app.player.track.lyrics.as_ref() else {
+    let (Some(primary), secondary) = app.lyric_display() else {
         return (String::new(), String::new());
     };
+    let lines = &primary.lines;
     if lines.is_empty() {
         return (String::new(), String::new());
     }
 
-    let pos_ms = app.player.position.as_millis() as u64;
-    let mut idx = 0;
-    for (i, l) in lines.iter().enumerate() {
-        if l.start_ms <= pos_ms {
-            idx = i;
-        } else {
-            break;
-        }
-    }
+    // Unsynced lyrics scrolled by hand show the lines at the scroll position.
+    let idx = app
+        .active_lyric_index()
+        .unwrap_or_else(|| (app.lyrics_scroll.round() as usize).min(lines.len() - 1));
 
     let l1 = lines.get(idx).map(|l| l.text.clone()).unwrap_or_default();
-    let l2 = lines.get(idx + 1).map(|l| l.text.clone()).unwrap_or_default();
+    // With a stacked track the second row is its line for the same moment.
+    let l2 = match secondary {
+        Some(t) => lyric_renderer::companion_at(primary, idx, t)
+            .map(|l| l.text.clone())
+            .unwrap_or_default(),
+        None => lines.get(idx + 1).map(|l| l.text.clone()).unwrap_or_default(),
+    };
     (l1, l2)
 }
@@ -1,54 +1,17 @@
 use ratatui::style::Color;
+use std::path::PathBuf;
+use std::time::SystemTime;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ColorCapability {
     TrueColor,
     Ansi256,
     NoColor,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum ThemeName {
-    System,
-    Latte,
-    Frappe,
-    Macchiato,
-    Mocha,
-}
-
-impl ThemeName {
-    pub fn from_str_or_system(s: &str) -> Self {
-        match s.to_lowercase().as_str() {
-            "latte" => Self::Latte,
-            "frappe" => Self::Frappe,
-            "macchiato" => Self::Macchiato,
-            "mocha" => Self::Mocha,
-            _ => Self::System,
-        }
-    }
-
-    pub fn as_label(&self) -> &'static str {
-        match self {
-            ThemeName::System => "System",
-            ThemeName::Latte => "Latte",
-            ThemeName::Frappe => "Frappe",
-            ThemeName::Macchiato => "Macchiato",
-            ThemeName::Mocha => "Mocha",
-        }
-    }
-
-    pub fn next(self) -> Self {
-        match self {
-            ThemeName::System => ThemeName::Latte,
-            ThemeName::Latte => ThemeName::Frappe,
-            ThemeName::Frappe => ThemeName::Macchiato,
-            ThemeName::Macchiato => ThemeName::Mocha,
-            ThemeName::Mocha => ThemeName::System,
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy)]
+/// Colors of a theme. The slots after `accent3` are optional in theme files and fall back
+/// to one of the seven base colors (see `ThemeLoader`).
+#[derive(Debug, Clone)]
 pub struct ThemePalette {
     pub text: (u8, u8, u8),
     pub subtext: (u8, u8, u8),
@@ -57,13 +20,28 @@ pub struct ThemePalette {
     pub accent: (u8, u8, u8),
     pub accent2: (u8, u8, u8),
     pub accent3: (u8, u8, u8),
+    /// Spectrum / oscilloscope gradient, top to bottom (at least two stops).
+    pub spectrum: Vec<(u8, u8, u8)>,
+    pub progress: (u8, u8, u8),
+    pub lyric_highlight: (u8, u8, u8),
+    pub border: (u8, u8, u8),
+    /// Background of the selected row in lists.
+    pub selection: (u8, u8, u8),
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct Theme {
-    pub name: ThemeName,
+    /// What `config.theme` stores: a built-in name ("mocha") or a theme file's stem.
+    pub key: String,
+    pub label: String,
+    /// Colors in use. Equal to `file_palette` unless the adaptive theme is on (or fading).
     pub palette: ThemePalette,
+    /// Colors as loaded from the theme file.
+    pub file_palette: ThemePalette,
     pub capability: ColorCapability,
+    /// Theme file and its modification time, for live reload.
+    pub path: PathBuf,
+    pub modified: Option<SystemTime>,
 }
 
 impl Theme {
@@ -88,6 +66,76 @@ impl Theme {
     pub fn color_accent3(&self) -> Color {
         map_color(self.capability, self.palette.accent3)
     }
+    pub fn color_progress(&self) -> Color {
+        map_color(self.capability, self.palette.progress)
+    }
+    pub fn color_lyric_highlight(&self) -> Color {
+        map_color(self.capability, self.palette.lyric_highlight)
+    }
+    pub fn color_border(&self) -> Color {
+        map_color(self.capability, self.palette.border)
+    }
+    pub fn color_selection(&self) -> Color {
+        map_color(self.capability, self.palette.selection)
+    }
+
+    /// Spectrum gradient at `t` (0 = top, 1 = bottom), interpolated between the stops.
+    pub fn color_gradient(&self, t: f32) -> Color {
+        match gradient_rgb(&self.palette.spectrum, t) {
+            Some(rgb) => map_color(self.capability, rgb),
+            None => self.color_accent2(),
+        }
+    }
+}
+
+impl ThemePalette {
+    /// Blend toward `other` (`t` = 0 is `self`, 1 is `other`), for theme transitions.
+    pub fn lerp(&self, other: &ThemePalette, t: f32) -> ThemePalette {
+        let c = |a: (u8, u8, u8), b: (u8, u8, u8)| lerp_rgb(a, b, t);
+        // Resample both gradients to the same number of stops.
+        let n = self.spectrum.len().max(other.spectrum.len()).max(2);
+        let spectrum = (0..n)
+            .map(|i| {
+                let x = i as f32 / (n - 1) as f32;
+                let a = gradient_rgb(&self.spectrum, x).unwrap_or(self.accent2);
+                let b = gradient_rgb(&other.spectrum, x).unwrap_or(other.accent2);
+                c(a, b)
+            })
+            .collect();
+        ThemePalette {
+            text: c(self.text, other.text),
+            subtext: c(self.subtext, other.subtext),
+            base: c(self.base, other.base),
+            surface: c(self.surface, other.surface),
+            accent: c(self.accent, other.accent),
+            accent2: c(self.accent2, other.accent2),
+            accent3: c(self.accent3, other.accent3),
+            spectrum,
+            progress: c(self.progress, other.progress),
+            lyric_highlight: c(self.lyric_highlight, other.lyric_highlight),
+            border: c(self.border, other.border),
+            selection: c(self.selection, other.selection),
+        }
+    }
+}
+
+pub fn lerp_rgb(a: (u8, u8, u8), b: (u8, u8, u8), t: f32) -> (u8, u8, u8) {
+    let t = t.clamp(0.0, 1.0);
+    let l = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+    (l(a.0, b.0), l(a.1, b.1), l(a.2, b.2))
+}
+
+/// Color at `t` (0..1) along evenly spaced gradient stops.
+fn gradient_rgb(stops: &[(u8, u8, u8)], t: f32) -> Option<(u8, u8, u8)> {
+    match stops.len() {
+        0 => None,
+        1 => Some(stops[0]),
+        n => {
+            let x = t.clamp(0.0, 1.0) * (n - 1) as f32;
+            let i = (x.floor() as usize).min(n - 2);
+            Some(lerp_rgb(stops[i], stops[i + 1], x - i as f32))
+        }
+    }
 }
 
 pub fn detect_color_capability() -> ColorCapability {
@@ -1,6 +1,8 @@
 use crate::app::state::{AppState, Overlay};
+use crate::data::keymap::KeyContext;
 use crate::ui::panels::{info_panel, playlist_panel, visual_panel};
 use crate::ui::components::control_buttons;
+use crate::utils::graphics::GraphicsProtocol;
 use crate::utils::input::Action;
 use anyhow::Result;
 use crossterm::execute;
@@ -41,16 +43,28 @@ pub struct UiLayout {
 
 struct KittyRenderRequest {
     hash: u64,
+    cells: (u16, u16),
     bytes: Vec<u8>,
     max_w: u32,
     max_h: u32,
+    /// Encode as Sixel with this many colors instead of PNG.
+    sixel_colors: Option<usize>,
 }
 
 struct KittyRenderResponse {
     hash: u64,
+    cells: (u16, u16),
+    /// Base64 PNG, or a complete Sixel sequence.
     b64: String,
 }
 
+/// Sixel / iTerm2 images are keyed by cover hash and the cell size they were encoded for.
+type InlineKey = (u64, u16, u16);
+
+// Approximate terminal cell pixel size. Used only for downscaling before encoding.
+const CELL_W_PX: u32 = 8;
+const CELL_H_PX: u32 = 16;
+
 pub struct Tui {
     terminal: Terminal<CrosstermBackend<Stdout>>,
     pub should_quit: bool,
@@ -67,6 +81,16 @@ pub struct Tui {
     kitty_pending: HashSet<u64>,
 
     kitty_last_cover_quality: u8,
+
+    // Sixel / iTerm2: images can't be deleted, only painted over, so anything that would
+    // hide or move one clears the screen before the next draw.
+    inline_info_last: Option<(u64, Rect)>,
+    inline_playlist_last: Option<(u64, Rect)>,
+    inline_overlay_last: Option<Overlay>,
+    inline_images: HashMap<InlineKey, String>,
+    inline_pending: HashSet<InlineKey>,
+    inline_dirty: bool,
+    cell_px: Option<(u32, u32)>,
 }
 
 impl Tui {
@@ -76,8 +100,12 @@ impl Tui {
 
         thread::spawn(move || {
             while let Ok(req) = rx.recv() {
-                if let Some(b64) = crate::render::kitty_graphics::encode_image_bytes_to_png_base64(&req.bytes, req.max_w, req.max_h) {
-                    let _ = res_tx.send(KittyRenderResponse { hash: req.hash, b64 });
+                let encoded = match req.sixel_colors {
+                    Some(colors) => crate::render::sixel_graphics::encode_image_bytes_to_sixel(&req.bytes, req.max_w, req.max_h, colors),
+                    None => crate::render::kitty_graphics::encode_image_bytes_to_png_base64(&req.bytes, req.max_w, req.max_h),
+                };
+                if let Some(b64) = encoded {
+                    let _ = res_tx.send(KittyRenderResponse { hash: req.hash, cells: req.cells, b64 });
                 }
             }
         });
@@ -97,6 +125,13 @@ impl Tui {
             kitty_rx: res_rx,
             kitty_pending: HashSet::new(),
             kitty_last_cover_quality: 0,
+            inline_info_last: None,
+            inline_playlist_last: None,
+            inline_overlay_last: None,
+            inline_images: HashMap::new(),
+            inline_pending: HashSet::new(),
+            inline_dirty: false,
+            cell_px: None,
         })
     }
 
@@ -125,6 +160,14 @@ impl Tui {
         self.kitty_transmitted.clear();
         self.kitty_pending.clear();
         while self.kitty_rx.try_recv().is_ok() {}
+
+        // The resize redraws the whole screen, which wipes Sixel / iTerm2 images; cells may
+        // have changed pixel size too.
+        self.inline_info_last = None;
+        self.inline_playlist_last = None;
+        self.inline_images.clear();
+        self.inline_pending.clear();
+        self.cell_px = None;
     }
 
     pub fn draw(&mut self, app: &mut AppState) -> Result<UiLayout> {
@@ -134,6 +177,8 @@ impl Tui {
 
         let mut layout_out = UiLayout::default();
 
+        self.clear_stale_inline_images(app)?;
+
         self.terminal.draw(|f| {
             let size = f.size();
             layout_out.full = size;
@@ -221,7 +266,7 @@ impl Tui {
             visual_panel::render(f, rows[0], rows[1], app);
 
             // playlist overlay slides in/out over left
-            if app.overlay == Overlay::Playlist || app.playlist_slide_x != app.playlist_slide_target_x {
+            if app.overlay.shows_playlist() || app.playlist_slide_x != app.playlist_slide_target_x {
                 // advance animation
                 let step: i16 = 4;
                 if app.playlist_slide_x < app.playlist_slide_target_x {
@@ -249,8 +294,30 @@ impl Tui {
                 }
             }
 
+            // full-screen lyrics view covers both panels (footer stays visible)
+            if matches!(app.overlay, Overlay::Lyrics | Overlay::LyricSync) {
+                let area = Rect {
+                    x: size.x,
+                    y: size.y,
+                    width: size.width,
+                    height: size.height.saturating_sub(1),
+                };
+                f.render_widget(ratatui::widgets::Clear, area);
+                f.render_widget(ratatui::widgets::Block::default().style(base_style), area);
+                if app.overlay == Overlay::LyricSync {
+                    crate::render::lyric_renderer::render_sync(f, area, app);
+                } else {
+                    crate::render::lyric_renderer::render(f, area, app);
+                }
+            }
+
             // footer hint
-            let footer = "Ctrl+K: Keys";
+            let footer = format!(
+                "{}: Keys",
+                app.keymap
+                    .key_for(KeyContext::Global, Action::OpenHelpModal)
+                    .unwrap_or_else(|| "Ctrl+K".to_string())
+            );
             let footer_area = Rect {
                 x: size.x,
                 y: size.y + size.height.saturating_sub(1),
@@ -301,13 +368,21 @@ impl Tui {
                 Overlay::AcoustIdModal => render_acoustid_modal(f, size, app),
                 Overlay::HelpModal => render_help_modal(f, size, app),
                 Overlay::EqModal => render_eq_modal(f, size, app),
+                Overlay::PlaylistManager => render_playlist_manager_modal(f, size, app),
+                Overlay::PlaylistNameInput => render_playlist_name_modal(f, size, app),
+                Overlay::Library | Overlay::LibraryFilter => render_library_modal(f, size, app),
+                Overlay::Tagger => render_tagger_modal(f, size, app),
+                Overlay::TagEditor => render_tag_editor_modal(f, size, app),
                 _ => {}
             }
         })?;
 
-        // After ratatui draw, optionally paint kitty images on top.
+        // After ratatui draw, optionally paint cover images on top.
         // (Keep it best-effort; if the terminal doesn't support it, users can keep it off.)
-        self.paint_kitty_images(app, &layout_out)?;
+        match app.graphics_protocol {
+            Some(GraphicsProtocol::Sixel | GraphicsProtocol::Iterm2) => self.paint_inline_images(app, &layout_out)?,
+            _ => self.paint_kitty_images(app, &layout_out)?,
+        }
 
         Ok(layout_out)
     }
@@ -326,26 +401,13 @@ impl Tui {
         const INFO_PLACEMENT_ID: u32 = 1;
         const PLAYLIST_PLACEMENT_ID: u32 = 2;
 
-        // While Settings modal is open, do NOT refresh/re-transmit covers; keep using
-        // the last applied quality so the cover doesn't constantly churn while tweaking.
-        let settings_open = matches!(
-            app.overlay,
-            Overlay::SettingsModal
-                | Overlay::AcoustIdModal
-                | Overlay::BarSettingsModal
-                | Overlay::LocalAudioSettingsModal
-                | Overlay::AboutModal
-        );
+        let settings_open = settings_open(app);
 
         // 0 is used as an internal sentinel for "not initialized yet".
         if self.kitty_last_cover_quality == 0 {
             self.kitty_last_cover_quality = app.config.kitty_cover_scale_percent;
         }
 
-        // Approximate terminal cell pixel size. Used only for downscaling before encoding.
-        const CELL_W_PX: u32 = 8;
-        const CELL_H_PX: u32 = 16;
-
         let hide_info = |this: &mut Self| {
             if let Some((hash, _, _)) = this.kitty_info_last {
                 if let Some(&image_id) = this.kitty_image_ids.get(&hash) {
@@ -375,7 +437,7 @@ impl Tui {
             }
         };
 
-        if !app.kitty_graphics_supported || !app.config.kitty_graphics {
+        if app.graphics_protocol.is_none() || !app.config.cover_images {
             // Kitty graphics disabled (or unsupported): hide any placed images and free
             // image data so the terminal doesn't keep stale cached images.
             drain_discard(self);
@@ -418,26 +480,7 @@ impl Tui {
             app.config.kitty_cover_scale_percent
         };
 
-        let target_px = |w_cells: u16, h_cells: u16| -> (u32, u32) {
-            let q = effective_quality.clamp(25, 100);
-            // 100% means no downscale "compression" at all.
-            if q >= 100 {
-                return (u32::MAX, u32::MAX);
-            }
-
-            let scale = (q as u32).max(1);
-            let w = (w_cells as u32)
-                .saturating_mul(CELL_W_PX)
-                .saturating_mul(scale)
-                / 100;
-            let h = (h_cells as u32)
-                .saturating_mul(CELL_H_PX)
-                .saturating_mul(scale)
-                / 100;
-            let w = w.clamp(64, 1024);
-            let h = h.clamp(64, 1024);
-            (w, h)
-        };
+        let target_px = |w_cells: u16, h_cells: u16| target_px(effective_quality, w_cells, h_cells);
 
         // Drain finished render results and transmit (only when kitty is enabled).
         while let Ok(res) = self.kitty_rx.try_recv() {
@@ -457,21 +500,23 @@ impl Tui {
             let (max_w, max_h) = target_px(rect.width, rect.height);
             let _ = this.kitty_tx.send(KittyRenderRequest {
                 hash,
+                cells: (rect.width, rect.height),
                 bytes: bytes.to_vec(),
                 max_w,
                 max_h,
+                sixel_colors: None,
             });
             this.kitty_pending.insert(hash);
         };
 
         // If playlist overlay is visible (including slide animation), hide the song cover
         // to avoid the kitty image overlapping the playlist UI.
-        let playlist_overlay_visible = app.overlay == Overlay::Playlist
+        let playlist_overlay_visible = app.overlay.shows_playlist()
             || app.playlist_slide_x != app.playlist_slide_target_x
             || layout.playlist_rect.width > 0;
 
         // Only show the playlist cover once fully expanded.
-        let playlist_fully_expanded = app.overlay == Overlay::Playlist
+        let playlist_fully_expanded = app.overlay.shows_playlist()
             && app.playlist_slide_x == 0
             && app.playlist_slide_target_x == 0;
 
@@ -480,7 +525,7 @@ impl Tui {
             enqueue(self, hash, bytes, layout.info_cover_image);
         }
 
-        if playlist_overlay_visible {
+        if playlist_overlay_visible || matches!(app.overlay, Overlay::Lyrics | Overlay::LyricSync) {
             hide_info(self);
         } else if let (Some(_bytes), Some(hash)) = (app.player.track.cover.as_deref(), app.player.track.cover_hash) {
             let sig = (hash, layout.info_cover_image.width, layout.info_cover_image.height);
@@ -526,6 +571,199 @@ impl Tui {
 
         Ok(())
     }
+
+    /// Sixel / iTerm2 images can only be painted over. Before drawing, clear the screen (so
+    /// ratatui repaints every cell) whenever a visible image has to go: the playlist or lyrics
+    /// overlay covers it (including the slide animation), its cover changed, a modal opened
+    /// or closed on top of it, or it moved.
+    fn clear_stale_inline_images(&mut self, app: &AppState) -> Result<()> {
+        if !matches!(app.graphics_protocol, Some(GraphicsProtocol::Sixel | GraphicsProtocol::Iterm2)) {
+            return Ok(());
+        }
+        let overlay_last = self.inline_overlay_last.replace(app.overlay);
+        let dirty = std::mem::take(&mut self.inline_dirty);
+        if self.inline_info_last.is_none() && self.inline_playlist_last.is_none() {
+            return Ok(());
+        }
+
+        let (want_info, want_playlist) = inline_cover_hashes(app);
+        let gone = |last: Option<(u64, Rect)>, want: Option<u64>| last.is_some_and(|(hash, _)| want != Some(hash));
+        if dirty
+            || overlay_last != Some(app.overlay)
+            || gone(self.inline_info_last, want_info)
+            || gone(self.inline_playlist_last, want_playlist)
+        {
+            self.terminal.clear()?;
+            self.inline_info_last = None;
+            self.inline_playlist_last = None;
+        }
+        Ok(())
+    }
+
+    fn paint_inline_images(&mut self, app: &mut AppState, layout: &UiLayout) -> Result<()> {
+        let sixel = app.graphics_protocol == Some(GraphicsProtocol::Sixel);
+
+        if !app.config.cover_images {
+            // Turned off: the pre-draw clear already removed whatever was on screen.
+            while self.kitty_rx.try_recv().is_ok() {}
+            self.inline_images.clear();
+            self.inline_pending.clear();
+            return Ok(());
+        }
+
+        // Same rule as kitty: keep the last applied quality while the Settings modal is open.
+        if self.kitty_last_cover_quality == 0 {
+            self.kitty_last_cover_quality = app.config.kitty_cover_scale_percent;
+        }
+        if !settings_open(app) && self.kitty_last_cover_quality != app.config.kitty_cover_scale_percent {
+            self.kitty_last_cover_quality = app.config.kitty_cover_scale_percent;
+            while self.kitty_rx.try_recv().is_ok() {}
+            self.inline_images.clear();
+            self.inline_pending.clear();
+            self.inline_dirty = true;
+        }
+        let quality = self.kitty_last_cover_quality;
+
+        while let Ok(res) = self.kitty_rx.try_recv() {
+            let key = (res.hash, res.cells.0, res.cells.1);
+            if self.inline_pending.remove(&key) {
+                self.inline_images.insert(key, res.b64);
+            }
+        }
+
+        // Sixel is drawn at its pixel size, so it needs the real cell size.
+        let (cell_w, cell_h) = *self.cell_px.get_or_insert_with(|| {
+            terminal::window_size()
+                .ok()
+                .filter(|ws| ws.columns > 0 && ws.rows > 0 && ws.width >= ws.columns && ws.height >= ws.rows)
+                .map(|ws| ((ws.width / ws.columns) as u32, (ws.height / ws.rows) as u32))
+                .unwrap_or((CELL_W_PX, CELL_H_PX))
+        });
+
+        let (want_info, want_playlist) = inline_cover_hashes(app);
+        let info = want_info
+            .zip(app.player.track.cover.as_deref())
+            .map(|(hash, bytes)| (hash, bytes, layout.info_cover_image));
+        let playlist = want_playlist
+            .zip(app.local_view_album_cover.as_deref())
+            .map(|(hash, bytes)| (hash, bytes, layout.playlist_cover_image));
+
+        let mut wanted: Vec<InlineKey> = Vec::new();
+        for (is_info, want) in [(true, info), (false, playlist)] {
+            let Some((hash, bytes, rect)) = want else {
+                continue;
+            };
+            if rect.width <= 1 || rect.height <= 1 {
+                continue;
+            }
+            let key = (hash, rect.width, rect.height);
+            wanted.push(key);
+
+            let last = if is_info { &mut self.inline_info_last } else { &mut self.inline_playlist_last };
+            if *last == Some((hash, rect)) {
+                continue;
+            }
+            if let Some(data) = self.inline_images.get(&key) {
+                // Same cover in a different spot: the old pixels stay until the next clear.
+                if last.is_some() {
+                    self.inline_dirty = true;
+                }
+                let _ = if sixel {
+                    crate::render::sixel_graphics::place_image(rect, data)
+                } else {
+                    crate::render::iterm2_graphics::place_image(rect, data)
+                };
+                *last = Some((hash, rect));
+            } else if !self.inline_pending.contains(&key) {
+                let (max_w, max_h) = if sixel {
+                    (rect.width as u32 * cell_w, rect.height as u32 * cell_h)
+                } else {
+                    target_px(quality, rect.width, rect.height)
+                };
+                // For Sixel, quality is the palette size instead of the resolution.
+                let sixel_colors = sixel.then(|| (256 * quality.clamp(25, 100) as usize / 100).max(16));
+                let _ = self.kitty_tx.send(KittyRenderRequest {
+                    hash,
+                    cells: (rect.width, rect.height),
+                    bytes: bytes.to_vec(),
+                    max_w,
+                    max_h,
+                    sixel_colors,
+                });
+                self.inline_pending.insert(key);
+            }
+        }
+
+        // Encoded images are large; keep only a few around.
+        if self.inline_images.len() > 4 {
+            self.inline_images.retain(|k, _| wanted.contains(k));
+        }
+
+        Ok(())
+    }
+}
+
+/// While Settings modal is open, do NOT refresh/re-transmit covers; keep using
+/// the last applied quality so the cover doesn't constantly churn while tweaking.
+fn settings_open(app: &AppState) -> bool {
+    matches!(
+        app.overlay,
+        Overlay::SettingsModal
+            | Overlay::AcoustIdModal
+            | Overlay::BarSettingsModal
+            | Overlay::LocalAudioSettingsModal
+            | Overlay::AboutModal
+            | Overlay::PlaylistManager
+            | Overlay::PlaylistNameInput
+            | Overlay::Library
+            | Overlay::LibraryFilter
+            | Overlay::Tagger
+            | Overlay::TagEditor
+    )
+}
+
+/// Pixel size to downscale a cover to before encoding, from the cover quality percent.
+fn target_px(quality: u8, w_cells: u16, h_cells: u16) -> (u32, u32) {
+    let q = quality.clamp(25, 100);
+    // 100% means no downscale "compression" at all.
+    if q >= 100 {
+        return (u32::MAX, u32::MAX);
+    }
+
+    let scale = (q as u32).max(1);
+    let w = (w_cells as u32)
+        .saturating_mul(CELL_W_PX)
+        .saturating_mul(scale)
+        / 100;
+    let h = (h_cells as u32)
+        .saturating_mul(CELL_H_PX)
+        .saturating_mul(scale)
+        / 100;
+    let w = w.clamp(64, 1024);
+    let h = h.clamp(64, 1024);
+    (w, h)
+}
+
+/// Covers that should be on screen as (info panel, playlist panel), by hash. Same rules as
+/// kitty: the song cover hides while the playlist or lyrics overlay is up (or sliding), and
+/// the playlist cover only shows once the panel is fully expanded.
+fn inline_cover_hashes(app: &AppState) -> (Option<u64>, Option<u64>) {
+    if !app.config.cover_images {
+        return (None, None);
+    }
+    let sliding = app.playlist_slide_x != app.playlist_slide_target_x;
+    let info = if app.overlay.shows_playlist() || sliding || matches!(app.overlay, Overlay::Lyrics | Overlay::LyricSync) {
+        None
+    } else {
+        app.player.track.cover.as_ref().and(app.player.track.cover_hash)
+    };
+    let fully_expanded = app.overlay.shows_playlist() && app.playlist_slide_x == 0 && app.playlist_slide_target_x == 0;
+    let playlist = if fully_expanded {
+        app.local_view_album_cover.as_ref().and(app.local_view_album_cover_hash)
+    } else {
+        None
+    };
+    (info, playlist)
 }
 
 fn centered_rect(size: Rect, width: u16, height: u16) -> Rect {
@@ -541,12 +779,13 @@ fn centered_rect(size: Rect, width: u16, height: u16) -> Rect {
 
 fn render_settings_modal(f: &mut ratatui::Frame, size: Rect, app: &mut AppState) {
     // Keep enough height to show header + all items.
-    let area = centered_rect(size, 62, 15);
+    let area = centered_rect(size, 62, 17);
     f.render_widget(ratatui::widgets::Clear, area);
 
     let block = Block::default()
         .borders(Borders::ALL)
             .border_set(crate::ui::borders::SOLID_BORDER)
+            .border_style(Style::default().fg(app.theme.color_border()))
         .title("Settings")
         .style(Style::default().fg(app.theme.color_subtext()).bg(app.theme.color_surface()));
     f.render_widget(block, area);
@@ -560,16 +799,16 @@ fn render_settings_modal(f: &mut ratatui::Frame, size: Rect, app: &mut AppState)
     ));
     lines.push(Line::styled("", Style::default().bg(app.theme.color_surface())));
 
-    let kitty_label = if !app.kitty_graphics_supported {
-        "Kitty graphics: Unsupported".to_string()
-    } else {
-        format!("Kitty graphics: {}", if app.config.kitty_graphics { "On" } else { "Off" })
+    let kitty_label = match app.graphics_protocol {
+        None => "Cover images: Unsupported".to_string(),
+        Some(p) if app.config.cover_images => format!("Cover images: On ({})", p.label()),
+        Some(_) => "Cover images: Off".to_string(),
     };
 
-    let cover_compress_label = if !app.kitty_graphics_supported {
+    let cover_compress_label = if app.graphics_protocol.is_none() {
         format!("Cover quality: {}% (unsupported)", app.config.kitty_cover_scale_percent)
-    } else if !app.config.kitty_graphics {
-        format!("Cover quality: {}% (kitty off)", app.config.kitty_cover_scale_percent)
+    } else if !app.config.cover_images {
+        format!("Cover quality: {}% (images off)", app.config.kitty_cover_scale_percent)
     } else {
         format!("Cover quality: {}%", app.config.kitty_cover_scale_percent)
     };
@@ -586,7 +825,8 @@ fn render_settings_modal(f: &mut ratatui::Frame, size: Rect, app: &mut AppState)
     let local_audio_setting_label = "Local audio...".to_string();
 
     let items = [
-        format!("Theme: {}", app.theme.name.as_label()),
+        format!("Theme: {}", app.theme.label),
+        format!("Adaptive colors (cover): {}", if app.config.adaptive_theme { "On" } else { "Off" }),
         format!(
             "Transparent background: {}",
             if app.config.transparent_background { "On" } else { "Off" }
@@ -594,6 +834,7 @@ fn render_settings_modal(f: &mut ratatui::Frame, size: Rect, app: &mut AppState)
         format!("Album border: {}", if app.config.album_border { "On" } else { "Off" }),
         visualize_label,
         bar_setting_label,
+        format!("Text cover: {}", app.config.cover_mode.as_label()),
         kitty_label,
         cover_compress_label,
         local_audio_setting_label,
@@ -602,9 +843,9 @@ fn render_settings_modal(f: &mut ratatui::Frame, size: Rect, app: &mut AppState)
 
     for (idx, text) in items.iter().enumerate() {
         let disabled = match idx {
-            4 => app.config.visualize != crate::data::config::VisualizeMode::Bars,
-            5 => !app.kitty_graphics_supported,
-            6 => !app.kitty_graphics_supported || !app.config.kitty_graphics,
+            5 => app.config.visualize != crate::data::config::VisualizeMode::Bars,
+            7 => app.graphics_protocol.is_none(),
+            8 => app.graphics_protocol.is_none() || !app.config.cover_images,
             _ => false,
         };
 
@@ -614,7 +855,7 @@ fn render_settings_modal(f: &mut ratatui::Frame, size: Rect, app: &mut AppState)
             } else {
                 Style::default()
                     .fg(app.theme.color_base())
-                    .bg(app.theme.color_accent())
+                    .bg(app.theme.color_selection())
                     .add_modifier(Modifier::BOLD)
             }
         } else if disabled {
@@ -641,6 +882,7 @@ fn render_acoustid_modal(f: &mut ratatui::Frame, size: Rect, app: &mut AppState)
     let block = Block::default()
         .borders(Borders::ALL)
         .border_set(crate::ui::borders::SOLID_BORDER)
+        .border_style(Style::default().fg(app.theme.color_border()))
         .title("AcoustID API Key")
         .style(Style::default().fg(app.theme.color_subtext()).bg(app.theme.color_surface()));
     f.render_widget(block, area);
@@ -664,6 +906,326 @@ fn render_acoustid_modal(f: &mut ratatui::Frame, size: Rect, app: &mut AppState)
     f.render_widget(p, inner);
 }
 
+fn render_playlist_manager_modal(f: &mut ratatui::Frame, size: Rect, app: &mut AppState) {
+    let rows = app.playlist_manager_items.len().clamp(1, 12) as u16;
+    let area = centered_rect(size, 60, rows + 6);
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(crate::ui::borders::SOLID_BORDER)
+        .border_style(Style::default().fg(app.theme.color_border()))
+        .title("Playlists")
+        .style(Style::default().fg(app.theme.color_subtext()).bg(app.theme.color_surface()));
+    f.render_widget(block, area);
+
+    let inner = area.inner(&ratatui::layout::Margin { horizontal: 1, vertical: 1 });
+
+    let bg = Style::default().bg(app.theme.color_surface());
+    let sub = Style::default().fg(app.theme.color_subtext()).bg(app.theme.color_surface());
+    let text = Style::default().fg(app.theme.color_text()).bg(app.theme.color_surface());
+
+    let mut lines: Vec<Line> = Vec::new();
+    let hint = match app.playlist_manager_delete_armed.as_deref() {
+        Some(p) => format!("Press d again to delete {}", crate::data::playlist_file::playlist_name(p)),
+        None => "Enter Open  n New  r Rename  d Delete  Esc Close".to_string(),
+    };
+    lines.push(Line::styled(hint, sub));
+    lines.push(Line::styled("", bg));
+
+    if app.playlist_manager_items.is_empty() {
+        lines.push(Line::styled("  No playlists yet (n to create one)", sub));
+    }
+
+    // Scroll so the selection stays visible.
+    let visible = inner.height.saturating_sub(2).max(1) as usize;
+    let start = app
+        .playlist_manager_selected
+        .saturating_sub(visible.saturating_sub(1));
+    for (idx, p) in app.playlist_manager_items.iter().enumerate().skip(start).take(visible) {
+        let playing = app.user_playlist.as_deref() == Some(p.as_path());
+        let target = app.user_playlist_target.as_deref() == Some(p.as_path());
+        let marker = if playing { "▶" } else if target { "+" } else { " " };
+        let label = format!("{marker} {}", crate::data::playlist_file::playlist_name(p));
+        let style = if idx == app.playlist_manager_selected {
+            Style::default()
+                .fg(app.theme.color_base())
+                .bg(app.theme.color_selection())
+                .add_modifier(Modifier::BOLD)
+        } else {
+            text
+        };
+        lines.push(Line::styled(label, style));
+    }
+
+    let p = Paragraph::new(lines).style(bg).wrap(Wrap { trim: true });
+    f.render_widget(p, inner);
+}
+
+fn render_library_modal(f: &mut ratatui::Frame, size: Rect, app: &mut AppState) {
+    use crate::app::library_browser::LibraryRow;
+
+    let area = centered_rect(size, 90, size.height.saturating_sub(4));
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let browser = &app.library_browser;
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(crate::ui::borders::SOLID_BORDER)
+        .border_style(Style::default().fg(app.theme.color_border()))
+        .title(format!("{} ({} tracks)", browser.title(), app.library.len()))
+        .style(Style::default().fg(app.theme.color_subtext()).bg(app.theme.color_surface()));
+    f.render_widget(block, area);
+
+    let inner = area.inner(&ratatui::layout::Margin { horizontal: 1, vertical: 1 });
+
+    let bg = Style::default().bg(app.theme.color_surface());
+    let sub = Style::default().fg(app.theme.color_subtext()).bg(app.theme.color_surface());
+    let text = Style::default().fg(app.theme.color_text()).bg(app.theme.color_surface());
+    let selected_style = Style::default()
+        .fg(app.theme.color_base())
+        .bg(app.theme.color_selection())
+        .add_modifier(Modifier::BOLD);
+
+    let mut lines: Vec<Line> = Vec::new();
+    let hint = if app.overlay == Overlay::LibraryFilter {
+        format!("Filter: {}_   (Enter Keep  Esc Clear)", browser.filter)
+    } else if let Some(status) = app.library_scan_status.as_deref() {
+        status.to_string()
+    } else if !browser.filter.is_empty() {
+        format!("Filter: {}   (/ Edit)", browser.filter)
+    } else {
+        "Enter Open/Play  Left Back  / Filter  R Rescan  Esc Close".to_string()
+    };
+    lines.push(Line::styled(hint, sub));
+    lines.push(Line::styled("", bg));
+
+    if app.library.tracks.is_empty() && browser.category.is_some() {
+        lines.push(Line::styled("  Library is empty (R to scan)", sub));
+    } else if browser.visible.is_empty() {
+        lines.push(Line::styled("  (no matches)", sub));
+    }
+
+    // Virtualized: only build the rows that fit.
+    let rows = inner.height.saturating_sub(2).max(1) as usize;
+    let total = browser.visible.len();
+    let mut start = browser.selected.saturating_sub(rows.saturating_sub(1));
+    start = start.min(total.saturating_sub(rows));
+    for vi in start..(start + rows).min(total) {
+        let label = match browser.row(vi) {
+            Some(LibraryRow::Category(c)) => format!("  {}", c.as_label()),
+            Some(LibraryRow::Entry(e)) => format!("  {} ({})", e.label, e.count),
+            Some(LibraryRow::Track(i)) => {
+                let t = &app.library.tracks[i];
+                let num = t.track_no.map(|n| format!("{n:02}. ")).unwrap_or_default();
+                let artist = if t.artist.trim().is_empty() { "Unknown" } else { t.artist.as_str() };
+                let secs = t.duration_ms / 1000;
+                format!("  {num}{} — {artist}  [{}:{:02}]", t.display_title(), secs / 60, secs % 60)
+            }
+            None => continue,
+        };
+        let style = if vi == browser.selected { selected_style } else { text };
+        lines.push(Line::styled(label, style));
+    }
+
+    let p = Paragraph::new(lines).style(bg);
+    f.render_widget(p, inner);
+}
+
+fn render_tagger_modal(f: &mut ratatui::Frame, size: Rect, app: &mut AppState) {
+    use crate::app::tag_review::TagJobKind;
+
+    let Some(review) = app.tag_review.as_ref() else {
+        return;
+    };
+    let area = centered_rect(size, 90, size.height.saturating_sub(4));
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(crate::ui::borders::SOLID_BORDER)
+        .border_style(Style::default().fg(app.theme.color_border()))
+        .title(format!(
+            "{} ({} to change, {} selected)",
+            review.title,
+            review.proposals.len(),
+            review.accepted_count()
+        ))
+        .style(Style::default().fg(app.theme.color_subtext()).bg(app.theme.color_surface()));
+    f.render_widget(block, area);
+
+    let inner = area.inner(&ratatui::layout::Margin { horizontal: 1, vertical: 1 });
+
+    let bg = Style::default().bg(app.theme.color_surface());
+    let sub = Style::default().fg(app.theme.color_subtext()).bg(app.theme.color_surface());
+    let text = Style::default().fg(app.theme.color_text()).bg(app.theme.color_surface());
+    let added = Style::default().fg(app.theme.color_accent()).bg(app.theme.color_surface());
+    let removed = Style::default().fg(app.theme.color_accent3()).bg(app.theme.color_surface());
+    let selected_style = Style::default()
+        .fg(app.theme.color_base())
+        .bg(app.theme.color_selection())
+        .add_modifier(Modifier::BOLD);
+
+    let (done, total) = review.progress;
+    let status = match review.busy() {
+        Some(TagJobKind::Lookup) => format!("Looking up… {done}/{total}"),
+        Some(TagJobKind::Write) if review.dry_run => format!("Dry run… {done}/{total}"),
+        Some(TagJobKind::Write) => format!("Writing… {done}/{total}"),
+        Some(TagJobKind::Undo) => format!("Undoing… {done}/{total}"),
+        None => review.status.clone().unwrap_or_default(),
+    };
+    let hint = format!(
+        "Space Include  A All  Enter {}  D Dry run: {}  U Undo ({})  Esc Close",
+        if review.dry_run { "Check" } else { "Write" },
+        if review.dry_run { "on" } else { "off" },
+        review.undo_depth
+    );
+    let mut lines: Vec<Line> = vec![Line::styled(hint, sub), Line::styled(status, sub), Line::styled("", bg)];
+
+    if review.proposals.is_empty() {
+        let msg = if review.busy() == Some(TagJobKind::Lookup) {
+            "  (nothing to change yet)"
+        } else {
+            "  Nothing to change: every file already has its tags, cover and lyrics"
+        };
+        lines.push(Line::styled(msg, sub));
+    }
+
+    // Each file is a header row plus its diff. Show as many files before the selected one as fit.
+    let blocks: Vec<Vec<Line>> = review
+        .proposals
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let mark = if p.accepted { "[x]" } else { "[ ]" };
+            let name = p.path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            let style = if i == review.selected { selected_style } else { text };
+            let mut rows = vec![Line::styled(format!("{mark} {name}"), style)];
+            for (is_removed, row) in p.diff() {
+                let (sign, style) = if is_removed { ('-', removed) } else { ('+', added) };
+                rows.push(Line::styled(format!("    {sign} {row}"), if p.accepted { style } else { sub }));
+            }
+            rows
+        })
+        .collect();
+    let rows = inner.height.saturating_sub(lines.len() as u16).max(1) as usize;
+    let mut start = review.selected.min(blocks.len().saturating_sub(1));
+    let mut used = blocks.get(start).map_or(0, |b| b.len());
+    while start > 0 && used + blocks[start - 1].len() <= rows {
+        start -= 1;
+        used += blocks[start].len();
+    }
+    lines.extend(blocks.into_iter().skip(start).flatten().take(rows));
+
+    let p = Paragraph::new(lines).style(bg);
+    f.render_widget(p, inner);
+}
+
+fn render_tag_editor_modal(f: &mut ratatui::Frame, size: Rect, app: &mut AppState) {
+    use crate::app::tag_form::EditScope;
+
+    let Some(form) = app.tag_form.as_ref() else {
+        return;
+    };
+    let area = centered_rect(size, 70, form.fields.len() as u16 + 8);
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(crate::ui::borders::SOLID_BORDER)
+        .border_style(Style::default().fg(app.theme.color_border()))
+        .title(form.title.as_str())
+        .style(Style::default().fg(app.theme.color_subtext()).bg(app.theme.color_surface()));
+    f.render_widget(block, area);
+
+    let inner = area.inner(&ratatui::layout::Margin { horizontal: 1, vertical: 1 });
+
+    let bg = Style::default().bg(app.theme.color_surface());
+    let sub = Style::default().fg(app.theme.color_subtext()).bg(app.theme.color_surface());
+    let text = Style::default().fg(app.theme.color_text()).bg(app.theme.color_surface());
+    let changed = Style::default().fg(app.theme.color_accent()).bg(app.theme.color_surface());
+    let error = Style::default().fg(app.theme.color_accent3()).bg(app.theme.color_surface());
+    let selected_style = Style::default()
+        .fg(app.theme.color_base())
+        .bg(app.theme.color_selection())
+        .add_modifier(Modifier::BOLD);
+
+    let mut lines: Vec<Line> = vec![Line::styled("Up/Down Field  Type to edit  Enter Save  Esc Cancel", sub)];
+    lines.push(Line::styled(
+        match form.scope {
+            EditScope::Track => "Empty fields are removed from the file".to_string(),
+            EditScope::Album => "(mixed) fields stay as each track has them unless typed over".to_string(),
+        },
+        sub,
+    ));
+    lines.push(Line::styled("", bg));
+
+    for (i, field) in form.fields.iter().enumerate() {
+        let mark = if field.changed() { '*' } else { ' ' };
+        let value = if i == form.selected {
+            format!("{}_", field.value)
+        } else if field.original.is_none() && field.value.is_empty() {
+            "(mixed)".to_string()
+        } else {
+            field.value.clone()
+        };
+        let style = if i == form.selected {
+            selected_style
+        } else if field.changed() {
+            changed
+        } else {
+            text
+        };
+        lines.push(Line::styled(format!("{mark} {:<13} {value}", field.field.as_label()), style));
+    }
+
+    lines.push(Line::styled("", bg));
+    if app.tag_save.is_some() {
+        lines.push(Line::styled("Saving…", sub));
+    } else if let Some(e) = form.error.as_deref() {
+        lines.push(Line::styled(e.to_string(), error));
+    }
+
+    let p = Paragraph::new(lines).style(bg).wrap(Wrap { trim: false });
+    f.render_widget(p, inner);
+}
+
+fn render_playlist_name_modal(f: &mut ratatui::Frame, size: Rect, app: &mut AppState) {
+    let area = centered_rect(size, 60, 8);
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let title = match app.playlist_name_mode {
+        crate::app::state::PlaylistNameMode::New => "New Playlist",
+        crate::app::state::PlaylistNameMode::Rename => "Rename Playlist",
+        crate::app::state::PlaylistNameMode::SaveQueue => "Save Queue As Playlist",
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(crate::ui::borders::SOLID_BORDER)
+        .border_style(Style::default().fg(app.theme.color_border()))
+        .title(title)
+        .style(Style::default().fg(app.theme.color_subtext()).bg(app.theme.color_surface()));
+    f.render_widget(block, area);
+
+    let inner = area.inner(&ratatui::layout::Margin { horizontal: 1, vertical: 1 });
+
+    let mut lines: Vec<Line> = Vec::new();
+    lines.push(Line::styled(
+        "Enter Save  Esc Cancel",
+        Style::default().fg(app.theme.color_subtext()).bg(app.theme.color_surface()),
+    ));
+    lines.push(Line::styled("", Style::default().bg(app.theme.color_surface())));
+    lines.push(Line::styled(
+        format!("Name: {}", app.playlist_name_input),
+        Style::default().fg(app.theme.color_text()).bg(app.theme.color_surface()),
+    ));
+
+    let p = Paragraph::new(lines)
+        .style(Style::default().bg(app.theme.color_surface()))
+        .wrap(Wrap { trim: true });
+    f.render_widget(p, inner);
+}
+
 fn render_bar_settings_modal(f: &mut ratatui::Frame, size: Rect, app: &mut AppState) {
     let area = centered_rect(size, 50, 12);
     f.render_widget(ratatui::widgets::Clear, area);
@@ -671,6 +1233,7 @@ fn render_bar_settings_modal(f: &mut ratatui::Frame, size: Rect, app: &mut AppSt
     let block = Block::default()
         .borders(Borders::ALL)
         .border_set(crate::ui::borders::SOLID_BORDER)
+        .border_style(Style::default().fg(app.theme.color_border()))
         .title("Bar Settings")
         .style(Style::default().fg(app.theme.color_subtext()).bg(app.theme.color_surface()));
     f.render_widget(block, area);
@@ -723,7 +1286,7 @@ fn render_bar_settings_modal(f: &mut ratatui::Frame, size: Rect, app: &mut AppSt
         let style = if idx == app.bar_settings_selected {
             Style::default()
                 .fg(app.theme.color_base())
-                .bg(app.theme.color_accent())
+                .bg(app.theme.color_selection())
                 .add_modifier(Modifier::BOLD)
         } else {
             Style::default().fg(app.theme.color_text()).bg(app.theme.color_surface())
@@ -738,12 +1301,13 @@ fn render_bar_settings_modal(f: &mut ratatui::Frame, size: Rect, app: &mut AppSt
 }
 
 fn render_local_audio_settings_modal(f: &mut ratatui::Frame, size: Rect, app: &mut AppState) {
-    let area = centered_rect(size, 60, 12);
+    let area = centered_rect(size, 60, 15);
     f.render_widget(ratatui::widgets::Clear, area);
 
     let block = Block::default()
         .borders(Borders::ALL)
         .border_set(crate::ui::borders::SOLID_BORDER)
+        .border_style(Style::default().fg(app.theme.color_border()))
         .title("Local Audio")
         .style(Style::default().fg(app.theme.color_subtext()).bg(app.theme.color_surface()));
     f.render_widget(block, area);
@@ -779,12 +1343,47 @@ fn render_local_audio_settings_modal(f: &mut ratatui::Frame, size: Rect, app: &m
         if app.config.resume_last_position { "On" } else { "Off" }
     );
 
+    let crossfade_label = if app.config.crossfade_ms == 0 {
+        "Crossfade: Off (gapless)".to_string()
+    } else {
+        format!("Crossfade: {}s", app.config.crossfade_ms / 1000)
+    };
+
+    let replay_gain_label = format!("ReplayGain: {}", app.config.replay_gain.as_label());
+    let loudness_scan_label = format!(
+        "Scan untagged loudness (R128): {}",
+        if app.config.replay_gain_scan { "On" } else { "Off" }
+    );
+
+    let album_shuffle_label = format!(
+        "Album shuffle (MultiAlbum): {}",
+        if app.config.shuffle_albums { "On" } else { "Off" }
+    );
+
+    let lookup_cache_label = if app.lookup_cache_purge_armed {
+        "Lookup cache: Enter again to purge".to_string()
+    } else {
+        let stats = app.lookup_cache_stats();
+        format!(
+            "Lookup cache: {} songs, {} hits/{} misses, {:.1} MB",
+            stats.songs,
+            stats.hits,
+            stats.misses,
+            stats.cover_bytes as f64 / (1024.0 * 1024.0)
+        )
+    };
+
     let items = [
         lyrics_fetch_label,
         lyrics_download_label,
         fingerprint_label,
         acoustid_label,
         resume_label,
+        crossfade_label,
+        replay_gain_label,
+        loudness_scan_label,
+        album_shuffle_label,
+        lookup_cache_label,
     ];
 
     for (idx, text) in items.iter().enumerate() {
@@ -799,7 +1398,7 @@ fn render_local_audio_settings_modal(f: &mut ratatui::Frame, size: Rect, app: &m
             } else {
                 Style::default()
                     .fg(app.theme.color_base())
-                    .bg(app.theme.color_accent())
+                    .bg(app.theme.color_selection())
                     .add_modifier(Modifier::BOLD)
             }
         } else if disabled {
@@ -823,6 +1422,7 @@ fn render_about_modal(f: &mut ratatui::Frame, size: Rect, app: &mut AppState) {
     let block = Block::default()
         .borders(Borders::ALL)
         .border_set(crate::ui::borders::SOLID_BORDER)
+        .border_style(Style::default().fg(app.theme.color_border()))
         .title("About")
         .style(Style::default().fg(app.theme.color_subtext()).bg(app.theme.color_surface()));
     f.render_widget(block, area);
@@ -1030,61 +1630,83 @@ fn braille_from_bits(bits: u8) -> char {
 }
 
 fn render_help_modal(f: &mut ratatui::Frame, size: Rect, app: &mut AppState) {
-    // Needs enough height to show Global + Playlist sections without truncation.
-    let area = centered_rect(size, 60, 25);
+    let bg = Style::default().bg(app.theme.color_surface());
+    let text = Style::default().fg(app.theme.color_text()).bg(app.theme.color_surface());
+    let sub = Style::default().fg(app.theme.color_subtext()).bg(app.theme.color_surface());
+
+    // Generated from the active keymap (defaults + keys.toml).
+    let sections: Vec<(KeyContext, Vec<(String, String)>)> = [
+        KeyContext::Global,
+        KeyContext::Playlist,
+        KeyContext::Lyrics,
+        KeyContext::LyricSync,
+        KeyContext::Library,
+        KeyContext::Tagger,
+        KeyContext::Eq,
+    ]
+    .into_iter()
+    .map(|ctx| (ctx, app.keymap.help_rows(ctx)))
+    .filter(|(_, rows)| !rows.is_empty())
+    .collect();
+
+    let key_w = sections
+        .iter()
+        .flat_map(|(_, rows)| rows.iter().map(|(k, _)| k.chars().count()))
+        .max()
+        .unwrap_or(0)
+        .min(22);
+    let mut entries: Vec<Line> = Vec::new();
+    let mut col_w = 0usize;
+    for (ctx, rows) in &sections {
+        if !entries.is_empty() {
+            entries.push(Line::styled("", bg));
+        }
+        entries.push(Line::styled(ctx.as_label(), sub));
+        for (keys, desc) in rows {
+            let l = format!("{keys:<key_w$} {desc}");
+            col_w = col_w.max(l.chars().count());
+            entries.push(Line::styled(l, text));
+        }
+    }
+
+    // Flow into as many columns as the terminal height requires (and its width allows).
+    let col_w = (col_w + 3) as u16;
+    let max_rows = (size.height.saturating_sub(8) as usize).max(10);
+    let max_cols = (size.width.saturating_sub(6) / col_w).max(1) as usize;
+    let cols = entries.len().div_ceil(max_rows).clamp(1, max_cols);
+    let rows = entries.len().div_ceil(cols);
+    let area = centered_rect(size, col_w * cols as u16 + 2, rows as u16 + 4);
     f.render_widget(ratatui::widgets::Clear, area);
 
     let block = Block::default()
         .borders(Borders::ALL)
             .border_set(crate::ui::borders::SOLID_BORDER)
+            .border_style(Style::default().fg(app.theme.color_border()))
         .title("Keys")
         .style(Style::default().fg(app.theme.color_subtext()).bg(app.theme.color_surface()));
     f.render_widget(block, area);
 
     let inner = area.inner(&ratatui::layout::Margin { horizontal: 1, vertical: 1 });
+    f.render_widget(
+        Paragraph::new(Line::styled("Esc = Close", sub)).style(bg),
+        Rect { height: 1, ..inner },
+    );
 
-    let mut lines: Vec<Line> = Vec::new();
-    let bg = Style::default().bg(app.theme.color_surface());
-    let text = Style::default().fg(app.theme.color_text()).bg(app.theme.color_surface());
-    let sub = Style::default().fg(app.theme.color_subtext()).bg(app.theme.color_surface());
-
-    lines.push(Line::styled("Esc = Close", sub));
-    lines.push(Line::styled("", bg));
-
-    lines.push(Line::styled("Global", sub));
-    for l in [
-        "Ctrl+F    Open folder",
-        "P         Toggle playlist",
-        "Space     Play/Pause",
-        "Left/Right Prev/Next",
-        "Up/Down   Volume",
-        "M         Repeat mode (Local)",
-        "E         Equalizer (Local)",
-        "T         Settings",
-        "Ctrl+K    This help",
-        "Q         Quit",
-    ] {
-        lines.push(Line::styled(l, text));
-    }
-
-    lines.push(Line::styled("", bg));
-    lines.push(Line::styled("Playlist", sub));
-    for l in [
-        "Up/Down   Select track",
-        "Enter     Play selected",
-        "Ctrl+Up   Move item up",
-        "Ctrl+Down Move item down",
-        "Ctrl+Left Prev album (Multi)",
-        "Ctrl+Right Next album (Multi)",
-        "P         Close playlist",
-    ] {
-        lines.push(Line::styled(l, text));
+    let mut chunks = entries.into_iter().peekable();
+    for c in 0..cols {
+        let x = inner.x + c as u16 * col_w;
+        if x >= inner.x + inner.width || chunks.peek().is_none() {
+            break;
+        }
+        let column: Vec<Line> = chunks.by_ref().take(rows).collect();
+        let area = Rect {
+            x,
+            y: inner.y + 2,
+            width: col_w.min(inner.x + inner.width - x),
+            height: inner.height.saturating_sub(2),
+        };
+        f.render_widget(Paragraph::new(column).style(bg), area);
     }
-
-    let p = Paragraph::new(lines)
-        .style(bg)
-        .wrap(Wrap { trim: true });
-    f.render_widget(p, inner);
 }
 
 fn render_eq_modal(f: &mut ratatui::Frame, size: Rect, app: &mut AppState) {
@@ -1096,6 +1718,7 @@ fn render_eq_modal(f: &mut ratatui::Frame, size: Rect, app: &mut AppState) {
     let block = Block::default()
         .borders(Borders::ALL)
             .border_set(crate::ui::borders::SOLID_BORDER)
+            .border_style(Style::default().fg(app.theme.color_border()))
         .title("Equalizer (Local)")
         .style(Style::default().fg(app.theme.color_subtext()).bg(app.theme.color_surface()));
     f.render_widget(block, area);
@@ -1107,7 +1730,7 @@ fn render_eq_modal(f: &mut ratatui::Frame, size: Rect, app: &mut AppState) {
     let text = Style::default().fg(app.theme.color_text()).bg(app.theme.color_surface());
     let selected_bg = Style::default()
         .fg(app.theme.color_base())
-        .bg(app.theme.color_accent())
+        .bg(app.theme.color_selection())
         .add_modifier(Modifier::BOLD);
 
     // layout inside modal
@@ -1394,6 +2017,11 @@ pub fn hit_test(layout: &UiLayout, app: &AppState, col: u16, row: u16) -> Option
         }
     }
 
+    // The lyrics view covers the panels below it.
+    if matches!(app.overlay, Overlay::Lyrics | Overlay::LyricSync) {
+        return None;
+    }
+
     if contains(layout.info_controls, col, row) {
         return control_buttons::hit_test(layout.info_controls, app, col, row);
     }
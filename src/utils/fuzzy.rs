@@ -0,0 +1,106 @@
+// Small fuzzy matcher for interactive filtering (fzf-like subsequence match).
+
+const SCORE_MATCH: i32 = 16;
+const BONUS_CONSECUTIVE: i32 = 24;
+const BONUS_WORD_START: i32 = 20;
+const PENALTY_GAP: i32 = 2;
+
+/// Case-insensitive subsequence match of `pattern` in `text`.
+/// Returns a score (higher is better) and the char indices of `text` that matched.
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<(i32, Vec<usize>)> {
+    let pat: Vec<char> = pattern
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if pat.is_empty() {
+        return Some((0, Vec::new()));
+    }
+    let txt: Vec<char> = text.chars().collect();
+    // Folded the same way as the pattern; `origin` maps each folded char back into `txt`.
+    let (low, origin): (Vec<char>, Vec<usize>) = txt
+        .iter()
+        .enumerate()
+        .flat_map(|(i, c)| c.to_lowercase().map(move |l| (l, i)))
+        .unzip();
+
+    // Try every start position of the first char and keep the best greedy alignment.
+    let mut best: Option<(i32, Vec<usize>)> = None;
+    for start in (0..low.len()).filter(|&i| low[i] == pat[0]) {
+        let Some(found) = align_from(&pat, &low, &origin, &txt, start) else {
+            // No later start can match more of the pattern.
+            break;
+        };
+        if best.as_ref().map(|(s, _)| found.0 > *s).unwrap_or(true) {
+            best = Some(found);
+        }
+    }
+    best
+}
+
+fn align_from(pat: &[char], low: &[char], origin: &[usize], txt: &[char], start: usize) -> Option<(i32, Vec<usize>)> {
+    let mut positions: Vec<usize> = Vec::with_capacity(pat.len());
+    let mut score = 0;
+    let mut prev: Option<usize> = None;
+    let mut ti = start;
+    for &pc in pat {
+        while ti < low.len() && low[ti] != pc {
+            ti += 1;
+        }
+        if ti >= low.len() {
+            return None;
+        }
+        score += SCORE_MATCH;
+        match prev {
+            Some(p) if p + 1 == ti => score += BONUS_CONSECUTIVE,
+            Some(p) => score -= PENALTY_GAP * (ti - p - 1).min(10) as i32,
+            None => {}
+        }
+        let ci = origin[ti];
+        let first_of_char = ti == 0 || origin[ti - 1] != ci;
+        if first_of_char && (ci == 0 || !txt[ci - 1].is_alphanumeric()) {
+            score += BONUS_WORD_START;
+        }
+        if positions.last() != Some(&ci) {
+            positions.push(ci);
+        }
+        prev = Some(ti);
+        ti += 1;
+    }
+    Some((score, positions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn positions(pattern: &str, text: &str) -> Option<Vec<usize>> {
+        fuzzy_match(pattern, text).map(|(_, p)| p)
+    }
+
+    #[test]
+    fn matches_subsequences_case_insensitively() {
+        assert_eq!(positions("AbC", "xaxbxc"), Some(vec![1, 3, 5]));
+        assert_eq!(positions("abc", "ACB"), None);
+        assert_eq!(positions(" ", "anything"), Some(vec![]));
+        assert_eq!(positions("a b", "a-b"), Some(vec![0, 2]));
+    }
+
+    #[test]
+    fn pattern_and_text_fold_alike() {
+        // 'İ' lowercases to two chars ("i̇"); the match still points at the one source char.
+        assert_eq!(positions("İs", "İstanbul"), Some(vec![0, 1]));
+        assert_eq!(positions("i̇s", "İSTANBUL"), Some(vec![0, 1]));
+        assert_eq!(positions("ΣΑ", "σαλάτα"), Some(vec![0, 1]));
+        assert_eq!(positions("ä", "BÄR"), Some(vec![1]));
+    }
+
+    #[test]
+    fn consecutive_and_word_start_matches_score_higher() {
+        let score = |p: &str, t: &str| fuzzy_match(p, t).unwrap().0;
+        assert!(score("abc", "abc") > score("abc", "a_b_c"));
+        assert!(score("bar", "foo bar") > score("bar", "foobar"));
+        // The best alignment wins, not the first one.
+        assert_eq!(positions("ab", "a xab"), Some(vec![3, 4]));
+    }
+}
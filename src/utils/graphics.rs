@@ -0,0 +1,178 @@
+use crate::data::config::ImageProtocol;
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+/// Image protocol actually used to draw covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphicsProtocol {
+    Kitty,
+    Sixel,
+    Iterm2,
+}
+
+impl GraphicsProtocol {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Kitty => "Kitty",
+            Self::Sixel => "Sixel",
+            Self::Iterm2 => "iTerm2",
+        }
+    }
+}
+
+/// Resolve the configured protocol. An explicit choice is trusted as-is; `auto` checks the
+/// environment first and only then asks the terminal (DA1) whether it speaks Sixel.
+///
+/// Must run before the TUI starts reading input, since the DA1 reply arrives on stdin.
+pub fn detect_graphics_protocol(preference: ImageProtocol) -> Option<GraphicsProtocol> {
+    match preference {
+        ImageProtocol::Kitty => return Some(GraphicsProtocol::Kitty),
+        ImageProtocol::Sixel => return Some(GraphicsProtocol::Sixel),
+        ImageProtocol::Iterm2 => return Some(GraphicsProtocol::Iterm2),
+        ImageProtocol::None => return None,
+        ImageProtocol::Auto => {}
+    }
+
+    if kitty_graphics_supported() {
+        return Some(GraphicsProtocol::Kitty);
+    }
+    if iterm2_images_supported() {
+        return Some(GraphicsProtocol::Iterm2);
+    }
+    if sixel_supported_by_env() || da1_reports_sixel() {
+        return Some(GraphicsProtocol::Sixel);
+    }
+    None
+}
+
+/// Best-effort detection for Kitty Graphics Protocol support.
+pub fn kitty_graphics_supported() -> bool {
+    // kitty sets TERM=xterm-kitty and KITTY_WINDOW_ID.
+    if env::var("KITTY_WINDOW_ID").is_ok() {
+        return true;
+    }
+
+    if let Ok(term) = env::var("TERM") {
+        let term_lc = term.to_ascii_lowercase();
+        if term_lc.contains("kitty") {
+            return true;
+        }
+    }
+
+    // A few other terminals implement the protocol; keep this conservative.
+    if let Ok(tp) = env::var("TERM_PROGRAM") {
+        let tp_lc = tp.to_ascii_lowercase();
+        if tp_lc.contains("wezterm") || tp_lc.contains("ghostty") || tp_lc.contains("warp") {
+            return true;
+        }
+    }
+
+    false
+}
+
+/// iTerm2 inline images (OSC 1337): iTerm2 itself and mintty, which copies it.
+fn iterm2_images_supported() -> bool {
+    let tp = env::var("TERM_PROGRAM").unwrap_or_default().to_ascii_lowercase();
+    let lc = env::var("LC_TERMINAL").unwrap_or_default().to_ascii_lowercase();
+    tp.contains("iterm") || tp.contains("mintty") || lc.contains("iterm")
+}
+
+/// Terminals known to ship with Sixel enabled. Plain xterm only has it when built and
+/// configured for it, which is what the DA1 query is for.
+fn sixel_supported_by_env() -> bool {
+    let term = env::var("TERM").unwrap_or_default().to_ascii_lowercase();
+    term.starts_with("foot") || term.contains("mlterm") || term.contains("contour")
+}
+
+/// Send DA1 (`ESC [ c`) and look for attribute 4 (Sixel graphics) in the reply.
+fn da1_reports_sixel() -> bool {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return false;
+    }
+    // Raw mode so the reply is neither echoed nor line-buffered.
+    if crossterm::terminal::enable_raw_mode().is_err() {
+        return false;
+    }
+    let reply = query_da1(Duration::from_millis(200));
+    let _ = crossterm::terminal::disable_raw_mode();
+    reply.is_some_and(|attrs| attrs.contains(&4))
+}
+
+#[cfg(unix)]
+fn query_da1(timeout: Duration) -> Option<Vec<u32>> {
+    let mut out = io::stdout();
+    out.write_all(b"\x1b[c").ok()?;
+    out.flush().ok()?;
+
+    let mut buf: Vec<u8> = Vec::new();
+    let reply = read_da1(&mut buf, Instant::now() + timeout);
+    if reply.is_none() {
+        // A slow terminal may still be answering; swallow the rest of the reply (up to its
+        // final `c`) so it doesn't turn up later as key presses.
+        let _ = read_da1(&mut buf, Instant::now() + DA1_LATE_GRACE);
+    }
+    unsafe {
+        libc::tcflush(libc::STDIN_FILENO, libc::TCIFLUSH);
+    }
+    reply
+}
+
+/// How long a DA1 reply that missed the timeout is waited for, only to discard it.
+#[cfg(unix)]
+const DA1_LATE_GRACE: Duration = Duration::from_millis(300);
+
+/// Read from fd 0 into `buf` until it holds a whole DA1 reply or `deadline` passes.
+/// Uses poll() on the raw fd; a std reader would block forever on terminals that never answer.
+#[cfg(unix)]
+fn read_da1(buf: &mut Vec<u8>, deadline: Instant) -> Option<Vec<u32>> {
+    loop {
+        if let Some(attrs) = parse_da1(buf) {
+            return Some(attrs);
+        }
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return None;
+        }
+        let mut fds = libc::pollfd {
+            fd: libc::STDIN_FILENO,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let ready = unsafe { libc::poll(&mut fds, 1, left.as_millis() as libc::c_int) };
+        if ready <= 0 {
+            return None;
+        }
+        let mut chunk = [0u8; 64];
+        let n = unsafe { libc::read(libc::STDIN_FILENO, chunk.as_mut_ptr().cast(), chunk.len()) };
+        if n <= 0 {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..n as usize]);
+    }
+}
+
+#[cfg(not(unix))]
+fn query_da1(_timeout: Duration) -> Option<Vec<u32>> {
+    None
+}
+
+/// `ESC [ ? 62 ; 4 ; 22 c` -> [62, 4, 22]. None until the whole reply has arrived.
+fn parse_da1(buf: &[u8]) -> Option<Vec<u32>> {
+    let start = buf.windows(3).position(|w| w == b"\x1b[?")? + 3;
+    let len = buf[start..].iter().position(|&b| b == b'c')?;
+    let body = std::str::from_utf8(&buf[start..start + len]).ok()?;
+    Some(body.split(';').filter_map(|p| p.parse().ok()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_da1;
+
+    #[test]
+    fn da1_reply_is_parsed_once_complete() {
+        assert_eq!(parse_da1(b"\x1b[?62;4"), None);
+        assert_eq!(parse_da1(b"x\x1b[?62;4;22c"), Some(vec![62, 4, 22]));
+        assert_eq!(parse_da1(b"\x1b[?1;2c"), Some(vec![1, 2]));
+    }
+}
@@ -1,5 +1,6 @@
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use crate::app::state::Overlay;
+use crate::data::keymap::{KeyChord, KeyContext, Keymap, PendingKeys};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Action {
@@ -36,11 +37,68 @@ pub enum Action {
     PlaylistMoveItemUp,
     PlaylistMoveItemDown,
     PlaylistSelect(usize),
+    PlaylistTop,
+    PlaylistBottom,
+    /// Move the selection by half a screen (-1 up, 1 down).
+    PlaylistHalfPage(i32),
+    /// Cut the selected track into the register (`dd`).
+    PlaylistCut,
+    PlaylistPaste,
+    PlaylistPasteBefore,
+    PlaylistSetMark(char),
+    PlaylistJumpToMark(char),
 
     PrevAlbum,
     NextAlbum,
 
+    OpenPlaylistManager,
+    PlaylistManagerNew,
+    PlaylistManagerRename,
+    PlaylistManagerDelete,
+    PlaylistAddToUserPlaylist,
+    PlaylistRemoveItem,
+    SaveQueueAsPlaylist,
+
+    QueuePlayNext,
+    QueueAppend,
+    QueueClear,
+    ToggleUpNextView,
+
+    OpenLibrary,
+    LibraryBack,
+    LibraryPage(i32),
+    LibraryFilterStart,
+    LibraryRescan,
+
+    PlaylistSearchStart,
+
+    ToggleLyricsView,
+    CycleLyricTracks,
+    /// Nudge the current song's lyric offset (ms, positive = lyrics earlier).
+    LyricsOffset(i64),
+    /// Scroll unsynced lyrics by this many lines.
+    LyricsScroll(i32),
+    ToggleLyricsEstimate,
+    LyricSyncStart,
+    LyricSyncTap,
+    LyricSyncUndo,
+    LyricSyncSave,
+
+    /// Look up the playlist view's files for the batch tagger.
+    TagFolder,
+    TagToggle,
+    TagToggleAll,
+    TagDryRun,
+    TagUndo,
+    /// Tag editor for the selected track / every track of its album.
+    EditTags,
+    EditAlbumTags,
+
     SeekToFraction(f32),
+    /// Seek relative to the current position, in ms.
+    SeekRelative(i64),
+    /// Mark A, then B, then clear the A–B loop.
+    AbLoopCycle,
 
     FolderChar(char),
     FolderBackspace,
@@ -50,7 +108,39 @@ pub enum Action {
     None,
 }
 
-pub fn map_key(ev: KeyEvent, overlay: Overlay) -> Action {
+/// Counts are capped so a stray "99999j" can't stall the UI.
+const MAX_COUNT: usize = 999;
+
+/// Vim-style count in front of the keymap ("5j", "3dd", "12G"). Where counts are enabled,
+/// digits typed before a binding are collected instead of being looked up.
+/// Returns the action and the count typed for it, if any.
+pub fn map_key_with_count(ev: KeyEvent, overlay: Overlay, keymap: &Keymap, pending: &mut PendingKeys) -> (Action, Option<usize>) {
+    let counts = KeyContext::for_overlay(overlay).is_some_and(|c| keymap.counts_in(c));
+    if !counts {
+        pending.count = None;
+        return (map_key(ev, overlay, keymap, &mut pending.keys), None);
+    }
+
+    if pending.keys.is_empty() && (ev.modifiers - KeyModifiers::SHIFT).is_empty() {
+        if let KeyCode::Char(c @ '0'..='9') = ev.code {
+            // A leading 0 is a key of its own, as in vim.
+            if c != '0' || pending.count.is_some() {
+                let d = c as usize - '0' as usize;
+                pending.count = Some((pending.count.unwrap_or(0) * 10 + d).min(MAX_COUNT));
+                return (Action::None, None);
+            }
+        }
+    }
+
+    let action = map_key(ev, overlay, keymap, &mut pending.keys);
+    if !pending.keys.is_empty() {
+        // Still in the middle of a sequence ("3d" waiting for the second "d").
+        return (Action::None, None);
+    }
+    (action, pending.count.take())
+}
+
+pub fn map_key(ev: KeyEvent, overlay: Overlay, keymap: &Keymap, pending: &mut Vec<KeyChord>) -> Action {
     if overlay == Overlay::FolderInput {
         if ev.modifiers.contains(KeyModifiers::CONTROL)
             && matches!(ev.code, KeyCode::Char('f') | KeyCode::Char('F'))
@@ -71,7 +161,7 @@ pub fn map_key(ev: KeyEvent, overlay: Overlay) -> Action {
         return Action::None;
     }
 
-    if overlay == Overlay::AcoustIdModal {
+    if overlay == Overlay::AcoustIdModal || overlay == Overlay::PlaylistNameInput {
         match ev.code {
             KeyCode::Esc => return Action::CloseOverlay,
             KeyCode::Enter => return Action::Confirm,
@@ -86,149 +176,47 @@ pub fn map_key(ev: KeyEvent, overlay: Overlay) -> Action {
         return Action::None;
     }
 
-    // modal-specific handling first
-    if overlay == Overlay::SettingsModal {
+    if overlay == Overlay::PlaylistSearch {
         return match ev.code {
             KeyCode::Esc => Action::CloseOverlay,
-            KeyCode::Char('t') | KeyCode::Char('T') => Action::CloseOverlay,
             KeyCode::Enter => Action::Confirm,
-            KeyCode::Up => Action::ModalUp,
-            KeyCode::Down => Action::ModalDown,
-            KeyCode::Left => Action::ModalLeft,
-            KeyCode::Right => Action::ModalRight,
+            KeyCode::Backspace => Action::FolderBackspace,
+            KeyCode::Up => Action::PlaylistUp,
+            KeyCode::Down => Action::PlaylistDown,
+            KeyCode::Char(c) => Action::FolderChar(c),
             _ => Action::None,
         };
     }
 
-    if overlay == Overlay::BarSettingsModal {
+    if overlay == Overlay::TagEditor {
         return match ev.code {
             KeyCode::Esc => Action::CloseOverlay,
             KeyCode::Enter => Action::Confirm,
-            KeyCode::Up => Action::ModalUp,
-            KeyCode::Down => Action::ModalDown,
-            KeyCode::Left => Action::ModalLeft,
-            KeyCode::Right => Action::ModalRight,
+            KeyCode::Backspace => Action::FolderBackspace,
+            KeyCode::Up | KeyCode::BackTab => Action::ModalUp,
+            KeyCode::Down | KeyCode::Tab => Action::ModalDown,
+            KeyCode::Char(c) => Action::FolderChar(c),
             _ => Action::None,
         };
     }
 
-    if overlay == Overlay::LocalAudioSettingsModal {
+    if overlay == Overlay::LibraryFilter {
         return match ev.code {
             KeyCode::Esc => Action::CloseOverlay,
             KeyCode::Enter => Action::Confirm,
+            KeyCode::Backspace => Action::FolderBackspace,
             KeyCode::Up => Action::ModalUp,
             KeyCode::Down => Action::ModalDown,
-            KeyCode::Left => Action::ModalLeft,
-            KeyCode::Right => Action::ModalRight,
-            _ => Action::None,
-        };
-    }
-
-    if overlay == Overlay::EqModal {
-        // Alt+R: reset EQ to default
-        if ev.modifiers.contains(KeyModifiers::ALT) {
-            if matches!(ev.code, KeyCode::Char('r') | KeyCode::Char('R')) {
-                return Action::EqResetDefault;
-            }
-        }
-
-        return match ev.code {
-            KeyCode::Esc => Action::CloseOverlay,
-            KeyCode::Char('e') | KeyCode::Char('E') => Action::CloseOverlay,
-            KeyCode::Enter => Action::Confirm,
-            KeyCode::Up => Action::ModalUp,
-            KeyCode::Down => Action::ModalDown,
-            KeyCode::Left => Action::ModalLeft,
-            KeyCode::Right => Action::ModalRight,
-            _ => Action::None,
-        };
-    }
-
-    if overlay == Overlay::HelpModal {
-        if ev.modifiers.contains(KeyModifiers::CONTROL)
-            && matches!(ev.code, KeyCode::Char('k') | KeyCode::Char('K'))
-        {
-            return Action::CloseOverlay;
-        }
-        return match ev.code {
-            KeyCode::Esc => Action::CloseOverlay,
-            _ => Action::None,
-        };
-    }
-
-    if overlay == Overlay::AboutModal {
-        return match ev.code {
-            KeyCode::Esc => Action::CloseOverlay,
+            KeyCode::Char(c) => Action::FolderChar(c),
             _ => Action::None,
         };
     }
 
-    // global shortcuts (except folder input)
-    match ev.code {
-        KeyCode::Char('t') | KeyCode::Char('T') => return Action::OpenSettingsModal,
-        KeyCode::Char('e') | KeyCode::Char('E') => return Action::OpenEqModal,
-        _ => {}
-    }
-
-    if ev.modifiers.contains(KeyModifiers::CONTROL) {
-        match ev.code {
-            KeyCode::Char('f') | KeyCode::Char('F') => return Action::OpenFolder,
-            KeyCode::Char('k') | KeyCode::Char('K') => return Action::OpenHelpModal,
-            _ => {}
-        }
-    }
-
-    if overlay == Overlay::Playlist {
-        return match ev.code {
-            KeyCode::Char('q') | KeyCode::Char('Q') => Action::Quit,
-            KeyCode::Char('p') | KeyCode::Char('P') => Action::TogglePlaylist,
-            KeyCode::Esc => Action::CloseOverlay,
-            KeyCode::Enter => Action::Confirm,
-            KeyCode::Left => {
-                if ev.modifiers.contains(KeyModifiers::CONTROL) {
-                    Action::PrevAlbum
-                } else {
-                    Action::None
-                }
-            }
-            KeyCode::Right => {
-                if ev.modifiers.contains(KeyModifiers::CONTROL) {
-                    Action::NextAlbum
-                } else {
-                    Action::None
-                }
-            }
-            KeyCode::Up => {
-                if ev.modifiers.contains(KeyModifiers::CONTROL) {
-                    Action::PlaylistMoveItemUp
-                } else {
-                    Action::PlaylistUp
-                }
-            }
-            KeyCode::Down => {
-                if ev.modifiers.contains(KeyModifiers::CONTROL) {
-                    Action::PlaylistMoveItemDown
-                } else {
-                    Action::PlaylistDown
-                }
-            }
-            _ => Action::None,
-        };
-    }
-
-    match ev.code {
-        KeyCode::Char('q') | KeyCode::Char('Q') => Action::Quit,
-        KeyCode::Char('p') | KeyCode::Char('P') => Action::TogglePlaylist,
-        KeyCode::Char('m') | KeyCode::Char('M') => Action::ToggleRepeatMode,
-        KeyCode::Esc => Action::CloseOverlay,
-        KeyCode::Enter => Action::Confirm,
-        KeyCode::Left => Action::Prev,
-        KeyCode::Right => Action::Next,
-        KeyCode::Up => Action::VolumeUp,
-        KeyCode::Down => Action::VolumeDown,
-        KeyCode::Char(' ') => Action::TogglePlayPause,
-        _ => Action::None,
-    }
+    // Text prompts above take raw characters; everything else goes through the keymap.
+    let Some(contexts) = KeyContext::for_overlay(overlay) else {
+        return Action::None;
+    };
+    keymap.resolve(contexts, KeyChord::from_event(&ev), pending)
 }
 
 pub fn map_mouse(ev: MouseEvent) -> Action {
@@ -1,6 +1,7 @@
 pub mod ascii_art;
+pub mod fuzzy;
+pub mod graphics;
 pub mod input;
-pub mod kitty;
 #[cfg(target_os = "linux")]
 pub mod stderr_filter;
 #[cfg(not(target_os = "linux"))]
@@ -0,0 +1,177 @@
+// The MPRIS server on a private session bus. Skipped when `dbus-daemon` isn't installed.
+#![cfg(target_os = "linux")]
+
+use cli_music_player::app::control::{ControlCommand, SeekTarget};
+use cli_music_player::app::state::{AppState, PlayMode, PlaybackState, RepeatMode};
+use cli_music_player::data::config::{Config, ImageProtocol};
+use cli_music_player::data::playlist::{Playlist, PlaylistItem};
+use cli_music_player::data::theme_loader::ThemeLoader;
+use cli_music_player::playback::mpris_server::{MprisServer, BUS_NAME};
+use dbus::arg::{prop_cast, PropMap};
+use dbus::blocking::stdintf::org_freedesktop_dbus::Properties;
+use dbus::blocking::Connection;
+use dbus::channel::MatchingReceiver;
+use dbus::message::MatchRule;
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+const PLAYER: &str = "org.mpris.MediaPlayer2.Player";
+const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+
+/// A `dbus-daemon` of our own, killed on drop.
+struct Bus {
+    daemon: Child,
+    _dir: tempfile::TempDir,
+}
+
+impl Drop for Bus {
+    fn drop(&mut self) {
+        let _ = self.daemon.kill();
+        let _ = self.daemon.wait();
+    }
+}
+
+/// Start a private bus and point `DBUS_SESSION_BUS_ADDRESS` at it; None without dbus-daemon.
+fn private_bus() -> Option<Bus> {
+    let dir = tempfile::tempdir().unwrap();
+    let conf = dir.path().join("bus.conf");
+    std::fs::write(
+        &conf,
+        format!(
+            r#"<busconfig>
+  <type>session</type>
+  <listen>unix:dir={}</listen>
+  <policy context="default">
+    <allow send_destination="*" eavesdrop="true"/>
+    <allow eavesdrop="true"/>
+    <allow own="*"/>
+  </policy>
+</busconfig>"#,
+            dir.path().display()
+        ),
+    )
+    .unwrap();
+    let mut daemon = Command::new("dbus-daemon")
+        .arg(format!("--config-file={}", conf.display()))
+        .args(["--nofork", "--print-address"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let mut address = String::new();
+    BufReader::new(daemon.stdout.take()?).read_line(&mut address).ok()?;
+    std::env::set_var("DBUS_SESSION_BUS_ADDRESS", address.trim());
+    Some(Bus { daemon, _dir: dir })
+}
+
+fn playing_app(path: &str) -> AppState {
+    std::env::set_var("CLI_MUSIC_PLAYER_ASSET_DIR", tempfile::tempdir().unwrap().keep());
+    let config = Config {
+        image_protocol: ImageProtocol::None,
+        ..Config::default()
+    };
+    let mut app = AppState::new(config, ThemeLoader::builtin());
+    app.player.mode = PlayMode::LocalPlayback;
+    app.player.playback = PlaybackState::Playing;
+    app.player.repeat_mode = RepeatMode::LoopAll;
+    app.player.volume = 0.5;
+    app.player.position = Duration::from_secs(3);
+    app.player.track.title = "Song".into();
+    app.player.track.artist = "Band".into();
+    app.player.track.album = "Record".into();
+    app.player.track.duration = Duration::from_secs(200);
+    app.playlist = Playlist::new(vec![PlaylistItem {
+        path: PathBuf::from(path),
+        title: "Song".into(),
+        missing: false,
+    }]);
+    app.playlist.current = Some(0);
+    app
+}
+
+fn commands(server: &MprisServer) -> Vec<ControlCommand> {
+    server.drain().into_iter().map(|r| r.command).collect()
+}
+
+#[test]
+fn publishes_state_and_forwards_commands() {
+    let Some(_bus) = private_bus() else {
+        eprintln!("dbus-daemon not found; skipping");
+        return;
+    };
+    let mut server = MprisServer::start().unwrap();
+    let mut app = playing_app("/music/A b#1.flac");
+    server.update(&app);
+
+    let conn = Connection::new_session().unwrap();
+    let player = conn.with_proxy(BUS_NAME, OBJECT_PATH, Duration::from_secs(2));
+
+    let status: String = player.get(PLAYER, "PlaybackStatus").unwrap();
+    assert_eq!(status, "Playing");
+    let loop_status: String = player.get(PLAYER, "LoopStatus").unwrap();
+    assert_eq!(loop_status, "Playlist");
+    let shuffle: bool = player.get(PLAYER, "Shuffle").unwrap();
+    assert!(!shuffle);
+
+    let meta: PropMap = player.get(PLAYER, "Metadata").unwrap();
+    assert_eq!(prop_cast::<String>(&meta, "xesam:title").map(String::as_str), Some("Song"));
+    assert_eq!(prop_cast::<i64>(&meta, "mpris:length"), Some(&200_000_000));
+    assert_eq!(
+        prop_cast::<String>(&meta, "xesam:url").map(String::as_str),
+        Some("file:///music/A%20b%231.flac")
+    );
+    let track_id = prop_cast::<dbus::Path>(&meta, "mpris:trackid").unwrap().clone();
+
+    player.set(PLAYER, "LoopStatus", "Track".to_string()).unwrap();
+    player.set(PLAYER, "Shuffle", true).unwrap();
+    assert_eq!(
+        commands(&server),
+        vec![ControlCommand::Repeat(RepeatMode::LoopOne), ControlCommand::Repeat(RepeatMode::Shuffle)]
+    );
+
+    let _: () = player.method_call(PLAYER, "Seek", (-2_500_000i64,)).unwrap();
+    let _: () = player.method_call(PLAYER, "SetPosition", (track_id, 10_000_000i64)).unwrap();
+    // A stale track id is ignored, as the spec asks.
+    let stale = dbus::Path::from("/org/mpris/MediaPlayer2/cli_music_player/track/t0");
+    let _: () = player.method_call(PLAYER, "SetPosition", (stale, 20_000_000i64)).unwrap();
+    let _: () = player.method_call(PLAYER, "Stop", ()).unwrap();
+    assert_eq!(
+        commands(&server),
+        vec![
+            ControlCommand::Seek(SeekTarget::Relative(-2.5)),
+            ControlCommand::Seek(SeekTarget::Absolute(10.0)),
+            ControlCommand::Stop,
+        ]
+    );
+
+    let _: () = player
+        .method_call(PLAYER, "OpenUri", ("file:///music/New%20Album",))
+        .unwrap();
+    assert_eq!(commands(&server), vec![ControlCommand::Open(PathBuf::from("/music/New Album"))]);
+
+    // Once the player applies a seek, the bus hears `Seeked` with the new position.
+    let (tx, rx) = mpsc::channel();
+    let rule = MatchRule::new_signal(PLAYER, "Seeked");
+    conn.add_match_no_cb(&rule.match_str()).unwrap();
+    conn.start_receive(
+        rule,
+        Box::new(move |msg, _| {
+            let _ = tx.send(msg.read1::<i64>().ok());
+            true
+        }),
+    );
+    app.player.position = Duration::from_secs(10);
+    app.player.seeks += 1;
+    server.update(&app);
+
+    let deadline = Instant::now() + Duration::from_secs(3);
+    let mut seeked = None;
+    while seeked.is_none() && Instant::now() < deadline {
+        conn.process(Duration::from_millis(50)).unwrap();
+        seeked = rx.try_recv().ok().flatten();
+    }
+    assert_eq!(seeked, Some(10_000_000));
+}
@@ -0,0 +1,218 @@
+// The lyrics / cover / identification pipeline against a local HTTP fixture: every
+// provider's `base_url` points at a `TcpListener` that answers canned responses per path.
+
+use cli_music_player::data::config::{ProviderKind, ProvidersConfig};
+use cli_music_player::playback::lookup_cache::{CacheTtl, LookupCache};
+use cli_music_player::playback::provider::{build_chain, ProviderChainConfig};
+use cli_music_player::playback::remote_fetch::{lookup, FetchOptions, Lookup, RemoteFetchRequest, TrackKey};
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, Once};
+
+/// Request paths (without the query string) in the order they arrived.
+type Hits = Arc<Mutex<Vec<String>>>;
+
+/// Serves `(path, status, body)` routes; anything else is a 404.
+fn serve(routes: Vec<(&str, u16, &[u8])>) -> (String, Hits) {
+    let routes: Vec<(String, u16, Vec<u8>)> = routes.into_iter().map(|(p, s, b)| (p.to_string(), s, b.to_vec())).collect();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let hits: Hits = Arc::default();
+    let seen = hits.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else {
+                continue;
+            };
+            let mut head = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+                match stream.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => head.extend_from_slice(&buf[..n]),
+                }
+            }
+            let head = String::from_utf8_lossy(&head);
+            let target = head.split_whitespace().nth(1).unwrap_or("/");
+            let path = target.split('?').next().unwrap_or(target).to_string();
+            seen.lock().unwrap().push(path.clone());
+            let (status, body) = routes
+                .iter()
+                .find(|(p, _, _)| *p == path)
+                .map(|(_, s, b)| (*s, b.clone()))
+                .unwrap_or((404, Vec::new()));
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {status} X\r\nContent-Length: {}\r\nContent-Type: application/octet-stream\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            let _ = stream.write_all(&body);
+        }
+    });
+    (base, hits)
+}
+
+/// Keep the lookup cache (index and cover blobs) out of the real config directory.
+fn isolate_assets() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        let dir = tempfile::tempdir().unwrap().keep();
+        std::env::set_var("CLI_MUSIC_PLAYER_ASSET_DIR", dir);
+    });
+}
+
+fn chain_config(order: &[ProviderKind], base: &str, lyrics_folders: Vec<PathBuf>) -> ProviderChainConfig {
+    let mut providers = ProvidersConfig::default();
+    for s in [
+        &mut providers.local,
+        &mut providers.lrclib,
+        &mut providers.netease,
+        &mut providers.qqmusic,
+        &mut providers.genius,
+        &mut providers.musicbrainz,
+        &mut providers.coverartarchive,
+        &mut providers.acoustid,
+    ] {
+        s.enabled = Some(true);
+        s.base_url = base.to_string();
+    }
+    ProviderChainConfig {
+        order: order.to_vec(),
+        providers,
+        lyrics_folders,
+        acoustid_api_key: None,
+    }
+}
+
+fn run(providers: ProviderChainConfig, has_lyrics: bool, has_cover: bool) -> Lookup {
+    isolate_assets();
+    let key = TrackKey {
+        path: None,
+        title: "Song".into(),
+        artist: "Band".into(),
+        album: "Record".into(),
+        duration_secs: 200,
+    };
+    let req = RemoteFetchRequest {
+        path: None,
+        title: key.title.clone(),
+        artist: key.artist.clone(),
+        album: key.album.clone(),
+        duration_secs: key.duration_secs,
+        key,
+        has_lyrics,
+        has_cover,
+        options: FetchOptions {
+            enable_fetch: true,
+            download: false,
+            providers,
+            cache_ttl: CacheTtl { hit_days: 30, miss_days: 7 },
+        },
+    };
+    lookup(&req, &Mutex::new(LookupCache::default()))
+}
+
+#[test]
+fn chain_follows_configured_order() {
+    use ProviderKind::*;
+    let mut cfg = chain_config(&[Genius, Coverartarchive, Lrclib, Genius, Musicbrainz, Local, Acoustid], "http://127.0.0.1:9", Vec::new());
+    cfg.providers.lrclib.enabled = Some(false);
+    let kinds: Vec<ProviderKind> = build_chain(&cfg).iter().map(|p| p.kind()).collect();
+    // Duplicates are dropped; local needs folders and AcoustID a key.
+    assert_eq!(kinds, vec![Genius, Coverartarchive, Musicbrainz]);
+}
+
+#[test]
+fn scraping_providers_need_an_explicit_enable() {
+    use ProviderKind::*;
+    let providers: ProvidersConfig = toml::from_str(
+        "[netease]\nbase_url = \"http://127.0.0.1:9\"\n[genius]\nbase_url = \"http://127.0.0.1:9\"\n[qqmusic]\nenabled = true\n",
+    )
+    .unwrap();
+    let cfg = ProviderChainConfig {
+        order: vec![Netease, Genius, Qqmusic, Lrclib],
+        providers,
+        lyrics_folders: Vec::new(),
+        acoustid_api_key: None,
+    };
+    let kinds: Vec<ProviderKind> = build_chain(&cfg).iter().map(|p| p.kind()).collect();
+    assert_eq!(kinds, vec![Qqmusic, Lrclib]);
+}
+
+#[test]
+fn lyrics_fall_through_empty_and_missing_answers() {
+    use ProviderKind::*;
+    let (base, hits) = serve(vec![("/api/get", 200, br#"{"syncedLyrics":"  ","plainLyrics":null}"#)]);
+    let folder = tempfile::tempdir().unwrap();
+    std::fs::write(folder.path().join("Band - Song.lrc"), "[00:01.00]from disk\n").unwrap();
+
+    let found = run(chain_config(&[Lrclib, Netease, Local], &base, vec![folder.path().to_path_buf()]), false, true);
+
+    let lyrics = found.lyrics.expect("local lyrics after two misses");
+    assert_eq!(lyrics.provider, Local);
+    assert!(lyrics.text.contains("from disk"));
+    // LRCLIB answered empty, NetEase's search was a 404; both were asked, in order.
+    assert_eq!(*hits.lock().unwrap(), vec!["/api/get", "/api/search/get/web"]);
+}
+
+#[test]
+fn synced_lyrics_from_a_later_provider_beat_plain_ones() {
+    use ProviderKind::*;
+    let (base, _) = serve(vec![("/api/get", 200, br#"{"plainLyrics":"just words"}"#)]);
+    let folder = tempfile::tempdir().unwrap();
+    std::fs::write(folder.path().join("Song.lrc"), "[00:01.00]timed\n").unwrap();
+
+    let found = run(chain_config(&[Lrclib, Local], &base, vec![folder.path().to_path_buf()]), false, true);
+    assert_eq!(found.lyrics.map(|l| l.provider), Some(Local));
+
+    let (base, _) = serve(vec![("/api/get", 200, br#"{"plainLyrics":"just words"}"#)]);
+    let found = run(chain_config(&[Lrclib], &base, Vec::new()), false, true);
+    assert_eq!(found.lyrics.map(|l| l.provider), Some(Lrclib));
+}
+
+#[test]
+fn identification_feeds_the_cover_lookup() {
+    use ProviderKind::*;
+    let (base, hits) = serve(vec![
+        ("/ws/2/recording/", 200, br#"{"recordings":[{"releases":[{"id":"rel-1"}]}]}"#),
+        ("/release/rel-1/front-500", 200, b"\x89PNG fake"),
+    ]);
+
+    let found = run(chain_config(&[Coverartarchive, Musicbrainz], &base, Vec::new()), true, false);
+
+    assert_eq!(found.identity.and_then(|id| id.release_mbid).as_deref(), Some("rel-1"));
+    assert_eq!(found.cover.map(|(bytes, _)| bytes), Some(b"\x89PNG fake".to_vec()));
+    assert!(found.lyrics.is_none());
+    assert_eq!(*hits.lock().unwrap(), vec!["/ws/2/recording/", "/release/rel-1/front-500"]);
+}
+
+#[test]
+fn cover_misses_on_404_and_empty_bodies() {
+    use ProviderKind::*;
+    let release = br#"{"recordings":[{"releases":[{"id":"rel-2"}]}]}"#;
+    for cover in [None, Some(&b""[..])] {
+        let mut routes: Vec<(&str, u16, &[u8])> = vec![("/ws/2/recording/", 200, release)];
+        if let Some(body) = cover {
+            routes.push(("/release/rel-2/front-500", 200, body));
+        }
+        let (base, hits) = serve(routes);
+
+        let found = run(chain_config(&[Musicbrainz, Coverartarchive], &base, Vec::new()), true, false);
+
+        assert!(found.cover.is_none());
+        assert!(found.queried);
+        assert_eq!(hits.lock().unwrap().last().map(String::as_str), Some("/release/rel-2/front-500"));
+    }
+}
+
+#[test]
+fn unidentified_tracks_skip_the_cover_request() {
+    use ProviderKind::*;
+    // MusicBrainz is a 404, so there is no release to ask the Cover Art Archive about.
+    let (base, hits) = serve(vec![]);
+    let found = run(chain_config(&[Musicbrainz, Coverartarchive], &base, Vec::new()), true, false);
+    assert!(found.identity.is_none());
+    assert!(found.cover.is_none());
+    assert_eq!(*hits.lock().unwrap(), vec!["/ws/2/recording/"]);
+}